@@ -0,0 +1,124 @@
+//! High Contrast interop - Windows contrast themes (forced colors) are built from a few exact
+//! colors, and tinting or dimming matrices break them. While a contrast theme is on, effects
+//! either switch to a gentle untinted lift or are suspended, as the user prefers
+
+use crate::suspend;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Suspension reason used while a contrast theme is on and the policy suspends effects
+const REASON: &str = "high_contrast";
+
+/// How often the contrast theme setting is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Strongest shadow lift applied under a contrast theme with the friendly preset
+pub const FRIENDLY_MAX_INTENSITY: f32 = 0.35;
+
+/// What to do while a Windows contrast theme is on
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HighContrastPolicy {
+    /// Untinted Magnification lift up to FRIENDLY_MAX_INTENSITY; no warmth or dimming matrices
+    #[default]
+    Friendly,
+    /// No effects at all until the contrast theme is turned off
+    Suspend,
+    /// Adjust as usual
+    Ignore,
+}
+
+/// Contrast theme state reported to the UI
+#[derive(Serialize, Clone, Debug)]
+pub struct AccessibilityState {
+    /// A Windows contrast theme (High Contrast / forced colors) is on
+    pub high_contrast: bool,
+    /// Name of the contrast theme, e.g. "High Contrast Black"
+    pub theme: Option<String>,
+    pub policy: HighContrastPolicy,
+}
+
+/// Contrast theme seen by the last poll
+static HIGH_CONTRAST: AtomicBool = AtomicBool::new(false);
+
+#[cfg(windows)]
+fn read_high_contrast() -> (bool, Option<String>) {
+    use std::ffi::c_void;
+
+    const SPI_GETHIGHCONTRAST: u32 = 0x0042;
+    const HCF_HIGHCONTRASTON: u32 = 0x0000_0001;
+
+    /// HIGHCONTRASTW structure
+    #[repr(C)]
+    struct HighContrast {
+        size: u32,
+        flags: u32,
+        default_scheme: *const u16,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn SystemParametersInfoW(action: u32, param: u32, value: *mut c_void, win_ini: u32) -> i32;
+    }
+
+    let mut info = HighContrast { size: std::mem::size_of::<HighContrast>() as u32, flags: 0, default_scheme: std::ptr::null() };
+    unsafe {
+        if SystemParametersInfoW(SPI_GETHIGHCONTRAST, info.size, &mut info as *mut _ as *mut c_void, 0) == 0 {
+            return (false, None);
+        }
+        let on = info.flags & HCF_HIGHCONTRASTON != 0;
+        if !on || info.default_scheme.is_null() {
+            return (on, None);
+        }
+        let len = (0..).take_while(|&i| *info.default_scheme.add(i) != 0).count();
+        let theme = String::from_utf16_lossy(std::slice::from_raw_parts(info.default_scheme, len));
+        (on, Some(theme).filter(|t| !t.is_empty()))
+    }
+}
+
+#[cfg(not(windows))]
+fn read_high_contrast() -> (bool, Option<String>) {
+    (false, None)
+}
+
+/// Current contrast theme state and policy
+pub fn state() -> AccessibilityState {
+    let (high_contrast, theme) = read_high_contrast();
+    AccessibilityState { high_contrast, theme, policy: crate::settings::get().high_contrast }
+}
+
+/// Whether effects should be limited to the friendly preset right now
+pub fn friendly() -> bool {
+    HIGH_CONTRAST.load(Ordering::SeqCst) && crate::settings::get().high_contrast == HighContrastPolicy::Friendly
+}
+
+/// Check the contrast theme and suspend or resume effects under the Suspend policy
+/// Returns Some(true) if effects were suspended, Some(false) if resumed, None if unchanged
+pub fn enforce() -> Option<bool> {
+    let (high_contrast, _) = read_high_contrast();
+    HIGH_CONTRAST.store(high_contrast, Ordering::SeqCst);
+    let blocked = high_contrast && crate::settings::get().high_contrast == HighContrastPolicy::Suspend;
+    if blocked && suspend::suspend(REASON) {
+        Some(true)
+    } else if !blocked && suspend::resume(REASON) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Start a background thread that follows the contrast theme setting
+/// `on_change` receives suspend changes in the same form as blocklist::enforce
+pub fn start_watcher<F>(on_change: F)
+where
+    F: Fn(Option<bool>) + Send + 'static,
+{
+    thread::spawn(move || {
+        while !crate::shutdown::is_shutting_down() {
+            on_change(enforce());
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,139 @@
+//! Auto-adjust controller - decides how strongly to lift shadows for a measured brightness
+//! Pure so the UI can preview decisions while tuning thresholds without applying anything
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Tunable controller thresholds
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct AdjustmentConfig {
+    /// Scenes darker than this (0.0 - 1.0) get their shadows lifted
+    pub dark_threshold: f32,
+    /// Half-width of the neutral band around the threshold in which the previous decision is
+    /// kept, so a camera pan through mid-brightness areas doesn't toggle effects
+    pub dead_zone: f32,
+    /// Time taken to fade in from zero the first time auto-adjust engages in a session
+    /// (0 = apply the target immediately); game profiles can override it
+    pub engage_ramp_ms: u64,
+    /// How long a manual intensity change holds auto-adjust off
+    pub manual_hold_ms: u64,
+    /// Brightness change (0.0 - 1.0) from where a manual change was made that ends the hold early
+    pub manual_release_delta: f32,
+}
+
+impl Default for AdjustmentConfig {
+    fn default() -> Self {
+        // Higher threshold = more aggressive night vision activation
+        Self {
+            dark_threshold: 0.40,
+            dead_zone: 0.0,
+            engage_ramp_ms: 3000,
+            manual_hold_ms: 30_000,
+            manual_release_delta: 0.25,
+        }
+    }
+}
+
+/// Reject thresholds outside 0.0 - 1.0 (including NaN)
+pub fn validate(config: &AdjustmentConfig) -> Result<(), String> {
+    let fields = [
+        ("field.dark_threshold", config.dark_threshold),
+        ("field.dead_zone", config.dead_zone),
+        ("field.scene_change", config.manual_release_delta),
+    ];
+    match fields.into_iter().find(|(_, value)| !(0.0..=1.0).contains(value)) {
+        Some((field, value)) => Err(crate::i18n::format("error.unit_interval", &[&crate::i18n::text(field), &value])),
+        None => Ok(()),
+    }
+}
+
+/// What the controller does for a brightness reading
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct AdjustmentDecision {
+    /// Shadow lift is applied
+    pub engaged: bool,
+    /// Lift intensity (0.0 - 1.0), zero when not engaged
+    pub intensity: f32,
+}
+
+/// Decide the adjustment for a scene brightness
+/// brightness: 0.0 (completely dark) to 1.0 (completely bright)
+///
+/// Below the dark threshold: lift shadows, the darker the scene the more
+/// At or above it: no adjustment (normal/bright)
+/// Within the dead zone the previous decision stands
+pub fn decide(brightness: f32, config: &AdjustmentConfig, previous: Option<AdjustmentDecision>) -> AdjustmentDecision {
+    let threshold = config.dark_threshold.clamp(0.0, 1.0);
+    if let Some(previous) = previous {
+        if (brightness - threshold).abs() < config.dead_zone {
+            return previous;
+        }
+    }
+    if brightness < threshold {
+        AdjustmentDecision { engaged: true, intensity: (threshold - brightness) / threshold }
+    } else {
+        AdjustmentDecision { engaged: false, intensity: 0.0 }
+    }
+}
+
+/// Fraction of the target intensity allowed `elapsed` into the engage ramp, rising linearly to 1
+pub fn engage_fraction(elapsed: Duration, ramp: Duration) -> f32 {
+    if ramp.is_zero() {
+        return 1.0;
+    }
+    (elapsed.as_secs_f32() / ramp.as_secs_f32()).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(dark_threshold: f32, dead_zone: f32) -> AdjustmentConfig {
+        AdjustmentConfig { dark_threshold, dead_zone, ..AdjustmentConfig::default() }
+    }
+
+    #[test]
+    fn dark_scene_engages_in_proportion() {
+        let decision = decide(0.1, &config(0.4, 0.0), None);
+        assert!(decision.engaged);
+        assert!((decision.intensity - 0.75).abs() < 1e-6);
+        assert_eq!(decide(0.0, &config(0.4, 0.0), None).intensity, 1.0);
+    }
+
+    #[test]
+    fn threshold_and_above_disengage() {
+        for brightness in [0.4, 0.9] {
+            let decision = decide(brightness, &config(0.4, 0.0), None);
+            assert!(!decision.engaged);
+            assert_eq!(decision.intensity, 0.0);
+        }
+    }
+
+    #[test]
+    fn dead_zone_keeps_previous_decision() {
+        let previous = AdjustmentDecision { engaged: true, intensity: 0.3 };
+        let kept = decide(0.42, &config(0.4, 0.05), Some(previous));
+        assert!(kept == previous);
+        let left = decide(0.5, &config(0.4, 0.05), Some(previous));
+        assert!(!left.engaged);
+    }
+
+    #[test]
+    fn engage_ramp_rises_linearly_to_one() {
+        let ramp = Duration::from_millis(2000);
+        assert_eq!(engage_fraction(Duration::ZERO, ramp), 0.0);
+        assert!((engage_fraction(Duration::from_millis(500), ramp) - 0.25).abs() < 1e-6);
+        assert_eq!(engage_fraction(Duration::from_millis(5000), ramp), 1.0);
+        assert_eq!(engage_fraction(Duration::ZERO, Duration::ZERO), 1.0);
+    }
+
+    #[test]
+    fn thresholds_must_be_unit_interval() {
+        assert!(validate(&AdjustmentConfig::default()).is_ok());
+        assert!(validate(&config(1.2, 0.0)).is_err());
+        assert!(validate(&config(0.4, f32::NAN)).is_err());
+        let release = AdjustmentConfig { manual_release_delta: -0.5, ..AdjustmentConfig::default() };
+        assert!(validate(&release).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Allowlist mode - effects only while specific apps are running
+//! The inverse of game profiles: with the mode on, effects are suspended whenever none of the
+//! listed processes is running, so Noctis never touches the display outside those games
+
+use crate::suspend;
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::Duration;
+
+/// Suspension reason used while no allowlisted process runs
+const REASON: &str = "allowlist";
+
+/// How often running processes are checked (launches are also reported by the process watcher)
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Allowlist mode configuration
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AllowlistSettings {
+    pub enabled: bool,
+    /// Executable names, e.g. "EscapeFromTarkov.exe"
+    pub processes: Vec<String>,
+}
+
+/// Executable names of the running processes
+#[cfg(windows)]
+pub fn running_processes() -> Vec<String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+
+    let mut names = Vec::new();
+    unsafe {
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) else { return names };
+        let mut entry = PROCESSENTRY32W { dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32, ..Default::default() };
+        let mut more = Process32FirstW(snapshot, &mut entry).is_ok();
+        while more {
+            let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+            names.push(String::from_utf16_lossy(&entry.szExeFile[..len]));
+            more = Process32NextW(snapshot, &mut entry).is_ok();
+        }
+        let _ = CloseHandle(snapshot);
+    }
+    names
+}
+
+#[cfg(not(windows))]
+pub fn running_processes() -> Vec<String> {
+    Vec::new()
+}
+
+/// Suspend or resume effects based on whether an allowlisted process is running
+/// Returns Some(true) if effects were suspended, Some(false) if resumed, None if unchanged
+pub fn enforce() -> Option<bool> {
+    let config = crate::settings::get().allowlist;
+    let blocked = config.enabled
+        && !running_processes().iter().any(|running| config.processes.iter().any(|p| p.eq_ignore_ascii_case(running)));
+    if blocked && suspend::suspend(REASON) {
+        Some(true)
+    } else if !blocked && suspend::resume(REASON) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Start a background thread that polls for allowlisted processes
+/// `on_change` receives suspend changes in the same form as blocklist::enforce
+pub fn start_watcher<F>(on_change: F)
+where
+    F: Fn(Option<bool>) + Send + 'static,
+{
+    thread::spawn(move || {
+        while !crate::shutdown::is_shutting_down() {
+            on_change(enforce());
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,179 @@
+//! Public API - the stable surface for embedding night-vision control in other Rust programs
+//! (game launchers, CLIs, services). Wraps the backend registry, monitor enumeration and the
+//! brightness sensor behind a few types that keep their shape between releases
+
+use crate::backend::{self, Backend};
+use crate::{gamma, i18n, magnification, night_mode, sensor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// A monitor, by its position in the system's enumeration order
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct MonitorId(pub u32);
+
+impl fmt::Display for MonitorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "monitor {}", self.0)
+    }
+}
+
+/// A connected monitor
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Monitor {
+    pub id: MonitorId,
+    /// Device name, e.g. "\\.\DISPLAY1"
+    pub name: String,
+    /// Position and size on the virtual desktop
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub primary: bool,
+}
+
+/// What to show on a monitor
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Adjustment {
+    /// Lift shadows, from 0.0 (normal) to 1.0 (max)
+    ShadowLift { intensity: f32 },
+    /// Scale brightness down, from 1.0 (normal) to 0.0 (black); gamma backend only
+    Dim { brightness: f32 },
+    /// Restore the monitor
+    Off,
+}
+
+impl Adjustment {
+    fn validate(self) -> Result<Self, String> {
+        match self {
+            Adjustment::ShadowLift { intensity } | Adjustment::Dim { brightness: intensity }
+                if !(0.0..=1.0).contains(&intensity) =>
+            {
+                Err(i18n::format("error.unit_interval", &[&i18n::text("field.adjustment_level"), &intensity]))
+            }
+            _ => Ok(self),
+        }
+    }
+}
+
+/// Drives one adjustment backend
+///
+/// Adjustments stay applied after the controller is dropped; call `restore` to undo them.
+/// Settings such as curve constants are read from the Noctis config folder, so an embedding
+/// program sees the same curves the user tuned in the app
+#[derive(Clone)]
+pub struct Controller {
+    backend: Arc<dyn Backend>,
+}
+
+impl Controller {
+    /// Controller using the gamma ramp backend
+    pub fn new() -> Result<Self, String> {
+        Self::with_backend("gamma")
+    }
+
+    /// Controller using a registered backend by id, see `backends`
+    pub fn with_backend(id: &str) -> Result<Self, String> {
+        Ok(Self { backend: backend::get(id)? })
+    }
+
+    /// Ids, capabilities and availability of every registered backend
+    pub fn backends() -> Vec<backend::BackendInfo> {
+        backend::list()
+    }
+
+    /// Id of the backend this controller drives
+    pub fn backend(&self) -> &'static str {
+        self.backend.id()
+    }
+
+    /// Connected monitors
+    pub fn monitors(&self) -> Vec<Monitor> {
+        gamma::get_monitors()
+            .into_iter()
+            .map(|m| Monitor {
+                id: MonitorId(m.index),
+                name: m.name,
+                x: m.x,
+                y: m.y,
+                width: m.width,
+                height: m.height,
+                primary: m.is_primary,
+            })
+            .collect()
+    }
+
+    fn monitor(&self, id: MonitorId) -> Result<Monitor, String> {
+        self.monitors().into_iter().find(|m| m.id == id).ok_or_else(|| i18n::format("error.monitor_not_found", &[&id.0]))
+    }
+
+    /// Apply an adjustment to a monitor
+    /// Backends without per-monitor control (Magnification) adjust every monitor at once
+    pub fn apply(&self, monitor: MonitorId, adjustment: Adjustment) -> Result<(), String> {
+        self.monitor(monitor)?;
+        match adjustment.validate()? {
+            Adjustment::ShadowLift { intensity } => self.backend.apply(intensity, monitor.0),
+            Adjustment::Dim { brightness } if self.backend.id() == "gamma" => gamma::dim_monitor(brightness, monitor.0),
+            Adjustment::Dim { .. } => Err(i18n::format("error.backend_cannot_dim", &[&self.backend.id()])),
+            Adjustment::Off => self.backend.remove(monitor.0),
+        }
+    }
+
+    /// Apply an adjustment to every monitor
+    pub fn apply_all(&self, adjustment: Adjustment) -> Result<(), String> {
+        self.monitors().into_iter().try_for_each(|m| self.apply(m.id, adjustment))
+    }
+
+    /// Shadow-lift intensity currently applied to a monitor by any backend, including the app
+    pub fn intensity(&self, monitor: MonitorId) -> f32 {
+        night_mode::intensity_of(night_mode::Source::Gamma(monitor.0))
+            .max(night_mode::intensity_of(night_mode::Source::Magnification))
+    }
+
+    /// Measure the brightness of what a monitor shows, from 0.0 (black) to 1.0 (white)
+    pub fn brightness(&self, monitor: MonitorId) -> Result<f32, String> {
+        let m = self.monitor(monitor)?;
+        sensor::get_screen_brightness(m.x, m.y, m.width as i32, m.height as i32)
+    }
+
+    /// Undo every adjustment on every monitor, whichever backend made it
+    pub fn restore(&self) {
+        gamma::restore_all();
+        let _ = magnification::remove_effects();
+        crate::icc_profile::remove_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjustment_levels_must_be_unit_interval() {
+        assert!(Adjustment::ShadowLift { intensity: 0.0 }.validate().is_ok());
+        assert!(Adjustment::ShadowLift { intensity: 1.0 }.validate().is_ok());
+        assert!(Adjustment::ShadowLift { intensity: 1.5 }.validate().is_err());
+        assert!(Adjustment::Dim { brightness: -0.1 }.validate().is_err());
+        assert!(Adjustment::Dim { brightness: f32::NAN }.validate().is_err());
+        assert!(Adjustment::Off.validate().is_ok());
+    }
+
+    #[test]
+    fn adjustment_serializes_with_kind_tag() {
+        let json = serde_json::to_value(Adjustment::ShadowLift { intensity: 0.5 }).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "shadow_lift", "intensity": 0.5 }));
+        let off: Adjustment = serde_json::from_str(r#"{"kind":"off"}"#).unwrap();
+        assert_eq!(off, Adjustment::Off);
+    }
+
+    #[test]
+    fn monitor_id_displays_index() {
+        assert_eq!(MonitorId(2).to_string(), "monitor 2");
+    }
+
+    #[test]
+    fn unknown_backend_is_rejected() {
+        assert!(Controller::with_backend("no-such-backend").is_err());
+    }
+}
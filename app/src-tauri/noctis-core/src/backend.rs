@@ -0,0 +1,222 @@
+//! Adjustment backends - the `Backend` trait every output mechanism implements, and the registry
+//! callers look them up in by id. Gamma, Magnification and ICC are built in; other backends
+//! (vendor APIs, DDC/CI, overlays) register themselves from their own module or crate with
+//! `register`, and show up in `list` without changes to the code driving them
+
+use serde::Serialize;
+use std::sync::{Arc, Mutex, Once};
+
+/// What a backend can do, shown to the user when picking one
+#[derive(Serialize, Clone, Copy, PartialEq, Debug, Default)]
+pub struct Capabilities {
+    /// Each monitor can be adjusted on its own
+    pub per_monitor: bool,
+    /// Applies an arbitrary tone curve rather than a linear lift
+    pub curve: bool,
+    /// Can mix color channels (tints, color filters)
+    pub color_matrix: bool,
+    /// Applied by the display itself, e.g. over DDC/CI
+    pub hardware: bool,
+}
+
+/// An output mechanism that adjusts what the display shows
+pub trait Backend: Send + Sync {
+    /// Stable identifier, e.g. "gamma"
+    fn id(&self) -> &'static str;
+
+    /// Name shown in the UI
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// Check the backend works on this system; called once when it is registered
+    fn init(&self) -> Result<(), String>;
+
+    /// Lift shadows at an intensity from 0.0 (normal) to 1.0 (max)
+    fn apply(&self, intensity: f32, monitor: u32) -> Result<(), String>;
+
+    /// Restore the monitor to normal
+    fn remove(&self, monitor: u32) -> Result<(), String>;
+}
+
+/// A backend as reported to the UI
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendInfo {
+    pub id: String,
+    pub name: String,
+    pub capabilities: Capabilities,
+    /// `init` succeeded
+    pub available: bool,
+    /// Why the backend isn't available
+    pub error: Option<String>,
+}
+
+struct Registered {
+    backend: Arc<dyn Backend>,
+    status: Result<(), String>,
+}
+
+static REGISTRY: Mutex<Vec<Registered>> = Mutex::new(Vec::new());
+static BUILTIN: Once = Once::new();
+
+/// Add a backend; ids must be unique
+pub fn register(backend: Arc<dyn Backend>) -> Result<(), String> {
+    register_builtin();
+    add(backend)
+}
+
+fn add(backend: Arc<dyn Backend>) -> Result<(), String> {
+    if REGISTRY.lock().unwrap().iter().any(|r| r.backend.id() == backend.id()) {
+        return Err(crate::i18n::format("error.backend_registered", &[&backend.id()]));
+    }
+    // Initialized outside the lock: a backend may take a while to probe its device
+    let status = backend.init();
+    if let Err(e) = &status {
+        crate::crash::log(format!("Backend {} unavailable: {}", backend.id(), e));
+    }
+    REGISTRY.lock().unwrap().push(Registered { backend, status });
+    Ok(())
+}
+
+fn register_builtin() {
+    BUILTIN.call_once(|| {
+        let _ = add(Arc::new(Gamma));
+        let _ = add(Arc::new(Magnification));
+        let _ = add(Arc::new(Icc));
+    });
+}
+
+/// Look up an available backend by id
+pub fn get(id: &str) -> Result<Arc<dyn Backend>, String> {
+    register_builtin();
+    let registry = REGISTRY.lock().unwrap();
+    let registered = registry
+        .iter()
+        .find(|r| r.backend.id() == id)
+        .ok_or_else(|| crate::i18n::format("error.backend_unknown", &[&id]))?;
+    registered.status.clone().map_err(|e| crate::i18n::format("error.backend_unavailable", &[&id, &e]))?;
+    Ok(registered.backend.clone())
+}
+
+/// Every registered backend, built-ins first
+pub fn list() -> Vec<BackendInfo> {
+    register_builtin();
+    REGISTRY
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|r| BackendInfo {
+            id: r.backend.id().to_string(),
+            name: r.backend.name().to_string(),
+            capabilities: r.backend.capabilities(),
+            available: r.status.is_ok(),
+            error: r.status.clone().err(),
+        })
+        .collect()
+}
+
+/// Device name of a monitor, which ICC profiles are associated with
+fn monitor_name(monitor: u32) -> Result<String, String> {
+    crate::gamma::get_monitors()
+        .into_iter()
+        .find(|m| m.index == monitor)
+        .map(|m| m.name)
+        .ok_or_else(|| crate::i18n::format("error.monitor_not_found", &[&monitor]))
+}
+
+/// Per-monitor gamma ramps
+struct Gamma;
+
+impl Backend for Gamma {
+    fn id(&self) -> &'static str {
+        "gamma"
+    }
+
+    fn name(&self) -> &'static str {
+        "Gamma ramp"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { per_monitor: true, curve: true, ..Default::default() }
+    }
+
+    fn init(&self) -> Result<(), String> {
+        let monitors = crate::gamma::get_monitors();
+        let primary = monitors
+            .iter()
+            .find(|m| m.is_primary)
+            .or(monitors.first())
+            .ok_or_else(|| crate::i18n::text("error.no_monitors"))?;
+        crate::gamma::probe(primary.index)
+    }
+
+    fn apply(&self, intensity: f32, monitor: u32) -> Result<(), String> {
+        crate::gamma::set_gamma(intensity, monitor)
+    }
+
+    fn remove(&self, monitor: u32) -> Result<(), String> {
+        crate::gamma::set_gamma(0.0, monitor)
+    }
+}
+
+/// Fullscreen Magnification color effect (all monitors at once)
+struct Magnification;
+
+impl Backend for Magnification {
+    fn id(&self) -> &'static str {
+        "magnification"
+    }
+
+    fn name(&self) -> &'static str {
+        "Magnification color effect"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { color_matrix: true, ..Default::default() }
+    }
+
+    fn init(&self) -> Result<(), String> {
+        crate::magnification::init()
+    }
+
+    fn apply(&self, intensity: f32, _monitor: u32) -> Result<(), String> {
+        crate::magnification::apply_shadow_lift(intensity)
+    }
+
+    fn remove(&self, _monitor: u32) -> Result<(), String> {
+        crate::magnification::remove_effects()
+    }
+}
+
+/// Shadow-lift ICC profile associated with the monitor
+struct Icc;
+
+impl Backend for Icc {
+    fn id(&self) -> &'static str {
+        "icc"
+    }
+
+    fn name(&self) -> &'static str {
+        "ICC profile"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { per_monitor: true, ..Default::default() }
+    }
+
+    fn init(&self) -> Result<(), String> {
+        if cfg!(windows) {
+            Ok(())
+        } else {
+            Err(crate::i18n::text("error.windows_only"))
+        }
+    }
+
+    fn apply(&self, intensity: f32, monitor: u32) -> Result<(), String> {
+        crate::icc_profile::apply_shadow_lift(intensity, &monitor_name(monitor)?)
+    }
+
+    fn remove(&self, monitor: u32) -> Result<(), String> {
+        crate::icc_profile::remove_shadow_lift(&monitor_name(monitor)?)
+    }
+}
@@ -0,0 +1,124 @@
+//! Backend comparison - applies one target lift through gamma, Magnification and ICC in turn,
+//! advancing on a hotkey, so users can judge which backend looks best on their panel
+//! The backend picked at the end is appended to `<config>/backend_choices.log`
+
+use crate::{backend, gamma};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CHOICES_FILE: &str = "backend_choices.log";
+
+/// Hotkey that moves to the next backend, registered only while a comparison runs
+pub const HOTKEY: &str = "Ctrl+Alt+B";
+
+/// A backend being compared
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Candidate {
+    Gamma,
+    Magnification,
+    Icc,
+}
+
+impl Candidate {
+    const ALL: [Candidate; 3] = [Candidate::Gamma, Candidate::Magnification, Candidate::Icc];
+
+    /// Id of the backend in the registry
+    fn id(self) -> &'static str {
+        match self {
+            Candidate::Gamma => "gamma",
+            Candidate::Magnification => "magnification",
+            Candidate::Icc => "icc",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Candidate::Gamma => Candidate::Magnification,
+            Candidate::Magnification => Candidate::Icc,
+            Candidate::Icc => Candidate::Gamma,
+        }
+    }
+}
+
+/// A comparison in progress
+#[derive(Serialize, Clone, Debug)]
+pub struct Comparison {
+    /// Target lift every backend applies (normalized per backend, see normalize.rs)
+    pub intensity: f32,
+    pub monitor: u32,
+    /// Backend currently showing
+    pub current: Candidate,
+}
+
+static ACTIVE: Mutex<Option<Comparison>> = Mutex::new(None);
+
+fn apply(candidate: Candidate, intensity: f32, monitor: u32) -> Result<(), String> {
+    backend::get(candidate.id())?.apply(intensity, monitor)
+}
+
+fn clear(candidate: Candidate, monitor: u32) {
+    if let Ok(backend) = backend::get(candidate.id()) {
+        let _ = backend.remove(monitor);
+    }
+}
+
+/// Start comparing at an intensity on a monitor, beginning with gamma
+/// Every backend is cleared first so only one shows at a time
+pub fn start(intensity: f32, monitor: u32) -> Result<Comparison, String> {
+    let mut active = ACTIVE.lock().unwrap();
+    if let Some(previous) = active.take() {
+        clear(previous.current, previous.monitor);
+    }
+    for candidate in Candidate::ALL {
+        clear(candidate, monitor);
+    }
+    apply(Candidate::Gamma, intensity, monitor)?;
+    let comparison = Comparison { intensity, monitor, current: Candidate::Gamma };
+    *active = Some(comparison.clone());
+    Ok(comparison)
+}
+
+/// Switch to the next backend
+pub fn next() -> Result<Comparison, String> {
+    let mut active = ACTIVE.lock().unwrap();
+    let comparison = active.as_mut().ok_or_else(|| crate::i18n::text("error.no_backend_comparison"))?;
+    clear(comparison.current, comparison.monitor);
+    comparison.current = comparison.current.next();
+    apply(comparison.current, comparison.intensity, comparison.monitor)?;
+    Ok(comparison.clone())
+}
+
+/// The comparison in progress, if any
+pub fn current() -> Option<Comparison> {
+    ACTIVE.lock().unwrap().clone()
+}
+
+/// Append the pick to the choices log
+fn log_choice(comparison: &Comparison, chosen: Candidate) -> Result<(), String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let monitor = gamma::get_monitors()
+        .into_iter()
+        .find(|m| m.index == comparison.monitor)
+        .map_or_else(|| comparison.monitor.to_string(), |m| m.name);
+    let line = format!("{} monitor={} intensity={:.2} chosen={:?}", now, monitor, comparison.intensity, chosen);
+    crate::crash::log(format!("Backend comparison: {}", line));
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(crate::settings::config_dir().join(CHOICES_FILE))
+        .map_err(|e| format!("Failed to open backend choices log: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to log backend choice: {}", e))
+}
+
+/// End the comparison, clearing its effect, and log the backend the user chose, if any
+pub fn finish(chosen: Option<Candidate>) -> Result<(), String> {
+    let Some(comparison) = ACTIVE.lock().unwrap().take() else { return Ok(()) };
+    clear(comparison.current, comparison.monitor);
+    match chosen {
+        Some(chosen) => log_choice(&comparison, chosen),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,46 @@
+//! Background mode - runs without the webview: tray, hotkeys and auto-adjust only
+//! Enabled in settings or with the `--background` flag; the window is only created if it's
+//! opened from the tray, and until then auto-adjust runs here instead of in the UI
+
+use crate::{gamma, magnification, profiles, sensor, toggle};
+use std::thread;
+use std::time::Duration;
+
+/// Command line flag that starts Noctis in background mode
+const BACKGROUND_FLAG: &str = "--background";
+
+/// Check whether Noctis should start without its window
+pub fn is_enabled() -> bool {
+    std::env::args().any(|arg| arg == BACKGROUND_FLAG) || crate::settings::get().background_mode
+}
+
+/// Monitor auto-adjust samples: the active profile's, else the primary
+fn target_monitor() -> Option<gamma::MonitorInfo> {
+    let monitors = gamma::get_monitors();
+    let index = profiles::active().map(|p| p.monitor);
+    monitors
+        .iter()
+        .find(|m| Some(m.index) == index)
+        .or_else(|| monitors.iter().find(|m| m.is_primary))
+        .cloned()
+}
+
+/// Start the auto-adjust loop, which runs while the toggle is on and `ui_open` is false
+pub fn start_auto_adjust<F>(ui_open: F)
+where
+    F: Fn() -> bool + Send + 'static,
+{
+    thread::spawn(move || {
+        while !crate::shutdown::is_shutting_down() {
+            let interval_ms = crate::frame_guard::interval_ms(profiles::sensing().sensor.interval_ms);
+            thread::sleep(Duration::from_millis(interval_ms));
+            if !toggle::is_active() || ui_open() {
+                continue;
+            }
+            let Some(monitor) = target_monitor() else { continue };
+            if let Ok(brightness) = sensor::get_screen_brightness(monitor.x, monitor.y, monitor.width as i32, monitor.height as i32) {
+                let _ = magnification::apply_smart_adjustment(brightness);
+            }
+        }
+    });
+}
@@ -0,0 +1,53 @@
+//! Per-application blocklist
+//! Suspends all effects while a color-critical app (photo/video editor) is in the foreground
+
+use crate::foreground::ForegroundApp;
+use crate::settings;
+use crate::suspend;
+
+/// Suspension reason used by the blocklist
+const REASON: &str = "blocklist";
+
+/// Check whether a process name is blocklisted (case-insensitive)
+pub fn is_blocked(process: &str) -> bool {
+    settings::get().blocklist.iter().any(|p| p.eq_ignore_ascii_case(process))
+}
+
+/// Suspend or resume effects for the given foreground app
+/// Returns Some(true) if effects were suspended, Some(false) if resumed, None if unchanged
+pub fn enforce(app: Option<&ForegroundApp>) -> Option<bool> {
+    let blocked = app.map(|a| is_blocked(&a.process)).unwrap_or(false);
+    if blocked && suspend::suspend(REASON) {
+        Some(true)
+    } else if !blocked && suspend::resume(REASON) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+pub fn list() -> Vec<String> {
+    settings::get().blocklist
+}
+
+/// Add a process name to the blocklist
+pub fn add(process: &str) -> Result<Vec<String>, String> {
+    let process = process.trim();
+    if process.is_empty() {
+        return Err(crate::i18n::text("error.process_empty"));
+    }
+    let settings = settings::update(|s| {
+        if !s.blocklist.iter().any(|p| p.eq_ignore_ascii_case(process)) {
+            s.blocklist.push(process.to_string());
+        }
+    })?;
+    Ok(settings.blocklist)
+}
+
+/// Remove a process name from the blocklist
+pub fn remove(process: &str) -> Result<Vec<String>, String> {
+    let settings = settings::update(|s| {
+        s.blocklist.retain(|p| !p.eq_ignore_ascii_case(process.trim()));
+    })?;
+    Ok(settings.blocklist)
+}
@@ -0,0 +1,128 @@
+//! Low-blue-light presets - color temperature settings chosen to meet a melanopic reduction
+//! target, and a report estimating how much blue light the temperature curve removes
+//! Estimates assume an sRGB panel; real panels differ, so they are a guide, not a measurement
+
+use crate::temperature::{self, Coupling, TemperatureSettings, NEUTRAL_KELVIN};
+use serde::{Deserialize, Serialize};
+
+/// Share of an sRGB white's melanopic stimulus (CIE S 026) each primary contributes
+const MELANOPIC_WEIGHTS: [f32; 3] = [0.02, 0.40, 0.58];
+
+/// Share of an sRGB white's luminance each primary contributes (Rec. 709)
+const LUMINANCE_WEIGHTS: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Display gamma, to turn the matrix's signal gains into light
+const DISPLAY_GAMMA: f32 = 2.2;
+
+/// Warmest temperature a preset may pick
+const MIN_PRESET_KELVIN: f32 = 1900.0;
+
+/// Bisection steps when solving for a preset's temperature, ~5K resolution
+const SOLVE_STEPS: u32 = 10;
+
+/// Low-blue-light targets, by melanopic reduction at the deepest part of the night
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BlueLightPreset {
+    /// At least half the melanopic stimulus removed
+    Moderate,
+    /// At least three quarters removed, as often recommended for the hour before sleep
+    Strong,
+}
+
+impl BlueLightPreset {
+    const ALL: [BlueLightPreset; 2] = [BlueLightPreset::Moderate, BlueLightPreset::Strong];
+
+    /// Melanopic reduction (0.0 - 1.0) the preset meets
+    pub fn target(self) -> f32 {
+        match self {
+            BlueLightPreset::Moderate => 0.5,
+            BlueLightPreset::Strong => 0.75,
+        }
+    }
+}
+
+/// Estimated effect of a color temperature on white, each reduction 0.0 - 1.0
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Estimate {
+    pub kelvin: f32,
+    /// Melanopic stimulus removed, the estimate of circadian impact
+    pub melanopic_reduction: f32,
+    /// Light from the blue primary removed
+    pub blue_reduction: f32,
+    /// Overall brightness removed
+    pub luminance_reduction: f32,
+}
+
+/// Whether the temperature curve meets a preset's target
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct TargetCheck {
+    pub preset: BlueLightPreset,
+    pub target: f32,
+    pub met: bool,
+}
+
+/// Blue light the temperature curve removes right now and at its warmest
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct BlueLightReport {
+    /// Temperature coupling is on; without it nothing is removed
+    pub enabled: bool,
+    /// What is removed now (night coupling) or on a fully dark scene (scene coupling)
+    pub now: Estimate,
+    /// What is removed at the deepest part of the night or the darkest scene
+    pub warmest: Estimate,
+    pub targets: Vec<TargetCheck>,
+}
+
+/// Estimate the effect of warming white to a temperature
+pub fn estimate(kelvin: f32) -> Estimate {
+    let light = temperature::channel_gains(kelvin).map(|gain| gain.powf(DISPLAY_GAMMA));
+    let weighted = |weights: [f32; 3]| (0..3).map(|i| weights[i] * light[i]).sum::<f32>();
+    Estimate {
+        kelvin,
+        melanopic_reduction: 1.0 - weighted(MELANOPIC_WEIGHTS),
+        blue_reduction: 1.0 - light[2],
+        luminance_reduction: 1.0 - weighted(LUMINANCE_WEIGHTS),
+    }
+}
+
+/// Warmest-point temperature of a preset: the mildest one that meets its target
+pub fn preset_kelvin(preset: BlueLightPreset) -> f32 {
+    // Reduction grows as the temperature drops, so bisect for the highest one that still meets it
+    let (mut warm, mut cool) = (MIN_PRESET_KELVIN, NEUTRAL_KELVIN);
+    for _ in 0..SOLVE_STEPS {
+        let mid = (warm + cool) / 2.0;
+        if estimate(mid).melanopic_reduction >= preset.target() {
+            warm = mid;
+        } else {
+            cool = mid;
+        }
+    }
+    warm.floor()
+}
+
+/// Temperature settings for a preset: warming with the night down to the preset's temperature
+pub fn preset_settings(preset: BlueLightPreset) -> TemperatureSettings {
+    TemperatureSettings {
+        enabled: true,
+        coupling: Coupling::Night,
+        warmest_kelvin: preset_kelvin(preset),
+    }
+}
+
+/// Report on the temperature curve in the settings
+pub fn report() -> BlueLightReport {
+    let config = crate::settings::get().temperature;
+    let hour = crate::schedule::local_hour() as f32;
+    let now = temperature::target_kelvin(&config, 0.0, hour).unwrap_or(NEUTRAL_KELVIN);
+    let warmest = estimate(if config.enabled { config.warmest_kelvin } else { NEUTRAL_KELVIN });
+    BlueLightReport {
+        enabled: config.enabled,
+        now: estimate(now),
+        targets: BlueLightPreset::ALL
+            .iter()
+            .map(|&preset| TargetCheck { preset, target: preset.target(), met: warmest.melanopic_reduction >= preset.target() })
+            .collect(),
+        warmest,
+    }
+}
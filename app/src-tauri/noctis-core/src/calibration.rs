@@ -0,0 +1,406 @@
+//! Display calibration - renders gray patches on a monitor and samples them through the sensor
+//! Black-level calibration finds the lowest lift that makes near-black detail visible;
+//! the measurement report estimates the panel's response and tailors the curve constants
+
+use crate::gamma::{self, CurveConstants};
+use crate::sensor;
+use crate::settings::{self, PanelTuning};
+use std::ffi::c_void;
+use std::ptr;
+use std::thread;
+use std::time::Duration;
+
+/// Gray levels rendered as patches (0-255), dark enough to be crushed on most panels
+const PATCH_LEVELS: [u8; 9] = [0, 3, 6, 9, 12, 15, 18, 21, 24];
+
+/// Gray levels rendered for the response measurement, denser near black
+const RESPONSE_LEVELS: [u8; 15] = [0, 4, 8, 12, 16, 24, 32, 48, 64, 96, 128, 160, 192, 224, 255];
+const PATCH_SIZE: i32 = 48;
+
+/// Lift levels swept during calibration
+const SWEEP_STEPS: u32 = 20;
+
+/// Output difference (in 8-bit levels) needed to tell two adjacent patches apart
+const MIN_VISIBLE_STEP: f32 = 4.0;
+
+/// Time for DWM to present the patches / settle after a ramp change
+const PRESENT_DELAY: Duration = Duration::from_millis(150);
+const SETTLE_DELAY: Duration = Duration::from_millis(30);
+
+/// Measurement at one lift level
+#[derive(serde::Serialize, Clone)]
+pub struct CalibrationLevel {
+    pub intensity: f32,
+    /// Patch colors as displayed at this level (luminance, 0-255)
+    pub displayed: Vec<u8>,
+    /// Number of adjacent patch pairs that can be told apart
+    pub distinguishable_steps: usize,
+}
+
+/// Result of a calibration run
+#[derive(serde::Serialize, Clone)]
+pub struct CalibrationResult {
+    pub monitor: u32,
+    /// Gray levels rendered for each patch
+    pub patches: Vec<u8>,
+    pub levels: Vec<CalibrationLevel>,
+    /// Lowest intensity at which every near-black step is distinguishable
+    /// (the strongest tested level if none fully resolve them)
+    pub suggested_intensity: f32,
+}
+
+/// Measured response of a panel
+#[derive(serde::Serialize, Clone)]
+pub struct PanelReport {
+    pub monitor: u32,
+    /// Monitor device name the tuning is stored under
+    pub name: String,
+    /// (input level, measured output level) for each patch
+    pub response: Vec<(u8, u8)>,
+    /// Approximate gamma relative to the signal (1.0 = neutral, above 1.0 = darker midtones)
+    pub gamma: f32,
+    /// Highest input level that still displays as black
+    pub black_crush: u8,
+    /// Curve constants tailored to this panel, now used by set_gamma
+    pub curve: CurveConstants,
+}
+
+/// RECT structure
+#[repr(C)]
+struct Rect {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+type WndProc = unsafe extern "system" fn(*mut c_void, u32, usize, isize) -> isize;
+
+/// WNDCLASSW structure
+#[repr(C)]
+struct WndClass {
+    style: u32,
+    wnd_proc: Option<WndProc>,
+    cls_extra: i32,
+    wnd_extra: i32,
+    instance: *mut c_void,
+    icon: *mut c_void,
+    cursor: *mut c_void,
+    background: *mut c_void,
+    menu_name: *const u16,
+    class_name: *const u16,
+}
+
+const WS_POPUP: u32 = 0x80000000;
+const WS_VISIBLE: u32 = 0x10000000;
+const WS_EX_TOPMOST: u32 = 0x00000008;
+const WS_EX_TOOLWINDOW: u32 = 0x00000080;
+const BLACK_BRUSH: i32 = 4;
+
+#[cfg(windows)]
+#[link(name = "user32")]
+extern "system" {
+    fn RegisterClassW(class: *const WndClass) -> u16;
+    fn CreateWindowExW(
+        ex_style: u32,
+        class_name: *const u16,
+        window_name: *const u16,
+        style: u32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        parent: *mut c_void,
+        menu: *mut c_void,
+        instance: *mut c_void,
+        param: *mut c_void,
+    ) -> *mut c_void;
+    fn DestroyWindow(hwnd: *mut c_void) -> i32;
+    fn DefWindowProcW(hwnd: *mut c_void, msg: u32, w_param: usize, l_param: isize) -> isize;
+    fn UpdateWindow(hwnd: *mut c_void) -> i32;
+    fn GetDC(hwnd: *mut c_void) -> *mut c_void;
+    fn ReleaseDC(hwnd: *mut c_void, hdc: *mut c_void) -> i32;
+    fn FillRect(hdc: *mut c_void, rect: *const Rect, brush: *mut c_void) -> i32;
+}
+
+#[cfg(windows)]
+#[link(name = "gdi32")]
+extern "system" {
+    fn CreateSolidBrush(color: u32) -> *mut c_void;
+    fn DeleteObject(object: *mut c_void) -> i32;
+    fn GetStockObject(object: i32) -> *mut c_void;
+    fn GdiFlush() -> i32;
+}
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetModuleHandleW(module_name: *const u16) -> *mut c_void;
+}
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Show a topmost strip of gray patches, returning the window handle
+#[cfg(windows)]
+fn show_patches(x: i32, y: i32, levels: &[u8]) -> Result<*mut c_void, String> {
+    let class_name = to_wide("NoctisCalibrationPatches");
+    let width = PATCH_SIZE * levels.len() as i32;
+
+    unsafe {
+        let instance = GetModuleHandleW(ptr::null());
+        let class = WndClass {
+            style: 0,
+            wnd_proc: Some(DefWindowProcW),
+            cls_extra: 0,
+            wnd_extra: 0,
+            instance,
+            icon: ptr::null_mut(),
+            cursor: ptr::null_mut(),
+            background: GetStockObject(BLACK_BRUSH),
+            menu_name: ptr::null(),
+            class_name: class_name.as_ptr(),
+        };
+        // Fails harmlessly if already registered by an earlier run
+        RegisterClassW(&class);
+
+        let hwnd = CreateWindowExW(
+            WS_EX_TOPMOST | WS_EX_TOOLWINDOW,
+            class_name.as_ptr(),
+            class_name.as_ptr(),
+            WS_POPUP | WS_VISIBLE,
+            x,
+            y,
+            width,
+            PATCH_SIZE,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            instance,
+            ptr::null_mut(),
+        );
+        if hwnd.is_null() {
+            return Err(crate::i18n::text("error.calibration_window"));
+        }
+        UpdateWindow(hwnd);
+
+        let hdc = GetDC(hwnd);
+        for (i, level) in levels.iter().enumerate() {
+            let level = *level as u32;
+            let brush = CreateSolidBrush(level | (level << 8) | (level << 16));
+            let rect = Rect {
+                left: i as i32 * PATCH_SIZE,
+                top: 0,
+                right: (i as i32 + 1) * PATCH_SIZE,
+                bottom: PATCH_SIZE,
+            };
+            FillRect(hdc, &rect, brush);
+            DeleteObject(brush);
+        }
+        GdiFlush();
+        ReleaseDC(hwnd, hdc);
+        Ok(hwnd)
+    }
+}
+
+#[cfg(windows)]
+fn hide_patches(hwnd: *mut c_void) {
+    unsafe {
+        DestroyWindow(hwnd);
+    }
+}
+
+#[cfg(not(windows))]
+fn show_patches(_x: i32, _y: i32, _levels: &[u8]) -> Result<*mut c_void, String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+#[cfg(not(windows))]
+fn hide_patches(_hwnd: *mut c_void) {}
+
+fn luminance(rgb: [u8; 3]) -> u8 {
+    ((rgb[0] as u32 + rgb[1] as u32 + rgb[2] as u32) / 3) as u8
+}
+
+/// Count adjacent patch pairs whose displayed levels differ enough to be seen
+fn distinguishable_steps(displayed: &[u8]) -> usize {
+    displayed
+        .windows(2)
+        .filter(|pair| (pair[1] as f32 - pair[0] as f32) >= MIN_VISIBLE_STEP)
+        .count()
+}
+
+/// Top-left corner of a patch strip centered on a monitor
+fn strip_origin(info: &gamma::MonitorInfo, patches: usize) -> (i32, i32) {
+    let strip_width = PATCH_SIZE * patches as i32;
+    (
+        info.x + (info.width as i32 - strip_width) / 2,
+        info.y + (info.height as i32 - PATCH_SIZE) / 2,
+    )
+}
+
+fn find_monitor(monitor: u32) -> Result<gamma::MonitorInfo, String> {
+    gamma::get_monitors()
+        .into_iter()
+        .find(|m| m.index == monitor)
+        .ok_or_else(|| crate::i18n::format("error.monitor_not_found", &[&monitor]))
+}
+
+/// Sample the displayed luminance at the center of each patch in a strip
+fn sample_patches(left: i32, top: i32, patches: usize) -> Result<Vec<u8>, String> {
+    (0..patches as i32)
+        .map(|i| {
+            let x = left + i * PATCH_SIZE + PATCH_SIZE / 2;
+            let y = top + PATCH_SIZE / 2;
+            sensor::sample_pixel(x, y).map(|sample| luminance(sample.transformed))
+        })
+        .collect()
+}
+
+/// Put back the ramp a monitor had before a calibration run
+fn restore_ramp(monitor: u32, previous: Option<gamma::GammaRamp>) {
+    let _ = match previous {
+        Some(ramp) => gamma::set_ramp(monitor, &ramp),
+        None => gamma::reset_monitor(monitor),
+    };
+}
+
+/// Sweep lift levels on a monitor and suggest the lowest one that resolves near-black detail
+/// The monitor's previous ramp is restored afterwards
+pub fn auto_calibrate(monitor: u32) -> Result<CalibrationResult, String> {
+    let info = find_monitor(monitor)?;
+    let (left, top) = strip_origin(&info, PATCH_LEVELS.len());
+
+    let previous = gamma::active_ramp(monitor);
+    let hwnd = show_patches(left, top, &PATCH_LEVELS)?;
+    thread::sleep(PRESENT_DELAY);
+
+    let mut levels = Vec::with_capacity(SWEEP_STEPS as usize + 1);
+    let mut error = None;
+    for step in 0..=SWEEP_STEPS {
+        let intensity = step as f32 / SWEEP_STEPS as f32;
+        if let Err(e) = gamma::set_gamma(intensity, monitor) {
+            error = Some(e);
+            break;
+        }
+        thread::sleep(SETTLE_DELAY);
+
+        let displayed = match sample_patches(left, top, PATCH_LEVELS.len()) {
+            Ok(displayed) => displayed,
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        };
+
+        levels.push(CalibrationLevel {
+            intensity,
+            distinguishable_steps: distinguishable_steps(&displayed),
+            displayed,
+        });
+    }
+
+    hide_patches(hwnd);
+    restore_ramp(monitor, previous);
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    let all_steps = PATCH_LEVELS.len() - 1;
+    let suggested_intensity = levels
+        .iter()
+        .find(|level| level.distinguishable_steps == all_steps)
+        .or(levels.last())
+        .map(|level| level.intensity)
+        .unwrap_or(0.0);
+
+    Ok(CalibrationResult {
+        monitor,
+        patches: PATCH_LEVELS.to_vec(),
+        levels,
+        suggested_intensity,
+    })
+}
+
+/// Highest input level whose output is indistinguishable from black
+fn black_crush(response: &[(u8, u8)]) -> u8 {
+    let black = response.first().map(|(_, output)| *output).unwrap_or(0) as f32;
+    response
+        .iter()
+        .take_while(|(_, output)| (*output as f32 - black) < MIN_VISIBLE_STEP)
+        .last()
+        .map(|(input, _)| *input)
+        .unwrap_or(0)
+}
+
+/// Fit output = input^gamma (normalized) by least squares in log space
+/// Crushed and clipped patches carry no slope information and are skipped
+fn fit_gamma(response: &[(u8, u8)], crush: u8) -> f32 {
+    let (mut xy, mut xx) = (0.0f32, 0.0f32);
+    for (input, output) in response {
+        if *input <= crush || *input == 255 || *output == 0 {
+            continue;
+        }
+        let x = (*input as f32 / 255.0).ln();
+        let y = (*output as f32 / 255.0).ln();
+        xy += x * y;
+        xx += x * x;
+    }
+    if xx == 0.0 {
+        1.0
+    } else {
+        xy / xx
+    }
+}
+
+/// Derive curve constants for a measured panel
+/// Panels that crush more of the low end get more lift; darker midtones get a stronger gamma
+fn tailor_curve(gamma: f32, crush: u8) -> CurveConstants {
+    let defaults = CurveConstants::default();
+    CurveConstants {
+        max_lift: (defaults.max_lift + crush as f32 / 255.0).clamp(0.15, 0.4),
+        min_gamma: (defaults.min_gamma / gamma.max(0.1)).clamp(0.35, 0.7),
+    }
+}
+
+/// Measure a monitor's effective response and store curve constants tailored to it
+/// The monitor is measured unadjusted; its previous ramp is restored afterwards
+pub fn measure_panel(monitor: u32) -> Result<PanelReport, String> {
+    let info = find_monitor(monitor)?;
+    let (left, top) = strip_origin(&info, RESPONSE_LEVELS.len());
+
+    let previous = gamma::active_ramp(monitor);
+    let hwnd = show_patches(left, top, &RESPONSE_LEVELS)?;
+    let measured = gamma::set_ramp(monitor, &gamma::identity_ramp()).and_then(|_| {
+        thread::sleep(PRESENT_DELAY);
+        sample_patches(left, top, RESPONSE_LEVELS.len())
+    });
+    hide_patches(hwnd);
+    restore_ramp(monitor, previous);
+
+    let response: Vec<(u8, u8)> = RESPONSE_LEVELS.iter().copied().zip(measured?).collect();
+    let crush = black_crush(&response);
+    let gamma = fit_gamma(&response, crush);
+    let curve = tailor_curve(gamma, crush);
+
+    settings::update(|s| {
+        s.panel_tuning.retain(|t| t.monitor != info.name);
+        s.panel_tuning.push(PanelTuning { monitor: info.name.clone(), curve });
+    })?;
+
+    Ok(PanelReport {
+        monitor,
+        name: info.name,
+        response,
+        gamma,
+        black_crush: crush,
+        curve,
+    })
+}
+
+/// Forget a monitor's measured tuning, returning it to the default curve constants
+pub fn reset_panel(monitor: u32) -> Result<(), String> {
+    let info = find_monitor(monitor)?;
+    settings::update(|s| s.panel_tuning.retain(|t| t.monitor != info.name))?;
+    Ok(())
+}
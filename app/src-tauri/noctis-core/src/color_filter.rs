@@ -0,0 +1,167 @@
+//! Windows color filters interop - the Accessibility color filters (Settings > Accessibility >
+//! Color filters) are applied on top of our Magnification effect, so an inverted desktop would
+//! turn our shadow lift into a highlight crush. Invert filters are compensated by wrapping the
+//! matrix in the inversion; filters that can't be undone are reported as double application
+
+use crate::magnification::MagColorEffect;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How often the color filter setting is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A Windows color filter, numbered as in the FilterType registry value
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorFilter {
+    Grayscale,
+    Inverted,
+    GrayscaleInverted,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl ColorFilter {
+    fn from_registry(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(ColorFilter::Grayscale),
+            1 => Some(ColorFilter::Inverted),
+            2 => Some(ColorFilter::GrayscaleInverted),
+            3 => Some(ColorFilter::Deuteranopia),
+            4 => Some(ColorFilter::Protanopia),
+            5 => Some(ColorFilter::Tritanopia),
+            _ => None,
+        }
+    }
+
+    /// Inverting filters can be undone around our matrix; grayscale leaves a neutral lift as is
+    fn inverts(self) -> bool {
+        matches!(self, ColorFilter::Inverted | ColorFilter::GrayscaleInverted)
+    }
+}
+
+/// Color filter state reported to the UI
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct SystemColorFilter {
+    /// A Windows color filter is turned on
+    pub active: bool,
+    pub filter: Option<ColorFilter>,
+    /// Our matrix is adjusted so the filter doesn't distort it
+    pub compensated: bool,
+    /// The filter is applied on top of a Noctis effect it distorts
+    pub double_applied: bool,
+}
+
+/// Filter seen by the last poll
+static ACTIVE_FILTER: Mutex<Option<ColorFilter>> = Mutex::new(None);
+
+#[cfg(windows)]
+fn read_filter() -> Option<ColorFilter> {
+    use std::ffi::c_void;
+
+    const HKEY_CURRENT_USER: isize = 0x8000_0001u32 as i32 as isize;
+    const RRF_RT_REG_DWORD: u32 = 0x0000_0010;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn RegGetValueW(key: isize, sub_key: *const u16, value: *const u16, flags: u32, kind: *mut u32, data: *mut c_void, size: *mut u32) -> i32;
+    }
+
+    let read_dword = |name: &str| {
+        let path: Vec<u16> = r"Software\Microsoft\ColorFiltering".encode_utf16().chain(std::iter::once(0)).collect();
+        let name: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut value = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                path.as_ptr(),
+                name.as_ptr(),
+                RRF_RT_REG_DWORD,
+                std::ptr::null_mut(),
+                &mut value as *mut _ as *mut c_void,
+                &mut size,
+            )
+        };
+        (status == 0).then_some(value)
+    };
+
+    if read_dword("Active")? == 0 {
+        return None;
+    }
+    // Windows defaults to grayscale until a filter is picked
+    ColorFilter::from_registry(read_dword("FilterType").unwrap_or(0))
+}
+
+#[cfg(not(windows))]
+fn read_filter() -> Option<ColorFilter> {
+    None
+}
+
+/// Matrix that inverts every channel: output = 1 - input
+fn inversion() -> MagColorEffect {
+    let mut effect = MagColorEffect::identity();
+    for channel in 0..3 {
+        effect.transform[channel][channel] = -1.0;
+        effect.transform[4][channel] = 1.0;
+    }
+    effect
+}
+
+/// Effect to write so that, once the color filter is applied on top, the screen shows `effect`
+/// applied to the filtered desktop
+pub fn compensate(effect: &MagColorEffect) -> MagColorEffect {
+    let filter = *ACTIVE_FILTER.lock().unwrap();
+    match filter {
+        Some(filter) if filter.inverts() && crate::settings::get().compensate_color_filter => {
+            inversion().then(effect).then(&inversion())
+        }
+        _ => *effect,
+    }
+}
+
+/// Current color filter state, for `get_system_color_filter`
+pub fn state() -> SystemColorFilter {
+    let filter = read_filter();
+    *ACTIVE_FILTER.lock().unwrap() = filter;
+    let compensated = filter.is_some_and(|f| f.inverts()) && crate::settings::get().compensate_color_filter;
+    let Some(effect) = crate::magnification::recorded_effect().filter(|e| e.transform != MagColorEffect::identity().transform) else {
+        return SystemColorFilter { active: filter.is_some(), filter, compensated, double_applied: false };
+    };
+    // A grayscale filter only drops tint, which a plain lift doesn't have
+    let [r, g, b] = effect.apply([128, 128, 128]);
+    let double_applied = match filter {
+        Some(ColorFilter::Grayscale) => r != g || g != b,
+        Some(_) => !compensated,
+        None => false,
+    };
+    SystemColorFilter { active: filter.is_some(), filter, compensated, double_applied }
+}
+
+/// Start a background thread that follows the color filter setting, re-applying our effect
+/// when the filter changes; `on_change` receives the new state
+pub fn start_watcher<F>(on_change: F)
+where
+    F: Fn(SystemColorFilter) + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut last: Option<Option<ColorFilter>> = None;
+        while !crate::shutdown::is_shutting_down() {
+            let state = state();
+            if last != Some(state.filter) {
+                // Also on the first poll: effects applied before it weren't compensated yet
+                if !crate::suspend::is_suspended() {
+                    crate::magnification::reapply();
+                }
+                if last.is_some() {
+                    on_change(state.clone());
+                }
+                last = Some(state.filter);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
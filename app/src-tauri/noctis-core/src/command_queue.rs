@@ -0,0 +1,69 @@
+//! Gamma command queue - coalesces rapid intensity changes per monitor
+//! Sliders can fire set_gamma dozens of times per second; a worker applies only the latest
+//! intensity queued for each monitor and drops the intermediates, avoiding DC churn
+
+use crate::gamma;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+/// Minimum time between ramp writes, so a burst collapses into a few writes per frame
+const MIN_WRITE_INTERVAL: Duration = Duration::from_millis(16);
+
+#[derive(Default)]
+struct Queue {
+    /// Latest requested intensity per monitor, not yet applied
+    pending: Vec<(u32, f32)>,
+    /// Last failure per monitor, reported to the next caller for that monitor
+    errors: Vec<(u32, String)>,
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue { pending: Vec::new(), errors: Vec::new() });
+static WAKE: Condvar = Condvar::new();
+static WORKER: OnceLock<()> = OnceLock::new();
+
+/// Apply queued intensities until the process exits
+fn run_worker() {
+    loop {
+        let batch = {
+            let mut queue = QUEUE.lock().unwrap();
+            while queue.pending.is_empty() {
+                queue = WAKE.wait(queue).unwrap();
+            }
+            std::mem::take(&mut queue.pending)
+        };
+
+        for (monitor, intensity) in batch {
+            let result = gamma::set_gamma(intensity, monitor);
+            let mut queue = QUEUE.lock().unwrap();
+            queue.errors.retain(|(m, _)| *m != monitor);
+            if let Err(e) = result {
+                queue.errors.push((monitor, e));
+            }
+        }
+        thread::sleep(MIN_WRITE_INTERVAL);
+    }
+}
+
+/// Queue an intensity for a monitor, replacing any not yet applied
+/// Returns the error from the last failed write to this monitor, if any
+pub fn submit(monitor: u32, intensity: f32) -> Result<(), String> {
+    if crate::shutdown::is_shutting_down() {
+        return Err(crate::i18n::text("error.shutting_down"));
+    }
+    WORKER.get_or_init(|| {
+        thread::spawn(run_worker);
+    });
+
+    let mut queue = QUEUE.lock().unwrap();
+    match queue.pending.iter_mut().find(|(m, _)| *m == monitor) {
+        Some(pending) => pending.1 = intensity,
+        None => queue.pending.push((monitor, intensity)),
+    }
+    WAKE.notify_one();
+
+    match queue.errors.iter().position(|(m, _)| *m == monitor) {
+        Some(index) => Err(queue.errors.remove(index).1),
+        None => Ok(()),
+    }
+}
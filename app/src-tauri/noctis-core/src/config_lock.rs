@@ -0,0 +1,89 @@
+//! Config folder locking - safe writes when the config folder is shared via a sync service
+//! Uses a lock file (visible to other machines once synced) plus write-then-rename
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Lock file created in the config folder while a write is in progress
+const LOCK_FILE: &str = "noctis.lock";
+
+/// A lock older than this is assumed abandoned (crash, or a machine that went offline mid-write)
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// How long to wait for another writer before giving up
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(3);
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Held lock on a config folder, released on drop
+pub struct ConfigLock {
+    path: PathBuf,
+}
+
+/// Identify this writer in the lock file so conflicts can be reported meaningfully
+fn owner() -> String {
+    let machine = std::env::var("COMPUTERNAME").unwrap_or("unknown".to_string());
+    format!("{} (pid {})", machine, std::process::id())
+}
+
+fn is_stale(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > STALE_AFTER)
+}
+
+impl ConfigLock {
+    /// Acquire the lock for a config folder, waiting briefly if another writer holds it
+    pub fn acquire(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(LOCK_FILE);
+        let mut waited = Duration::ZERO;
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let _ = file.write_all(owner().as_bytes());
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if is_stale(&path) {
+                        let _ = std::fs::remove_file(&path);
+                        continue;
+                    }
+                    if waited >= ACQUIRE_TIMEOUT {
+                        let holder = std::fs::read_to_string(&path).unwrap_or_default();
+                        return Err(format!("Config folder is locked by {}", holder.trim()));
+                    }
+                    thread::sleep(RETRY_INTERVAL);
+                    waited += RETRY_INTERVAL;
+                }
+                Err(e) => return Err(format!("Failed to lock config folder: {}", e)),
+            }
+        }
+    }
+}
+
+impl Drop for ConfigLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Write a config file under the folder lock
+/// Contents go to a temporary file that is renamed into place, so a sync client
+/// never picks up a half-written file
+pub fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    let dir = path.parent().ok_or("Invalid config path")?;
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    let _lock = ConfigLock::acquire(dir)?;
+    let temp = path.with_extension("tmp");
+    std::fs::write(&temp, contents)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    std::fs::rename(&temp, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
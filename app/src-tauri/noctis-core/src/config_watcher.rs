@@ -0,0 +1,81 @@
+//! Config file watcher - hot-reloads settings, profiles and rules edited outside the app
+//! Lets users edit the JSON files in a text editor or sync them between machines
+
+use notify::{RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Editors often save in several steps; wait for writes to settle before reloading
+const SETTLE_TIME: Duration = Duration::from_millis(250);
+
+/// How often to check whether the config store was moved to another folder
+const DIR_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Which config file changed on disk
+#[derive(Clone, Copy, PartialEq)]
+pub enum ConfigChange {
+    Settings,
+    Profiles,
+    Rules,
+}
+
+fn classify(path: &Path) -> Option<ConfigChange> {
+    match path.file_name()?.to_str()? {
+        crate::settings::SETTINGS_FILE => Some(ConfigChange::Settings),
+        crate::profiles::PROFILES_FILE => Some(ConfigChange::Profiles),
+        crate::rules::RULES_FILE => Some(ConfigChange::Rules),
+        _ => None,
+    }
+}
+
+/// Start a background thread that calls `on_change` once per burst of edits to a config file
+pub fn start_watcher<F>(on_change: F)
+where
+    F: Fn(ConfigChange) + Send + 'static,
+{
+    thread::spawn(move || {
+        let (tx, rx) = mpsc::channel();
+        let Ok(mut watcher) = notify::recommended_watcher(tx) else { return };
+        let mut dir = crate::settings::config_dir();
+        let _ = std::fs::create_dir_all(&dir);
+        let _ = watcher.watch(&dir, RecursiveMode::NonRecursive);
+
+        loop {
+            let first = match rx.recv_timeout(DIR_CHECK_INTERVAL) {
+                Ok(first) => first,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    // Follow the config store if it was moved with set_config_dir
+                    let current = crate::settings::config_dir();
+                    if current != dir {
+                        let _ = watcher.unwatch(&dir);
+                        let _ = watcher.watch(&current, RecursiveMode::NonRecursive);
+                        dir = current;
+                    }
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            };
+
+            let mut changes = Vec::new();
+            let mut next = Some(first);
+            while let Some(result) = next {
+                if let Ok(event) = result {
+                    if event.kind.is_create() || event.kind.is_modify() {
+                        for change in event.paths.iter().filter_map(|p| classify(p)) {
+                            if !changes.contains(&change) {
+                                changes.push(change);
+                            }
+                        }
+                    }
+                }
+                next = rx.recv_timeout(SETTLE_TIME).ok();
+            }
+
+            for change in changes {
+                on_change(change);
+            }
+        }
+    });
+}
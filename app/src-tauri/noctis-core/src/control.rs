@@ -0,0 +1,313 @@
+//! Local control - lets scripts on this machine drive Noctis without a broker
+//! Listens on a 127.0.0.1 socket and/or the `\\.\pipe\noctis` named pipe (for machines where
+//! policy blocks local TCP ports), one command per line, in the same format as the MQTT control
+//! topic: "toggle", "off", an intensity like "0.4", or {"intensity": 0.4, "monitor": 1}
+//! (a monitor group name such as "monitor": "Wall" also works)
+
+use crate::monitor_groups::Target;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Default port of the control socket
+pub const DEFAULT_PORT: u16 = 47811;
+
+/// Name of the control pipe, as scripts pass it to NamedPipeClientStream
+pub const PIPE_NAME: &str = "noctis";
+
+/// Wait between accept attempts, and between checks while disabled
+const ACCEPT_POLL: Duration = Duration::from_millis(200);
+
+/// A client that sends nothing for this long is dropped
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Local control settings
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct ControlSettings {
+    /// Listen on the TCP socket
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on the named pipe
+    pub pipe: bool,
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        Self { enabled: false, port: DEFAULT_PORT, pipe: false }
+    }
+}
+
+/// A command from a local script or the MQTT control topic
+pub enum Command {
+    /// Toggle night mode as the hotkey would
+    Toggle,
+    /// Remove all adjustments
+    Disable,
+    /// Apply an intensity to a monitor or group (the primary adjusted monitor if unset)
+    SetIntensity { intensity: f32, monitor: Option<Target> },
+}
+
+#[derive(Deserialize)]
+struct IntensityCommand {
+    intensity: f32,
+    monitor: Option<Target>,
+}
+
+/// Reject intensities outside 0.0 - 1.0, NaN included, as deep links do
+fn intensity(value: f32) -> Result<f32, String> {
+    if !(0.0..=1.0).contains(&value) {
+        return Err(crate::i18n::format("error.unit_interval", &[&crate::i18n::text("field.intensity"), &value]));
+    }
+    Ok(value)
+}
+
+/// Parse a command in the control protocol
+pub fn parse(text: &str) -> Result<Command, String> {
+    let text = text.trim();
+    match text.to_ascii_lowercase().as_str() {
+        "toggle" => Ok(Command::Toggle),
+        "off" | "disable" => Ok(Command::Disable),
+        _ => {
+            if let Ok(value) = text.parse::<f32>() {
+                return Ok(Command::SetIntensity { intensity: intensity(value)?, monitor: None });
+            }
+            let command: IntensityCommand = serde_json::from_str(text).map_err(|_| "unknown command".to_string())?;
+            Ok(Command::SetIntensity { intensity: intensity(command.intensity)?, monitor: command.monitor })
+        }
+    }
+}
+
+/// Carry out one protocol line and return the reply for the client
+pub fn respond(line: &str, on_command: &dyn Fn(Command) -> Result<(), String>) -> String {
+    match parse(line).and_then(on_command) {
+        Ok(()) => "ok".to_string(),
+        Err(e) => format!("error: {}", e),
+    }
+}
+
+/// Answer a client's lines until it disconnects
+pub fn answer(reader: impl Read, mut writer: impl Write, respond: &dyn Fn(&str) -> String) {
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if writeln!(writer, "{}", respond(&line)).is_err() {
+            break;
+        }
+    }
+}
+
+/// Answer a socket client until it disconnects or goes quiet
+fn handle(stream: TcpStream, on_command: &dyn Fn(Command) -> Result<(), String>) {
+    // Accepted sockets inherit the listener's non-blocking mode on Windows
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(CLIENT_TIMEOUT));
+    let Ok(writer) = stream.try_clone() else { return };
+    answer(stream, writer, &|line| respond(line, on_command));
+}
+
+/// Accept clients until the settings change or Noctis shuts down
+fn serve(config: &ControlSettings, on_command: &dyn Fn(Command) -> Result<(), String>) {
+    let Ok(listener) = TcpListener::bind(("127.0.0.1", config.port)) else {
+        crate::crash::log(format!("Control socket could not bind port {}", config.port));
+        return;
+    };
+    let _ = listener.set_nonblocking(true);
+    let listening = |c: &ControlSettings| c.enabled == config.enabled && c.port == config.port;
+    while listening(&crate::settings::get().control) && !crate::shutdown::is_shutting_down() {
+        match listener.accept() {
+            Ok((stream, _)) => handle(stream, on_command),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL),
+            Err(_) => break,
+        }
+    }
+}
+
+#[cfg(windows)]
+pub mod pipe {
+    use std::ffi::c_void;
+    use std::fs::File;
+    use std::os::windows::io::FromRawHandle;
+
+    const PIPE_ACCESS_DUPLEX: u32 = 0x0000_0003;
+    /// Byte mode, blocking, local clients only
+    const PIPE_MODE: u32 = 0x0000_0008;
+    const PIPE_UNLIMITED_INSTANCES: u32 = 255;
+    const BUFFER_SIZE: u32 = 4096;
+    const ERROR_PIPE_CONNECTED: i32 = 535;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateNamedPipeW(
+            name: *const u16,
+            open_mode: u32,
+            pipe_mode: u32,
+            max_instances: u32,
+            out_buffer_size: u32,
+            in_buffer_size: u32,
+            default_timeout: u32,
+            security_attributes: *const c_void,
+        ) -> *mut c_void;
+        fn ConnectNamedPipe(pipe: *mut c_void, overlapped: *mut c_void) -> i32;
+        fn DisconnectNamedPipe(pipe: *mut c_void) -> i32;
+        fn FlushFileBuffers(file: *mut c_void) -> i32;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+        fn GetNamedPipeClientProcessId(pipe: *mut c_void, process_id: *mut u32) -> i32;
+        fn LocalFree(memory: *mut c_void) -> *mut c_void;
+    }
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            sddl: *const u16,
+            revision: u32,
+            descriptor: *mut *mut c_void,
+            size: *mut u32,
+        ) -> i32;
+    }
+
+    /// SECURITY_ATTRIBUTES
+    #[repr(C)]
+    struct SecurityAttributes {
+        length: u32,
+        descriptor: *mut c_void,
+        inherit_handle: i32,
+    }
+
+    const SDDL_REVISION_1: u32 = 1;
+
+    /// Wait for one client on a new instance of `\\.\pipe\<name>` and answer it until it disconnects
+    /// The default security only lets the current user (and admins) write to the pipe
+    /// Returns false if the pipe could not be created
+    pub fn serve_one(name: &str, respond: &dyn Fn(&str) -> String) -> bool {
+        serve_one_restricted(name, None, None, respond)
+    }
+
+    /// `serve_one` with the pipe secured by an SDDL string instead of the default security,
+    /// answering only the client process `client` if given
+    pub fn serve_one_restricted(name: &str, sddl: Option<&str>, client: Option<u32>, respond: &dyn Fn(&str) -> String) -> bool {
+        let name: Vec<u16> = format!(r"\\.\pipe\{}", name).encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe {
+            let mut attributes = SecurityAttributes { length: std::mem::size_of::<SecurityAttributes>() as u32, descriptor: std::ptr::null_mut(), inherit_handle: 0 };
+            if let Some(sddl) = sddl {
+                let sddl: Vec<u16> = sddl.encode_utf16().chain(std::iter::once(0)).collect();
+                let converted = ConvertStringSecurityDescriptorToSecurityDescriptorW(sddl.as_ptr(), SDDL_REVISION_1, &mut attributes.descriptor, std::ptr::null_mut());
+                if converted == 0 {
+                    return false;
+                }
+            }
+            let handle = CreateNamedPipeW(
+                name.as_ptr(),
+                PIPE_ACCESS_DUPLEX,
+                PIPE_MODE,
+                PIPE_UNLIMITED_INSTANCES,
+                BUFFER_SIZE,
+                BUFFER_SIZE,
+                0,
+                if sddl.is_some() { &attributes as *const SecurityAttributes as *const c_void } else { std::ptr::null() },
+            );
+            if !attributes.descriptor.is_null() {
+                LocalFree(attributes.descriptor);
+            }
+            if handle.is_null() || handle as isize == -1 {
+                return false;
+            }
+            if ConnectNamedPipe(handle, std::ptr::null_mut()) == 0
+                && std::io::Error::last_os_error().raw_os_error() != Some(ERROR_PIPE_CONNECTED)
+            {
+                CloseHandle(handle);
+                return true;
+            }
+            let mut pid = 0;
+            if client.is_some_and(|client| GetNamedPipeClientProcessId(handle, &mut pid) == 0 || pid != client) {
+                DisconnectNamedPipe(handle);
+                CloseHandle(handle);
+                return true;
+            }
+            let file = File::from_raw_handle(handle);
+            if let Ok(writer) = file.try_clone() {
+                super::answer(&file, writer, respond);
+            }
+            FlushFileBuffers(handle);
+            DisconnectNamedPipe(handle);
+            drop(file);
+        }
+        true
+    }
+}
+
+/// Answer pipe clients one after another while the pipe is enabled
+#[cfg(windows)]
+fn serve_pipe(on_command: &dyn Fn(Command) -> Result<(), String>) {
+    while crate::settings::get().control.pipe && !crate::shutdown::is_shutting_down() {
+        // Pipes can be turned off while waiting for a client, which then only gets an error
+        let served = pipe::serve_one(PIPE_NAME, &|line| {
+            if crate::settings::get().control.pipe {
+                respond(line, on_command)
+            } else {
+                "error: control pipe is off".to_string()
+            }
+        });
+        if !served {
+            crate::crash::log("Control pipe could not be created");
+            return;
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn serve_pipe(_on_command: &dyn Fn(Command) -> Result<(), String>) {}
+
+/// Start the control socket and pipe on background threads
+/// Each listens whenever it is enabled and follows settings changes
+pub fn start<F>(on_command: F)
+where
+    F: Fn(Command) -> Result<(), String> + Send + Sync + 'static,
+{
+    let on_command = Arc::new(on_command);
+
+    let socket_command = on_command.clone();
+    thread::spawn(move || {
+        while !crate::shutdown::is_shutting_down() {
+            let config = crate::settings::get().control;
+            if config.enabled {
+                serve(&config, &*socket_command);
+            }
+            thread::sleep(ACCEPT_POLL);
+        }
+    });
+
+    thread::spawn(move || {
+        while !crate::shutdown::is_shutting_down() {
+            serve_pipe(&*on_command);
+            thread::sleep(ACCEPT_POLL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intensities_are_validated() {
+        assert!(matches!(parse("0.4"), Ok(Command::SetIntensity { monitor: None, .. })));
+        assert!(matches!(parse(r#"{"intensity": 1.0, "monitor": 2}"#), Ok(Command::SetIntensity { monitor: Some(Target::Monitor(2)), .. })));
+        for text in ["1.5", "-0.1", "nan", "inf", r#"{"intensity": 2.0}"#] {
+            assert!(parse(text).is_err(), "{} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn replies_carry_command_errors() {
+        assert_eq!(respond("toggle", &|_| Ok(())), "ok");
+        assert_eq!(respond("toggle", &|_| Err("busy".to_string())), "error: busy");
+        assert!(respond("nan", &|_| Ok(())).starts_with("error: "));
+        assert_eq!(respond("jump", &|_| Ok(())), "error: unknown command");
+    }
+}
@@ -0,0 +1,34 @@
+//! Second-monitor control panel - the control window can be pinned to a monitor and brought
+//! up there when a game profile activates on another one, so adjustments can be watched
+//! and made without leaving the game. The app moves the window; this decides where
+
+use crate::gamma::{self, MonitorInfo};
+use crate::profiles::GameProfile;
+
+/// Gap between the window and the pinned monitor's edges, in pixels
+const MARGIN: i32 = 24;
+
+/// The monitor the control window is pinned to, if one is set and connected
+pub fn pinned_monitor() -> Option<MonitorInfo> {
+    let pinned = crate::settings::get().window.pin_monitor?;
+    gamma::get_monitors().into_iter().find(|m| m.device_id == pinned)
+}
+
+/// Top-left corner for a window of `width` x `height` in the pinned monitor's top-right
+/// corner, kept on the monitor if the window is larger than it
+pub fn position(monitor: &MonitorInfo, width: u32, height: u32) -> (i32, i32) {
+    let right = monitor.x + monitor.width as i32;
+    let x = (right - width as i32 - MARGIN).max(monitor.x);
+    let y = (monitor.y + MARGIN).min(monitor.y + monitor.height as i32 - height as i32).max(monitor.y);
+    (x, y)
+}
+
+/// Monitor to show the control window on when a profile activates: the pinned one, if
+/// showing on activation is on and the profile adjusts other monitors
+pub fn show_for(profile: &GameProfile) -> Option<MonitorInfo> {
+    if !crate::settings::get().window.show_on_profile {
+        return None;
+    }
+    let monitor = pinned_monitor()?;
+    (!profile.monitors().contains(&monitor.index)).then_some(monitor)
+}
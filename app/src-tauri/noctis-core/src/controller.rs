@@ -0,0 +1,98 @@
+//! Auto-adjust controller state - the operating mode and whether the controller may act
+//! In auto mode a manual intensity change holds auto-adjust off for a while, or until the scene
+//! brightness moves far enough that the user's choice no longer fits; hybrid mode instead keeps
+//! the user's change as an offset on top of the auto-adjust baseline
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How intensity is decided
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Only the user sets intensity; brightness readings are ignored
+    Manual,
+    /// Auto-adjust follows the scene, pausing after manual changes
+    #[default]
+    Auto,
+    /// Auto-adjust sets a baseline and the user's manual offset is added on top
+    Hybrid,
+}
+
+/// A manual change the controller is holding off for
+struct ManualOverride {
+    at: Instant,
+    /// Scene brightness when the change was made, if a reading had been taken
+    brightness: Option<f32>,
+}
+
+static OVERRIDE: Mutex<Option<ManualOverride>> = Mutex::new(None);
+
+/// Latest brightness reading the controller was given
+static LAST_BRIGHTNESS: Mutex<Option<f32>> = Mutex::new(None);
+
+/// Hybrid mode: last auto-adjust baseline and the user's offset from it
+static HYBRID: Mutex<(f32, f32)> = Mutex::new((0.0, 0.0));
+
+pub fn mode() -> Mode {
+    crate::settings::get().mode
+}
+
+/// Switch mode, starting the new one without a held change or offset
+pub fn set_mode(mode: Mode) -> Result<(), String> {
+    crate::settings::update(|s| s.mode = mode)?;
+    clear_override();
+    Ok(())
+}
+
+/// Hybrid mode: keep a manual intensity as an offset from the current baseline
+pub fn set_manual_offset(intensity: f32) {
+    let mut hybrid = HYBRID.lock().unwrap();
+    hybrid.1 = intensity - hybrid.0;
+}
+
+/// Intensity to apply for an auto-adjust baseline: the baseline itself, or in hybrid
+/// mode the baseline plus the user's offset
+pub fn compose(baseline: f32) -> f32 {
+    if mode() != Mode::Hybrid {
+        return baseline;
+    }
+    let mut hybrid = HYBRID.lock().unwrap();
+    hybrid.0 = baseline;
+    (baseline + hybrid.1).clamp(0.0, 1.0)
+}
+
+/// Record a manual intensity change, holding off auto-adjust from now
+pub fn manual_override() {
+    let brightness = *LAST_BRIGHTNESS.lock().unwrap();
+    *OVERRIDE.lock().unwrap() = Some(ManualOverride { at: Instant::now(), brightness });
+}
+
+/// Drop any held manual change or hybrid offset, e.g. when auto-adjust starts over
+pub fn clear_override() {
+    *OVERRIDE.lock().unwrap() = None;
+    *HYBRID.lock().unwrap() = (0.0, 0.0);
+}
+
+/// Check whether auto-adjust may act on a reading: never in manual mode, always in hybrid,
+/// and in auto mode unless a manual change is held that hasn't timed out and whose scene
+/// hasn't changed by more than the configured threshold
+pub fn allows_auto(brightness: f32) -> bool {
+    *LAST_BRIGHTNESS.lock().unwrap() = Some(brightness);
+    match mode() {
+        Mode::Manual => return false,
+        Mode::Hybrid => return true,
+        Mode::Auto => {}
+    }
+    let mut held = OVERRIDE.lock().unwrap();
+    let Some(manual) = held.as_ref() else { return true };
+    let config = crate::profiles::sensing().adjustment;
+    let expired = manual.at.elapsed() >= Duration::from_millis(config.manual_hold_ms);
+    let scene_changed = manual.brightness.is_some_and(|b| (brightness - b).abs() > config.manual_release_delta);
+    if expired || scene_changed {
+        *held = None;
+        return true;
+    }
+    false
+}
@@ -0,0 +1,234 @@
+//! Crash reporting (opt-in) - minidumps and recent log lines for panics and SEH crashes
+//! Reports are only written locally under `<config>/crashes` and leave the machine only
+//! when the user exports them, so driver crashes in the FFI layer can be diagnosed
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CRASH_DIR: &str = "crashes";
+
+/// Log lines kept for the next report
+const LOG_LINES: usize = 200;
+
+/// Recent log lines, oldest first
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// A crash report on disk
+#[derive(Serialize, Clone)]
+pub struct CrashReport {
+    /// Unix time of the crash, also the file name stem
+    pub id: String,
+    /// First line of the report (panic message or exception code)
+    pub summary: String,
+    pub has_minidump: bool,
+}
+
+fn crash_dir() -> PathBuf {
+    crate::settings::config_dir().join(CRASH_DIR)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Record a line for the next crash report
+pub fn log(line: impl Into<String>) {
+    let mut log = LOG.lock().unwrap();
+    if log.len() == LOG_LINES {
+        log.pop_front();
+    }
+    log.push_back(format!("[{}] {}", now_secs(), line.into()));
+}
+
+/// Write `<id>.txt` with the summary, details and recent log lines, returning the report id
+/// Never blocks on the log lock: the crashing thread may be holding it
+fn write_report(summary: &str, details: &str) -> Option<String> {
+    let dir = crash_dir();
+    std::fs::create_dir_all(&dir).ok()?;
+    let id = now_secs().to_string();
+
+    let mut text = format!("{}\n\n{}\n\nRecent log:\n", summary, details);
+    match LOG.try_lock() {
+        Ok(log) => log.iter().for_each(|line| {
+            let _ = writeln!(text, "{}", line);
+        }),
+        Err(_) => text.push_str("(unavailable)\n"),
+    }
+    std::fs::write(dir.join(format!("{}.txt", id)), text).ok()?;
+    Some(id)
+}
+
+#[cfg(windows)]
+mod minidump {
+    use std::ffi::c_void;
+    use std::os::windows::io::AsRawHandle;
+    use std::path::Path;
+
+    /// MiniDumpWithIndirectlyReferencedMemory | MiniDumpWithThreadInfo
+    const DUMP_TYPE: u32 = 0x40 | 0x1000;
+
+    /// EXCEPTION_CONTINUE_SEARCH: let Windows (and the watchdog) handle the crash as usual
+    const CONTINUE_SEARCH: i32 = 0;
+
+    /// MINIDUMP_EXCEPTION_INFORMATION structure (4-byte packed in dbghelp.h)
+    #[repr(C, packed(4))]
+    struct ExceptionInformation {
+        thread_id: u32,
+        exception_pointers: *mut c_void,
+        client_pointers: i32,
+    }
+
+    /// EXCEPTION_RECORD header, enough to read the exception code and address
+    #[repr(C)]
+    struct ExceptionRecord {
+        code: u32,
+        flags: u32,
+        record: *mut ExceptionRecord,
+        address: *mut c_void,
+    }
+
+    /// EXCEPTION_POINTERS structure
+    #[repr(C)]
+    struct ExceptionPointers {
+        record: *mut ExceptionRecord,
+        context: *mut c_void,
+    }
+
+    type ExceptionFilter = unsafe extern "system" fn(*mut c_void) -> i32;
+
+    #[link(name = "dbghelp")]
+    extern "system" {
+        fn MiniDumpWriteDump(
+            process: *mut c_void,
+            process_id: u32,
+            file: *mut c_void,
+            dump_type: u32,
+            exception: *const ExceptionInformation,
+            user_stream: *const c_void,
+            callback: *const c_void,
+        ) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn GetCurrentProcessId() -> u32;
+        fn GetCurrentThreadId() -> u32;
+        fn SetUnhandledExceptionFilter(filter: Option<ExceptionFilter>) -> Option<ExceptionFilter>;
+    }
+
+    /// Write a minidump of this process, with the faulting thread's context if known
+    pub fn write(path: &Path, exception_pointers: *mut c_void) -> bool {
+        let Ok(file) = std::fs::File::create(path) else { return false };
+        let exception = ExceptionInformation {
+            thread_id: unsafe { GetCurrentThreadId() },
+            exception_pointers,
+            client_pointers: 0,
+        };
+        let exception = if exception_pointers.is_null() { std::ptr::null() } else { &exception as *const _ };
+        unsafe {
+            MiniDumpWriteDump(
+                GetCurrentProcess(),
+                GetCurrentProcessId(),
+                file.as_raw_handle() as *mut c_void,
+                DUMP_TYPE,
+                exception,
+                std::ptr::null(),
+                std::ptr::null(),
+            ) != 0
+        }
+    }
+
+    /// Report access violations and other SEH exceptions that bypass the panic hook
+    unsafe extern "system" fn exception_filter(pointers: *mut c_void) -> i32 {
+        let record = (pointers as *const ExceptionPointers).as_ref().and_then(|p| p.record.as_ref());
+        let summary = match record {
+            Some(record) => format!("Unhandled exception 0x{:08X} at {:?}", record.code, record.address),
+            None => "Unhandled exception".to_string(),
+        };
+        super::report(&summary, "", pointers);
+        CONTINUE_SEARCH
+    }
+
+    pub fn install_exception_filter() {
+        unsafe {
+            SetUnhandledExceptionFilter(Some(exception_filter));
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod minidump {
+    use std::ffi::c_void;
+    use std::path::Path;
+
+    pub fn write(_path: &Path, _exception_pointers: *mut c_void) -> bool {
+        false
+    }
+}
+
+/// Write a report and a minidump next to it
+fn report(summary: &str, details: &str, exception_pointers: *mut std::ffi::c_void) {
+    let Some(id) = write_report(summary, details) else { return };
+    minidump::write(&crash_dir().join(format!("{}.dmp", id)), exception_pointers);
+}
+
+/// Install the panic hook and SEH filter if the user opted in
+pub fn install() {
+    if !crate::settings::get().crash_reports {
+        return;
+    }
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let summary = format!("Panic on thread '{}': {}", thread.name().unwrap_or("unnamed"), info);
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        report(&summary, &backtrace.to_string(), std::ptr::null_mut());
+        previous(info);
+    }));
+    #[cfg(windows)]
+    minidump::install_exception_filter();
+}
+
+/// Saved crash reports, newest first
+pub fn list() -> Vec<CrashReport> {
+    let Ok(entries) = std::fs::read_dir(crash_dir()) else { return Vec::new() };
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_string_lossy().to_string();
+            let summary = std::fs::read_to_string(&path).ok()?.lines().next().unwrap_or_default().to_string();
+            Some(CrashReport { has_minidump: path.with_extension("dmp").exists(), id, summary })
+        })
+        .collect();
+    reports.sort_by(|a, b| b.id.cmp(&a.id));
+    reports
+}
+
+/// Copy a report and its minidump into `destination`, returning the copied files
+pub fn export(id: &str, destination: &Path) -> Result<Vec<PathBuf>, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(crate::i18n::format("error.crash_report_id", &[&id]));
+    }
+    std::fs::create_dir_all(destination).map_err(|e| format!("Failed to create export folder: {}", e))?;
+    let mut copied = Vec::new();
+    for ext in ["txt", "dmp"] {
+        let source = crash_dir().join(format!("{}.{}", id, ext));
+        if !source.exists() {
+            continue;
+        }
+        let target = destination.join(format!("noctis-crash-{}.{}", id, ext));
+        std::fs::copy(&source, &target).map_err(|e| format!("Failed to export crash report: {}", e))?;
+        copied.push(target);
+    }
+    if copied.is_empty() {
+        return Err(crate::i18n::format("error.crash_report_not_found", &[&id]));
+    }
+    Ok(copied)
+}
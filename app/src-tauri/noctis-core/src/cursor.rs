@@ -0,0 +1,426 @@
+//! Cursor compensation - a full-white cursor over strongly dimmed content is blinding, and
+//! the hardware cursor plane is untouched by gamma ramps and Magnification effects alike.
+//! While the screen under the pointer is dimmed, the system cursors are swapped for dimmed
+//! copies, a custom scheme or hidden (MagShowSystemCursor), and restored afterwards
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How often the screen's white level under the pointer is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Dimmed cursors are redrawn when the white level moves by more than this
+const LEVEL_STEP: f32 = 0.05;
+
+/// What to show instead of the normal cursors while dimmed
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CursorStyle {
+    /// The user's own cursors, darkened to the screen's white level
+    #[default]
+    Dimmed,
+    /// Cursors loaded from `custom_folder`, named like arrow.cur, ibeam.cur, hand.cur
+    Custom,
+    /// No cursor at all, through MagShowSystemCursor
+    Hidden,
+}
+
+/// Cursor compensation settings
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct CursorSettings {
+    pub enabled: bool,
+    pub style: CursorStyle,
+    /// Folder holding the cursor files for the Custom style
+    pub custom_folder: String,
+    /// White level (0.0 - 1.0) below which the screen counts as dimmed
+    pub threshold: f32,
+}
+
+impl Default for CursorSettings {
+    fn default() -> Self {
+        Self { enabled: false, style: CursorStyle::Dimmed, custom_folder: String::new(), threshold: 0.9 }
+    }
+}
+
+/// Check a cursor configuration before saving it
+pub fn validate(config: &CursorSettings) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&config.threshold) {
+        return Err(crate::i18n::format("error.unit_interval", &[&crate::i18n::text("field.cursor_threshold"), &config.threshold]));
+    }
+    if config.enabled && config.style == CursorStyle::Custom && !std::path::Path::new(&config.custom_folder).is_dir() {
+        return Err(crate::i18n::format("error.cursor_folder", &[&config.custom_folder]));
+    }
+    Ok(())
+}
+
+/// Replacement currently shown, with the white level dimmed cursors were drawn at
+#[derive(Clone, Copy, PartialEq)]
+enum Swap {
+    Dimmed(f32),
+    Custom,
+    Hidden,
+}
+
+static SWAPPED: Mutex<Option<Swap>> = Mutex::new(None);
+
+/// Brightness of white on screen after a gamma ramp
+fn ramp_white(ramp: &crate::gamma::GammaRamp) -> f32 {
+    let sum = ramp.red[255] as f32 * 0.2126 + ramp.green[255] as f32 * 0.7152 + ramp.blue[255] as f32 * 0.0722;
+    sum / 65535.0
+}
+
+/// Brightness of white under the pointer after Noctis's effects, 1.0 while effects are suspended
+pub fn white_level() -> f32 {
+    if crate::suspend::is_suspended() {
+        return 1.0;
+    }
+    let magnification = crate::magnification::recorded_effect().map(|e| e.white_level()).unwrap_or(1.0);
+    let gamma = pointer_position()
+        .and_then(|(x, y)| crate::gamma::monitor_at(x, y))
+        .and_then(|monitor| crate::gamma::active_ramp(monitor.index))
+        .map(|ramp| ramp_white(&ramp))
+        .unwrap_or(1.0);
+    (magnification * gamma).clamp(0.0, 1.0)
+}
+
+/// Swap or restore the cursors to match the current white level and settings
+pub fn refresh() {
+    let config = crate::settings::get().cursor;
+    let level = white_level();
+    let wanted = (config.enabled && level < config.threshold).then_some(match config.style {
+        CursorStyle::Dimmed => Swap::Dimmed(level),
+        CursorStyle::Custom => Swap::Custom,
+        CursorStyle::Hidden => Swap::Hidden,
+    });
+
+    let mut swapped = SWAPPED.lock().unwrap();
+    let unchanged = match (*swapped, wanted) {
+        (Some(Swap::Dimmed(shown)), Some(Swap::Dimmed(level))) => (shown - level).abs() <= LEVEL_STEP,
+        (current, wanted) => current == wanted,
+    };
+    if unchanged {
+        return;
+    }
+    undo(*swapped);
+    let applied = match wanted {
+        Some(Swap::Dimmed(level)) => swap_dimmed(level),
+        Some(Swap::Custom) => swap_custom(&config.custom_folder),
+        Some(Swap::Hidden) => show_system_cursor(false),
+        None => Ok(()),
+    };
+    *swapped = match applied {
+        Ok(()) => wanted,
+        Err(e) => {
+            crate::crash::log(format!("Cursor swap failed: {}", e));
+            undo(wanted);
+            None
+        }
+    };
+}
+
+/// Take back a replacement
+fn undo(swap: Option<Swap>) {
+    match swap {
+        Some(Swap::Hidden) => {
+            let _ = show_system_cursor(true);
+        }
+        Some(_) => restore_scheme(),
+        None => {}
+    }
+}
+
+/// Put the user's cursors back, for shutdown
+pub fn restore() {
+    undo(SWAPPED.lock().unwrap().take());
+}
+
+/// Start a background thread that keeps the cursors in step with dimming
+pub fn start_watcher() {
+    thread::spawn(|| {
+        while !crate::shutdown::is_shutting_down() {
+            refresh();
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(windows)]
+mod windows_api {
+    use std::ffi::c_void;
+    use std::ptr;
+
+    const SPI_SETCURSORS: u32 = 0x0057;
+    const IMAGE_CURSOR: u32 = 2;
+    const LR_LOADFROMFILE: u32 = 0x0010;
+    const LR_DEFAULTSIZE: u32 = 0x0040;
+    const DIB_RGB_COLORS: u32 = 0;
+
+    /// System cursors that get replaced, with their file names for custom schemes
+    /// The busy cursors are usually animated and left alone by the Dimmed style
+    const CURSORS: [(u32, &str, bool); 14] = [
+        (32512, "arrow", true),
+        (32513, "ibeam", true),
+        (32514, "wait", false),
+        (32515, "cross", true),
+        (32516, "up", true),
+        (32642, "sizenwse", true),
+        (32643, "sizenesw", true),
+        (32644, "sizewe", true),
+        (32645, "sizens", true),
+        (32646, "sizeall", true),
+        (32648, "no", true),
+        (32649, "hand", true),
+        (32650, "appstarting", false),
+        (32651, "help", true),
+    ];
+
+    /// POINT structure
+    #[repr(C)]
+    #[derive(Default)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    /// ICONINFO structure
+    #[repr(C)]
+    struct IconInfo {
+        icon: i32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+        mask: *mut c_void,
+        color: *mut c_void,
+    }
+
+    /// BITMAP structure
+    #[repr(C)]
+    struct Bitmap {
+        kind: i32,
+        width: i32,
+        height: i32,
+        width_bytes: i32,
+        planes: u16,
+        bits_pixel: u16,
+        bits: *mut c_void,
+    }
+
+    /// BITMAPINFOHEADER structure
+    #[repr(C)]
+    struct BitmapInfoHeader {
+        size: u32,
+        width: i32,
+        height: i32,
+        planes: u16,
+        bit_count: u16,
+        compression: u32,
+        size_image: u32,
+        x_pels_per_meter: i32,
+        y_pels_per_meter: i32,
+        clr_used: u32,
+        clr_important: u32,
+    }
+
+    /// RGBQUAD structure
+    #[repr(C)]
+    struct RgbQuad {
+        blue: u8,
+        green: u8,
+        red: u8,
+        reserved: u8,
+    }
+
+    /// BITMAPINFO structure; 32-bit bitmaps leave the color table unused
+    #[repr(C)]
+    struct BitmapInfo {
+        header: BitmapInfoHeader,
+        colors: [RgbQuad; 1],
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetCursorPos(point: *mut Point) -> i32;
+        fn LoadCursorW(instance: *mut c_void, name: *const u16) -> *mut c_void;
+        fn LoadImageW(instance: *mut c_void, name: *const u16, kind: u32, cx: i32, cy: i32, flags: u32) -> *mut c_void;
+        fn GetIconInfo(icon: *mut c_void, info: *mut IconInfo) -> i32;
+        fn CreateIconIndirect(info: *const IconInfo) -> *mut c_void;
+        fn SetSystemCursor(cursor: *mut c_void, id: u32) -> i32;
+        fn SystemParametersInfoW(action: u32, param: u32, value: *mut c_void, win_ini: u32) -> i32;
+        fn GetDC(hwnd: *mut c_void) -> *mut c_void;
+        fn ReleaseDC(hwnd: *mut c_void, hdc: *mut c_void) -> i32;
+    }
+
+    #[link(name = "gdi32")]
+    extern "system" {
+        fn GetObjectW(object: *mut c_void, size: i32, out: *mut c_void) -> i32;
+        fn GetDIBits(hdc: *mut c_void, bitmap: *mut c_void, start: u32, lines: u32, bits: *mut c_void, info: *mut BitmapInfo, usage: u32) -> i32;
+        fn CreateBitmap(width: i32, height: i32, planes: u32, bit_count: u32, bits: *const c_void) -> *mut c_void;
+        fn DeleteObject(object: *mut c_void) -> i32;
+    }
+
+    #[link(name = "magnification")]
+    extern "system" {
+        fn MagShowSystemCursor(show: i32) -> i32;
+    }
+
+    /// Virtual-desktop position of the pointer
+    pub(super) fn pointer_position() -> Option<(i32, i32)> {
+        let mut point = Point::default();
+        unsafe { (GetCursorPos(&mut point) != 0).then_some((point.x, point.y)) }
+    }
+
+    /// Copy of a cursor with its colors scaled by `level`; None for monochrome cursors,
+    /// which draw by inverting the screen and have no colors to scale
+    unsafe fn dimmed_copy(cursor: *mut c_void, level: f32) -> Option<*mut c_void> {
+        let mut info = IconInfo { icon: 0, hotspot_x: 0, hotspot_y: 0, mask: ptr::null_mut(), color: ptr::null_mut() };
+        if GetIconInfo(cursor, &mut info) == 0 {
+            return None;
+        }
+        let release = |info: &IconInfo| {
+            DeleteObject(info.mask);
+            if !info.color.is_null() {
+                DeleteObject(info.color);
+            }
+        };
+        if info.color.is_null() {
+            release(&info);
+            return None;
+        }
+
+        let mut bitmap: Bitmap = std::mem::zeroed();
+        GetObjectW(info.color, std::mem::size_of::<Bitmap>() as i32, &mut bitmap as *mut _ as *mut c_void);
+        let (width, height) = (bitmap.width, bitmap.height);
+        let mut bitmap_info = BitmapInfo {
+            header: BitmapInfoHeader {
+                size: std::mem::size_of::<BitmapInfoHeader>() as u32,
+                width,
+                // Negative height: top-down rows, matching CreateBitmap
+                height: -height,
+                planes: 1,
+                bit_count: 32,
+                compression: 0,
+                size_image: 0,
+                x_pels_per_meter: 0,
+                y_pels_per_meter: 0,
+                clr_used: 0,
+                clr_important: 0,
+            },
+            colors: [RgbQuad { blue: 0, green: 0, red: 0, reserved: 0 }],
+        };
+        let mut pixels = vec![0u32; (width * height).max(0) as usize];
+        let hdc = GetDC(ptr::null_mut());
+        let lines = GetDIBits(hdc, info.color, 0, height as u32, pixels.as_mut_ptr() as *mut c_void, &mut bitmap_info, DIB_RGB_COLORS);
+        ReleaseDC(ptr::null_mut(), hdc);
+        if lines == 0 {
+            release(&info);
+            return None;
+        }
+
+        // BGRA with premultiplied alpha, so scaling the color bytes keeps edges intact
+        for pixel in pixels.iter_mut() {
+            let alpha = *pixel & 0xFF00_0000;
+            let scale = |shift: u32| ((((*pixel >> shift) & 0xFF) as f32 * level).round() as u32) << shift;
+            *pixel = alpha | scale(16) | scale(8) | scale(0);
+        }
+        let dimmed = CreateBitmap(width, height, 1, 32, pixels.as_ptr() as *const c_void);
+        DeleteObject(info.color);
+        info.color = dimmed;
+        let copy = CreateIconIndirect(&info);
+        release(&info);
+        (!copy.is_null()).then_some(copy)
+    }
+
+    /// Replace the system cursors with copies darkened to `level`
+    pub(super) fn swap_dimmed(level: f32) -> Result<(), String> {
+        unsafe {
+            for (id, _, static_cursor) in CURSORS {
+                if !static_cursor {
+                    continue;
+                }
+                let current = LoadCursorW(ptr::null_mut(), id as usize as *const u16);
+                if current.is_null() {
+                    continue;
+                }
+                if let Some(copy) = dimmed_copy(current, level) {
+                    // SetSystemCursor takes ownership of the copy
+                    if SetSystemCursor(copy, id) == 0 {
+                        return Err(crate::i18n::format("error.replace_cursor", &[&id]));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the system cursors with those found in a folder, as .cur or .ani files
+    pub(super) fn swap_custom(folder: &str) -> Result<(), String> {
+        let folder = std::path::Path::new(folder);
+        let mut replaced = 0;
+        for (id, name, _) in CURSORS {
+            let Some(path) = ["cur", "ani"].iter().map(|ext| folder.join(format!("{}.{}", name, ext))).find(|p| p.is_file()) else {
+                continue;
+            };
+            let path: Vec<u16> = path.as_os_str().to_string_lossy().encode_utf16().chain(std::iter::once(0)).collect();
+            unsafe {
+                let cursor = LoadImageW(ptr::null_mut(), path.as_ptr(), IMAGE_CURSOR, 0, 0, LR_LOADFROMFILE | LR_DEFAULTSIZE);
+                if !cursor.is_null() && SetSystemCursor(cursor, id) != 0 {
+                    replaced += 1;
+                }
+            }
+        }
+        if replaced == 0 {
+            return Err(crate::i18n::format("error.no_cursor_files", &[&folder.display()]));
+        }
+        Ok(())
+    }
+
+    /// Show or hide the system cursor through the Magnification API
+    pub fn show_system_cursor(show: bool) -> Result<(), String> {
+        crate::magnification::init()?;
+        unsafe {
+            if MagShowSystemCursor(i32::from(show)) == 0 {
+                return Err(crate::i18n::text(if show { "error.show_cursor" } else { "error.hide_cursor" }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reload the user's cursor scheme from the registry
+    /// Safe to call at any time, including from the watchdog after a crash
+    pub fn restore_scheme() {
+        unsafe {
+            SystemParametersInfoW(SPI_SETCURSORS, 0, ptr::null_mut(), 0);
+        }
+    }
+}
+
+#[cfg(windows)]
+use windows_api::{pointer_position, swap_custom, swap_dimmed};
+
+#[cfg(windows)]
+pub use windows_api::{restore_scheme, show_system_cursor};
+
+#[cfg(not(windows))]
+fn pointer_position() -> Option<(i32, i32)> {
+    None
+}
+
+#[cfg(not(windows))]
+fn swap_dimmed(_level: f32) -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+#[cfg(not(windows))]
+fn swap_custom(_folder: &str) -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+#[cfg(not(windows))]
+pub fn show_system_cursor(_show: bool) -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+#[cfg(not(windows))]
+pub fn restore_scheme() {}
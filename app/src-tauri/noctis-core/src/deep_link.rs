@@ -0,0 +1,332 @@
+//! `noctis://` deep links - lets shortcuts, browsers and launchers like Playnite trigger actions
+//! Windows starts a new Noctis with the URL as its argument; when one is already running the
+//! link is forwarded to it over the `\\.\pipe\noctis-links` pipe and the new process exits
+
+use crate::control::Command;
+use crate::monitor_groups::Target;
+use crate::i18n;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::thread;
+use std::time::Duration;
+
+/// URL scheme registered for the current user
+pub const SCHEME: &str = "noctis";
+
+/// Pipe the running instance takes forwarded links on
+const LINK_PIPE: &str = "noctis-links";
+
+/// Longest profile name accepted from a link
+const MAX_NAME_LEN: usize = 128;
+
+/// Attempts to reach a running instance whose pipe is busy with another link
+const FORWARD_ATTEMPTS: u32 = 10;
+const FORWARD_RETRY: Duration = Duration::from_millis(50);
+
+/// Action requested by a link
+pub enum Link {
+    /// `noctis://toggle`, `noctis://off` and `noctis://intensity/0.4?monitor=1` (or `?monitor=<group>`)
+    Command(Command),
+    /// `noctis://profile/<name>`, matched by profile name or executable
+    Profile(String),
+    /// `noctis://release/<name>`, sent when the profile's game exits
+    Release(String),
+    /// `noctis://show`
+    Show,
+}
+
+/// Decode `%XX` escapes in a path segment
+fn percent_decode(text: &str) -> Result<String, String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3).ok_or_else(|| i18n::format("error.link_escape", &[&text]))?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| i18n::format("error.link_escape", &[&text]))?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| i18n::format("error.link_text", &[&text]))
+}
+
+/// Encode text for a path segment, leaving only unreserved characters as they are
+pub fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Link that applies a profile, as a launcher would open it
+pub fn profile_url(name: &str) -> String {
+    format!("{}://profile/{}", SCHEME, percent_encode(name))
+}
+
+/// Link that releases a profile after its game exits
+pub fn release_url(name: &str) -> String {
+    format!("{}://release/{}", SCHEME, percent_encode(name))
+}
+
+/// Decode and validate the profile name segment of a link
+fn profile_name(segment: &str, url: &str) -> Result<String, String> {
+    let name = percent_decode(segment)?;
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LEN || name.chars().any(char::is_control) {
+        return Err(i18n::format("error.link_profile_name", &[&url]));
+    }
+    Ok(name.to_string())
+}
+
+/// Monitor index or group name from a `monitor=N` / `monitor=Group` query parameter
+/// Every pair is checked: unknown keys and a repeated `monitor` are rejected
+fn monitor_param(query: &str) -> Result<Option<Target>, String> {
+    let mut monitor = None;
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        match pair.split_once('=') {
+            Some(("monitor", _)) if monitor.is_some() => return Err(i18n::text("error.link_monitor_repeated")),
+            Some(("monitor", "")) => return Err(i18n::text("error.link_monitor_empty")),
+            Some(("monitor", value)) => {
+                monitor = Some(value.parse().map(Target::Monitor).unwrap_or_else(|_| Target::Group(value.to_string())));
+            }
+            _ => return Err(i18n::format("error.link_parameter", &[&pair])),
+        }
+    }
+    Ok(monitor)
+}
+
+/// Parse and validate a `noctis://` URL
+pub fn parse(url: &str) -> Result<Link, String> {
+    let url = url.trim();
+    let rest = url
+        .split_once(':')
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(SCHEME))
+        .map(|(_, rest)| rest.trim_start_matches('/'))
+        .ok_or_else(|| i18n::format("error.link_scheme", &[&SCHEME, &url]))?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    let action = parts.first().map(|a| a.to_ascii_lowercase()).unwrap_or_default();
+
+    let link = match (action.as_str(), &parts[1..]) {
+        ("toggle", []) => Link::Command(Command::Toggle),
+        ("off", []) => Link::Command(Command::Disable),
+        ("show", []) => Link::Show,
+        ("intensity", [value]) => {
+            let intensity: f32 = value.parse().map_err(|_| i18n::format("error.link_intensity", &[value]))?;
+            if !(0.0..=1.0).contains(&intensity) {
+                return Err(i18n::format("error.unit_interval", &[&i18n::text("field.intensity"), &intensity]));
+            }
+            let monitor = monitor_param(query)?;
+            if let Some(target) = &monitor {
+                target.resolve()?;
+            }
+            return Ok(Link::Command(Command::SetIntensity { intensity, monitor }));
+        }
+        ("profile", [name]) => Link::Profile(profile_name(name, url)?),
+        ("release", [name]) => Link::Release(profile_name(name, url)?),
+        _ => return Err(i18n::format("error.link_unsupported", &[&url])),
+    };
+    if !query.is_empty() {
+        return Err(i18n::format("error.link_parameters", &[&url]));
+    }
+    Ok(link)
+}
+
+/// The link this process was started with, if any
+pub fn from_args() -> Option<String> {
+    let prefix = format!("{}:", SCHEME);
+    std::env::args().skip(1).find(|arg| arg.get(..prefix.len()).is_some_and(|p| p.eq_ignore_ascii_case(&prefix)))
+}
+
+/// Hand a link to an already running instance and return its reply
+/// None if no instance is listening
+pub fn forward(url: &str) -> Option<String> {
+    let path = format!(r"\\.\pipe\{}", LINK_PIPE);
+    let mut pipe = None;
+    for _ in 0..FORWARD_ATTEMPTS {
+        match std::fs::OpenOptions::new().read(true).write(true).open(&path) {
+            Ok(file) => {
+                pipe = Some(file);
+                break;
+            }
+            // Every pipe instance is busy with another client; a new one is created right after
+            Err(e) if e.kind() != ErrorKind::NotFound => thread::sleep(FORWARD_RETRY),
+            Err(_) => return None,
+        }
+    }
+    let mut pipe = pipe?;
+    writeln!(pipe, "{}", url).ok()?;
+    let mut reply = String::new();
+    BufReader::new(pipe).read_line(&mut reply).ok()?;
+    Some(reply.trim().to_string())
+}
+
+#[cfg(windows)]
+mod registry {
+    use std::ffi::c_void;
+
+    const HKEY_CURRENT_USER: isize = 0x8000_0001u32 as i32 as isize;
+    const KEY_WRITE: u32 = 0x0002_0006;
+    const REG_SZ: u32 = 1;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn RegCreateKeyExW(
+            key: isize,
+            sub_key: *const u16,
+            reserved: u32,
+            class: *const u16,
+            options: u32,
+            desired: u32,
+            security_attributes: *const c_void,
+            result: *mut isize,
+            disposition: *mut u32,
+        ) -> i32;
+        fn RegSetValueExW(key: isize, name: *const u16, reserved: u32, kind: u32, data: *const u8, size: u32) -> i32;
+        fn RegCloseKey(key: isize) -> i32;
+    }
+
+    fn to_wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    /// Create `HKCU\<path>` and set string values on it, None naming the default value
+    pub fn set_values(path: &str, values: &[(Option<&str>, &str)]) -> Result<(), String> {
+        let mut key = 0;
+        let status = unsafe {
+            RegCreateKeyExW(
+                HKEY_CURRENT_USER,
+                to_wide(path).as_ptr(),
+                0,
+                std::ptr::null(),
+                0,
+                KEY_WRITE,
+                std::ptr::null(),
+                &mut key,
+                std::ptr::null_mut(),
+            )
+        };
+        if status != 0 {
+            return Err(crate::i18n::format("error.registry_key", &[&path, &status]));
+        }
+        let result = values.iter().try_for_each(|(name, value)| {
+            let name = name.map(to_wide);
+            let data = to_wide(value);
+            let status = unsafe {
+                RegSetValueExW(
+                    key,
+                    name.as_ref().map_or(std::ptr::null(), |n| n.as_ptr()),
+                    0,
+                    REG_SZ,
+                    data.as_ptr() as *const u8,
+                    (data.len() * 2) as u32,
+                )
+            };
+            if status == 0 {
+                Ok(())
+            } else {
+                Err(crate::i18n::format("error.registry_value", &[&path, &status]))
+            }
+        });
+        unsafe { RegCloseKey(key) };
+        result
+    }
+}
+
+/// Register the `noctis://` scheme for the current user, pointing at this executable
+/// Re-run at startup so the registration follows the app when it moves
+#[cfg(windows)]
+pub fn register() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))?;
+    let key = format!(r"Software\Classes\{}", SCHEME);
+    registry::set_values(&key, &[(None, "URL:Noctis"), (Some("URL Protocol"), "")])?;
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    registry::set_values(&format!(r"{}\shell\open\command", key), &[(None, &command)])
+}
+
+#[cfg(not(windows))]
+pub fn register() -> Result<(), String> {
+    Ok(())
+}
+
+/// Take links forwarded by later instances on a background thread
+/// `on_link` returns the error to send back, if any
+#[cfg(windows)]
+pub fn start_listener<F>(on_link: F)
+where
+    F: Fn(Link) -> Result<(), String> + Send + 'static,
+{
+    let respond = move |line: &str| match parse(line).and_then(&on_link) {
+        Ok(()) => "ok".to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+    thread::spawn(move || {
+        while !crate::shutdown::is_shutting_down() {
+            if !crate::control::pipe::serve_one(LINK_PIPE, &respond) {
+                crate::crash::log("Deep link pipe could not be created");
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn start_listener<F>(_on_link: F)
+where
+    F: Fn(Link) -> Result<(), String> + Send + 'static,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_actions_parse() {
+        assert!(matches!(parse("noctis://toggle"), Ok(Link::Command(Command::Toggle))));
+        assert!(matches!(parse("NOCTIS://Off/"), Ok(Link::Command(Command::Disable))));
+        assert!(matches!(parse("noctis://show"), Ok(Link::Show)));
+    }
+
+    #[test]
+    fn intensity_is_validated() {
+        assert!(matches!(
+            parse("noctis://intensity/0.4"),
+            Ok(Link::Command(Command::SetIntensity { monitor: None, .. }))
+        ));
+        assert!(parse("noctis://intensity/1.5").is_err());
+        assert!(parse("noctis://intensity/abc").is_err());
+    }
+
+    #[test]
+    fn profile_names_are_decoded_and_checked() {
+        assert!(matches!(parse(&profile_url("Hunt: Showdown")), Ok(Link::Profile(name)) if name == "Hunt: Showdown"));
+        assert!(matches!(parse(&release_url("Tarkov")), Ok(Link::Release(name)) if name == "Tarkov"));
+        assert!(parse("noctis://profile/%20").is_err());
+        assert!(parse("noctis://profile/%0A").is_err());
+        assert!(parse("noctis://profile/%zz").is_err());
+    }
+
+    #[test]
+    fn unknown_links_and_parameters_are_rejected() {
+        assert!(parse("https://toggle").is_err());
+        assert!(parse("noctis://explode").is_err());
+        assert!(parse("noctis://toggle?monitor=1").is_err());
+        assert!(parse("noctis://toggle/extra").is_err());
+    }
+
+    #[test]
+    fn monitor_param_checks_every_pair() {
+        assert!(matches!(monitor_param(""), Ok(None)));
+        assert!(matches!(monitor_param("monitor=2"), Ok(Some(Target::Monitor(2)))));
+        assert!(matches!(monitor_param("monitor=Left"), Ok(Some(Target::Group(name))) if name == "Left"));
+        assert!(monitor_param("monitor=").is_err());
+        assert!(monitor_param("monitor=1&monitor=2").is_err());
+        assert!(monitor_param("monitor=1&x=2").is_err());
+        assert!(monitor_param("x=2&monitor=1").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! Discord Rich Presence (opt-in) - shows "Night vision active — <game> profile" to friends
+//! Talks to the local Discord client over its `\\.\pipe\discord-ipc-N` pipe; nothing is sent
+//! unless the integration is enabled with the id of a Discord application to show it under
+
+use crate::{night_mode, profiles};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::thread;
+use std::time::Duration;
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+
+/// Discord listens on the first free of discord-ipc-0 to discord-ipc-9
+const PIPE_COUNT: u32 = 10;
+
+/// How often the presence follows the state (Discord allows 5 updates per 20 seconds)
+const UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Wait between connection attempts, and between checks while disabled
+const RECONNECT_DELAY: Duration = Duration::from_secs(15);
+
+/// Discord Rich Presence settings
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct DiscordSettings {
+    pub enabled: bool,
+    /// Id of the Discord application whose name the presence shows under
+    pub application_id: String,
+    /// Name the active game profile, not just that night vision is on
+    pub show_profile: bool,
+}
+
+impl Default for DiscordSettings {
+    fn default() -> Self {
+        Self { enabled: false, application_id: String::new(), show_profile: true }
+    }
+}
+
+/// Check that an application id looks like a Discord snowflake
+pub fn validate(config: &DiscordSettings) -> Result<(), String> {
+    let id = &config.application_id;
+    if config.enabled && (id.is_empty() || !id.chars().all(|c| c.is_ascii_digit())) {
+        return Err(crate::i18n::text("error.discord_id"));
+    }
+    Ok(())
+}
+
+/// Open the local Discord client's pipe
+fn connect() -> Option<File> {
+    (0..PIPE_COUNT).find_map(|i| {
+        std::fs::OpenOptions::new().read(true).write(true).open(format!(r"\\.\pipe\discord-ipc-{}", i)).ok()
+    })
+}
+
+/// Send one frame and read Discord's reply
+fn send(pipe: &mut File, op: u32, payload: &Value) -> io::Result<Value> {
+    let body = payload.to_string();
+    let mut frame = Vec::with_capacity(8 + body.len());
+    frame.extend(op.to_le_bytes());
+    frame.extend((body.len() as u32).to_le_bytes());
+    frame.extend(body.as_bytes());
+    pipe.write_all(&frame)?;
+
+    let mut header = [0u8; 8];
+    pipe.read_exact(&mut header)?;
+    let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let mut reply = vec![0u8; len];
+    pipe.read_exact(&mut reply)?;
+    serde_json::from_slice(&reply).map_err(io::Error::other)
+}
+
+/// Presence text for the current state, None while night vision is off
+fn activity_text(show_profile: bool) -> Option<String> {
+    if !night_mode::current().engaged {
+        return None;
+    }
+    match profiles::active().filter(|_| show_profile) {
+        Some(profile) => Some(format!("Night vision active — {} profile", profile.name)),
+        None => Some("Night vision active".to_string()),
+    }
+}
+
+/// Show or clear the presence
+fn set_activity(pipe: &mut File, text: Option<&str>, nonce: u64) -> io::Result<()> {
+    let payload = json!({
+        "cmd": "SET_ACTIVITY",
+        "args": { "pid": std::process::id(), "activity": text.map(|t| json!({ "details": t })) },
+        "nonce": nonce.to_string(),
+    });
+    let reply = send(pipe, OP_FRAME, &payload)?;
+    if reply["evt"] == "ERROR" {
+        return Err(io::Error::other(reply["data"]["message"].to_string()));
+    }
+    Ok(())
+}
+
+/// Keep the presence in step with night mode until the settings change or Discord goes away
+fn run(config: &DiscordSettings) {
+    let Some(mut pipe) = connect() else { return };
+    let handshake = json!({ "v": 1, "client_id": config.application_id });
+    if !send(&mut pipe, OP_HANDSHAKE, &handshake).is_ok_and(|reply| reply["evt"] == "READY") {
+        return;
+    }
+
+    let mut shown = None;
+    let mut nonce = 0;
+    while crate::settings::get().discord == *config && !crate::shutdown::is_shutting_down() {
+        let text = activity_text(config.show_profile);
+        if shown.as_ref() != Some(&text) {
+            nonce += 1;
+            if set_activity(&mut pipe, text.as_deref(), nonce).is_err() {
+                return;
+            }
+            shown = Some(text);
+        }
+        thread::sleep(UPDATE_INTERVAL);
+    }
+    // Discord also drops the presence once the pipe closes
+    let _ = set_activity(&mut pipe, None, nonce + 1);
+}
+
+/// Run the presence on a background thread whenever it is enabled
+pub fn start() {
+    thread::spawn(|| {
+        while !crate::shutdown::is_shutting_down() {
+            let config = crate::settings::get().discord;
+            if config.enabled && validate(&config).is_ok() {
+                run(&config);
+            }
+            thread::sleep(RECONNECT_DELAY);
+        }
+    });
+}
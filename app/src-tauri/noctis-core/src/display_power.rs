@@ -0,0 +1,115 @@
+//! Display power watcher - tracks monitors going to sleep and waking
+//! Registers for GUID_CONSOLE_DISPLAY_STATE on the message_window so gamma writes are
+//! held while displays are off (CreateDCW/SetDeviceGammaRamp fail against sleeping displays)
+//! and effects are re-applied on wake, when drivers often reset the ramps
+//! The same window hears WM_DISPLAYCHANGE, which invalidates the cached monitor list
+
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+static DISPLAYS_ON: AtomicBool = AtomicBool::new(true);
+
+/// Check whether the displays are awake
+pub fn displays_on() -> bool {
+    DISPLAYS_ON.load(Ordering::SeqCst)
+}
+
+/// GUID structure
+#[repr(C)]
+#[derive(PartialEq)]
+struct Guid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8],
+}
+
+/// GUID_CONSOLE_DISPLAY_STATE {6FE69556-704A-47A0-8F24-C28D936FDA47}
+const GUID_CONSOLE_DISPLAY_STATE: Guid = Guid {
+    data1: 0x6FE69556,
+    data2: 0x704A,
+    data3: 0x47A0,
+    data4: [0x8F, 0x24, 0xC2, 0x8D, 0x93, 0x6F, 0xDA, 0x47],
+};
+
+/// POWERBROADCAST_SETTING header, followed by `data_length` bytes of data
+#[repr(C)]
+struct PowerBroadcastSetting {
+    power_setting: Guid,
+    data_length: u32,
+    data: [u8; 1],
+}
+
+const WM_POWERBROADCAST: u32 = 0x0218;
+const WM_DISPLAYCHANGE: u32 = 0x007E;
+const PBT_POWERSETTINGCHANGE: usize = 0x8013;
+const DEVICE_NOTIFY_WINDOW_HANDLE: u32 = 0;
+
+/// Console display states reported in the setting data
+const DISPLAY_OFF: u32 = 0;
+
+#[cfg(windows)]
+#[link(name = "user32")]
+extern "system" {
+    fn RegisterPowerSettingNotification(recipient: *mut c_void, power_setting: *const Guid, flags: u32) -> *mut c_void;
+}
+
+type ChangeCallback = Box<dyn Fn(bool) + Send>;
+
+/// Called when the displays sleep (false) or wake (true)
+static WATCHER: Mutex<Option<ChangeCallback>> = Mutex::new(None);
+
+/// Record a display state change, re-applying effects on wake
+fn report(on: bool) {
+    if DISPLAYS_ON.swap(on, Ordering::SeqCst) == on {
+        return;
+    }
+    if on && !crate::suspend::is_suspended() {
+        crate::gamma::reapply_all();
+        crate::magnification::reapply();
+    }
+    if let Some(on_change) = WATCHER.lock().unwrap().as_ref() {
+        on_change(on);
+    }
+}
+
+/// Handle a message for the shared message window, if it's a power or display change
+///
+/// # Safety
+/// `l_param` must be the message's own parameter, as passed to the window procedure
+#[cfg(windows)]
+pub unsafe fn window_message(msg: u32, w_param: usize, l_param: isize) -> Option<isize> {
+    if msg == WM_POWERBROADCAST && w_param == PBT_POWERSETTINGCHANGE && l_param != 0 {
+        let setting = &*(l_param as *const PowerBroadcastSetting);
+        if setting.power_setting == GUID_CONSOLE_DISPLAY_STATE && setting.data_length >= 4 {
+            let state = ptr::read_unaligned(setting.data.as_ptr() as *const u32);
+            // Dimmed displays are still on
+            report(state != DISPLAY_OFF);
+        }
+        return Some(1);
+    }
+    if msg == WM_DISPLAYCHANGE {
+        crate::gamma::invalidate_monitors();
+    }
+    None
+}
+
+/// Ask for console display state changes on the shared message window
+/// The current state is delivered immediately after registering
+///
+/// # Safety
+/// `hwnd` must be a window owned by the calling thread
+#[cfg(windows)]
+pub unsafe fn register(hwnd: *mut c_void) {
+    RegisterPowerSettingNotification(hwnd, &GUID_CONSOLE_DISPLAY_STATE, DEVICE_NOTIFY_WINDOW_HANDLE);
+}
+
+/// Call `on_change` when the displays sleep (false) or wake (true), once message_window is started
+pub fn on_change<F>(on_change: F)
+where
+    F: Fn(bool) + Send + 'static,
+{
+    *WATCHER.lock().unwrap() = Some(Box::new(on_change));
+}
@@ -0,0 +1,229 @@
+//! Secondary display sleep - powers down every monitor but the primary at night
+//! Monitors are put into standby over DDC/CI (SC_MONITORPOWER can only switch off all of them)
+//! on a hotkey or a nightly schedule, and woken again by any keyboard or mouse input
+
+use crate::gamma::{self, MonitorInfo};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How often input and the schedule are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Secondary display sleep configuration
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DisplaySleepSettings {
+    /// Hotkey that sleeps or wakes the secondary displays, e.g. "Ctrl+Alt+S" (empty = none)
+    pub hotkey: String,
+    /// Sleep the secondary displays when the night window starts
+    pub schedule_enabled: bool,
+    /// Local hours the night window starts and ends at
+    pub from_hour: u8,
+    pub to_hour: u8,
+}
+
+impl Default for DisplaySleepSettings {
+    fn default() -> Self {
+        Self {
+            hotkey: String::new(),
+            schedule_enabled: false,
+            from_hour: 23,
+            to_hour: 6,
+        }
+    }
+}
+
+pub fn validate(config: &DisplaySleepSettings) -> Result<(), String> {
+    for hour in [config.from_hour, config.to_hour] {
+        if hour > 23 {
+            return Err(crate::i18n::format("error.schedule_hour", &[&hour]));
+        }
+    }
+    if !config.hotkey.is_empty() {
+        config.hotkey.parse::<crate::hotkey::Hotkey>().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Monitors put to sleep and the input tick at that moment; None while all are awake
+static SLEEPING: Mutex<Option<(Vec<MonitorInfo>, u32)>> = Mutex::new(None);
+
+/// Check whether the secondary displays are asleep
+pub fn is_sleeping() -> bool {
+    SLEEPING.lock().unwrap().is_some()
+}
+
+/// Whether an hour falls in the night window (which may wrap past midnight)
+fn in_window(config: &DisplaySleepSettings, hour: u8) -> bool {
+    if config.from_hour <= config.to_hour {
+        (config.from_hour..config.to_hour).contains(&hour)
+    } else {
+        hour >= config.from_hour || hour < config.to_hour
+    }
+}
+
+#[cfg(windows)]
+mod ddc {
+    use crate::gamma::MonitorInfo;
+    use std::ffi::c_void;
+
+    /// VCP code for the display's power mode
+    const VCP_POWER_MODE: u8 = 0xD6;
+    pub const POWER_ON: u32 = 1;
+    /// DPM standby, which the monitor can still be woken from over DDC/CI
+    pub const POWER_STANDBY: u32 = 4;
+
+    const MONITOR_DEFAULTTONULL: u32 = 0;
+
+    /// POINT structure
+    #[repr(C)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    /// PHYSICAL_MONITOR structure
+    #[repr(C)]
+    struct PhysicalMonitor {
+        handle: *mut c_void,
+        description: [u16; 128],
+    }
+
+    /// LASTINPUTINFO structure
+    #[repr(C)]
+    struct LastInputInfo {
+        cb_size: u32,
+        time: u32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn MonitorFromPoint(pt: Point, flags: u32) -> *mut c_void;
+        fn GetLastInputInfo(info: *mut LastInputInfo) -> i32;
+    }
+
+    #[link(name = "dxva2")]
+    extern "system" {
+        fn GetNumberOfPhysicalMonitorsFromHMONITOR(monitor: *mut c_void, count: *mut u32) -> i32;
+        fn GetPhysicalMonitorsFromHMONITOR(monitor: *mut c_void, count: u32, monitors: *mut PhysicalMonitor) -> i32;
+        fn SetVCPFeature(monitor: *mut c_void, code: u8, value: u32) -> i32;
+        fn DestroyPhysicalMonitors(count: u32, monitors: *const PhysicalMonitor) -> i32;
+    }
+
+    /// Set the power mode of every physical display behind a monitor
+    pub fn set_power(monitor: &MonitorInfo, mode: u32) -> Result<(), String> {
+        unsafe {
+            let hmonitor = MonitorFromPoint(Point { x: monitor.x, y: monitor.y }, MONITOR_DEFAULTTONULL);
+            if hmonitor.is_null() {
+                return Err(crate::i18n::format("error.monitor_not_found", &[&monitor.index]));
+            }
+            let mut count = 0;
+            if GetNumberOfPhysicalMonitorsFromHMONITOR(hmonitor, &mut count) == 0 || count == 0 {
+                return Err(crate::i18n::format("error.no_ddc", &[&monitor.index]));
+            }
+            let mut physical: Vec<PhysicalMonitor> = (0..count)
+                .map(|_| PhysicalMonitor { handle: std::ptr::null_mut(), description: [0; 128] })
+                .collect();
+            if GetPhysicalMonitorsFromHMONITOR(hmonitor, count, physical.as_mut_ptr()) == 0 {
+                return Err(crate::i18n::format("error.no_ddc", &[&monitor.index]));
+            }
+            let written = physical.iter().filter(|p| SetVCPFeature(p.handle, VCP_POWER_MODE, mode) != 0).count();
+            DestroyPhysicalMonitors(count, physical.as_ptr());
+            if written == 0 {
+                return Err(crate::i18n::format("error.ddc_power_ignored", &[&monitor.index]));
+            }
+        }
+        Ok(())
+    }
+
+    /// Tick count of the last keyboard or mouse input
+    pub fn last_input() -> u32 {
+        let mut info = LastInputInfo { cb_size: std::mem::size_of::<LastInputInfo>() as u32, time: 0 };
+        unsafe { GetLastInputInfo(&mut info) };
+        info.time
+    }
+}
+
+#[cfg(not(windows))]
+mod ddc {
+    use crate::gamma::MonitorInfo;
+
+    pub const POWER_ON: u32 = 1;
+    pub const POWER_STANDBY: u32 = 4;
+
+    pub fn set_power(_monitor: &MonitorInfo, _mode: u32) -> Result<(), String> {
+        Err(crate::i18n::text("error.windows_only"))
+    }
+
+    pub fn last_input() -> u32 {
+        0
+    }
+}
+
+/// Put every secondary display into standby until the next input
+/// Returns true if this call changed anything
+pub fn sleep() -> Result<bool, String> {
+    let mut sleeping = SLEEPING.lock().unwrap();
+    if sleeping.is_some() {
+        return Ok(false);
+    }
+    let secondaries: Vec<MonitorInfo> = gamma::get_monitors().into_iter().filter(|m| !m.is_primary).collect();
+    if secondaries.is_empty() {
+        return Err(crate::i18n::text("error.no_secondary_displays"));
+    }
+    let slept: Vec<MonitorInfo> = secondaries.into_iter().filter(|m| ddc::set_power(m, ddc::POWER_STANDBY).is_ok()).collect();
+    if slept.is_empty() {
+        return Err(crate::i18n::text("error.no_display_slept"));
+    }
+    *sleeping = Some((slept, ddc::last_input()));
+    Ok(true)
+}
+
+/// Wake the displays put to sleep, returning true if any were asleep
+pub fn wake() -> bool {
+    let Some((slept, _)) = SLEEPING.lock().unwrap().take() else { return false };
+    for monitor in &slept {
+        let _ = ddc::set_power(monitor, ddc::POWER_ON);
+    }
+    true
+}
+
+/// Sleep the secondary displays, or wake them if they're asleep; returns whether they're now asleep
+pub fn toggle() -> Result<bool, String> {
+    if wake() {
+        return Ok(false);
+    }
+    sleep()
+}
+
+/// Wake the displays on input and sleep them when the night window starts, calling
+/// `on_change` with the new sleeping state
+pub fn start_watcher<F>(on_change: F)
+where
+    F: Fn(bool) + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut was_in_window = false;
+        while !crate::shutdown::is_shutting_down() {
+            thread::sleep(POLL_INTERVAL);
+            let input_since_sleep = SLEEPING
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|(_, input)| ddc::last_input() != *input);
+            if input_since_sleep && wake() {
+                on_change(false);
+            }
+
+            // Only the start of the window sleeps the displays, so waking them sticks
+            let config = crate::settings::get().display_sleep;
+            let now_in_window = config.schedule_enabled && in_window(&config, crate::schedule::local_hour());
+            if now_in_window && !was_in_window && sleep().unwrap_or(false) {
+                on_change(true);
+            }
+            was_in_window = now_in_window;
+        }
+    });
+}
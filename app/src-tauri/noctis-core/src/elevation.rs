@@ -0,0 +1,379 @@
+//! Elevated games - Windows keeps a non-elevated Noctis from capturing windows of, or reading
+//! input meant for, a game run as administrator. Detects that case so the UI can explain it,
+//! and can start an elevated broker (a second copy of the executable with `--broker <pid>`)
+//! that takes sensor captures and reads hotkey state on the main process' behalf
+
+use crate::foreground::ForegroundApp;
+use serde::Serialize;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(windows)]
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Command line flag that runs the executable as the broker for a process id
+const BROKER_FLAG: &str = "--broker";
+
+/// Pipe the broker answers on
+const BROKER_PIPE: &str = "noctis-broker";
+
+/// How often the relay reads the hotkey through the broker
+const RELAY_POLL: Duration = Duration::from_millis(30);
+
+/// How often the relay checks whether the broker is running
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Attempts to reach a broker whose pipe is busy with another request
+const CONNECT_ATTEMPTS: u32 = 10;
+const CONNECT_RETRY: Duration = Duration::from_millis(5);
+
+/// The broker answered its last request
+static BROKER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// What the UI needs to explain elevation problems
+#[derive(Serialize, Clone, PartialEq, Default)]
+pub struct ElevationStatus {
+    /// Noctis itself runs as administrator, so nothing is out of reach
+    pub elevated: bool,
+    /// The elevated broker is running
+    pub broker: bool,
+    /// Elevated foreground process Noctis can't reach on its own, e.g. "Game.exe"
+    pub foreground: Option<String>,
+    /// Sensor and hotkeys are degraded right now: an elevated game is focused and no broker runs
+    pub limited: bool,
+    /// Explanation for the user while limited
+    pub message: Option<String>,
+}
+
+#[cfg(windows)]
+mod token {
+    use std::ffi::c_void;
+
+    /// TOKEN_INFORMATION_CLASS values
+    const TOKEN_USER: u32 = 1;
+    const TOKEN_ELEVATION: u32 = 20;
+    const TOKEN_QUERY: u32 = 0x0008;
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+    const ERROR_ACCESS_DENIED: i32 = 5;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn OpenProcessToken(process: *mut c_void, access: u32, token: *mut *mut c_void) -> i32;
+        fn GetTokenInformation(token: *mut c_void, class: u32, info: *mut c_void, len: u32, returned: *mut u32) -> i32;
+        fn ConvertSidToStringSidW(sid: *mut c_void, string_sid: *mut *mut u16) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> *mut c_void;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+        fn LocalFree(memory: *mut c_void) -> *mut c_void;
+    }
+
+    /// Whether a process token is elevated
+    unsafe fn token_elevated(process: *mut c_void) -> Result<bool, i32> {
+        let mut token = std::ptr::null_mut();
+        if OpenProcessToken(process, TOKEN_QUERY, &mut token) == 0 {
+            return Err(std::io::Error::last_os_error().raw_os_error().unwrap_or(0));
+        }
+        let mut elevated: u32 = 0;
+        let mut returned = 0;
+        let ok = GetTokenInformation(token, TOKEN_ELEVATION, &mut elevated as *mut u32 as *mut c_void, 4, &mut returned);
+        CloseHandle(token);
+        Ok(ok != 0 && elevated != 0)
+    }
+
+    pub fn current_elevated() -> bool {
+        unsafe { token_elevated(GetCurrentProcess()).unwrap_or(false) }
+    }
+
+    /// Whether another process runs elevated
+    /// A token we may not even query belongs to a process above our integrity level
+    pub fn process_elevated(pid: u32) -> bool {
+        unsafe {
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if process.is_null() {
+                return false;
+            }
+            let elevated = token_elevated(process);
+            CloseHandle(process);
+            match elevated {
+                Ok(elevated) => elevated,
+                Err(code) => code == ERROR_ACCESS_DENIED,
+            }
+        }
+    }
+
+    /// String SID of the user this process runs as, e.g. "S-1-5-21-..."
+    pub fn user_sid() -> Option<String> {
+        unsafe {
+            let mut token = std::ptr::null_mut();
+            if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+                return None;
+            }
+            // TOKEN_USER is a SID pointer and attributes, followed by the SID itself
+            let mut buffer = [0u64; 64];
+            let mut returned = 0;
+            let ok = GetTokenInformation(token, TOKEN_USER, buffer.as_mut_ptr() as *mut c_void, std::mem::size_of_val(&buffer) as u32, &mut returned);
+            CloseHandle(token);
+            if ok == 0 {
+                return None;
+            }
+            let sid = buffer[0] as usize as *mut c_void;
+            let mut string = std::ptr::null_mut();
+            if ConvertSidToStringSidW(sid, &mut string) == 0 {
+                return None;
+            }
+            let len = (0..).take_while(|&i| *string.add(i) != 0).count();
+            let sid = String::from_utf16_lossy(std::slice::from_raw_parts(string, len));
+            LocalFree(string as *mut c_void);
+            Some(sid)
+        }
+    }
+}
+
+/// Whether Noctis runs as administrator (checked once; it can't change while running)
+#[cfg(windows)]
+pub fn is_elevated() -> bool {
+    static ELEVATED: OnceLock<bool> = OnceLock::new();
+    *ELEVATED.get_or_init(token::current_elevated)
+}
+
+#[cfg(not(windows))]
+pub fn is_elevated() -> bool {
+    false
+}
+
+/// Whether a process runs above Noctis' integrity level, out of reach of its capture and hooks
+#[cfg(windows)]
+pub fn out_of_reach(pid: u32) -> bool {
+    !is_elevated() && token::process_elevated(pid)
+}
+
+#[cfg(not(windows))]
+pub fn out_of_reach(_pid: u32) -> bool {
+    false
+}
+
+/// Whether the broker should stand in for this process with the foreground app
+pub fn use_broker(app: Option<&ForegroundApp>) -> bool {
+    app.is_some_and(|a| a.elevated) && BROKER_RUNNING.load(Ordering::SeqCst)
+}
+
+/// Current elevation status for the foreground app
+pub fn status(app: Option<&ForegroundApp>) -> ElevationStatus {
+    let foreground = app.filter(|a| a.elevated).map(|a| a.process.clone());
+    let broker = BROKER_RUNNING.load(Ordering::SeqCst);
+    let limited = foreground.is_some() && !broker;
+    ElevationStatus {
+        elevated: is_elevated(),
+        broker,
+        message: foreground.as_ref().filter(|_| limited).map(|p| crate::i18n::format("elevation.limited", &[p])),
+        foreground,
+        limited,
+    }
+}
+
+/// Send one request line to the broker and return its reply
+fn request(line: &str) -> Result<String, String> {
+    let path = format!(r"\\.\pipe\{}", BROKER_PIPE);
+    let mut pipe = None;
+    for _ in 0..CONNECT_ATTEMPTS {
+        match std::fs::OpenOptions::new().read(true).write(true).open(&path) {
+            Ok(file) => {
+                pipe = Some(file);
+                break;
+            }
+            // The broker answers one client at a time and opens the next instance right after
+            Err(e) if e.kind() != ErrorKind::NotFound => thread::sleep(CONNECT_RETRY),
+            Err(_) => break,
+        }
+    }
+    let Some(mut pipe) = pipe else {
+        BROKER_RUNNING.store(false, Ordering::SeqCst);
+        return Err(crate::i18n::text("error.broker_not_running"));
+    };
+    BROKER_RUNNING.store(true, Ordering::SeqCst);
+    writeln!(pipe, "{}", line).map_err(|e| crate::i18n::format("error.broker_request", &[&e]))?;
+    let mut reply = String::new();
+    BufReader::new(pipe).read_line(&mut reply).map_err(|e| crate::i18n::format("error.broker_request", &[&e]))?;
+    let reply = reply.trim();
+    match reply.strip_prefix("error: ") {
+        Some(error) => Err(error.to_string()),
+        None => Ok(reply.to_string()),
+    }
+}
+
+/// Measure screen brightness through the broker, see `sensor::get_screen_brightness`
+pub fn brightness(x: i32, y: i32, width: i32, height: i32) -> Result<f32, String> {
+    let reply = request(&format!("brightness {} {} {} {}", x, y, width, height))?;
+    reply.parse().map_err(|_| crate::i18n::format("error.broker_reply", &[&reply]))
+}
+
+/// Whether every key group is held, each group being virtual-key codes any of which counts
+fn keys_held(keys: &[Vec<i32>]) -> Result<bool, String> {
+    let groups: Vec<String> = keys.iter().map(|g| g.iter().map(|k| k.to_string()).collect::<Vec<_>>().join(",")).collect();
+    Ok(request(&format!("keys {}", groups.join(" ")))? == "1")
+}
+
+/// Answer one broker request
+#[cfg(windows)]
+fn respond(line: &str) -> String {
+    let mut parts = line.split_whitespace();
+    let reply = match parts.next() {
+        Some("ping") => Ok("ok".to_string()),
+        Some("brightness") => {
+            let values: Vec<i32> = parts.filter_map(|p| p.parse().ok()).collect();
+            match values[..] {
+                [x, y, width, height] if width > 0 && height > 0 => {
+                    crate::sensor::get_screen_brightness(x, y, width, height).map(|b| b.to_string())
+                }
+                _ => Err(crate::i18n::text("error.capture_region")),
+            }
+        }
+        Some("keys") => {
+            let groups: Vec<Vec<i32>> = parts.map(|g| g.split(',').filter_map(|k| k.parse().ok()).collect()).collect();
+            Ok(if held(&groups) { "1" } else { "0" }.to_string())
+        }
+        _ => Err("unknown request".to_string()),
+    };
+    reply.unwrap_or_else(|e| format!("error: {}", e))
+}
+
+#[cfg(windows)]
+fn held(groups: &[Vec<i32>]) -> bool {
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetAsyncKeyState(key: i32) -> i16;
+    }
+    !groups.is_empty() && groups.iter().all(|g| g.iter().any(|&vk| unsafe { GetAsyncKeyState(vk) } < 0))
+}
+
+/// Process id to serve if this instance was started as the broker
+pub fn broker_for() -> Option<u32> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == BROKER_FLAG)?;
+    args.get(index + 1)?.parse().ok()
+}
+
+/// Answer the main process until it exits or sends "exit"
+/// The pipe admits only the current user at medium integrity, and only the main process is
+/// answered, so the broker's elevation can't be borrowed by other programs
+#[cfg(windows)]
+pub fn run_broker(pid: u32) {
+    let Some(sid) = token::user_sid() else { return };
+    let sddl = format!("D:(A;;GRGW;;;{})(A;;GA;;;SY)S:(ML;;NW;;;ME)", sid);
+
+    // Leave with the main process, like the watchdog
+    thread::spawn(move || {
+        crate::watchdog::wait_for(pid);
+        std::process::exit(0);
+    });
+
+    let exit = Mutex::new(false);
+    while !*exit.lock().unwrap() {
+        let served = crate::control::pipe::serve_one_restricted(BROKER_PIPE, Some(&sddl), Some(pid), &|line| {
+            if line.trim() == "exit" {
+                *exit.lock().unwrap() = true;
+                return "ok".to_string();
+            }
+            respond(line)
+        });
+        if !served {
+            return;
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn run_broker(_pid: u32) {}
+
+/// Start the broker elevated; Windows asks the user to confirm
+#[cfg(windows)]
+pub fn start_broker() -> Result<(), String> {
+    use std::ffi::c_void;
+
+    const SW_HIDE: i32 = 0;
+
+    #[link(name = "shell32")]
+    extern "system" {
+        fn ShellExecuteW(hwnd: *mut c_void, verb: *const u16, file: *const u16, params: *const u16, dir: *const u16, show: i32) -> isize;
+    }
+
+    if is_elevated() {
+        return Err(crate::i18n::text("error.already_elevated"));
+    }
+    if request("ping").is_ok() {
+        return Ok(());
+    }
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))?;
+    // The broker must find the same config folder for the sensor settings
+    let portable = if crate::portable::is_portable() { " --portable" } else { "" };
+    let params = format!("{} {}{}", BROKER_FLAG, std::process::id(), portable);
+    let wide = |s: &str| s.encode_utf16().chain(std::iter::once(0)).collect::<Vec<u16>>();
+    let (verb, file, params) = (wide("runas"), wide(&exe.to_string_lossy()), wide(&params));
+    let result = unsafe { ShellExecuteW(std::ptr::null_mut(), verb.as_ptr(), file.as_ptr(), params.as_ptr(), std::ptr::null(), SW_HIDE) };
+    // Values up to 32 are errors, including the user declining the prompt
+    if result <= 32 {
+        return Err(crate::i18n::text("error.broker_not_started"));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn start_broker() -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+/// Ask the broker to exit
+pub fn stop_broker() {
+    let _ = request("exit");
+    BROKER_RUNNING.store(false, Ordering::SeqCst);
+}
+
+/// Keep the broker state current and relay the toggle hotkey through the broker while an
+/// elevated game has focus (the shortcut backend can't see those key presses)
+/// `on_change` gets the status whenever it changes, `on_hotkey` fires on hotkey release
+pub fn start<C, H>(on_change: C, on_hotkey: H)
+where
+    C: Fn(ElevationStatus) + Send + 'static,
+    H: Fn() + Send + 'static,
+{
+    if is_elevated() {
+        return;
+    }
+    thread::spawn(move || {
+        let mut last = None;
+        let mut pressed = false;
+        let mut pinged: Option<Instant> = None;
+        while !crate::shutdown::is_shutting_down() {
+            let app = crate::foreground::current();
+            let elevated = app.as_ref().is_some_and(|a| a.elevated);
+            if (elevated || BROKER_RUNNING.load(Ordering::SeqCst)) && pinged.is_none_or(|at| at.elapsed() >= PING_INTERVAL) {
+                let _ = request("ping");
+                pinged = Some(Instant::now());
+            }
+
+            let status = status(app.as_ref());
+            if last.as_ref() != Some(&status) {
+                on_change(status.clone());
+                last = Some(status);
+            }
+
+            if use_broker(app.as_ref()) {
+                let settings = crate::settings::get();
+                let keys = settings.hotkey.parse::<crate::hotkey::Hotkey>().ok().and_then(|h| h.virtual_keys());
+                let down = keys.is_some_and(|keys| keys_held(&keys).unwrap_or(false));
+                if pressed && !down && crate::hotkey::in_scope(&settings.hotkey_scope, app.as_ref()) {
+                    on_hotkey();
+                }
+                pressed = down;
+            } else {
+                pressed = false;
+            }
+            thread::sleep(RELAY_POLL);
+        }
+    });
+}
@@ -0,0 +1,102 @@
+//! Emergency reset - a fixed chord that always restores the display
+//! Registered with its own Win32 hotkey rather than the shortcut plugin, so no setting, scope
+//! or rebinding of the toggle hotkey can take it away
+
+use crate::{safe_mode, toggle};
+
+/// The chord, for display in the UI
+pub const LABEL: &str = "Ctrl+Alt+Shift+F12";
+
+/// Undo everything and stop automation: the toggle switches off and safe mode holds
+/// effects off (no profiles, no auto-adjust) until the user leaves it
+/// Returns whether the toggle was on, and the suspension change
+pub fn reset() -> (bool, Option<bool>) {
+    crate::crash::log("Emergency reset");
+    let was_active = toggle::switch_off();
+    (was_active, safe_mode::enter())
+}
+
+#[cfg(windows)]
+mod hotkey {
+    use std::ffi::c_void;
+    use std::ptr;
+
+    const MOD_ALT: u32 = 0x0001;
+    const MOD_CONTROL: u32 = 0x0002;
+    const MOD_SHIFT: u32 = 0x0004;
+    const MOD_NOREPEAT: u32 = 0x4000;
+    const VK_F12: u32 = 0x7B;
+    const WM_HOTKEY: u32 = 0x0312;
+
+    /// Hotkey id, unique to this thread
+    const HOTKEY_ID: i32 = 1;
+
+    /// POINT structure
+    #[repr(C)]
+    #[derive(Default)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    /// MSG structure for the hotkey thread's message loop
+    #[repr(C)]
+    struct Msg {
+        hwnd: *mut c_void,
+        message: u32,
+        w_param: usize,
+        l_param: isize,
+        time: u32,
+        pt: Point,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn RegisterHotKey(hwnd: *mut c_void, id: i32, modifiers: u32, vk: u32) -> i32;
+        fn GetMessageW(msg: *mut Msg, hwnd: *mut c_void, filter_min: u32, filter_max: u32) -> i32;
+    }
+
+    /// Register the chord for this thread and run `on_reset` each time it's pressed
+    pub fn run<F: Fn()>(on_reset: F) {
+        unsafe {
+            if RegisterHotKey(ptr::null_mut(), HOTKEY_ID, MOD_CONTROL | MOD_ALT | MOD_SHIFT | MOD_NOREPEAT, VK_F12) == 0 {
+                crate::crash::log(format!("Emergency reset hotkey {} is taken", super::LABEL));
+                return;
+            }
+            let mut msg = Msg {
+                hwnd: ptr::null_mut(),
+                message: 0,
+                w_param: 0,
+                l_param: 0,
+                time: 0,
+                pt: Point::default(),
+            };
+            while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) > 0 {
+                if msg.message == WM_HOTKEY && msg.w_param == HOTKEY_ID as usize {
+                    on_reset();
+                }
+            }
+        }
+    }
+}
+
+/// Listen for the chord on a dedicated thread, calling `on_reset` with the result of each reset
+#[cfg(windows)]
+pub fn start<F>(on_reset: F)
+where
+    F: Fn(bool, Option<bool>) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        hotkey::run(|| {
+            let (was_active, changed) = reset();
+            on_reset(was_active, changed);
+        })
+    });
+}
+
+#[cfg(not(windows))]
+pub fn start<F>(_on_reset: F)
+where
+    F: Fn(bool, Option<bool>) + Send + 'static,
+{
+}
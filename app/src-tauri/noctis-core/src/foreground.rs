@@ -0,0 +1,237 @@
+//! Foreground application watcher
+//! Uses a SetWinEventHook(EVENT_SYSTEM_FOREGROUND) hook so focus changes are reported
+//! instantly without polling
+
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::ptr;
+use std::thread;
+
+/// Process that owns the current foreground window
+#[derive(serde::Serialize, Clone, PartialEq, Default)]
+pub struct ForegroundApp {
+    pub pid: u32,
+    /// Executable file name, e.g. "EscapeFromTarkov.exe"
+    pub process: String,
+    /// Full image path, empty if it could not be queried
+    pub path: String,
+    /// Runs as administrator while Noctis doesn't, see `elevation`
+    pub elevated: bool,
+}
+
+const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+const EVENT_SYSTEM_FOREGROUND: u32 = 0x0003;
+const WINEVENT_OUTOFCONTEXT: u32 = 0x0000;
+
+/// POINT structure
+#[repr(C)]
+#[derive(Default)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+/// MSG structure for the hook thread's message loop
+#[repr(C)]
+struct Msg {
+    hwnd: *mut c_void,
+    message: u32,
+    w_param: usize,
+    l_param: isize,
+    time: u32,
+    pt: Point,
+}
+
+type WinEventProc = unsafe extern "system" fn(*mut c_void, u32, *mut c_void, i32, i32, u32, u32);
+
+#[cfg(windows)]
+#[link(name = "user32")]
+extern "system" {
+    fn GetForegroundWindow() -> *mut c_void;
+    fn GetWindowThreadProcessId(hwnd: *mut c_void, process_id: *mut u32) -> u32;
+    fn SetWinEventHook(
+        event_min: u32,
+        event_max: u32,
+        hmod: *mut c_void,
+        callback: WinEventProc,
+        process_id: u32,
+        thread_id: u32,
+        flags: u32,
+    ) -> *mut c_void;
+    fn GetMessageW(msg: *mut Msg, hwnd: *mut c_void, filter_min: u32, filter_max: u32) -> i32;
+    fn TranslateMessage(msg: *const Msg) -> i32;
+    fn DispatchMessageW(msg: *const Msg) -> isize;
+}
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> *mut c_void;
+    fn QueryFullProcessImageNameW(process: *mut c_void, flags: u32, exe_name: *mut u16, size: *mut u32) -> i32;
+    fn CloseHandle(handle: *mut c_void) -> i32;
+}
+
+/// Get the full image path of a process by id
+#[cfg(windows)]
+pub fn process_path(pid: u32) -> Option<String> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut buffer = [0u16; 1024];
+        let mut size = buffer.len() as u32;
+        let ok = QueryFullProcessImageNameW(handle, 0, buffer.as_mut_ptr(), &mut size);
+        CloseHandle(handle);
+
+        if ok == 0 {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&buffer[..size as usize]))
+    }
+}
+
+/// Get the process owning the foreground window
+#[cfg(windows)]
+pub fn current() -> Option<ForegroundApp> {
+    unsafe { app_for_window(GetForegroundWindow()) }
+}
+
+/// Get the foreground window handle
+#[cfg(windows)]
+pub fn window() -> Option<isize> {
+    let hwnd = unsafe { GetForegroundWindow() };
+    (!hwnd.is_null()).then_some(hwnd as isize)
+}
+
+/// Get the process owning a window
+#[cfg(windows)]
+fn app_for_window(hwnd: *mut c_void) -> Option<ForegroundApp> {
+    unsafe {
+        if hwnd.is_null() {
+            return None;
+        }
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        if pid == 0 {
+            return None;
+        }
+
+        let path = process_path(pid).unwrap_or_default();
+        Some(ForegroundApp {
+            pid,
+            process: file_name(&path),
+            path,
+            elevated: crate::elevation::out_of_reach(pid),
+        })
+    }
+}
+
+#[cfg(not(windows))]
+pub fn process_path(_pid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(not(windows))]
+pub fn current() -> Option<ForegroundApp> {
+    None
+}
+
+#[cfg(not(windows))]
+pub fn window() -> Option<isize> {
+    None
+}
+
+/// Extract the executable name from a full path
+pub fn file_name(path: &str) -> String {
+    path.rsplit(['\\', '/']).next().unwrap_or(path).to_string()
+}
+
+type ChangeCallback = Box<dyn Fn(&ForegroundApp)>;
+
+thread_local! {
+    /// Callback and last reported app for the hook thread
+    /// Out-of-context WinEvent hooks are delivered on the thread that installed them
+    static WATCHER: RefCell<Option<(ChangeCallback, ForegroundApp)>> = const { RefCell::new(None) };
+}
+
+/// Report an app to the watcher callback if it differs from the last one
+fn report(app: ForegroundApp) {
+    WATCHER.with(|watcher| {
+        if let Some((on_change, last)) = watcher.borrow_mut().as_mut() {
+            if app != *last {
+                on_change(&app);
+                *last = app;
+            }
+        }
+    });
+}
+
+/// WinEvent callback for EVENT_SYSTEM_FOREGROUND
+#[cfg(windows)]
+unsafe extern "system" fn foreground_event_callback(
+    _hook: *mut c_void,
+    _event: u32,
+    hwnd: *mut c_void,
+    _id_object: i32,
+    _id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    if let Some(app) = app_for_window(hwnd) {
+        report(app);
+    }
+}
+
+/// Start a background thread that calls `on_change` whenever the foreground app changes
+/// The current foreground app is reported immediately
+#[cfg(windows)]
+pub fn start_watcher<F>(on_change: F)
+where
+    F: Fn(&ForegroundApp) + Send + 'static,
+{
+    thread::spawn(move || unsafe {
+        WATCHER.with(|watcher| {
+            *watcher.borrow_mut() = Some((Box::new(on_change), ForegroundApp::default()));
+        });
+        if let Some(app) = current() {
+            report(app);
+        }
+
+        let hook = SetWinEventHook(
+            EVENT_SYSTEM_FOREGROUND,
+            EVENT_SYSTEM_FOREGROUND,
+            ptr::null_mut(),
+            foreground_event_callback,
+            0,
+            0,
+            WINEVENT_OUTOFCONTEXT,
+        );
+        if hook.is_null() {
+            return;
+        }
+
+        // Hook callbacks are dispatched through this thread's message loop
+        let mut msg = Msg {
+            hwnd: ptr::null_mut(),
+            message: 0,
+            w_param: 0,
+            l_param: 0,
+            time: 0,
+            pt: Point::default(),
+        };
+        while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn start_watcher<F>(_on_change: F)
+where
+    F: Fn(&ForegroundApp) + Send + 'static,
+{
+}
@@ -0,0 +1,171 @@
+//! Frame-rate guard - watches what the adjustment pipeline costs per second while a game
+//! profile is active and backs off when it goes over budget: a smaller capture first, then
+//! fewer readings, then no sensing at all until the cost has stayed low for a while
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How often the pipeline cost is measured
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Seconds over budget in a row before backing off a level
+const OVER_SECONDS: u32 = 3;
+
+/// Seconds under half the budget in a row before recovering a level
+const UNDER_SECONDS: u32 = 30;
+
+/// Readings are this many times further apart at the Reduced rate level
+const RATE_FACTOR: u64 = 4;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct GuardSettings {
+    pub enabled: bool,
+    /// Pipeline time allowed per second while a game profile is active, in milliseconds
+    pub budget_ms: f32,
+}
+
+impl Default for GuardSettings {
+    fn default() -> Self {
+        Self { enabled: true, budget_ms: 20.0 }
+    }
+}
+
+/// How far the guard has backed off, in the order it escalates
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardLevel {
+    #[default]
+    Normal,
+    /// Captures are downscaled to half the frame size
+    ReducedResolution,
+    /// Also reading RATE_FACTOR times less often
+    ReducedRate,
+    /// Sensing stops until the cost recovers
+    Paused,
+}
+
+impl GuardLevel {
+    const ALL: [GuardLevel; 4] = [GuardLevel::Normal, GuardLevel::ReducedResolution, GuardLevel::ReducedRate, GuardLevel::Paused];
+
+    fn escalate(self) -> Self {
+        Self::ALL[(self as usize + 1).min(Self::ALL.len() - 1)]
+    }
+
+    fn relax(self) -> Self {
+        Self::ALL[(self as usize).saturating_sub(1)]
+    }
+}
+
+/// Guard state, sent to the UI when it changes
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct GuardStatus {
+    pub level: GuardLevel,
+    /// Pipeline time over the last second, in milliseconds
+    pub cost_ms: f32,
+    pub budget_ms: f32,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(GuardLevel::Normal as u8);
+
+/// Pipeline time over the last measured second, in microseconds
+static COST_US: AtomicU64 = AtomicU64::new(0);
+
+/// Current back-off level
+pub fn level() -> GuardLevel {
+    GuardLevel::ALL[LEVEL.load(Ordering::Relaxed) as usize]
+}
+
+/// Current level and cost
+pub fn status() -> GuardStatus {
+    GuardStatus {
+        level: level(),
+        cost_ms: COST_US.load(Ordering::Relaxed) as f32 / 1000.0,
+        budget_ms: crate::settings::get().frame_guard.budget_ms,
+    }
+}
+
+/// Capture frame size to use for a configured one
+pub fn frame_size(width: i32, height: i32) -> (i32, i32) {
+    if level() >= GuardLevel::ReducedResolution {
+        ((width / 2).max(1), (height / 2).max(1))
+    } else {
+        (width, height)
+    }
+}
+
+/// Time between readings to use for a configured one
+pub fn interval_ms(interval_ms: u64) -> u64 {
+    if level() >= GuardLevel::ReducedRate {
+        interval_ms * RATE_FACTOR
+    } else {
+        interval_ms
+    }
+}
+
+/// Whether sensing is paused to protect the frame rate
+pub fn is_paused() -> bool {
+    level() == GuardLevel::Paused
+}
+
+/// Validate and save the guard settings
+pub fn set_settings(config: GuardSettings) -> Result<(), String> {
+    if !config.budget_ms.is_finite() || config.budget_ms <= 0.0 {
+        return Err(crate::i18n::text("error.frame_budget"));
+    }
+    crate::settings::update(|s| s.frame_guard = config)?;
+    Ok(())
+}
+
+/// Start measuring the pipeline cost; `on_change` gets the status whenever the level changes
+pub fn start<F>(on_change: F)
+where
+    F: Fn(GuardStatus) + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut last_total = crate::perf::total_us();
+        let (mut over, mut under) = (0u32, 0u32);
+        while !crate::shutdown::is_shutting_down() {
+            thread::sleep(CHECK_INTERVAL);
+            let total = crate::perf::total_us();
+            let cost_us = total.saturating_sub(last_total);
+            COST_US.store(cost_us, Ordering::Relaxed);
+            let cost_ms = cost_us as f32 / 1000.0;
+            last_total = total;
+
+            let config = crate::settings::get().frame_guard;
+            let current = level();
+            let next = if !config.enabled || crate::profiles::active().is_none() {
+                (over, under) = (0, 0);
+                GuardLevel::Normal
+            } else if cost_ms > config.budget_ms {
+                (over, under) = (over + 1, 0);
+                if over >= OVER_SECONDS {
+                    over = 0;
+                    current.escalate()
+                } else {
+                    current
+                }
+            } else if cost_ms < config.budget_ms / 2.0 {
+                (over, under) = (0, under + 1);
+                if under >= UNDER_SECONDS {
+                    under = 0;
+                    current.relax()
+                } else {
+                    current
+                }
+            } else {
+                (over, under) = (0, 0);
+                current
+            };
+
+            if next != current {
+                LEVEL.store(next as u8, Ordering::Relaxed);
+                crate::crash::log(format!("Frame-rate guard: {:?} at {:.1} ms/s (budget {} ms/s)", next, cost_ms, config.budget_ms));
+                on_change(GuardStatus { level: next, cost_ms, budget_ms: config.budget_ms });
+            }
+        }
+    });
+}
@@ -0,0 +1,743 @@
+//! Gamma control module - Windows API implementation
+//! Supports multi-monitor with position info for layout visualization
+//! Uses manual FFI for GDI functions to avoid crate version conflicts.
+
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::{Mutex, RwLock};
+
+/// The RAMP structure matches Windows GAMMARAMP (768 bytes total)
+#[repr(C)]
+#[derive(Clone)]
+pub struct GammaRamp {
+    pub red: [u16; 256],
+    pub green: [u16; 256],
+    pub blue: [u16; 256],
+}
+
+impl GammaRamp {
+    /// Map an 8-bit RGB color through the ramp, as the display would at scanout
+    pub fn apply(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let to_u8 = |v: u16| ((v as f32 / 65535.0) * 255.0).round() as u8;
+        [
+            to_u8(self.red[rgb[0] as usize]),
+            to_u8(self.green[rgb[1] as usize]),
+            to_u8(self.blue[rgb[2] as usize]),
+        ]
+    }
+}
+
+/// Last ramp successfully applied to each monitor, keyed by monitor index
+static ACTIVE_RAMPS: Mutex<Vec<(u32, GammaRamp)>> = Mutex::new(Vec::new());
+
+/// Remember the ramp now active on a monitor
+fn record_active_ramp(monitor_index: u32, ramp: &GammaRamp) {
+    let mut ramps = ACTIVE_RAMPS.lock().unwrap();
+    ramps.retain(|(index, _)| *index != monitor_index);
+    ramps.push((monitor_index, ramp.clone()));
+    drop(ramps);
+    crate::session::changed();
+    crate::ramp_log::record_ramp(monitor_index, ramp);
+}
+
+/// Get the ramp currently applied to a monitor, if Noctis has set one
+pub fn active_ramp(monitor_index: u32) -> Option<GammaRamp> {
+    let ramps = ACTIVE_RAMPS.lock().unwrap();
+    ramps.iter().find(|(index, _)| *index == monitor_index).map(|(_, ramp)| ramp.clone())
+}
+
+/// RECT structure for monitor bounds
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Rect {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+/// MONITORINFOEX structure
+#[repr(C)]
+struct MonitorInfoEx {
+    cb_size: u32,
+    rc_monitor: Rect,
+    rc_work: Rect,
+    dw_flags: u32,
+    sz_device: [u16; 32],
+}
+
+/// Monitor information returned to frontend with position
+#[derive(serde::Serialize, Clone)]
+pub struct MonitorInfo {
+    pub index: u32,
+    pub name: String,
+    /// Device interface path of the monitor itself, which stays the same when monitors are
+    /// reordered or reconnected (falls back to `name` if the driver reports none)
+    pub device_id: String,
+    /// Monitors showing this display, more than one when it is duplicated
+    pub outputs: Vec<crate::topology::Output>,
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+    pub is_primary: bool,
+}
+
+const MONITORINFOF_PRIMARY: u32 = 0x1;
+
+type MonitorEnumProc = unsafe extern "system" fn(*mut c_void, *mut c_void, *mut Rect, isize) -> i32;
+
+// Windows API function signatures
+#[cfg(windows)]
+#[link(name = "gdi32")]
+extern "system" {
+    fn SetDeviceGammaRamp(hdc: *mut c_void, lp_ramp: *const GammaRamp) -> i32;
+    fn GetDeviceGammaRamp(hdc: *mut c_void, lp_ramp: *mut GammaRamp) -> i32;
+    fn CreateDCW(driver: *const u16, device: *const u16, output: *const u16, init_data: *const c_void) -> *mut c_void;
+    fn DeleteDC(hdc: *mut c_void) -> i32;
+}
+
+#[cfg(windows)]
+#[link(name = "user32")]
+extern "system" {
+    fn EnumDisplayMonitors(hdc: *mut c_void, lprc_clip: *const Rect, lpfn_enum: MonitorEnumProc, dw_data: isize) -> i32;
+    fn GetMonitorInfoW(hmonitor: *mut c_void, lpmi: *mut MonitorInfoEx) -> i32;
+}
+
+/// Convert wide string to Rust string
+fn wide_to_string(wide: &[u16]) -> String {
+    let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+    String::from_utf16_lossy(&wide[..len])
+}
+
+/// Convert Rust string to wide string
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Collected monitor data during enumeration
+struct MonitorData {
+    monitors: Vec<MonitorInfo>,
+}
+
+/// DISPLAY_DEVICEW, for looking up the monitor attached to a display
+#[cfg(windows)]
+#[repr(C)]
+struct DisplayDevice {
+    cb: u32,
+    device_name: [u16; 32],
+    device_string: [u16; 128],
+    state_flags: u32,
+    device_id: [u16; 128],
+    device_key: [u16; 128],
+}
+
+/// Ask EnumDisplayDevices for the device interface name instead of the hardware id
+#[cfg(windows)]
+const EDD_GET_DEVICE_INTERFACE_NAME: u32 = 0x1;
+
+#[cfg(windows)]
+#[link(name = "user32")]
+extern "system" {
+    fn EnumDisplayDevicesW(device: *const u16, dev_num: u32, display_device: *mut DisplayDevice, flags: u32) -> i32;
+}
+
+/// Device interface path of the first monitor on a display, e.g. "\\?\DISPLAY#DEL40F5#..."
+#[cfg(windows)]
+fn monitor_device_id(display_name: &str) -> Option<String> {
+    let name = to_wide(display_name);
+    let mut device = DisplayDevice {
+        cb: std::mem::size_of::<DisplayDevice>() as u32,
+        device_name: [0; 32],
+        device_string: [0; 128],
+        state_flags: 0,
+        device_id: [0; 128],
+        device_key: [0; 128],
+    };
+    let found = unsafe { EnumDisplayDevicesW(name.as_ptr(), 0, &mut device, EDD_GET_DEVICE_INTERFACE_NAME) } != 0;
+    Some(wide_to_string(&device.device_id)).filter(|id| found && !id.is_empty())
+}
+
+/// Callback for EnumDisplayMonitors
+#[cfg(windows)]
+unsafe extern "system" fn monitor_enum_callback(
+    hmonitor: *mut c_void,
+    _hdc: *mut c_void,
+    _lprc: *mut Rect,
+    dw_data: isize,
+) -> i32 {
+    let data = &mut *(dw_data as *mut MonitorData);
+    
+    let mut info = MonitorInfoEx {
+        cb_size: std::mem::size_of::<MonitorInfoEx>() as u32,
+        rc_monitor: Rect::default(),
+        rc_work: Rect::default(),
+        dw_flags: 0,
+        sz_device: [0; 32],
+    };
+    
+    if GetMonitorInfoW(hmonitor, &mut info) != 0 {
+        let is_primary = (info.dw_flags & MONITORINFOF_PRIMARY) != 0;
+        let width = (info.rc_monitor.right - info.rc_monitor.left) as u32;
+        let height = (info.rc_monitor.bottom - info.rc_monitor.top) as u32;
+        
+        let name = wide_to_string(&info.sz_device);
+        // A duplicated display is identified by its first physical output, whichever
+        // clone the driver happens to list first
+        let outputs = crate::topology::outputs(&name);
+        let device_id = outputs
+            .iter()
+            .find(|o| !o.virtual_output)
+            .or(outputs.first())
+            .map(|o| o.device_path.clone())
+            .or_else(|| monitor_device_id(&name))
+            .unwrap_or_else(|| name.clone());
+        data.monitors.push(MonitorInfo {
+            index: data.monitors.len() as u32 + 1,
+            device_id,
+            outputs,
+            name,
+            width,
+            height,
+            x: info.rc_monitor.left,
+            y: info.rc_monitor.top,
+            is_primary,
+        });
+    }
+    
+    1 // Continue enumeration
+}
+
+/// Monitors from the last enumeration, cleared on WM_DISPLAYCHANGE by display_power
+static MONITORS: RwLock<Option<Vec<MonitorInfo>>> = RwLock::new(None);
+
+/// Get list of all connected monitors with position and size
+/// Enumerated once and cached until the display configuration changes
+pub fn get_monitors() -> Vec<MonitorInfo> {
+    if let Some(monitors) = MONITORS.read().unwrap().as_ref() {
+        return monitors.clone();
+    }
+    let monitors = enumerate_monitors();
+    *MONITORS.write().unwrap() = Some(monitors.clone());
+    monitors
+}
+
+/// Forget the cached monitor list and device contexts, e.g. after a monitor was connected or a resolution changed
+pub fn invalidate_monitors() {
+    *MONITORS.write().unwrap() = None;
+    #[cfg(windows)]
+    {
+        close_device_contexts(None);
+        forget_written(None);
+    }
+}
+
+#[cfg(windows)]
+fn enumerate_monitors() -> Vec<MonitorInfo> {
+    unsafe {
+        let mut data = MonitorData { monitors: Vec::new() };
+        
+        EnumDisplayMonitors(
+            ptr::null_mut(),
+            ptr::null(),
+            monitor_enum_callback,
+            &mut data as *mut MonitorData as isize,
+        );
+        
+        // Sort by position: primary first, then left-to-right, top-to-bottom
+        data.monitors.sort_by(|a, b| {
+            if a.is_primary != b.is_primary {
+                return b.is_primary.cmp(&a.is_primary); // Primary first
+            }
+            if a.x != b.x {
+                return a.x.cmp(&b.x); // Left to right
+            }
+            a.y.cmp(&b.y) // Top to bottom
+        });
+        
+        // Reassign indices after sorting
+        for (i, m) in data.monitors.iter_mut().enumerate() {
+            m.index = i as u32 + 1;
+        }
+        
+        data.monitors
+    }
+}
+
+#[cfg(not(windows))]
+fn enumerate_monitors() -> Vec<MonitorInfo> {
+    vec![MonitorInfo { 
+        index: 1, 
+        name: "Primary".to_string(), 
+        device_id: "Primary".to_string(),
+        outputs: Vec::new(),
+        width: 1920, 
+        height: 1080, 
+        x: 0, 
+        y: 0, 
+        is_primary: true 
+    }]
+}
+
+/// Shadow Hunter curve strength at full intensity
+/// Defaults suit a typical sRGB panel; tailored per panel by the measurement report
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct CurveConstants {
+    /// Black lift at full intensity (fraction of full scale)
+    pub max_lift: f32,
+    /// Gamma exponent at full intensity (1.0 = unchanged)
+    pub min_gamma: f32,
+}
+
+impl Default for CurveConstants {
+    fn default() -> Self {
+        Self { max_lift: 0.25, min_gamma: 0.5 }
+    }
+}
+
+/// Curve constants for a monitor, from its stored panel tuning if measured
+fn curve_constants(monitor_index: u32) -> CurveConstants {
+    let Some(monitor) = get_monitors().into_iter().find(|m| m.index == monitor_index) else {
+        return CurveConstants::default();
+    };
+    crate::settings::get()
+        .panel_tuning
+        .iter()
+        .find(|t| t.monitor == monitor.name)
+        .map(|t| t.curve)
+        .unwrap_or_default()
+}
+
+/// Input level above which "preserve contrast" reshapes the lifted curve
+const CONTRAST_KNEE: f32 = 0.25;
+
+/// Preserve-contrast S-curve strength at full intensity (below 1.0 keeps the curve monotonic)
+const CONTRAST_STRENGTH: f32 = 0.4;
+
+/// Tone curves are built at this resolution and reduced to the 256-entry ramp
+const CURVE_POINTS: usize = 1024;
+
+/// Ramp entries are dithered to this precision, the LUT depth of most display pipelines
+const LUT_BITS: u32 = 10;
+
+/// Reduce a fine tone curve to ramp entries
+/// Each entry averages the curve over the input range it stands for (black and white stay
+/// exact), then is quantized to the LUT depth with the rounding error carried into the next
+/// entry, so lifted dark gradients step evenly instead of banding
+fn dither_to_ramp(curve: &[f32]) -> [u16; 256] {
+    let last = curve.len() - 1;
+    let levels = ((1u32 << LUT_BITS) - 1) as f32;
+    let mut entries = [0u16; 256];
+    let mut error = 0.0;
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let value = match i {
+            0 => curve[0],
+            255 => curve[last],
+            _ => {
+                let from = ((i as f32 - 0.5) / 255.0 * last as f32).round() as usize;
+                let to = ((i as f32 + 0.5) / 255.0 * last as f32).round() as usize;
+                curve[from..=to].iter().sum::<f32>() / (to - from + 1) as f32
+            }
+        };
+        let target = value * levels + error;
+        let level = target.round().clamp(0.0, levels);
+        error = target - level;
+        *entry = (level / levels * 65535.0).round() as u16;
+    }
+    entries
+}
+
+/// Apply Gamma Power Curve, then Linear Black Lift
+/// output = lift + input^gamma * (1 - lift)
+fn lifted_level(x: f32, lift: f32, gamma: f32) -> f32 {
+    lift + x.powf(gamma) * (1.0 - lift)
+}
+
+/// Output level (0.0 - 1.0) of the Shadow Hunter curve for an input level, before the
+/// optional contrast guard; the reference other backends are normalized against
+pub fn curve_level(x: f32, intensity: f32, constants: CurveConstants) -> f32 {
+    let intensity = intensity.clamp(0.0, 1.0);
+    lifted_level(x, intensity * constants.max_lift, 1.0 - (intensity * (1.0 - constants.min_gamma)))
+}
+
+// "Shadow Hunter" Hybrid Gamma Curve
+// intensity: 0.0 (Normal) to 1.0 (Max Night Vision)
+// Combines:
+// 1. Gamma Correction (Power Law) - brightens midtones
+// 2. Black Equalizer (Linear Lift) - lifts absolute black
+// 3. Optional contrast guard - a mild S-curve above the knee so text-heavy apps
+//    don't look washed out while shadows stay lifted
+// The curve is computed at CURVE_POINTS and dithered down to the ramp
+fn calculate_curve(intensity: f32, constants: CurveConstants, preserve_contrast: bool) -> GammaRamp {
+    let intensity = intensity.max(0.0).min(1.0);
+    
+    // No adjustment: the exact linear ramp, so restores match what Windows expects
+    if intensity == 0.0 {
+        let mut entries = [0u16; 256];
+        for (i, entry) in entries.iter_mut().enumerate() {
+            *entry = ((i as f32 / 255.0) * 65535.0) as u16;
+        }
+        return GammaRamp { red: entries, green: entries, blue: entries };
+    }
+
+    // 1. Black Equalizer Lift
+    // Max 25% lift at full intensity (by default)
+    let lift = intensity * constants.max_lift;
+    
+    // 2. Gamma Correction
+    // Gamma 1.0 = Normal. Gamma < 1.0 = Brighter.
+    // At max intensity, we go down to gamma 0.5 (by default)
+    let gamma = 1.0 - (intensity * (1.0 - constants.min_gamma));
+
+    let lifted = |x: f32| lifted_level(x, lift, gamma);
+
+    // 3. Contrast Guard
+    // Blend toward smoothstep over the output range above the knee; it meets the
+    // lifted curve at the knee and at white, and grows with intensity
+    let knee = lifted(CONTRAST_KNEE);
+    let strength = intensity * CONTRAST_STRENGTH;
+
+    let curve: Vec<f32> = (0..CURVE_POINTS)
+        .map(|i| {
+            let x = i as f32 / (CURVE_POINTS - 1) as f32;
+            let mut y = lifted(x);
+            if preserve_contrast && x > CONTRAST_KNEE {
+                let t = (y - knee) / (1.0 - knee);
+                let s = t * t * (3.0 - 2.0 * t);
+                y = knee + (t + strength * (s - t)) * (1.0 - knee);
+            }
+            y.clamp(0.0, 1.0)
+        })
+        .collect();
+
+    let entries = dither_to_ramp(&curve);
+    GammaRamp { red: entries, green: entries, blue: entries }
+}
+
+
+/// The unadjusted ramp
+pub fn identity_ramp() -> GammaRamp {
+    calculate_curve(0.0, CurveConstants::default(), false)
+}
+
+/// Ramp for an intensity under given curve constants, e.g. to preview a tuning before storing it
+pub fn curve_ramp(intensity: f32, constants: CurveConstants) -> GammaRamp {
+    calculate_curve(intensity, constants, crate::settings::get().preserve_contrast)
+}
+
+/// Ramps are only written while effects are active and the displays are awake;
+/// otherwise they are recorded and written on resume or wake
+#[cfg(windows)]
+fn should_write() -> bool {
+    !crate::suspend::is_suspended() && crate::display_power::displays_on() && !crate::shutdown::is_shutting_down()
+}
+
+/// Device contexts kept open per monitor device name, closed on WM_DISPLAYCHANGE
+/// Stored as addresses: HDCs are plain handles, usable from any thread
+#[cfg(windows)]
+static DEVICE_CONTEXTS: Mutex<Vec<(String, isize)>> = Mutex::new(Vec::new());
+
+/// Run `use_dc` with the cached DC for a monitor, creating it on first use
+/// The cache stays locked throughout so a concurrent close can't delete the DC mid-call
+#[cfg(windows)]
+fn with_device_context<R>(name: &str, use_dc: impl FnOnce(*mut c_void) -> R) -> Result<R, String> {
+    let mut contexts = DEVICE_CONTEXTS.lock().unwrap();
+    if let Some((_, hdc)) = contexts.iter().find(|(n, _)| n == name) {
+        return Ok(use_dc(*hdc as *mut c_void));
+    }
+
+    let name_wide = to_wide(name);
+    let hdc = unsafe { CreateDCW(ptr::null(), name_wide.as_ptr(), ptr::null(), ptr::null()) };
+    if hdc.is_null() {
+        return Err(crate::i18n::text("error.device_context"));
+    }
+    contexts.push((name.to_string(), hdc as isize));
+    Ok(use_dc(hdc))
+}
+
+/// Close and forget a monitor's cached DC (all monitors if None)
+#[cfg(windows)]
+fn close_device_contexts(name: Option<&str>) {
+    DEVICE_CONTEXTS.lock().unwrap().retain(|(n, hdc)| {
+        if name.is_some_and(|name| name != n) {
+            return true;
+        }
+        unsafe { DeleteDC(*hdc as *mut c_void) };
+        false
+    });
+}
+
+/// Largest per-entry difference (of 65535) for a ramp to count as already applied;
+/// well under one 8-bit output step
+const RAMP_EPSILON: u16 = 32;
+
+/// Last ramp written to the driver per monitor index, to skip redundant writes
+#[cfg(windows)]
+static WRITTEN_RAMPS: Mutex<Vec<(u32, GammaRamp)>> = Mutex::new(Vec::new());
+
+#[cfg(windows)]
+fn ramps_match(a: &GammaRamp, b: &GammaRamp) -> bool {
+    let channels = [(&a.red, &b.red), (&a.green, &b.green), (&a.blue, &b.blue)];
+    channels.iter().all(|(a, b)| a.iter().zip(b.iter()).all(|(x, y)| x.abs_diff(*y) <= RAMP_EPSILON))
+}
+
+/// Forget what was written to a monitor (all monitors if None), so the next write goes
+/// to the driver even if it matches
+#[cfg(windows)]
+fn forget_written(monitor_index: Option<u32>) {
+    WRITTEN_RAMPS.lock().unwrap().retain(|(index, _)| monitor_index.is_some_and(|m| m != *index));
+}
+
+/// Write a ramp through the monitor's cached DC, skipping the driver call if the monitor
+/// already has it (the auto-adjust loop re-sends the same intensity in steady state)
+#[cfg(windows)]
+fn write_ramp(monitor_index: u32, ramp: &GammaRamp, failure: &str) -> Result<(), String> {
+    {
+        let written = WRITTEN_RAMPS.lock().unwrap();
+        if written.iter().any(|(index, last)| *index == monitor_index && ramps_match(last, ramp)) {
+            return Ok(());
+        }
+    }
+    forget_written(Some(monitor_index));
+    crate::perf::time(crate::perf::Stage::Apply, || write_ramp_to_driver(monitor_index, ramp, failure))?;
+    WRITTEN_RAMPS.lock().unwrap().push((monitor_index, ramp.clone()));
+    Ok(())
+}
+
+#[cfg(windows)]
+fn write_ramp_to_driver(monitor_index: u32, ramp: &GammaRamp, failure: &str) -> Result<(), String> {
+    let name = get_monitors()
+        .into_iter()
+        .find(|m| m.index == monitor_index)
+        .map(|m| m.name)
+        .ok_or_else(|| crate::i18n::format("error.monitor_not_found", &[&monitor_index]))?;
+
+    let set_ramp = |hdc| unsafe { SetDeviceGammaRamp(hdc, ramp as *const _ as *const _) } != 0;
+    if with_device_context(&name, set_ramp)? {
+        return Ok(());
+    }
+
+    // The DC may have gone stale (driver reset, display reconnected): retry with a fresh one
+    crate::crash::log(format!("SetDeviceGammaRamp failed on {}, retrying with a new DC", name));
+    close_device_contexts(Some(&name));
+    if !with_device_context(&name, set_ramp)? {
+        crate::crash::log(format!("SetDeviceGammaRamp failed again on {}", name));
+        return Err(failure.to_string());
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn set_gamma(intensity: f32, monitor_index: u32) -> Result<(), String> {
+    // 1. Calculate the "Shadow Hunter" curve
+    let preserve_contrast = crate::settings::get().preserve_contrast;
+    let constants = curve_constants(monitor_index);
+    let ramp = crate::perf::time(crate::perf::Stage::Curve, || calculate_curve(intensity, constants, preserve_contrast));
+
+    // 2. Write it, unless effects are suspended (it is still recorded for resume)
+    if should_write() {
+        write_ramp(monitor_index, &ramp, "Failed to set gamma ramp (Driver may be blocking it)")?;
+    }
+
+    record_active_ramp(monitor_index, &ramp);
+    crate::night_mode::record(crate::night_mode::Source::Gamma(monitor_index), intensity);
+    Ok(())
+}
+
+// Dim a monitor by reducing brightness linearly
+// brightness: 0.0 (black) to 1.0 (normal)
+#[cfg(windows)]
+pub fn dim_monitor(brightness: f32, monitor_index: u32) -> Result<(), String> {
+    // Clamp brightness to 0.5-1.0 due to Windows gamma restrictions
+    let brightness = brightness.max(0.5).min(1.0);
+    
+    // Create linear dimming ramp: output = input * brightness
+    let mut ramp = GammaRamp {
+        red: [0; 256],
+        green: [0; 256],
+        blue: [0; 256],
+    };
+    
+    for i in 0..256 {
+        // Identity value for this index
+        let identity = (i as u32) * 256;
+        // Dimmed value
+        let dimmed = ((i as f32) * brightness * 256.0) as u32;
+        // Clamp to within 32768 of identity (Windows restriction sometimes)
+        let min_val = identity.saturating_sub(32768);
+        let max_val = identity.saturating_add(32768).min(65535);
+        let value = dimmed.max(min_val).min(max_val) as u16;
+        
+        ramp.red[i] = value;
+        ramp.green[i] = value;
+        ramp.blue[i] = value;
+    }
+    
+    if should_write() {
+        write_ramp(monitor_index, &ramp, "Failed to dim monitor")?;
+    }
+
+    record_active_ramp(monitor_index, &ramp);
+    crate::night_mode::record(crate::night_mode::Source::Gamma(monitor_index), 0.0);
+    Ok(())
+}
+
+/// Apply a precomputed ramp to a monitor
+#[cfg(windows)]
+pub fn set_ramp(monitor_index: u32, ramp: &GammaRamp) -> Result<(), String> {
+    if should_write() {
+        write_ramp(monitor_index, ramp, "Failed to set gamma ramp (Driver may be blocking it)")?;
+    }
+    record_active_ramp(monitor_index, ramp);
+    Ok(())
+}
+
+/// Read a monitor's ramp from the driver and write it straight back,
+/// checking that gamma writes work without changing what's on screen
+#[cfg(windows)]
+pub fn probe(monitor_index: u32) -> Result<(), String> {
+    let name = get_monitors()
+        .into_iter()
+        .find(|m| m.index == monitor_index)
+        .map(|m| m.name)
+        .ok_or_else(|| crate::i18n::format("error.monitor_not_found", &[&monitor_index]))?;
+    with_device_context(&name, |hdc| {
+        let mut ramp = GammaRamp { red: [0; 256], green: [0; 256], blue: [0; 256] };
+        if unsafe { GetDeviceGammaRamp(hdc, &mut ramp) } == 0 {
+            return Err(crate::i18n::text("error.read_gamma"));
+        }
+        if unsafe { SetDeviceGammaRamp(hdc, &ramp) } == 0 {
+            return Err(crate::i18n::text("error.set_gamma"));
+        }
+        Ok(())
+    })?
+}
+
+/// Restore a monitor's default ramp and forget any recorded adjustment
+#[cfg(windows)]
+pub fn reset_monitor(monitor_index: u32) -> Result<(), String> {
+    // Always reach the driver: something else may have changed the ramp since
+    forget_written(Some(monitor_index));
+    write_ramp(monitor_index, &identity_ramp(), "Failed to reset gamma ramp")?;
+    ACTIVE_RAMPS.lock().unwrap().retain(|(index, _)| *index != monitor_index);
+    crate::session::changed();
+    crate::night_mode::record(crate::night_mode::Source::Gamma(monitor_index), 0.0);
+    Ok(())
+}
+
+/// Restore the default ramp on every monitor, for shutdown
+/// Covers monitors Noctis has no record of, in case another path left them adjusted
+#[cfg(windows)]
+pub fn restore_all() {
+    for monitor in get_monitors() {
+        let _ = reset_monitor(monitor.index);
+    }
+    ACTIVE_RAMPS.lock().unwrap().clear();
+}
+
+/// Write identity ramps to every adjusted monitor, keeping the recorded ramps for later
+#[cfg(windows)]
+pub fn bypass_all() {
+    let identity = identity_ramp();
+    let indices: Vec<u32> = ACTIVE_RAMPS.lock().unwrap().iter().map(|(index, _)| *index).collect();
+    for index in indices {
+        let _ = write_ramp(index, &identity, "Failed to reset gamma ramp");
+    }
+}
+
+/// Re-write the recorded ramps after a bypass
+#[cfg(windows)]
+pub fn reapply_all() {
+    // Drivers often reset ramps on wake, so what was written no longer applies
+    forget_written(None);
+    let ramps = ACTIVE_RAMPS.lock().unwrap().clone();
+    for (index, ramp) in ramps {
+        let _ = write_ramp(index, &ramp, "Failed to set gamma ramp (Driver may be blocking it)");
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_ramp(_monitor_index: u32, _ramp: &GammaRamp) -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+#[cfg(not(windows))]
+pub fn reset_monitor(_monitor_index: u32) -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+#[cfg(not(windows))]
+pub fn restore_all() {}
+
+#[cfg(not(windows))]
+pub fn bypass_all() {}
+
+#[cfg(not(windows))]
+pub fn reapply_all() {}
+
+#[cfg(not(windows))]
+pub fn dim_monitor(_brightness: f32, _monitor_index: u32) -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+#[cfg(not(windows))]
+pub fn set_gamma(_intensity: f32, _monitor_index: u32) -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+#[cfg(not(windows))]
+pub fn probe(_monitor_index: u32) -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+/// Find the monitor whose bounds contain a virtual-desktop point
+pub fn monitor_at(x: i32, y: i32) -> Option<MonitorInfo> {
+    get_monitors().into_iter().find(|m| {
+        x >= m.x && y >= m.y && x < m.x + m.width as i32 && y < m.y + m.height as i32
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_monotonic(entries: &[u16; 256]) -> bool {
+        entries.windows(2).all(|w| w[0] <= w[1])
+    }
+
+    #[test]
+    fn zero_intensity_is_linear() {
+        let ramp = calculate_curve(0.0, CurveConstants::default(), true);
+        assert_eq!(ramp.red[0], 0);
+        assert_eq!(ramp.red[128], ((128.0 / 255.0) * 65535.0) as u16);
+        assert_eq!(ramp.red[255], 65535);
+        assert_eq!(ramp.red, ramp.blue);
+    }
+
+    #[test]
+    fn full_intensity_lifts_black_and_keeps_white() {
+        let constants = CurveConstants::default();
+        let ramp = calculate_curve(1.0, constants, false);
+        let black = ramp.green[0] as f32 / 65535.0;
+        assert!((black - constants.max_lift).abs() < 0.01, "black lifted to {}", black);
+        assert_eq!(ramp.green[255], 65535);
+        assert!(is_monotonic(&ramp.green));
+    }
+
+    #[test]
+    fn intensity_is_clamped() {
+        let constants = CurveConstants::default();
+        assert_eq!(calculate_curve(-1.0, constants, false).red, calculate_curve(0.0, constants, false).red);
+        assert_eq!(calculate_curve(2.0, constants, false).red, calculate_curve(1.0, constants, false).red);
+    }
+
+    #[test]
+    fn contrast_guard_stays_monotonic_and_darkens_midtones() {
+        let constants = CurveConstants::default();
+        let plain = calculate_curve(1.0, constants, false);
+        let guarded = calculate_curve(1.0, constants, true);
+        assert!(is_monotonic(&guarded.red));
+        assert_eq!(guarded.red[0], plain.red[0]);
+        assert!(guarded.red[96] < plain.red[96]);
+    }
+}
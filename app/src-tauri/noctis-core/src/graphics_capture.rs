@@ -0,0 +1,406 @@
+//! Windows.Graphics.Capture sensor backend - compositor frames through WinRT
+//! Handles DPI-scaled and HDR surfaces correctly and can capture a single window,
+//! unlike GDI BitBlt; the forward-compatible capture path on Windows 11
+//! HDR monitors are captured as FP16 scRGB and tone-mapped to the SDR-equivalent
+//! brightness, so thresholds mean the same on HDR and SDR displays
+
+/// What a capture session records
+#[derive(Clone, Copy, PartialEq)]
+pub enum Target {
+    /// The monitor containing a virtual-desktop point
+    Monitor { x: i32, y: i32 },
+    /// A top-level window handle
+    Window(isize),
+}
+
+#[cfg(all(windows, feature = "sensor"))]
+mod wgc {
+    use super::Target;
+    use std::cell::RefCell;
+    use std::time::{Duration, Instant};
+    use windows::core::{factory, Interface};
+    use windows::Graphics::Capture::{Direct3D11CaptureFramePool, GraphicsCaptureItem, GraphicsCaptureSession};
+    use windows::Graphics::DirectX::Direct3D11::IDirect3DDevice;
+    use windows::Graphics::DirectX::DirectXPixelFormat;
+    use windows::Graphics::SizeInt32;
+    use windows::Win32::Foundation::{HWND, POINT, RECT};
+    use windows::Win32::Graphics::Direct3D::D3D_DRIVER_TYPE_HARDWARE;
+    use windows::Win32::Graphics::Direct3D11::{
+        D3D11CreateDevice, ID3D11Device, ID3D11DeviceContext, ID3D11Texture2D, D3D11_CPU_ACCESS_READ,
+        D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_MAPPED_SUBRESOURCE, D3D11_MAP_READ, D3D11_SDK_VERSION,
+        D3D11_TEXTURE2D_DESC, D3D11_USAGE_STAGING,
+    };
+    use windows::Win32::Graphics::Dxgi::IDXGIDevice;
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromPoint, MonitorFromWindow, HMONITOR, MONITORINFO, MONITORINFOEXW,
+        MONITOR_DEFAULTTONEAREST,
+    };
+    use windows::Win32::System::WinRT::Direct3D11::{CreateDirect3D11DeviceFromDXGIDevice, IDirect3DDxgiInterfaceAccess};
+    use windows::Win32::System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop;
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowRect;
+
+    /// How long to wait for the first frame of a new session
+    const FIRST_FRAME_TIMEOUT: Duration = Duration::from_millis(250);
+
+    /// Largest number of source pixels averaged per axis for one output pixel
+    const MAX_SAMPLES_PER_AXIS: u32 = 8;
+
+    /// scRGB 1.0 is 80 nits
+    const SCRGB_NITS: f32 = 80.0;
+
+    /// Relative luminance where HDR highlights start rolling off toward SDR white
+    const SHOULDER: f32 = 0.8;
+
+    /// A running capture session and the latest frame it delivered
+    struct Session {
+        target: Target,
+        /// Virtual-desktop rectangle the frames cover
+        bounds: RECT,
+        device: ID3D11Device,
+        context: ID3D11DeviceContext,
+        pool: Direct3D11CaptureFramePool,
+        session: GraphicsCaptureSession,
+        size: SizeInt32,
+        /// SDR white level in nits if the monitor is in HDR mode (frames are FP16 scRGB)
+        sdr_white: Option<f32>,
+        /// CPU-readable copy of the newest frame; frames only arrive when content changes
+        staging: Option<ID3D11Texture2D>,
+    }
+
+    impl Drop for Session {
+        fn drop(&mut self) {
+            let _ = self.session.Close();
+            let _ = self.pool.Close();
+        }
+    }
+
+    /// Sessions kept running at once: one per monitor, plus recently captured windows
+    const MAX_SESSIONS: usize = 4;
+
+    // Sessions live on the capture worker thread, which makes every sensor capture
+    thread_local! {
+        static SESSIONS: RefCell<Vec<Session>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn error(context: &str, e: windows::core::Error) -> String {
+        format!("{}: {}", context, e)
+    }
+
+    unsafe fn target_monitor(target: Target) -> HMONITOR {
+        match target {
+            Target::Monitor { x, y } => MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONEAREST),
+            Target::Window(hwnd) => MonitorFromWindow(HWND(hwnd as _), MONITOR_DEFAULTTONEAREST),
+        }
+    }
+
+    /// SDR white level of the target's monitor if it is in HDR mode
+    unsafe fn target_sdr_white(target: Target) -> Option<f32> {
+        let mut info = MONITORINFOEXW::default();
+        info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+        if !GetMonitorInfoW(target_monitor(target), &mut info as *mut MONITORINFOEXW as *mut MONITORINFO).as_bool() {
+            return None;
+        }
+        let len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+        crate::hdr_video::sdr_white_level(&String::from_utf16_lossy(&info.szDevice[..len]))
+    }
+
+    unsafe fn target_bounds(target: Target) -> Result<RECT, String> {
+        match target {
+            Target::Monitor { x, y } => {
+                let monitor = MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONEAREST);
+                let mut info = MONITORINFO { cbSize: std::mem::size_of::<MONITORINFO>() as u32, ..Default::default() };
+                if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+                    return Err(crate::i18n::text("error.monitor_info"));
+                }
+                Ok(info.rcMonitor)
+            }
+            Target::Window(hwnd) => {
+                let mut rect = RECT::default();
+                GetWindowRect(HWND(hwnd as _), &mut rect).map_err(|e| error("Failed to get window rect", e))?;
+                Ok(rect)
+            }
+        }
+    }
+
+    unsafe fn start(target: Target, sdr_white: Option<f32>) -> Result<Session, String> {
+        let interop = factory::<GraphicsCaptureItem, IGraphicsCaptureItemInterop>()
+            .map_err(|e| error("Graphics capture is unavailable", e))?;
+        let item: GraphicsCaptureItem = match target {
+            Target::Monitor { .. } => interop.CreateForMonitor(target_monitor(target)),
+            Target::Window(hwnd) => interop.CreateForWindow(HWND(hwnd as _)),
+        }
+        .map_err(|e| error("Failed to create capture item", e))?;
+
+        let mut device = None;
+        let mut context = None;
+        D3D11CreateDevice(
+            None,
+            D3D_DRIVER_TYPE_HARDWARE,
+            None,
+            D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+            None,
+            D3D11_SDK_VERSION,
+            Some(&mut device),
+            None,
+            Some(&mut context),
+        )
+        .map_err(|e| error("Failed to create D3D11 device", e))?;
+        let (device, context): (ID3D11Device, ID3D11DeviceContext) = (
+            device.ok_or_else(|| crate::i18n::text("error.no_d3d_device"))?,
+            context.ok_or_else(|| crate::i18n::text("error.no_d3d_context"))?,
+        );
+
+        let dxgi: IDXGIDevice = device.cast().map_err(|e| error("Failed to get DXGI device", e))?;
+        let winrt_device: IDirect3DDevice = CreateDirect3D11DeviceFromDXGIDevice(&dxgi)
+            .and_then(|d| d.cast())
+            .map_err(|e| error("Failed to wrap D3D11 device", e))?;
+
+        let size = item.Size().map_err(|e| error("Failed to get capture size", e))?;
+        // 8-bit frames of an HDR desktop are clipped SDR conversions, so read the real values
+        let format = match sdr_white {
+            Some(_) => DirectXPixelFormat::R16G16B16A16Float,
+            None => DirectXPixelFormat::B8G8R8A8UIntNormalized,
+        };
+        let pool = Direct3D11CaptureFramePool::CreateFreeThreaded(&winrt_device, format, 1, size)
+            .map_err(|e| error("Failed to create frame pool", e))?;
+        let session = pool.CreateCaptureSession(&item).map_err(|e| error("Failed to create capture session", e))?;
+
+        // Not available before Windows 10 2004 / Windows 11; the defaults still work
+        let _ = session.SetIsCursorCaptureEnabled(false);
+        let _ = session.SetIsBorderRequired(false);
+        session.StartCapture().map_err(|e| error("Failed to start capture", e))?;
+
+        Ok(Session { target, bounds: target_bounds(target)?, device, context, pool, session, size, sdr_white, staging: None })
+    }
+
+    impl Session {
+        /// Copy the newest delivered frame into the staging texture
+        /// Returns false if the frame size changed and the session must be restarted
+        unsafe fn update(&mut self) -> Result<bool, String> {
+            let deadline = Instant::now() + FIRST_FRAME_TIMEOUT;
+            loop {
+                let mut newest = None;
+                while let Ok(frame) = self.pool.TryGetNextFrame() {
+                    newest = Some(frame);
+                }
+
+                if let Some(frame) = newest {
+                    if frame.ContentSize().map_err(|e| error("Failed to read frame", e))? != self.size {
+                        return Ok(false);
+                    }
+                    let texture: ID3D11Texture2D = frame
+                        .Surface()
+                        .and_then(|s| s.cast::<IDirect3DDxgiInterfaceAccess>())
+                        .and_then(|access| access.GetInterface())
+                        .map_err(|e| error("Failed to read frame", e))?;
+
+                    if self.staging.is_none() {
+                        let mut desc = D3D11_TEXTURE2D_DESC::default();
+                        texture.GetDesc(&mut desc);
+                        desc.Usage = D3D11_USAGE_STAGING;
+                        desc.BindFlags = 0;
+                        desc.CPUAccessFlags = D3D11_CPU_ACCESS_READ.0 as u32;
+                        desc.MiscFlags = 0;
+                        let mut staging = None;
+                        self.device
+                            .CreateTexture2D(&desc, None, Some(&mut staging))
+                            .map_err(|e| error("Failed to create staging texture", e))?;
+                        self.staging = staging;
+                    }
+                    if let Some(staging) = &self.staging {
+                        self.context.CopyResource(staging, &texture);
+                    }
+                    let _ = frame.Close();
+                    return Ok(true);
+                }
+
+                if self.staging.is_some() {
+                    // Nothing changed since the last frame
+                    return Ok(true);
+                }
+                if Instant::now() >= deadline {
+                    return Err(crate::i18n::text("error.no_frame"));
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        }
+
+        /// Average a virtual-desktop region of the staged frame into a `width` x `height` BGRA buffer
+        unsafe fn read(&self, left: i32, top: i32, region_w: i32, region_h: i32, width: i32, height: i32) -> Result<Vec<u8>, String> {
+            let staging = self.staging.as_ref().ok_or_else(|| crate::i18n::text("error.no_frame_captured"))?;
+            let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+            self.context
+                .Map(staging, 0, D3D11_MAP_READ, 0, Some(&mut mapped))
+                .map_err(|e| error("Failed to map frame", e))?;
+
+            // Frame-relative region, clamped to the captured surface
+            let (frame_w, frame_h) = (self.size.Width.max(1), self.size.Height.max(1));
+            let x0 = (left - self.bounds.left).clamp(0, frame_w - 1);
+            let y0 = (top - self.bounds.top).clamp(0, frame_h - 1);
+            let x1 = (x0 + region_w).clamp(x0 + 1, frame_w);
+            let y1 = (y0 + region_h).clamp(y0 + 1, frame_h);
+
+            let data = mapped.pData as *const u8;
+            let pitch = mapped.RowPitch as usize;
+            let mut pixels = vec![0u8; (width * height) as usize * 4];
+
+            for dy in 0..height {
+                let sy0 = y0 + (y1 - y0) * dy / height;
+                let sy1 = (y0 + (y1 - y0) * (dy + 1) / height).max(sy0 + 1);
+                let step_y = ((sy1 - sy0) as u32 / MAX_SAMPLES_PER_AXIS).max(1) as usize;
+                for dx in 0..width {
+                    let sx0 = x0 + (x1 - x0) * dx / width;
+                    let sx1 = (x0 + (x1 - x0) * (dx + 1) / width).max(sx0 + 1);
+                    let step_x = ((sx1 - sx0) as u32 / MAX_SAMPLES_PER_AXIS).max(1) as usize;
+
+                    // Blue, green, red like the 8-bit frames
+                    let mut sum = [0f32; 3];
+                    let mut count = 0u32;
+                    for sy in (sy0..sy1).step_by(step_y) {
+                        let row = data.add(sy as usize * pitch);
+                        for sx in (sx0..sx1).step_by(step_x) {
+                            match self.sdr_white {
+                                None => {
+                                    let pixel = row.add(sx as usize * 4);
+                                    for (channel, total) in sum.iter_mut().enumerate() {
+                                        *total += *pixel.add(channel) as f32;
+                                    }
+                                }
+                                Some(_) => {
+                                    // Averaged in linear light, encoded once per output pixel
+                                    let pixel = row.add(sx as usize * 8) as *const u16;
+                                    for (channel, total) in sum.iter_mut().enumerate() {
+                                        *total += f16_to_f32(pixel.add(2 - channel).read_unaligned()).max(0.0);
+                                    }
+                                }
+                            }
+                            count += 1;
+                        }
+                    }
+
+                    let out = ((dy * width + dx) * 4) as usize;
+                    for channel in 0..3 {
+                        let mean = sum[channel] / count.max(1) as f32;
+                        pixels[out + channel] = match self.sdr_white {
+                            None => mean as u8,
+                            Some(white) => tone_map(mean * SCRGB_NITS / white),
+                        };
+                    }
+                }
+            }
+
+            self.context.Unmap(staging, 0);
+            Ok(pixels)
+        }
+    }
+
+    /// Decode an IEEE half-precision float
+    fn f16_to_f32(bits: u16) -> f32 {
+        let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+        let exponent = ((bits >> 10) & 0x1F) as i32;
+        let mantissa = (bits & 0x3FF) as f32;
+        match exponent {
+            0 => sign * mantissa * 2f32.powi(-24),
+            // Infinity and NaN don't occur in desktop frames; treat as very bright
+            31 => sign * 65504.0,
+            _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+        }
+    }
+
+    /// Map linear light relative to SDR white to an sRGB-encoded byte
+    /// Highlights above SHOULDER roll off smoothly instead of clipping, so a bright HDR
+    /// highlight reads as bright rather than saturating the whole measurement
+    fn tone_map(relative: f32) -> u8 {
+        let mapped = if relative <= SHOULDER {
+            relative
+        } else {
+            let range = 1.0 - SHOULDER;
+            SHOULDER + range * (1.0 - (-(relative - SHOULDER) / range).exp())
+        };
+        let encoded = if mapped <= 0.0031308 {
+            mapped * 12.92
+        } else {
+            1.055 * mapped.powf(1.0 / 2.4) - 0.055
+        };
+        (encoded.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+
+    /// Whether the target's monitor is in HDR mode
+    pub fn is_hdr(target: Target) -> bool {
+        unsafe { target_sdr_white(target).is_some() }
+    }
+
+    /// Capture a virtual-desktop region of the target into a `width` x `height` BGRA buffer
+    pub fn capture(
+        target: Target,
+        left: i32,
+        top: i32,
+        region_w: i32,
+        region_h: i32,
+        width: i32,
+        height: i32,
+    ) -> Result<Vec<u8>, String> {
+        SESSIONS.with(|cell| unsafe {
+            let mut sessions = cell.borrow_mut();
+            let sdr_white = target_sdr_white(target);
+            for _ in 0..2 {
+                // Restart when the target's HDR mode changed
+                sessions.retain(|s| s.target != target || s.sdr_white == sdr_white);
+                let index = match sessions.iter().position(|s| s.target == target) {
+                    Some(index) => index,
+                    None => {
+                        if sessions.len() >= MAX_SESSIONS {
+                            sessions.remove(0);
+                        }
+                        sessions.push(start(target, sdr_white)?);
+                        sessions.len() - 1
+                    }
+                };
+                match sessions[index].update() {
+                    Ok(true) => return sessions[index].read(left, top, region_w, region_h, width, height),
+                    // Resolution or window size changed: start over with the new size
+                    Ok(false) => {
+                        sessions.remove(index);
+                    }
+                    Err(e) => {
+                        sessions.remove(index);
+                        return Err(e);
+                    }
+                }
+            }
+            Err(crate::i18n::text("error.capture_size_changing"))
+        })
+    }
+
+    /// Virtual-desktop bounds of a capture target
+    pub fn bounds(target: Target) -> Result<(i32, i32, i32, i32), String> {
+        let rect = unsafe { target_bounds(target)? };
+        Ok((rect.left, rect.top, rect.right - rect.left, rect.bottom - rect.top))
+    }
+
+    /// Stop capturing a target, e.g. when it is read through GDI again
+    pub fn stop(target: Target) {
+        SESSIONS.with(|cell| cell.borrow_mut().retain(|s| s.target != target));
+    }
+}
+
+#[cfg(all(windows, feature = "sensor"))]
+pub use wgc::{bounds, capture, is_hdr, stop};
+
+#[cfg(not(all(windows, feature = "sensor")))]
+pub fn capture(_target: Target, _x: i32, _y: i32, _w: i32, _h: i32, _width: i32, _height: i32) -> Result<Vec<u8>, String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(not(all(windows, feature = "sensor")))]
+pub fn bounds(_target: Target) -> Result<(i32, i32, i32, i32), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(not(all(windows, feature = "sensor")))]
+pub fn is_hdr(_target: Target) -> bool {
+    false
+}
+
+#[cfg(not(all(windows, feature = "sensor")))]
+pub fn stop(_target: Target) {}
@@ -0,0 +1,188 @@
+//! HDR video detection - bypasses effects while HDR video plays
+//! Gamma ramps and color matrices band and clip HDR content, so effects are suspended while a
+//! display is in HDR (advanced color) mode and a media session reports video playback
+
+use crate::suspend;
+use std::thread;
+use std::time::Duration;
+
+/// Suspension reason used while HDR video plays
+const REASON: &str = "hdr-video";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Windows' default SDR content brightness on HDR displays
+const DEFAULT_SDR_WHITE_NITS: f32 = 80.0;
+
+#[cfg(windows)]
+mod detect {
+    use windows::Media::Control::{
+        GlobalSystemMediaTransportControlsSessionManager,
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus,
+    };
+    use windows::Media::MediaPlaybackType;
+    use super::DEFAULT_SDR_WHITE_NITS;
+    use windows::Win32::Devices::Display::{
+        DisplayConfigGetDeviceInfo, GetDisplayConfigBufferSizes, QueryDisplayConfig,
+        DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO, DISPLAYCONFIG_DEVICE_INFO_GET_SDR_WHITE_LEVEL,
+        DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME, DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO,
+        DISPLAYCONFIG_MODE_INFO, DISPLAYCONFIG_PATH_INFO, DISPLAYCONFIG_SDR_WHITE_LEVEL,
+        DISPLAYCONFIG_SOURCE_DEVICE_NAME, QDC_ONLY_ACTIVE_PATHS,
+    };
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+
+    /// advancedColorEnabled bit of DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO
+    const ADVANCED_COLOR_ENABLED: u32 = 0x2;
+
+    /// GDI device names (e.g. `\\.\DISPLAY1`) and SDR white levels in nits of active
+    /// displays running in HDR (advanced color) mode
+    pub fn hdr_displays() -> Vec<(String, f32)> {
+        unsafe {
+            let (mut path_count, mut mode_count) = (0u32, 0u32);
+            if GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_count, &mut mode_count) != ERROR_SUCCESS {
+                return Vec::new();
+            }
+            let mut paths = vec![DISPLAYCONFIG_PATH_INFO::default(); path_count as usize];
+            let mut modes = vec![DISPLAYCONFIG_MODE_INFO::default(); mode_count as usize];
+            if QueryDisplayConfig(
+                QDC_ONLY_ACTIVE_PATHS,
+                &mut path_count,
+                paths.as_mut_ptr(),
+                &mut mode_count,
+                modes.as_mut_ptr(),
+                None,
+            ) != ERROR_SUCCESS
+            {
+                return Vec::new();
+            }
+
+            paths
+                .iter()
+                .take(path_count as usize)
+                .filter(|path| {
+                    let mut info = DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO::default();
+                    info.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO;
+                    info.header.size = std::mem::size_of::<DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO>() as u32;
+                    info.header.adapterId = path.targetInfo.adapterId;
+                    info.header.id = path.targetInfo.id;
+                    DisplayConfigGetDeviceInfo(&mut info.header) == 0
+                        && info.Anonymous.value & ADVANCED_COLOR_ENABLED != 0
+                })
+                .map(|path| {
+                    let mut source = DISPLAYCONFIG_SOURCE_DEVICE_NAME::default();
+                    source.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME;
+                    source.header.size = std::mem::size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32;
+                    source.header.adapterId = path.sourceInfo.adapterId;
+                    source.header.id = path.sourceInfo.id;
+                    DisplayConfigGetDeviceInfo(&mut source.header);
+                    let name = &source.viewGdiDeviceName;
+                    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+
+                    // Reported in thousandths of the 80 nit scRGB reference
+                    let mut white = DISPLAYCONFIG_SDR_WHITE_LEVEL::default();
+                    white.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SDR_WHITE_LEVEL;
+                    white.header.size = std::mem::size_of::<DISPLAYCONFIG_SDR_WHITE_LEVEL>() as u32;
+                    white.header.adapterId = path.targetInfo.adapterId;
+                    white.header.id = path.targetInfo.id;
+                    let nits = if DisplayConfigGetDeviceInfo(&mut white.header) == 0 {
+                        white.SDRWhiteLevel as f32 / 1000.0 * 80.0
+                    } else {
+                        DEFAULT_SDR_WHITE_NITS
+                    };
+
+                    (String::from_utf16_lossy(&name[..len]), nits)
+                })
+                .collect()
+        }
+    }
+
+    /// Check whether any active display is running in HDR (advanced color) mode
+    pub fn any_hdr_display() -> bool {
+        !hdr_displays().is_empty()
+    }
+
+    /// Check whether the current media session is playing video
+    /// Sessions that don't report a playback type (some browsers) count as video
+    pub fn video_playing() -> bool {
+        let playing = || -> windows::core::Result<bool> {
+            let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()?.get()?;
+            let info = manager.GetCurrentSession()?.GetPlaybackInfo()?;
+            if info.PlaybackStatus()? != GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing {
+                return Ok(false);
+            }
+            let kind = info.PlaybackType().and_then(|t| t.Value()).unwrap_or(MediaPlaybackType::Video);
+            Ok(kind != MediaPlaybackType::Music && kind != MediaPlaybackType::Image)
+        };
+        playing().unwrap_or(false)
+    }
+}
+
+#[cfg(not(windows))]
+mod detect {
+    pub fn hdr_displays() -> Vec<(String, f32)> {
+        Vec::new()
+    }
+
+    pub fn any_hdr_display() -> bool {
+        false
+    }
+
+    pub fn video_playing() -> bool {
+        false
+    }
+}
+
+/// SDR white level in nits of a monitor (GDI device name), None if it isn't in HDR mode
+pub fn sdr_white_level(device_name: &str) -> Option<f32> {
+    detect::hdr_displays()
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(device_name))
+        .map(|(_, nits)| nits)
+}
+
+/// Whether HDR video should bypass effects right now
+/// The active profile's flag takes precedence over the global setting
+fn bypass_enabled() -> bool {
+    crate::profiles::active()
+        .and_then(|p| p.hdr_video_bypass)
+        .unwrap_or_else(|| crate::settings::get().hdr_video_bypass)
+}
+
+/// Suspend or resume effects based on HDR video playback
+/// Returns Some(true) if effects were suspended, Some(false) if resumed, None if unchanged
+fn enforce() -> Option<bool> {
+    let hdr_video = bypass_enabled() && detect::any_hdr_display() && detect::video_playing();
+    if hdr_video && suspend::suspend(REASON) {
+        Some(true)
+    } else if !hdr_video && suspend::resume(REASON) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Start a background thread that polls for HDR video playback
+/// `on_change` receives suspend changes in the same form as blocklist::enforce
+#[cfg(windows)]
+pub fn start_watcher<F>(on_change: F)
+where
+    F: Fn(Option<bool>) + Send + 'static,
+{
+    use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+
+    thread::spawn(move || {
+        // Media session queries are WinRT calls and need an apartment
+        let _ = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+        while !crate::shutdown::is_shutting_down() {
+            on_change(enforce());
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn start_watcher<F>(_on_change: F)
+where
+    F: Fn(Option<bool>) + Send + 'static,
+{
+}
@@ -0,0 +1,78 @@
+//! Brightness history - rolling record of sensor readings and applied intensities
+//! Kept in memory only, so the UI can plot scene brightness against the applied lift
+//! and users can tune thresholds with real data
+
+use crate::night_mode::{self, NightMode};
+use std::collections::VecDeque;
+use std::sync::{Mutex, Once};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How far back the history reaches
+const RETENTION: Duration = Duration::from_secs(10 * 60);
+
+/// A sensor reading
+#[derive(serde::Serialize, Clone)]
+pub struct BrightnessReading {
+    /// Unix time in milliseconds
+    pub at_ms: u64,
+    /// Monitor containing the captured region, if known
+    pub monitor: Option<u32>,
+    /// Measured brightness (0.0 - 1.0)
+    pub brightness: f32,
+}
+
+/// A change in the strongest applied intensity
+#[derive(serde::Serialize, Clone)]
+pub struct IntensityChange {
+    pub at_ms: u64,
+    pub intensity: f32,
+}
+
+/// History returned to the UI, oldest first
+#[derive(serde::Serialize, Clone, Default)]
+pub struct BrightnessHistory {
+    pub readings: Vec<BrightnessReading>,
+    pub intensities: Vec<IntensityChange>,
+}
+
+static READINGS: Mutex<VecDeque<BrightnessReading>> = Mutex::new(VecDeque::new());
+static INTENSITIES: Mutex<VecDeque<IntensityChange>> = Mutex::new(VecDeque::new());
+static SUBSCRIBED: Once = Once::new();
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// Drop entries older than RETENTION from the front of a history
+fn push_pruned<T>(history: &mut VecDeque<T>, entry: T, at_ms: impl Fn(&T) -> u64) {
+    let cutoff = now_ms().saturating_sub(RETENTION.as_millis() as u64);
+    while history.front().is_some_and(|e| at_ms(e) < cutoff) {
+        history.pop_front();
+    }
+    history.push_back(entry);
+}
+
+/// Start following applied intensities
+pub fn start() {
+    SUBSCRIBED.call_once(|| {
+        night_mode::subscribe(|mode: NightMode| {
+            let change = IntensityChange { at_ms: now_ms(), intensity: mode.intensity };
+            push_pruned(&mut INTENSITIES.lock().unwrap(), change, |c| c.at_ms);
+        });
+    });
+}
+
+/// Record a sensor reading
+pub fn record_reading(monitor: Option<u32>, brightness: f32) {
+    let reading = BrightnessReading { at_ms: now_ms(), monitor, brightness };
+    push_pruned(&mut READINGS.lock().unwrap(), reading, |r| r.at_ms);
+}
+
+/// Get the last ten minutes of readings and intensity changes
+pub fn get() -> BrightnessHistory {
+    let cutoff = now_ms().saturating_sub(RETENTION.as_millis() as u64);
+    BrightnessHistory {
+        readings: READINGS.lock().unwrap().iter().filter(|r| r.at_ms >= cutoff).cloned().collect(),
+        intensities: INTENSITIES.lock().unwrap().iter().filter(|c| c.at_ms >= cutoff).cloned().collect(),
+    }
+}
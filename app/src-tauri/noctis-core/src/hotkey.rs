@@ -0,0 +1,411 @@
+//! Hotkey parsing - turns user-entered key names like "Ctrl+Shift+F" into a binding
+//! Accepts W3C key codes ("KeyF"), KeyboardEvent.key names ("ARROWUP") and common aliases,
+//! and formats bindings back into the label the UI shows
+
+use crate::foreground::ForegroundApp;
+use crate::i18n;
+use std::fmt;
+use std::str::FromStr;
+
+/// Toggle hotkey used until the user picks another
+pub const DEFAULT: &str = "Insert";
+
+/// A supported key: W3C code, UI label, and any other accepted spellings
+struct Key {
+    code: &'static str,
+    label: &'static str,
+    aliases: &'static [&'static str],
+}
+
+const fn key(code: &'static str, label: &'static str, aliases: &'static [&'static str]) -> Key {
+    Key { code, label, aliases }
+}
+
+/// Every key the global shortcut backend can register
+const KEYS: &[Key] = &[
+    // Letters
+    key("KeyA", "A", &[]), key("KeyB", "B", &[]), key("KeyC", "C", &[]), key("KeyD", "D", &[]),
+    key("KeyE", "E", &[]), key("KeyF", "F", &[]), key("KeyG", "G", &[]), key("KeyH", "H", &[]),
+    key("KeyI", "I", &[]), key("KeyJ", "J", &[]), key("KeyK", "K", &[]), key("KeyL", "L", &[]),
+    key("KeyM", "M", &[]), key("KeyN", "N", &[]), key("KeyO", "O", &[]), key("KeyP", "P", &[]),
+    key("KeyQ", "Q", &[]), key("KeyR", "R", &[]), key("KeyS", "S", &[]), key("KeyT", "T", &[]),
+    key("KeyU", "U", &[]), key("KeyV", "V", &[]), key("KeyW", "W", &[]), key("KeyX", "X", &[]),
+    key("KeyY", "Y", &[]), key("KeyZ", "Z", &[]),
+    // Numbers
+    key("Digit0", "0", &[]), key("Digit1", "1", &[]), key("Digit2", "2", &[]), key("Digit3", "3", &[]),
+    key("Digit4", "4", &[]), key("Digit5", "5", &[]), key("Digit6", "6", &[]), key("Digit7", "7", &[]),
+    key("Digit8", "8", &[]), key("Digit9", "9", &[]),
+    // Function keys
+    key("F1", "F1", &[]), key("F2", "F2", &[]), key("F3", "F3", &[]), key("F4", "F4", &[]),
+    key("F5", "F5", &[]), key("F6", "F6", &[]), key("F7", "F7", &[]), key("F8", "F8", &[]),
+    key("F9", "F9", &[]), key("F10", "F10", &[]), key("F11", "F11", &[]), key("F12", "F12", &[]),
+    key("F13", "F13", &[]), key("F14", "F14", &[]), key("F15", "F15", &[]), key("F16", "F16", &[]),
+    key("F17", "F17", &[]), key("F18", "F18", &[]), key("F19", "F19", &[]), key("F20", "F20", &[]),
+    key("F21", "F21", &[]), key("F22", "F22", &[]), key("F23", "F23", &[]), key("F24", "F24", &[]),
+    // Navigation
+    key("Insert", "Insert", &["Ins"]),
+    key("Delete", "Delete", &["Del"]),
+    key("Home", "Home", &[]),
+    key("End", "End", &[]),
+    key("PageUp", "PageUp", &["PgUp"]),
+    key("PageDown", "PageDown", &["PgDn"]),
+    key("ArrowUp", "Up", &[]),
+    key("ArrowDown", "Down", &[]),
+    key("ArrowLeft", "Left", &[]),
+    key("ArrowRight", "Right", &[]),
+    // Special
+    key("Escape", "Esc", &[]),
+    key("Pause", "Pause", &["Break", "PauseBreak", "Pause/Break"]),
+    key("PrintScreen", "PrintScreen", &["PrtSc", "PrtScn", "PrintScr", "Print", "SysRq", "Snapshot"]),
+    key("ScrollLock", "ScrollLock", &["ScrLk"]),
+    key("Backquote", "`", &["Grave", "Tilde"]),
+    key("Minus", "-", &[]),
+    key("Equal", "=", &[]),
+    key("BracketLeft", "[", &[]),
+    key("BracketRight", "]", &[]),
+    key("Backslash", "\\", &[]),
+    key("Semicolon", ";", &[]),
+    key("Quote", "'", &[]),
+    key("Comma", ",", &[]),
+    key("Period", ".", &[]),
+    key("Slash", "/", &[]),
+    key("Space", "Space", &[" ", "Spacebar"]),
+    key("Tab", "Tab", &[]),
+    key("Enter", "Enter", &["Return"]),
+    key("Backspace", "Backspace", &[]),
+    key("CapsLock", "CapsLock", &[]),
+    key("NumLock", "NumLock", &[]),
+    // Numpad
+    key("Numpad0", "Num0", &[]), key("Numpad1", "Num1", &[]), key("Numpad2", "Num2", &[]),
+    key("Numpad3", "Num3", &[]), key("Numpad4", "Num4", &[]), key("Numpad5", "Num5", &[]),
+    key("Numpad6", "Num6", &[]), key("Numpad7", "Num7", &[]), key("Numpad8", "Num8", &[]),
+    key("Numpad9", "Num9", &[]),
+    key("NumpadAdd", "NumAdd", &["NumPlus"]),
+    key("NumpadSubtract", "NumSubtract", &["NumMinus"]),
+    key("NumpadMultiply", "NumMultiply", &[]),
+    key("NumpadDivide", "NumDivide", &[]),
+    key("NumpadDecimal", "NumDecimal", &[]),
+    key("NumpadEnter", "NumEnter", &[]),
+    key("NumpadEqual", "NumEqual", &[]),
+    // Media
+    key("MediaPlayPause", "PlayPause", &[]),
+    key("MediaPlay", "Play", &[]),
+    key("MediaPause", "MediaPause", &[]),
+    key("MediaStop", "Stop", &[]),
+    key("MediaTrackNext", "NextTrack", &["MediaNextTrack"]),
+    key("MediaTrackPrevious", "PreviousTrack", &["PrevTrack", "MediaPreviousTrack", "MediaTrackPrev"]),
+    key("AudioVolumeUp", "VolumeUp", &[]),
+    key("AudioVolumeDown", "VolumeDown", &[]),
+    key("AudioVolumeMute", "Mute", &["VolumeMute"]),
+];
+
+/// Modifier keys, in the order they're displayed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    Ctrl,
+    Alt,
+    Shift,
+    Win,
+}
+
+impl Modifier {
+    const ALL: [Modifier; 4] = [Modifier::Ctrl, Modifier::Alt, Modifier::Shift, Modifier::Win];
+
+    fn parse(token: &str) -> Option<Self> {
+        match token.to_ascii_uppercase().as_str() {
+            "CTRL" | "CONTROL" | "CONTROLLEFT" | "CONTROLRIGHT" => Some(Modifier::Ctrl),
+            "ALT" | "OPTION" | "ALTLEFT" | "ALTRIGHT" => Some(Modifier::Alt),
+            "SHIFT" | "SHIFTLEFT" | "SHIFTRIGHT" => Some(Modifier::Shift),
+            "WIN" | "SUPER" | "META" | "CMD" | "COMMAND" | "METALEFT" | "METARIGHT" => Some(Modifier::Win),
+            _ => None,
+        }
+    }
+
+    /// Virtual-key codes that count as this modifier being held
+    pub fn virtual_keys(self) -> &'static [i32] {
+        match self {
+            Modifier::Ctrl => &[0x11],
+            Modifier::Alt => &[0x12],
+            Modifier::Shift => &[0x10],
+            Modifier::Win => &[0x5B, 0x5C],
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "Ctrl",
+            Modifier::Alt => "Alt",
+            Modifier::Shift => "Shift",
+            Modifier::Win => "Win",
+        }
+    }
+
+    /// Modifier name understood by the global shortcut backend
+    fn accelerator(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "control",
+            Modifier::Alt => "alt",
+            Modifier::Shift => "shift",
+            Modifier::Win => "super",
+        }
+    }
+}
+
+/// Why a key string couldn't be parsed
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// Nothing but whitespace
+    Empty,
+    /// A `+`-separated part was blank, e.g. "Ctrl++F"
+    EmptyPart,
+    /// Not a modifier or a supported key
+    UnknownKey(String),
+    /// The same modifier appeared twice
+    DuplicateModifier(String),
+    /// Only modifiers, no key to bind
+    MissingKey,
+    /// More than one non-modifier key
+    MultipleKeys(String, String),
+    /// A key where only modifiers are allowed
+    NotAModifier(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ParseError::Empty => i18n::text("hotkey.empty"),
+            ParseError::EmptyPart => i18n::text("hotkey.empty_part"),
+            ParseError::UnknownKey(key) => i18n::format("hotkey.unknown_key", &[key]),
+            ParseError::DuplicateModifier(key) => i18n::format("hotkey.duplicate_modifier", &[key]),
+            ParseError::MissingKey => i18n::text("hotkey.missing_key"),
+            ParseError::MultipleKeys(a, b) => i18n::format("hotkey.multiple_keys", &[a, b]),
+            ParseError::NotAModifier(key) => i18n::format("hotkey.not_a_modifier", &[key]),
+        };
+        write!(f, "{}", message)
+    }
+}
+
+/// A parsed key binding: any modifiers plus one key
+#[derive(Clone, PartialEq, Eq)]
+pub struct Hotkey {
+    modifiers: Vec<Modifier>,
+    key: &'static Key,
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code
+    }
+}
+
+impl Eq for Key {}
+
+impl Hotkey {
+    /// Accelerator string for the global shortcut plugin, e.g. "control+shift+KeyF"
+    pub fn accelerator(&self) -> String {
+        let mut parts: Vec<&str> = self.modifiers.iter().map(|m| m.accelerator()).collect();
+        parts.push(self.key.code);
+        parts.join("+")
+    }
+
+    /// Virtual-key codes that must all be held for the hotkey, each as the codes that count for
+    /// it (either Win key); None if the key has no fixed code (e.g. NumpadEqual)
+    pub fn virtual_keys(&self) -> Option<Vec<Vec<i32>>> {
+        let mut keys: Vec<Vec<i32>> = self.modifiers.iter().map(|m| m.virtual_keys().to_vec()).collect();
+        keys.push(vec![virtual_key(self.key.code)?]);
+        Some(keys)
+    }
+}
+
+/// Windows virtual-key code of a key, for reading its state directly
+fn virtual_key(code: &str) -> Option<i32> {
+    let byte = |prefix: &str| code.strip_prefix(prefix).filter(|c| c.len() == 1).map(|c| c.as_bytes()[0] as i32);
+    if let Some(letter) = byte("Key") {
+        return Some(letter);
+    }
+    if let Some(digit) = byte("Digit") {
+        return Some(digit);
+    }
+    if let Some(n) = byte("Numpad") {
+        return Some(0x60 + n - b'0' as i32);
+    }
+    if let Some(n) = code.strip_prefix('F').and_then(|n| n.parse::<i32>().ok()) {
+        return Some(0x70 + n - 1);
+    }
+    let vk = match code {
+        "Insert" => 0x2D, "Delete" => 0x2E, "Home" => 0x24, "End" => 0x23,
+        "PageUp" => 0x21, "PageDown" => 0x22,
+        "ArrowLeft" => 0x25, "ArrowUp" => 0x26, "ArrowRight" => 0x27, "ArrowDown" => 0x28,
+        "Escape" => 0x1B, "Pause" => 0x13, "PrintScreen" => 0x2C, "ScrollLock" => 0x91,
+        "Backquote" => 0xC0, "Minus" => 0xBD, "Equal" => 0xBB, "BracketLeft" => 0xDB,
+        "BracketRight" => 0xDD, "Backslash" => 0xDC, "Semicolon" => 0xBA, "Quote" => 0xDE,
+        "Comma" => 0xBC, "Period" => 0xBE, "Slash" => 0xBF,
+        "Space" => 0x20, "Tab" => 0x09, "Enter" | "NumpadEnter" => 0x0D, "Backspace" => 0x08,
+        "CapsLock" => 0x14, "NumLock" => 0x90,
+        "NumpadAdd" => 0x6B, "NumpadSubtract" => 0x6D, "NumpadMultiply" => 0x6A,
+        "NumpadDivide" => 0x6F, "NumpadDecimal" => 0x6E,
+        "MediaPlayPause" | "MediaPlay" | "MediaPause" => 0xB3, "MediaStop" => 0xB2,
+        "MediaTrackNext" => 0xB0, "MediaTrackPrevious" => 0xB1,
+        "AudioVolumeUp" => 0xAF, "AudioVolumeDown" => 0xAE, "AudioVolumeMute" => 0xAD,
+        _ => return None,
+    };
+    Some(vk)
+}
+
+/// Find a key by its code, label or alias (case-insensitive)
+fn find_key(token: &str) -> Option<&'static Key> {
+    KEYS.iter().find(|k| {
+        k.code.eq_ignore_ascii_case(token)
+            || k.label.eq_ignore_ascii_case(token)
+            || k.aliases.iter().any(|a| a.eq_ignore_ascii_case(token))
+    })
+}
+
+impl FromStr for Hotkey {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.trim().is_empty() {
+            // A lone space is the Space key, as sent by KeyboardEvent.key
+            return match input {
+                " " => Ok(Hotkey { modifiers: Vec::new(), key: find_key(" ").unwrap() }),
+                _ => Err(ParseError::Empty),
+            };
+        }
+
+        let mut modifiers = Vec::new();
+        let mut key: Option<&'static Key> = None;
+        for part in input.split('+') {
+            let token = part.trim();
+            if token.is_empty() {
+                return Err(ParseError::EmptyPart);
+            }
+            if let Some(modifier) = Modifier::parse(token) {
+                if modifiers.contains(&modifier) {
+                    return Err(ParseError::DuplicateModifier(modifier.label().to_string()));
+                }
+                modifiers.push(modifier);
+                continue;
+            }
+            let found = find_key(token).ok_or_else(|| ParseError::UnknownKey(token.to_string()))?;
+            if let Some(existing) = key {
+                return Err(ParseError::MultipleKeys(existing.label.to_string(), found.label.to_string()));
+            }
+            key = Some(found);
+        }
+
+        // Display order is fixed so equivalent inputs format identically
+        modifiers.sort_by_key(|m| Modifier::ALL.iter().position(|a| a == m));
+        let key = key.ok_or(ParseError::MissingKey)?;
+        Ok(Hotkey { modifiers, key })
+    }
+}
+
+/// Parse a modifier-only chord like "Ctrl+Alt", as used by the intensity wheel
+pub fn parse_modifiers(input: &str) -> Result<Vec<Modifier>, ParseError> {
+    if input.trim().is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut modifiers = Vec::new();
+    for part in input.split('+') {
+        let token = part.trim();
+        if token.is_empty() {
+            return Err(ParseError::EmptyPart);
+        }
+        let modifier = Modifier::parse(token).ok_or_else(|| ParseError::NotAModifier(token.to_string()))?;
+        if modifiers.contains(&modifier) {
+            return Err(ParseError::DuplicateModifier(modifier.label().to_string()));
+        }
+        modifiers.push(modifier);
+    }
+    Ok(modifiers)
+}
+
+/// The UI label, e.g. "Ctrl+Shift+F", which parses back to the same binding
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier.label())?;
+        }
+        write!(f, "{}", self.key.label)
+    }
+}
+
+/// Whether a hotkey scoped to the given processes or profile names applies to the foreground app
+/// An empty scope applies everywhere
+pub fn in_scope(scope: &[String], app: Option<&ForegroundApp>) -> bool {
+    if scope.is_empty() {
+        return true;
+    }
+    let Some(app) = app else { return false };
+    let profile = crate::profiles::find_for_process(&app.process);
+    scope.iter().any(|entry| {
+        entry.eq_ignore_ascii_case(&app.process) || profile.as_ref().is_some_and(|p| p.name.eq_ignore_ascii_case(entry))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Hotkey, ParseError> {
+        input.parse()
+    }
+
+    #[test]
+    fn aliases_case_and_modifier_order_parse_alike() {
+        let canonical = parse("Ctrl+Shift+PageUp").unwrap();
+        for input in ["shift+control+pgup", "SHIFT + CTRL + PAGEUP", "ControlLeft+ShiftRight+pageup"] {
+            assert!(parse(input).unwrap() == canonical, "{} parsed differently", input);
+        }
+        assert_eq!(canonical.to_string(), "Ctrl+Shift+PageUp");
+        assert_eq!(canonical.accelerator(), "control+shift+PageUp");
+        assert!(parse("KeyF").unwrap() == parse("f").unwrap());
+        assert!(parse("ArrowUp").unwrap() == parse("Up").unwrap());
+    }
+
+    #[test]
+    fn every_key_round_trips_through_its_label() {
+        for key in KEYS {
+            for input in [key.code.to_string(), format!("Win+Alt+{}", key.label)] {
+                let hotkey = parse(&input).unwrap();
+                assert!(parse(&hotkey.to_string()).unwrap() == hotkey, "{} did not round-trip", input);
+            }
+        }
+    }
+
+    #[test]
+    fn lone_space_is_the_space_key() {
+        assert_eq!(parse(" ").unwrap().to_string(), "Space");
+        assert_eq!(parse("  ").err(), Some(ParseError::Empty));
+    }
+
+    #[test]
+    fn each_error_is_reported() {
+        assert_eq!(parse("").err(), Some(ParseError::Empty));
+        assert_eq!(parse("Ctrl++F").err(), Some(ParseError::EmptyPart));
+        assert_eq!(parse("Ctrl+Hyper").err(), Some(ParseError::UnknownKey("Hyper".to_string())));
+        assert_eq!(parse("Ctrl+control+F").err(), Some(ParseError::DuplicateModifier("Ctrl".to_string())));
+        assert_eq!(parse("Ctrl+Alt").err(), Some(ParseError::MissingKey));
+        assert_eq!(parse("A+Ins").err(), Some(ParseError::MultipleKeys("A".to_string(), "Insert".to_string())));
+        assert_eq!(parse_modifiers("Ctrl+F").err(), Some(ParseError::NotAModifier("F".to_string())));
+    }
+
+    #[test]
+    fn modifier_chords_parse() {
+        assert!(parse_modifiers("ctrl+alt").unwrap() == [Modifier::Ctrl, Modifier::Alt]);
+        assert_eq!(parse_modifiers(" ").err(), Some(ParseError::Empty));
+    }
+
+    #[test]
+    fn function_and_numpad_keys_have_virtual_keys() {
+        assert_eq!(virtual_key("F1"), Some(0x70));
+        assert_eq!(virtual_key("F12"), Some(0x7B));
+        assert_eq!(virtual_key("F24"), Some(0x87));
+        assert_eq!(virtual_key("Numpad0"), Some(0x60));
+        assert_eq!(virtual_key("Numpad9"), Some(0x69));
+        assert_eq!(virtual_key("NumpadAdd"), Some(0x6B));
+        assert_eq!(virtual_key("NumpadEqual"), None);
+        assert!(parse("Win+F5").unwrap().virtual_keys() == Some(vec![vec![0x5B, 0x5C], vec![0x74]]));
+    }
+}
@@ -0,0 +1,827 @@
+//! Localization of strings produced in the backend (tray menu, error messages)
+//! The locale comes from settings, falling back to the Windows display language, then English
+//! The settings store itself (migration, config_lock) stays in English: it runs under the
+//! settings lock that looking up the locale takes
+
+/// Locales with translations, English first as the fallback
+pub const LOCALES: &[&str] = &["en", "de", "fr", "es"];
+
+/// Message id and its text in each locale, in LOCALES order
+/// `{0}`, `{1}`... are replaced with arguments by `format`
+const STRINGS: &[(&str, [&str; 4])] = &[
+    ("tray.show", ["Show", "Anzeigen", "Afficher", "Mostrar"]),
+    ("tray.quit", ["Quit", "Beenden", "Quitter", "Salir"]),
+    ("tray.tooltip", ["Noctis - Night Vision", "Noctis - Nachtsicht", "Noctis - Vision nocturne", "Noctis - Visión nocturna"]),
+    ("field.intensity", ["Intensity", "Intensität", "Intensité", "Intensidad"]),
+    ("field.brightness", ["Brightness", "Helligkeit", "Luminosité", "Brillo"]),
+    ("field.dark_threshold", ["Dark threshold", "Dunkelschwelle", "Seuil d'obscurité", "Umbral de oscuridad"]),
+    ("field.dead_zone", ["Dead zone", "Totzone", "Zone morte", "Zona muerta"]),
+    ("field.scene_change", ["Scene change", "Szenenwechsel", "Changement de scène", "Cambio de escena"]),
+    ("field.max_intensity", ["Maximum intensity", "Maximale Intensität", "Intensité maximale", "Intensidad máxima"]),
+    ("field.wheel_step", ["Wheel step", "Mausrad-Schritt", "Pas de la molette", "Paso de la rueda"]),
+    ("field.cursor_threshold", ["Cursor threshold", "Mauszeiger-Schwelle", "Seuil du curseur", "Umbral del cursor"]),
+    ("field.adjustment_level", ["Adjustment level", "Anpassungsstufe", "Niveau d'ajustement", "Nivel de ajuste"]),
+    ("field.wind_down_dim", ["Wind-down dimming", "Abdunkeln beim Ausklingen", "Assombrissement de fin de soirée", "Atenuación de final del día"]),
+    ("error.unit_interval", [
+        "{0} must be between 0 and 1, got {1}",
+        "{0} muss zwischen 0 und 1 liegen, erhalten: {1}",
+        "{0} : la valeur doit être comprise entre 0 et 1, reçu {1}",
+        "{0}: el valor debe estar entre 0 y 1, se recibió {1}",
+    ]),
+    ("error.sampling_interval", [
+        "Sampling interval must be above zero",
+        "Das Abtastintervall muss größer als null sein",
+        "L'intervalle d'échantillonnage doit être supérieur à zéro",
+        "El intervalo de muestreo debe ser mayor que cero",
+    ]),
+    ("error.monitor_not_found", [
+        "Monitor {0} not found",
+        "Monitor {0} nicht gefunden",
+        "Moniteur {0} introuvable",
+        "Monitor {0} no encontrado",
+    ]),
+    ("error.windows_only", [
+        "Only supported on Windows",
+        "Nur unter Windows unterstützt",
+        "Uniquement pris en charge sous Windows",
+        "Solo compatible con Windows",
+    ]),
+    ("error.shutting_down", [
+        "Noctis is shutting down",
+        "Noctis wird beendet",
+        "Noctis est en cours de fermeture",
+        "Noctis se está cerrando",
+    ]),
+    ("error.device_context", [
+        "Failed to create device context",
+        "Gerätekontext konnte nicht erstellt werden",
+        "Impossible de créer le contexte de périphérique",
+        "No se pudo crear el contexto de dispositivo",
+    ]),
+    ("error.read_gamma", [
+        "Failed to read gamma ramp",
+        "Gammakurve konnte nicht gelesen werden",
+        "Impossible de lire la rampe gamma",
+        "No se pudo leer la rampa gamma",
+    ]),
+    ("error.set_gamma", [
+        "Failed to set gamma ramp (Driver may be blocking it)",
+        "Gammakurve konnte nicht gesetzt werden (möglicherweise blockiert der Treiber sie)",
+        "Impossible de définir la rampe gamma (le pilote la bloque peut-être)",
+        "No se pudo establecer la rampa gamma (puede que el controlador la bloquee)",
+    ]),
+    ("error.enum_display_devices", [
+        "EnumDisplayDevices failed",
+        "EnumDisplayDevices fehlgeschlagen",
+        "Échec de EnumDisplayDevices",
+        "EnumDisplayDevices falló",
+    ]),
+    ("error.associate_profile", [
+        "Failed to associate profile with device (WCS)",
+        "Profil konnte dem Gerät nicht zugeordnet werden (WCS)",
+        "Impossible d'associer le profil au périphérique (WCS)",
+        "No se pudo asociar el perfil al dispositivo (WCS)",
+    ]),
+    ("error.mag_initialize", [
+        "Failed to initialize Magnification API",
+        "Magnification-API konnte nicht initialisiert werden",
+        "Impossible d'initialiser l'API Magnification",
+        "No se pudo inicializar la API de Magnification",
+    ]),
+    ("error.mag_transform", [
+        "Failed to set fullscreen transform",
+        "Vollbildtransformation konnte nicht gesetzt werden",
+        "Impossible de définir la transformation plein écran",
+        "No se pudo establecer la transformación de pantalla completa",
+    ]),
+    ("error.mag_color_effect", [
+        "Failed to set fullscreen color effect (error: {0})",
+        "Vollbild-Farbeffekt konnte nicht gesetzt werden (Fehler: {0})",
+        "Impossible de définir l'effet de couleur plein écran (erreur : {0})",
+        "No se pudo establecer el efecto de color de pantalla completa (error: {0})",
+    ]),
+    ("error.displays_asleep", [
+        "Displays are asleep",
+        "Die Bildschirme sind im Ruhezustand",
+        "Les écrans sont en veille",
+        "Las pantallas están en reposo",
+    ]),
+    ("error.screen_dc", [
+        "Failed to get screen DC",
+        "Bildschirm-DC konnte nicht abgerufen werden",
+        "Impossible d'obtenir le DC de l'écran",
+        "No se pudo obtener el DC de la pantalla",
+    ]),
+    ("error.compatible_dc", [
+        "Failed to create compatible DC",
+        "Kompatibler DC konnte nicht erstellt werden",
+        "Impossible de créer un DC compatible",
+        "No se pudo crear un DC compatible",
+    ]),
+    ("error.create_bitmap", [
+        "Failed to create bitmap",
+        "Bitmap konnte nicht erstellt werden",
+        "Impossible de créer le bitmap",
+        "No se pudo crear el mapa de bits",
+    ]),
+    ("error.bitblt", [
+        "BitBlt failed",
+        "BitBlt fehlgeschlagen",
+        "Échec de BitBlt",
+        "BitBlt falló",
+    ]),
+    ("error.get_dibits", [
+        "GetDIBits failed",
+        "GetDIBits fehlgeschlagen",
+        "Échec de GetDIBits",
+        "GetDIBits falló",
+    ]),
+    ("error.exclude_capture", [
+        "Failed to exclude window from capture (error: {0})",
+        "Fenster konnte nicht von der Aufnahme ausgeschlossen werden (Fehler: {0})",
+        "Impossible d'exclure la fenêtre de la capture (erreur : {0})",
+        "No se pudo excluir la ventana de la captura (error: {0})",
+    ]),
+    ("error.read_pixel", [
+        "Failed to read pixel at ({0}, {1})",
+        "Pixel bei ({0}, {1}) konnte nicht gelesen werden",
+        "Impossible de lire le pixel en ({0}, {1})",
+        "No se pudo leer el píxel en ({0}, {1})",
+    ]),
+    ("error.capture_size", [
+        "Invalid capture size {0}x{1}",
+        "Ungültige Aufnahmegröße {0}x{1}",
+        "Taille de capture invalide {0}x{1}",
+        "Tamaño de captura no válido {0}x{1}",
+    ]),
+    ("error.window_handle", [
+        "Failed to get window handle: {0}",
+        "Fensterhandle konnte nicht abgerufen werden: {0}",
+        "Impossible d'obtenir le handle de la fenêtre : {0}",
+        "No se pudo obtener el identificador de la ventana: {0}",
+    ]),
+    ("error.monitor_info", [
+        "Failed to get monitor info",
+        "Monitorinformationen konnten nicht abgerufen werden",
+        "Impossible d'obtenir les informations du moniteur",
+        "No se pudo obtener la información del monitor",
+    ]),
+    ("error.no_frame", [
+        "No frame received from graphics capture",
+        "Keine Frames von der Grafikaufnahme erhalten",
+        "Aucune image reçue de la capture graphique",
+        "No se recibió ningún fotograma de la captura gráfica",
+    ]),
+    ("error.capture_size_changing", [
+        "Capture size keeps changing",
+        "Die Aufnahmegröße ändert sich ständig",
+        "La taille de capture change sans cesse",
+        "El tamaño de captura no deja de cambiar",
+    ]),
+    ("error.calibration_window", [
+        "Failed to create calibration window",
+        "Kalibrierungsfenster konnte nicht erstellt werden",
+        "Impossible de créer la fenêtre de calibrage",
+        "No se pudo crear la ventana de calibración",
+    ]),
+    ("error.process_empty", [
+        "Process name cannot be empty",
+        "Der Prozessname darf nicht leer sein",
+        "Le nom du processus ne peut pas être vide",
+        "El nombre del proceso no puede estar vacío",
+    ]),
+    ("error.profile_name_empty", [
+        "Profile name cannot be empty",
+        "Der Profilname darf nicht leer sein",
+        "Le nom du profil ne peut pas être vide",
+        "El nombre del perfil no puede estar vacío",
+    ]),
+    ("error.profile_process_empty", [
+        "Profile process cannot be empty",
+        "Der Profilprozess darf nicht leer sein",
+        "Le processus du profil ne peut pas être vide",
+        "El proceso del perfil no puede estar vacío",
+    ]),
+    ("error.unsupported_hotkey", [
+        "Unsupported hotkey {0}: {1}",
+        "Nicht unterstütztes Tastenkürzel {0}: {1}",
+        "Raccourci non pris en charge {0} : {1}",
+        "Atajo no compatible {0}: {1}",
+    ]),
+    ("error.unsupported_locale", [
+        "Unsupported locale: {0}",
+        "Nicht unterstützte Sprache: {0}",
+        "Langue non prise en charge : {0}",
+        "Idioma no compatible: {0}",
+    ]),
+    ("error.crash_report_id", [
+        "Invalid crash report id: {0}",
+        "Ungültige Absturzbericht-ID: {0}",
+        "Identifiant de rapport de plantage invalide : {0}",
+        "Id. de informe de error no válido: {0}",
+    ]),
+    ("error.crash_report_not_found", [
+        "Crash report {0} not found",
+        "Absturzbericht {0} nicht gefunden",
+        "Rapport de plantage {0} introuvable",
+        "Informe de error {0} no encontrado",
+    ]),
+    ("error.schedule_hour", [
+        "Schedule hour must be between 0 and 23, got {0}",
+        "Die Stunde im Zeitplan muss zwischen 0 und 23 liegen, erhalten: {0}",
+        "L'heure du programme doit être comprise entre 0 et 23, reçu {0}",
+        "La hora del horario debe estar entre 0 y 23, se recibió {0}",
+    ]),
+    ("error.schedule_hour_repeated", [
+        "Schedule hour {0} is listed more than once",
+        "Die Stunde {0} steht mehrfach im Zeitplan",
+        "L'heure {0} figure plusieurs fois dans le programme",
+        "La hora {0} aparece más de una vez en el horario",
+    ]),
+    ("error.schedule_override_repeated", [
+        "{0} has more than one schedule override",
+        "{0} hat mehr als eine Zeitplan-Ausnahme",
+        "{0} a plus d'une exception au programme",
+        "{0} tiene más de una excepción de horario",
+    ]),
+    ("error.not_in_build", [
+        "Not available in this build",
+        "In diesem Build nicht verfügbar",
+        "Non disponible dans cette version",
+        "No disponible en esta compilación",
+    ]),
+    ("error.color_temperature", [
+        "Color temperature must be between {0}K and {1}K, got {2}K",
+        "Die Farbtemperatur muss zwischen {0} K und {1} K liegen, erhalten: {2} K",
+        "La température de couleur doit être comprise entre {0} K et {1} K, reçu {2} K",
+        "La temperatura de color debe estar entre {0} K y {1} K, se recibió {2} K",
+    ]),
+    ("error.no_ddc", [
+        "Monitor {0} has no DDC/CI connection",
+        "Monitor {0} hat keine DDC/CI-Verbindung",
+        "Le moniteur {0} n'a pas de connexion DDC/CI",
+        "El monitor {0} no tiene conexión DDC/CI",
+    ]),
+    ("error.ddc_power_ignored", [
+        "Monitor {0} ignored the DDC/CI power command",
+        "Monitor {0} hat den DDC/CI-Energiebefehl ignoriert",
+        "Le moniteur {0} a ignoré la commande d'alimentation DDC/CI",
+        "El monitor {0} ignoró la orden de energía DDC/CI",
+    ]),
+    ("error.no_secondary_displays", [
+        "No secondary displays to sleep",
+        "Keine weiteren Bildschirme zum Ausschalten",
+        "Aucun écran secondaire à mettre en veille",
+        "No hay pantallas secundarias que suspender",
+    ]),
+    ("error.no_display_slept", [
+        "No secondary display accepted the power command (DDC/CI may be disabled)",
+        "Kein weiterer Bildschirm hat den Energiebefehl angenommen (DDC/CI ist möglicherweise deaktiviert)",
+        "Aucun écran secondaire n'a accepté la commande d'alimentation (DDC/CI est peut-être désactivé)",
+        "Ninguna pantalla secundaria aceptó la orden de energía (puede que DDC/CI esté desactivado)",
+    ]),
+    ("error.control_port", [
+        "Control port must be between 1 and 65535",
+        "Der Steuerungsport muss zwischen 1 und 65535 liegen",
+        "Le port de contrôle doit être compris entre 1 et 65535",
+        "El puerto de control debe estar entre 1 y 65535",
+    ]),
+    ("error.link_escape", [
+        "Invalid escape in {0}",
+        "Ungültige Escape-Sequenz in {0}",
+        "Séquence d'échappement invalide dans {0}",
+        "Secuencia de escape no válida en {0}",
+    ]),
+    ("error.link_text", [
+        "Invalid text in {0}",
+        "Ungültiger Text in {0}",
+        "Texte invalide dans {0}",
+        "Texto no válido en {0}",
+    ]),
+    ("error.link_monitor_repeated", [
+        "Monitor given more than once",
+        "Monitor mehrfach angegeben",
+        "Moniteur indiqué plusieurs fois",
+        "Monitor indicado más de una vez",
+    ]),
+    ("error.link_parameter", [
+        "Unsupported parameter: {0}",
+        "Nicht unterstützter Parameter: {0}",
+        "Paramètre non pris en charge : {0}",
+        "Parámetro no compatible: {0}",
+    ]),
+    ("error.link_scheme", [
+        "Not a {0}:// link: {1}",
+        "Kein {0}://-Link: {1}",
+        "Ce n'est pas un lien {0}:// : {1}",
+        "No es un enlace {0}://: {1}",
+    ]),
+    ("error.link_intensity", [
+        "Invalid intensity: {0}",
+        "Ungültige Intensität: {0}",
+        "Intensité invalide : {0}",
+        "Intensidad no válida: {0}",
+    ]),
+    ("error.link_unsupported", [
+        "Unsupported link: {0}",
+        "Nicht unterstützter Link: {0}",
+        "Lien non pris en charge : {0}",
+        "Enlace no compatible: {0}",
+    ]),
+    ("error.link_parameters", [
+        "Unsupported parameters in {0}",
+        "Nicht unterstützte Parameter in {0}",
+        "Paramètres non pris en charge dans {0}",
+        "Parámetros no compatibles en {0}",
+    ]),
+    ("error.registry_key", [
+        "Failed to create registry key {0} (error {1})",
+        "Registrierungsschlüssel {0} konnte nicht erstellt werden (Fehler {1})",
+        "Impossible de créer la clé de registre {0} (erreur {1})",
+        "No se pudo crear la clave del registro {0} (error {1})",
+    ]),
+    ("error.registry_value", [
+        "Failed to write registry value in {0} (error {1})",
+        "Registrierungswert in {0} konnte nicht geschrieben werden (Fehler {1})",
+        "Impossible d'écrire la valeur de registre dans {0} (erreur {1})",
+        "No se pudo escribir el valor del registro en {0} (error {1})",
+    ]),
+    ("error.safe_mode_profiles", [
+        "Profiles are not applied in safe mode",
+        "Im abgesicherten Modus werden keine Profile angewendet",
+        "Les profils ne sont pas appliqués en mode sans échec",
+        "Los perfiles no se aplican en modo seguro",
+    ]),
+    ("error.openrgb_address", [
+        "Invalid OpenRGB address",
+        "Ungültige OpenRGB-Adresse",
+        "Adresse OpenRGB invalide",
+        "Dirección de OpenRGB no válida",
+    ]),
+    ("error.no_last_session", [
+        "No previous session to restore",
+        "Keine vorherige Sitzung zum Wiederherstellen",
+        "Aucune session précédente à restaurer",
+        "No hay ninguna sesión anterior que restaurar",
+    ]),
+    ("error.no_d3d_device", [
+        "No D3D11 device",
+        "Kein D3D11-Gerät",
+        "Aucun périphérique D3D11",
+        "No hay dispositivo D3D11",
+    ]),
+    ("error.no_d3d_context", [
+        "No D3D11 context",
+        "Kein D3D11-Kontext",
+        "Aucun contexte D3D11",
+        "No hay contexto D3D11",
+    ]),
+    ("error.no_frame_captured", [
+        "No frame captured",
+        "Kein Frame aufgenommen",
+        "Aucune image capturée",
+        "No se capturó ningún fotograma",
+    ]),
+    ("error.sample_area_covered", [
+        "Sensing paused while a Noctis window covers the sample area",
+        "Messung pausiert, solange ein Noctis-Fenster den Messbereich verdeckt",
+        "Mesure suspendue tant qu'une fenêtre Noctis couvre la zone mesurée",
+        "Medición en pausa mientras una ventana de Noctis cubre la zona de muestra",
+    ]),
+    ("error.no_monitors", [
+        "No monitors found",
+        "Keine Monitore gefunden",
+        "Aucun moniteur trouvé",
+        "No se encontraron monitores",
+    ]),
+    ("error.profile_not_found", [
+        "Profile {0} not found",
+        "Profil {0} nicht gefunden",
+        "Profil {0} introuvable",
+        "Perfil {0} no encontrado",
+    ]),
+    ("error.link_profile_name", [
+        "Invalid profile name in {0}",
+        "Ungültiger Profilname in {0}",
+        "Nom de profil invalide dans {0}",
+        "Nombre de perfil no válido en {0}",
+    ]),
+    ("error.launch_quotes", [
+        "Profile names with double quotes can't be used in launch options",
+        "Profilnamen mit doppelten Anführungszeichen können nicht in Startoptionen verwendet werden",
+        "Les noms de profil contenant des guillemets doubles ne peuvent pas être utilisés dans les options de lancement",
+        "Los nombres de perfil con comillas dobles no se pueden usar en las opciones de inicio",
+    ]),
+    ("error.discord_id", [
+        "Discord presence needs the numeric id of a Discord application",
+        "Die Discord-Präsenz braucht die numerische ID einer Discord-Anwendung",
+        "La présence Discord nécessite l'identifiant numérique d'une application Discord",
+        "La presencia de Discord necesita el id. numérico de una aplicación de Discord",
+    ]),
+    ("error.effect_white", [
+        "Color effect moves white to {0}",
+        "Der Farbeffekt verschiebt Weiß nach {0}",
+        "L'effet de couleur déplace le blanc vers {0}",
+        "El efecto de color desplaza el blanco a {0}",
+    ]),
+    ("error.effect_black", [
+        "Color effect lifts black to {0}, below {1}",
+        "Der Farbeffekt hebt Schwarz auf {0} an, unter {1}",
+        "L'effet de couleur relève le noir à {0}, sous {1}",
+        "El efecto de color eleva el negro a {0}, por debajo de {1}",
+    ]),
+    ("error.effect_invalid", [
+        "Color effect contains invalid values",
+        "Der Farbeffekt enthält ungültige Werte",
+        "L'effet de couleur contient des valeurs invalides",
+        "El efecto de color contiene valores no válidos",
+    ]),
+    ("error.magnifier_host_window", [
+        "Failed to create magnifier host window",
+        "Lupen-Hostfenster konnte nicht erstellt werden",
+        "Impossible de créer la fenêtre hôte de la loupe",
+        "No se pudo crear la ventana anfitriona de la lupa",
+    ]),
+    ("error.magnifier_window", [
+        "Failed to create magnifier window",
+        "Lupenfenster konnte nicht erstellt werden",
+        "Impossible de créer la fenêtre de la loupe",
+        "No se pudo crear la ventana de la lupa",
+    ]),
+    ("error.cursor_folder", [
+        "Cursor folder {0} does not exist",
+        "Der Mauszeiger-Ordner {0} existiert nicht",
+        "Le dossier de curseurs {0} n'existe pas",
+        "La carpeta de cursores {0} no existe",
+    ]),
+    ("error.replace_cursor", [
+        "Failed to replace system cursor {0}",
+        "Systemmauszeiger {0} konnte nicht ersetzt werden",
+        "Impossible de remplacer le curseur système {0}",
+        "No se pudo reemplazar el cursor del sistema {0}",
+    ]),
+    ("error.no_cursor_files", [
+        "No cursor files found in {0}",
+        "Keine Mauszeiger-Dateien in {0} gefunden",
+        "Aucun fichier de curseur trouvé dans {0}",
+        "No se encontraron archivos de cursor en {0}",
+    ]),
+    ("error.show_cursor", [
+        "Failed to show the system cursor",
+        "Systemmauszeiger konnte nicht angezeigt werden",
+        "Impossible d'afficher le curseur système",
+        "No se pudo mostrar el cursor del sistema",
+    ]),
+    ("error.hide_cursor", [
+        "Failed to hide the system cursor",
+        "Systemmauszeiger konnte nicht ausgeblendet werden",
+        "Impossible de masquer le curseur système",
+        "No se pudo ocultar el cursor del sistema",
+    ]),
+    ("error.rule_hours", [
+        "Rule hours must be between 0 and 23",
+        "Die Stunden einer Regel müssen zwischen 0 und 23 liegen",
+        "Les heures d'une règle doivent être comprises entre 0 et 23",
+        "Las horas de una regla deben estar entre 0 y 23",
+    ]),
+    ("error.rule_brightness_bound", [
+        "Brightness rules need a lower or upper bound",
+        "Helligkeitsregeln brauchen eine Unter- oder Obergrenze",
+        "Les règles de luminosité nécessitent une borne inférieure ou supérieure",
+        "Las reglas de brillo necesitan un límite inferior o superior",
+    ]),
+    ("error.rule_process_empty", [
+        "Rule process cannot be empty",
+        "Der Prozess einer Regel darf nicht leer sein",
+        "Le processus d'une règle ne peut pas être vide",
+        "El proceso de una regla no puede estar vacío",
+    ]),
+    ("error.rule_battery", [
+        "Battery percentage must be at most 100",
+        "Der Akkustand darf höchstens 100 Prozent betragen",
+        "Le pourcentage de batterie doit être au plus 100",
+        "El porcentaje de batería debe ser como máximo 100",
+    ]),
+    ("error.rule_message_empty", [
+        "Notification message cannot be empty",
+        "Die Benachrichtigung darf nicht leer sein",
+        "Le message de notification ne peut pas être vide",
+        "El mensaje de notificación no puede estar vacío",
+    ]),
+    ("error.rule_name_empty", [
+        "Rule name cannot be empty",
+        "Der Regelname darf nicht leer sein",
+        "Le nom de la règle ne peut pas être vide",
+        "El nombre de la regla no puede estar vacío",
+    ]),
+    ("error.rule_name_repeated", [
+        "Duplicate rule name {0}",
+        "Doppelter Regelname {0}",
+        "Nom de règle en double {0}",
+        "Nombre de regla duplicado {0}",
+    ]),
+    ("error.rule_no_actions", [
+        "Rule {0} has no actions",
+        "Regel {0} hat keine Aktionen",
+        "La règle {0} n'a aucune action",
+        "La regla {0} no tiene acciones",
+    ]),
+    ("error.rule", [
+        "Rule {0}: {1}",
+        "Regel {0}: {1}",
+        "Règle {0} : {1}",
+        "Regla {0}: {1}",
+    ]),
+    ("error.script_interval", [
+        "Script interval must be between 100 ms and 60 s",
+        "Das Skriptintervall muss zwischen 100 ms und 60 s liegen",
+        "L'intervalle du script doit être compris entre 100 ms et 60 s",
+        "El intervalo del script debe estar entre 100 ms y 60 s",
+    ]),
+    ("error.backend_registered", [
+        "Backend {0} is already registered",
+        "Backend {0} ist bereits registriert",
+        "Le backend {0} est déjà enregistré",
+        "El backend {0} ya está registrado",
+    ]),
+    ("error.backend_unknown", [
+        "Unknown backend {0}",
+        "Unbekanntes Backend {0}",
+        "Backend inconnu {0}",
+        "Backend desconocido {0}",
+    ]),
+    ("error.backend_unavailable", [
+        "Backend {0} is unavailable: {1}",
+        "Backend {0} ist nicht verfügbar: {1}",
+        "Le backend {0} n'est pas disponible : {1}",
+        "El backend {0} no está disponible: {1}",
+    ]),
+    ("error.backend_cannot_dim", [
+        "Backend {0} can't dim",
+        "Backend {0} kann nicht abdunkeln",
+        "Le backend {0} ne peut pas assombrir",
+        "El backend {0} no puede atenuar",
+    ]),
+    ("error.no_backend_comparison", [
+        "No backend comparison is running",
+        "Es läuft kein Backend-Vergleich",
+        "Aucune comparaison de backends n'est en cours",
+        "No hay ninguna comparación de backends en curso",
+    ]),
+    ("error.no_tuning", [
+        "No curve tuning is running",
+        "Es läuft keine Kurvenabstimmung",
+        "Aucun réglage de courbe n'est en cours",
+        "No hay ningún ajuste de curva en curso",
+    ]),
+    ("error.link_monitor_empty", [
+        "Invalid monitor: empty",
+        "Ungültiger Monitor: leer",
+        "Moniteur invalide : vide",
+        "Monitor no válido: vacío",
+    ]),
+    ("error.group_name_empty", [
+        "Group name cannot be empty",
+        "Der Gruppenname darf nicht leer sein",
+        "Le nom du groupe ne peut pas être vide",
+        "El nombre del grupo no puede estar vacío",
+    ]),
+    ("error.group_name_repeated", [
+        "Duplicate group name: {0}",
+        "Doppelter Gruppenname: {0}",
+        "Nom de groupe en double : {0}",
+        "Nombre de grupo duplicado: {0}",
+    ]),
+    ("error.group_empty", [
+        "Group {0} has no monitors",
+        "Gruppe {0} hat keine Monitore",
+        "Le groupe {0} n'a aucun moniteur",
+        "El grupo {0} no tiene monitores",
+    ]),
+    ("error.group_not_found", [
+        "Monitor group {0} not found",
+        "Monitorgruppe {0} nicht gefunden",
+        "Groupe de moniteurs {0} introuvable",
+        "Grupo de monitores {0} no encontrado",
+    ]),
+    ("error.group_disconnected", [
+        "No monitor of group {0} is connected",
+        "Kein Monitor der Gruppe {0} ist angeschlossen",
+        "Aucun moniteur du groupe {0} n'est connecté",
+        "Ningún monitor del grupo {0} está conectado",
+    ]),
+    ("error.broker_not_running", [
+        "Elevated broker is not running",
+        "Der Hilfsprozess mit Adminrechten läuft nicht",
+        "L'assistant élevé n'est pas en cours d'exécution",
+        "El asistente elevado no se está ejecutando",
+    ]),
+    ("error.broker_request", [
+        "Broker request failed: {0}",
+        "Anfrage an den Hilfsprozess fehlgeschlagen: {0}",
+        "Échec de la requête à l'assistant : {0}",
+        "Falló la solicitud al asistente: {0}",
+    ]),
+    ("error.broker_reply", [
+        "Invalid broker reply: {0}",
+        "Ungültige Antwort des Hilfsprozesses: {0}",
+        "Réponse de l'assistant invalide : {0}",
+        "Respuesta del asistente no válida: {0}",
+    ]),
+    ("error.capture_region", [
+        "Invalid capture region",
+        "Ungültiger Aufnahmebereich",
+        "Zone de capture invalide",
+        "Región de captura no válida",
+    ]),
+    ("error.already_elevated", [
+        "Noctis already runs as administrator",
+        "Noctis läuft bereits als Administrator",
+        "Noctis s'exécute déjà en tant qu'administrateur",
+        "Noctis ya se ejecuta como administrador",
+    ]),
+    ("error.broker_not_started", [
+        "The elevated broker was not started",
+        "Der Hilfsprozess mit Adminrechten wurde nicht gestartet",
+        "L'assistant élevé n'a pas été lancé",
+        "El asistente elevado no se inició",
+    ]),
+    ("error.frame_budget", [
+        "Budget must be greater than 0 ms",
+        "Das Budget muss größer als 0 ms sein",
+        "Le budget doit être supérieur à 0 ms",
+        "El presupuesto debe ser mayor que 0 ms",
+    ]),
+    ("error.sensing_paused_frame_guard", [
+        "Sensing paused by the frame-rate guard",
+        "Messung vom Bildraten-Wächter pausiert",
+        "Mesure suspendue par le garde de fréquence d'images",
+        "Medición en pausa por el control de fotogramas",
+    ]),
+    ("error.wind_down_times", [
+        "Wind-down times must be valid times of day, got {0} until {1}",
+        "Die Zeiten fürs Ausklingen müssen gültige Uhrzeiten sein, erhalten: {0} bis {1}",
+        "Les heures de fin de soirée doivent être des heures valides, reçu {0} jusqu'à {1}",
+        "Las horas de final del día deben ser horas válidas, se recibió {0} hasta {1}",
+    ]),
+    ("error.wind_down_duration", [
+        "Wind-down must last between {0} and {1} minutes, got {2}",
+        "Das Ausklingen muss zwischen {0} und {1} Minuten dauern, erhalten: {2}",
+        "La fin de soirée doit durer entre {0} et {1} minutes, reçu {2}",
+        "El final del día debe durar entre {0} y {1} minutos, se recibió {2}",
+    ]),
+    ("error.wind_down_window", [
+        "Wind-down must end after its fade completes",
+        "Das Ausklingen muss nach dem Abschluss der Überblendung enden",
+        "La fin de soirée doit se terminer après la fin du fondu",
+        "El final del día debe terminar después de completar el fundido",
+    ]),
+    ("error.session_length", [
+        "Session length must be between 1 and {0} minutes, got {1}",
+        "Die Sitzungsdauer muss zwischen 1 und {0} Minuten liegen, erhalten: {1}",
+        "La durée de session doit être comprise entre 1 et {0} minutes, reçu {1}",
+        "La duración de la sesión debe estar entre 1 y {0} minutos, se recibió {1}",
+    ]),
+    ("error.monitor_disconnected", [
+        "Monitor {0} is not connected",
+        "Monitor {0} ist nicht angeschlossen",
+        "Le moniteur {0} n'est pas connecté",
+        "El monitor {0} no está conectado",
+    ]),
+    ("hotkey.empty", ["No key given", "Keine Taste angegeben", "Aucune touche indiquée", "No se indicó ninguna tecla"]),
+    ("hotkey.empty_part", [
+        "Empty key between '+' separators",
+        "Leere Taste zwischen '+'-Trennzeichen",
+        "Touche vide entre les séparateurs '+'",
+        "Tecla vacía entre separadores '+'",
+    ]),
+    ("hotkey.unknown_key", [
+        "Unsupported key: {0}",
+        "Nicht unterstützte Taste: {0}",
+        "Touche non prise en charge : {0}",
+        "Tecla no compatible: {0}",
+    ]),
+    ("hotkey.duplicate_modifier", [
+        "Modifier {0} appears more than once",
+        "Modifikator {0} kommt mehrfach vor",
+        "Le modificateur {0} apparaît plusieurs fois",
+        "El modificador {0} aparece más de una vez",
+    ]),
+    ("hotkey.missing_key", [
+        "A hotkey needs a key besides modifiers",
+        "Ein Tastenkürzel braucht neben Modifikatoren eine Taste",
+        "Un raccourci nécessite une touche en plus des modificateurs",
+        "Un atajo necesita una tecla además de los modificadores",
+    ]),
+    ("hotkey.multiple_keys", [
+        "Only one key can be bound, got {0} and {1}",
+        "Nur eine Taste kann belegt werden, erhalten: {0} und {1}",
+        "Une seule touche peut être associée, reçu {0} et {1}",
+        "Solo se puede asignar una tecla, se recibió {0} y {1}",
+    ]),
+    ("hotkey.not_a_modifier", [
+        "{0} is not a modifier (Ctrl, Alt, Shift or Win)",
+        "{0} ist kein Modifikator (Strg, Alt, Umschalt oder Win)",
+        "{0} n'est pas un modificateur (Ctrl, Alt, Maj ou Win)",
+        "{0} no es un modificador (Ctrl, Alt, Mayús o Win)",
+    ]),
+    ("elevation.limited", [
+        "{0} runs as administrator, so the sensor and hotkeys can't reach it. Start the elevated helper or run Noctis as administrator.",
+        "{0} läuft als Administrator, daher erreichen Sensor und Tastenkürzel es nicht. Starte den Hilfsprozess mit Adminrechten oder führe Noctis als Administrator aus.",
+        "{0} s'exécute en tant qu'administrateur : le capteur et les raccourcis ne peuvent pas l'atteindre. Lancez l'assistant élevé ou exécutez Noctis en tant qu'administrateur.",
+        "{0} se ejecuta como administrador, por lo que el sensor y los atajos no lo alcanzan. Inicia el asistente elevado o ejecuta Noctis como administrador.",
+    ]),
+    ("wind_down.reminder", [
+        "It's getting late. Time to wrap up and get some rest.",
+        "Es wird spät. Zeit, zum Ende zu kommen und dich auszuruhen.",
+        "Il se fait tard. Il est temps de terminer et d'aller vous reposer.",
+        "Se está haciendo tarde. Es hora de terminar y descansar.",
+    ]),
+];
+
+#[cfg(windows)]
+fn system_language() -> Option<String> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetUserDefaultLocaleName(name: *mut u16, len: i32) -> i32;
+    }
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+    (len > 1).then(|| String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+#[cfg(not(windows))]
+fn system_language() -> Option<String> {
+    std::env::var("LANG").ok()
+}
+
+/// Index into LOCALES of a locale tag such as "de-AT", if it has translations
+fn locale_index(tag: &str) -> Option<usize> {
+    let language = tag.split(['-', '_', '.']).next()?;
+    LOCALES.iter().position(|l| l.eq_ignore_ascii_case(language))
+}
+
+/// Locale in use: the configured one, else the system language, else English
+pub fn current() -> &'static str {
+    let index = crate::settings::get()
+        .locale
+        .as_deref()
+        .and_then(locale_index)
+        .or_else(|| system_language().as_deref().and_then(locale_index))
+        .unwrap_or(0);
+    LOCALES[index]
+}
+
+/// Check whether a locale has translations
+pub fn is_supported(locale: &str) -> bool {
+    locale_index(locale).is_some()
+}
+
+/// Text for a message id in the current locale (the id itself if unknown)
+pub fn text(id: &str) -> String {
+    let index = locale_index(current()).unwrap_or(0);
+    STRINGS
+        .iter()
+        .find(|(key, _)| *key == id)
+        .map(|(_, texts)| texts[index].to_string())
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Text for a message id with `{0}`, `{1}`... replaced by the arguments
+pub fn format(id: &str, args: &[&dyn std::fmt::Display]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(text(id), |text, (i, arg)| text.replace(&format!("{{{}}}", i), &arg.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `{0}`, `{1}`... used in a text, sorted
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut found: Vec<&str> = text
+            .match_indices('{')
+            .filter_map(|(i, _)| text[i..].split_once('}').map(|(p, _)| p))
+            .collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        for (i, (id, _)) in STRINGS.iter().enumerate() {
+            assert!(!STRINGS[i + 1..].iter().any(|(other, _)| other == id), "{} is listed twice", id);
+        }
+    }
+
+    #[test]
+    fn translations_keep_placeholders() {
+        for (id, texts) in STRINGS {
+            for text in &texts[1..] {
+                assert_eq!(placeholders(text), placeholders(texts[0]), "{}: {}", id, text);
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
 //! ICC Profile Module - Creates and applies custom gamma curve profiles
 //! Uses Windows Color System (WCS) APIs for MHC pipeline integration
 
+#[cfg(all(windows, feature = "icc"))]
+use crate::topology::CloneOutputs;
 use std::path::PathBuf;
 
 /// Shadow lift intensity levels
@@ -8,11 +10,18 @@ pub const SHADOW_LIFT_LIGHT: f32 = 0.10;
 pub const SHADOW_LIFT_MEDIUM: f32 = 0.15;
 pub const SHADOW_LIFT_STRONG: f32 = 0.20;
 
+/// Entries in the profile's tone curve tables
+const CURVE_ENTRIES: usize = 4096;
+
 /// Profile file name
 const PROFILE_NAME: &str = "NoctisShadowLift.icm";
 
 /// Get path to store the ICC profile
 fn get_profile_dir() -> PathBuf {
+    // Portable mode keeps the profile with the rest of its data instead of installing it
+    if crate::portable::is_portable() {
+        return crate::portable::data_dir();
+    }
     // Use Windows color profile directory (requires admin)
     let mut path = PathBuf::from(std::env::var("WINDIR").unwrap_or("C:\\Windows".to_string()));
     path.push("System32");
@@ -31,6 +40,7 @@ pub fn get_profile_path() -> PathBuf {
 /// 
 /// The curve formula: output = offset + (input * (1 - offset))
 /// This lifts black to `offset` while keeping white at 1.0
+#[cfg(feature = "icc")]
 pub fn create_shadow_lift_profile(intensity: f32) -> Result<PathBuf, String> {
     use lcms2::*;
     
@@ -38,12 +48,13 @@ pub fn create_shadow_lift_profile(intensity: f32) -> Result<PathBuf, String> {
     let offset = intensity * SHADOW_LIFT_STRONG; // Scale to max 20% lift
     
     // Create tone curve with shadow lift
-    // We need to define the curve as a table of values
-    let mut curve_values: Vec<u16> = Vec::with_capacity(256);
-    for i in 0..256 {
-        let input = i as f32 / 255.0;
+    // We need to define the curve as a table of values, fine enough that the
+    // color pipeline doesn't interpolate visible steps into dark gradients
+    let mut curve_values: Vec<u16> = Vec::with_capacity(CURVE_ENTRIES);
+    for i in 0..CURVE_ENTRIES {
+        let input = i as f32 / (CURVE_ENTRIES - 1) as f32;
         let output = offset + (input * (1.0 - offset));
-        let value = (output * 65535.0).min(65535.0) as u16;
+        let value = (output * 65535.0).round().min(65535.0) as u16;
         curve_values.push(value);
     }
     
@@ -65,6 +76,10 @@ pub fn create_shadow_lift_profile(intensity: f32) -> Result<PathBuf, String> {
     
     // Set profile description
     let path = get_profile_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+    }
     
     // Save the profile
     profile.save_profile_to_file(&path)
@@ -73,7 +88,7 @@ pub fn create_shadow_lift_profile(intensity: f32) -> Result<PathBuf, String> {
     Ok(path)
 }
 
-#[cfg(windows)]
+#[cfg(all(windows, feature = "icc"))]
 mod windows_api {
     use super::*;
     use std::ptr;
@@ -153,7 +168,7 @@ mod windows_api {
         unsafe {
             // First call: get adapter info
             if EnumDisplayDevicesW(ptr::null(), 0, &mut dev, 0) == 0 {
-                return Err("EnumDisplayDevices failed".to_string());
+                return Err(crate::i18n::text("error.enum_display_devices"));
             }
             
             // Second call: get monitor info for the adapter with EDD_GET_DEVICE_INTERFACE_NAME
@@ -169,7 +184,7 @@ mod windows_api {
             if EnumDisplayDevicesW(display_wide.as_ptr(), 0, &mut mon, EDD_GET_DEVICE_INTERFACE_NAME) == 0 {
                 // Try without the flag
                 if EnumDisplayDevicesW(display_wide.as_ptr(), 0, &mut mon, 0) == 0 {
-                    return Err("EnumDisplayDevices for monitor failed".to_string());
+                    return Err(crate::i18n::text("error.enum_display_devices"));
                 }
             }
             
@@ -214,7 +229,7 @@ mod windows_api {
             );
             
             if result == 0 {
-                return Err("Failed to associate profile with device (WCS)".to_string());
+                return Err(crate::i18n::text("error.associate_profile"));
             }
         }
         Ok(())
@@ -241,28 +256,59 @@ mod windows_api {
     }
 }
 
-#[cfg(windows)]
+#[cfg(all(windows, feature = "icc"))]
 pub use windows_api::*;
 
 // Track if we've applied a profile (to avoid crashing on disassociate of non-existent profile)
-#[cfg(windows)]
+#[cfg(all(windows, feature = "icc"))]
 static mut PROFILE_APPLIED: bool = false;
 
+/// Name our profile is associated under: installed by name, or by full path in portable mode
+#[cfg(all(windows, feature = "icc"))]
+fn associated_name() -> String {
+    if crate::portable::is_portable() {
+        get_profile_path().to_string_lossy().to_string()
+    } else {
+        PROFILE_NAME.to_string()
+    }
+}
+
+/// WCS device ids of the outputs of a display a policy selects, or of its first monitor if
+/// the topology is unknown
+#[cfg(all(windows, feature = "icc"))]
+fn device_ids(monitor_device: &str, policy: CloneOutputs) -> Result<Vec<String>, String> {
+    let outputs = crate::topology::outputs(monitor_device);
+    if outputs.is_empty() {
+        return get_monitor_device_id(monitor_device).map(|id| vec![id]);
+    }
+    Ok(outputs.into_iter().filter(|o| policy.includes(o)).map(|o| o.device_path).collect())
+}
+
 /// Apply shadow lift to a specific monitor
-#[cfg(windows)]
+#[cfg(all(windows, feature = "icc"))]
 pub fn apply_shadow_lift(intensity: f32, monitor_device: &str) -> Result<(), String> {
     
-    // Get the proper DeviceID for WCS API (NOT the display name)
-    let device_id = get_monitor_device_id(monitor_device)?;
+    // Get the proper DeviceIDs for WCS API (NOT the display name): one per output the
+    // display is duplicated to, as the clone policy selects
+    let device_ids = device_ids(monitor_device, crate::settings::get().clone_outputs)?;
     
     // Create the profile
-    let profile_path = create_shadow_lift_profile(intensity)?;
+    let lift = crate::normalize::equivalent(crate::normalize::Backend::Icc, intensity);
+    let profile_path = create_shadow_lift_profile(lift)?;
     
-    // Install it
-    install_profile(&profile_path)?;
+    // Install it system-wide, except in portable mode which only associates
+    // the profile by full path for the current user
+    let profile_name = if crate::portable::is_portable() {
+        profile_path.to_string_lossy().to_string()
+    } else {
+        install_profile(&profile_path)?;
+        PROFILE_NAME.to_string()
+    };
     
-    // Associate with the device (using proper DeviceID)
-    associate_profile_with_device(PROFILE_NAME, &device_id)?;
+    // Associate with each device (using proper DeviceID)
+    for device_id in &device_ids {
+        associate_profile_with_device(&profile_name, device_id)?;
+    }
     
     // Mark that we've applied a profile
     unsafe { PROFILE_APPLIED = true; }
@@ -271,7 +317,7 @@ pub fn apply_shadow_lift(intensity: f32, monitor_device: &str) -> Result<(), Str
 }
 
 /// Remove shadow lift from a monitor (restore default)
-#[cfg(windows)]
+#[cfg(all(windows, feature = "icc"))]
 pub fn remove_shadow_lift(monitor_device: &str) -> Result<(), String> {
     
     // Only try to disassociate if we've previously applied a profile
@@ -281,32 +327,46 @@ pub fn remove_shadow_lift(monitor_device: &str) -> Result<(), String> {
         }
     }
     
-    // Get the proper DeviceID for WCS API
-    let device_id = match get_monitor_device_id(monitor_device) {
-        Ok(id) => id,
-        Err(e) => {
+    // Get the proper DeviceIDs for WCS API, every output so a policy change leaves nothing behind
+    let device_ids = match device_ids(monitor_device, CloneOutputs::All) {
+        Ok(ids) => ids,
+        Err(_) => {
             return Ok(());
         }
     };
     
     // Try to disassociate our profile
-    match disassociate_profile_from_device(PROFILE_NAME, &device_id) {
-        Ok(_) => {
-            unsafe { PROFILE_APPLIED = false; }
-        },
-        Err(_) => (),
+    let profile_name = associated_name();
+    if device_ids.iter().all(|id| disassociate_profile_from_device(&profile_name, id).is_ok()) {
+        unsafe { PROFILE_APPLIED = false; }
     }
     
     Ok(())
 }
 
-// Fallback for non-Windows
-#[cfg(not(windows))]
+/// Disassociate our profile from every monitor, whether or not this process applied it,
+/// so a profile left behind by a crashed session is removed as well
+#[cfg(all(windows, feature = "icc"))]
+pub fn remove_all() {
+    let profile_name = associated_name();
+    for monitor in crate::gamma::get_monitors() {
+        for device_id in device_ids(&monitor.name, CloneOutputs::All).unwrap_or_default() {
+            let _ = disassociate_profile_from_device(&profile_name, &device_id);
+        }
+    }
+    unsafe { PROFILE_APPLIED = false; }
+}
+
+// Fallback for non-Windows and builds without ICC support
+#[cfg(not(all(windows, feature = "icc")))]
 pub fn apply_shadow_lift(_intensity: f32, _monitor_device: &str) -> Result<(), String> {
-    Err("ICC profile support only available on Windows".to_string())
+    Err(crate::i18n::text("error.not_in_build"))
 }
 
-#[cfg(not(windows))]
+#[cfg(not(all(windows, feature = "icc")))]
 pub fn remove_shadow_lift(_monitor_device: &str) -> Result<(), String> {
-    Err("ICC profile support only available on Windows".to_string())
+    Err(crate::i18n::text("error.not_in_build"))
 }
+
+#[cfg(not(all(windows, feature = "icc")))]
+pub fn remove_all() {}
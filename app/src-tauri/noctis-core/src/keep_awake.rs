@@ -0,0 +1,48 @@
+//! Keep-awake - stops the display dimming or sleeping while a game profile is engaged
+//! Long stealth sections can go minutes without input; the execution state is held by a
+//! dedicated thread because SetThreadExecutionState only lasts as long as the thread setting it
+
+use std::thread;
+use std::time::Duration;
+
+/// How often the active profile is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[cfg(windows)]
+fn set_awake(awake: bool) {
+    const ES_CONTINUOUS: u32 = 0x8000_0000;
+    const ES_SYSTEM_REQUIRED: u32 = 0x0000_0001;
+    const ES_DISPLAY_REQUIRED: u32 = 0x0000_0002;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetThreadExecutionState(flags: u32) -> u32;
+    }
+
+    let flags = if awake { ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED } else { ES_CONTINUOUS };
+    unsafe { SetThreadExecutionState(flags) };
+}
+
+#[cfg(not(windows))]
+fn set_awake(_awake: bool) {}
+
+/// Whether the display should be kept awake right now
+fn wanted() -> bool {
+    crate::settings::get().keep_awake && crate::profiles::active().is_some()
+}
+
+/// Start the thread that holds the display awake while a profile is engaged
+pub fn start() {
+    thread::spawn(|| {
+        let mut awake = false;
+        while !crate::shutdown::is_shutting_down() {
+            let want = wanted();
+            if want != awake {
+                set_awake(want);
+                awake = want;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        set_awake(false);
+    });
+}
@@ -0,0 +1,126 @@
+//! Game launch wrappers - apply a profile, start the game, release the profile when it exits
+//! Steam launch options run `noctis.exe --launch "<profile>" -- %command%`; the wrapper drives the
+//! running Noctis with deep links (starting it if needed) and waits for the game's process to exit
+
+use crate::{allowlist, deep_link, profiles};
+use serde::Serialize;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Command line flag that runs the executable as a launch wrapper for a profile
+const LAUNCH_FLAG: &str = "--launch";
+
+/// How often the wrapper checks whether the game is still running
+const EXIT_POLL: Duration = Duration::from_secs(2);
+
+/// A launcher that exits sooner than this is assumed to have handed off to the game,
+/// which then gets as long to show up in the process list
+const HANDOFF_GRACE: Duration = Duration::from_secs(30);
+
+/// Ready-to-paste launch integration for one profile
+#[derive(Serialize)]
+pub struct LaunchWrapper {
+    /// Steam launch options (game Properties > General)
+    pub steam: String,
+    /// PowerShell for Playnite's "before starting a game" script
+    pub playnite_start: String,
+    /// PowerShell for Playnite's "after a game is closed" script
+    pub playnite_exit: String,
+}
+
+/// Profile and game command line if this instance was started as a launch wrapper
+pub fn launch_args() -> Option<(String, Vec<String>)> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == LAUNCH_FLAG)?;
+    let profile = args.get(index + 1)?.clone();
+    let rest = &args[index + 2..];
+    let command = match rest.first() {
+        Some(separator) if separator == "--" => &rest[1..],
+        _ => rest,
+    };
+    Some((profile, command.to_vec()))
+}
+
+/// Arguments that keep a started Noctis on this instance's config folder
+fn portable_args() -> &'static [&'static str] {
+    if crate::portable::is_portable() {
+        &["--portable"]
+    } else {
+        &[]
+    }
+}
+
+/// Send a link to the running Noctis, or start Noctis with it if none is running
+fn send(url: &str) -> Result<(), String> {
+    match deep_link::forward(url) {
+        Some(reply) => match reply.strip_prefix("error: ") {
+            Some(error) => Err(error.to_string()),
+            None => Ok(()),
+        },
+        None => {
+            let exe = std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))?;
+            Command::new(exe)
+                .args(portable_args())
+                .arg(url)
+                .spawn()
+                .map_err(|e| format!("Failed to start Noctis: {}", e))?;
+            Ok(())
+        }
+    }
+}
+
+/// Wait for a game whose launcher exited early, then for the game itself
+fn wait_for_game(profile: &str, launcher_ran: Duration) {
+    let Some(process) = profiles::find_by_name(profile).map(|p| p.process) else { return };
+    let running = || allowlist::running_processes().iter().any(|p| p.eq_ignore_ascii_case(&process));
+    if launcher_ran < HANDOFF_GRACE {
+        let deadline = Instant::now() + HANDOFF_GRACE;
+        while !running() && Instant::now() < deadline {
+            thread::sleep(EXIT_POLL);
+        }
+    }
+    while running() {
+        thread::sleep(EXIT_POLL);
+    }
+}
+
+/// Apply the profile, run the game to completion and release the profile
+/// Returns the game's exit code; the game starts even if Noctis can't be reached
+pub fn run(profile: &str, command: &[String]) -> i32 {
+    let _ = send(&deep_link::profile_url(profile));
+    let Some((program, args)) = command.split_first() else { return 1 };
+    let started = Instant::now();
+    let status = Command::new(program).args(args).status();
+    wait_for_game(profile, started.elapsed());
+    // Only a running Noctis has anything to release
+    let _ = deep_link::forward(&deep_link::release_url(profile));
+    status.ok().and_then(|s| s.code()).unwrap_or(1)
+}
+
+/// Quote a string for PowerShell, where single quotes take no escapes but themselves
+fn powershell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Launch options and scripts that apply a profile around its game
+pub fn wrapper(name: &str) -> Result<LaunchWrapper, String> {
+    let profile = profiles::find_by_name(name).ok_or_else(|| crate::i18n::format("error.profile_not_found", &[&name]))?;
+    if profile.name.contains('"') {
+        return Err(crate::i18n::text("error.launch_quotes"));
+    }
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))?;
+    let exe = exe.display().to_string();
+    let portable = portable_args().iter().map(|arg| format!("{} ", arg)).collect::<String>();
+
+    let script = |url: String| {
+        let mut args: Vec<String> = portable_args().iter().map(|arg| powershell_quote(arg)).collect();
+        args.push(powershell_quote(&url));
+        format!("Start-Process -FilePath {} -ArgumentList {}", powershell_quote(&exe), args.join(","))
+    };
+    Ok(LaunchWrapper {
+        steam: format!("\"{}\" {}{} \"{}\" -- %command%", exe, portable, LAUNCH_FLAG, profile.name),
+        playnite_start: script(deep_link::profile_url(&profile.name)),
+        playnite_exit: script(deep_link::release_url(&profile.name)),
+    })
+}
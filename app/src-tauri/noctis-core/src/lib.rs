@@ -0,0 +1,89 @@
+//! Noctis core - display control without the app shell: gamma ramps, the Magnification
+//! and ICC backends, the brightness sensor and auto-adjust, profiles and the rest of the
+//! background services. The Tauri app in the parent folder wires these up to its UI
+//!
+//! Other programs should use the stable API at the crate root: `Controller` applies an
+//! `Adjustment` to a `MonitorId` through any registered backend and reads scene brightness.
+//! The modules below are the app's internals and may change between releases
+
+mod api;
+
+pub use api::{Adjustment, Controller, Monitor, MonitorId};
+
+pub mod gamma;
+pub mod sensor;
+pub mod magnification;
+pub mod settings;
+pub mod migration;
+pub mod config_lock;
+pub mod portable;
+pub mod calibration;
+pub mod suspend;
+pub mod foreground;
+pub mod blocklist;
+pub mod profiles;
+pub mod process_watcher;
+pub mod config_watcher;
+pub mod night_mode;
+pub mod peripherals;
+pub mod lights;
+pub mod mqtt;
+pub mod hdr_video;
+pub mod display_power;
+pub mod command_queue;
+pub mod worker;
+pub mod shutdown;
+pub mod message_window;
+pub mod session;
+pub mod watchdog;
+pub mod state;
+pub mod ramp_log;
+pub mod history;
+pub mod graphics_capture;
+pub mod adjustment;
+pub mod hotkey;
+pub mod wheel;
+pub mod toggle;
+pub mod allowlist;
+pub mod background;
+pub mod i18n;
+pub mod updates;
+pub mod crash;
+pub mod preflight;
+pub mod safe_mode;
+pub mod icc_profile;
+pub mod emergency;
+pub mod schedule;
+pub mod controller;
+pub mod temperature;
+pub mod display_sleep;
+pub mod keep_awake;
+pub mod control;
+pub mod snippets;
+pub mod deep_link;
+pub mod launcher;
+pub mod discord;
+pub mod accessibility;
+pub mod normalize;
+pub mod magnifier_host;
+pub mod cursor;
+pub mod screenshot;
+pub mod markers;
+pub mod user_switch;
+pub mod mag_conflict;
+pub mod color_filter;
+pub mod backend;
+pub mod backend_compare;
+pub mod rules;
+pub mod scripting;
+pub mod tuning;
+pub mod monitor_groups;
+pub mod topology;
+pub mod elevation;
+pub mod perf;
+pub mod frame_guard;
+pub mod wind_down;
+pub mod session_timer;
+pub mod blue_light;
+pub mod startup;
+pub mod control_panel;
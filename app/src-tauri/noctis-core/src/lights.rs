@@ -0,0 +1,147 @@
+//! Smart-light bias lighting - follows night mode on a Philips Hue or Home Assistant light group
+//! Dims and warms the room lights as Noctis lifts the screen, and restores them afterwards
+
+use crate::night_mode::{self, NightMode};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+use std::thread;
+
+#[cfg(feature = "network")]
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Intensity changes smaller than this are not sent, so fades don't flood the bridge
+const MIN_INTENSITY_CHANGE: f32 = 0.05;
+
+/// Where the light group lives
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LightBridge {
+    /// Hue bridge local API (v1), using an authorized bridge username
+    Hue { bridge: String, username: String, group: String },
+    /// Home Assistant REST API with a long-lived access token
+    HomeAssistant { url: String, token: String, entity_id: String },
+}
+
+/// Light level for one end of the night mode range
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct LightScene {
+    /// 0.0 - 1.0
+    pub brightness: f32,
+    pub kelvin: u32,
+}
+
+/// Smart-light integration settings
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct LightSettings {
+    pub enabled: bool,
+    pub bridge: Option<LightBridge>,
+    /// Lights while night mode is off
+    pub day: LightScene,
+    /// Lights at full night mode intensity, blended toward as intensity rises
+    pub night: LightScene,
+}
+
+impl Default for LightSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bridge: None,
+            day: LightScene { brightness: 0.8, kelvin: 4000 },
+            night: LightScene { brightness: 0.15, kelvin: 2200 },
+        }
+    }
+}
+
+/// Blend the day and night scenes for a night mode state
+fn scene_for(config: &LightSettings, state: NightMode) -> LightScene {
+    let t = if state.engaged { state.intensity } else { 0.0 };
+    let lerp = |day: f32, night: f32| day + (night - day) * t;
+    LightScene {
+        brightness: lerp(config.day.brightness, config.night.brightness).clamp(0.0, 1.0),
+        kelvin: lerp(config.day.kelvin as f32, config.night.kelvin as f32).round() as u32,
+    }
+}
+
+/// Send a scene to the configured light group
+#[cfg(feature = "network")]
+pub fn apply_scene(bridge: &LightBridge, scene: LightScene) -> Result<(), String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .into();
+
+    let result = match bridge {
+        LightBridge::Hue { bridge, username, group } => {
+            let url = format!("http://{}/api/{}/groups/{}/action", bridge, username, group);
+            agent.put(&url).send_json(serde_json::json!({
+                "on": scene.brightness > 0.0,
+                "bri": (scene.brightness * 254.0).round().max(1.0) as u32,
+                // Hue takes color temperature in mireds, 153 (6500K) to 500 (2000K)
+                "ct": (1_000_000 / scene.kelvin.max(1)).clamp(153, 500),
+                "transitiontime": 4,
+            }))
+        }
+        LightBridge::HomeAssistant { url, token, entity_id } => {
+            let url = format!("{}/api/services/light/turn_on", url.trim_end_matches('/'));
+            agent
+                .post(&url)
+                .header("Authorization", &format!("Bearer {}", token))
+                .send_json(serde_json::json!({
+                    "entity_id": entity_id,
+                    "brightness_pct": (scene.brightness * 100.0).round() as u32,
+                    "color_temp_kelvin": scene.kelvin,
+                    "transition": 0.4,
+                }))
+        }
+    };
+    result.map(|_| ()).map_err(|e| format!("Failed to update lights: {}", e))
+}
+
+#[cfg(not(feature = "network"))]
+pub fn apply_scene(_bridge: &LightBridge, _scene: LightScene) -> Result<(), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+/// Follow night mode on a background thread
+pub fn start() {
+    let (tx, rx) = mpsc::channel::<NightMode>();
+    night_mode::subscribe(move |state| {
+        let _ = tx.send(state);
+    });
+
+    thread::spawn(move || {
+        let mut last_sent: Option<NightMode> = None;
+        while let Ok(mut state) = rx.recv() {
+            // Only the latest state matters if several changes queued up during a request
+            while let Ok(next) = rx.try_recv() {
+                state = next;
+            }
+
+            let config = crate::settings::get().lights;
+            let Some(bridge) = config.bridge.as_ref().filter(|_| config.enabled) else {
+                last_sent = None;
+                continue;
+            };
+            let unchanged = last_sent.is_some_and(|last| {
+                last.engaged == state.engaged
+                    && (last.intensity - state.intensity).abs() < MIN_INTENSITY_CHANGE
+            });
+            if unchanged {
+                continue;
+            }
+            if apply_scene(bridge, scene_for(&config, state)).is_ok() {
+                last_sent = Some(state);
+            }
+        }
+    });
+}
+
+/// Apply the scene for the current night mode state immediately
+pub fn sync_now() -> Result<(), String> {
+    let config = crate::settings::get().lights;
+    match config.bridge.as_ref().filter(|_| config.enabled) {
+        Some(bridge) => apply_scene(bridge, scene_for(&config, night_mode::current())),
+        None => Ok(()),
+    }
+}
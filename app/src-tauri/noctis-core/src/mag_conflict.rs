@@ -0,0 +1,162 @@
+//! Magnification conflicts - the fullscreen color effect is one matrix for the whole desktop,
+//! so Windows Magnifier or another tool setting it replaces ours and vice versa. A watcher
+//! notices a foreign matrix or a known consumer and either takes over, composing our effect
+//! on top of theirs, or yields the Magnification backend until the other consumer is gone
+
+use crate::magnification::{self, MagColorEffect};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How often the fullscreen effect is compared with ours
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Processes known to set the fullscreen color effect, with the name shown to the user
+const KNOWN_CONSUMERS: [(&str, &str); 1] = [("magnify.exe", "Windows Magnifier")];
+
+/// Matrices closer than this per entry count as the same
+const MATCH_TOLERANCE: f32 = 1e-3;
+
+/// What to do while another process uses the fullscreen color effect
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Keep applying our effect, composed on top of theirs when their matrix is known
+    #[default]
+    TakeOver,
+    /// Stop writing the fullscreen effect until the other consumer is gone
+    Yield,
+}
+
+/// Conflict state reported to the UI
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct ConflictState {
+    /// Another process is using the fullscreen color effect
+    pub conflict: bool,
+    /// Name of the other consumer, if it is a known one
+    pub consumer: Option<String>,
+    pub policy: ConflictPolicy,
+    /// Our Magnification effect is held back
+    pub yielded: bool,
+}
+
+/// What the last checks saw
+struct Tracker {
+    /// Matrix we last wrote to the fullscreen effect
+    written: Option<MagColorEffect>,
+    /// Matrix another process set, which our effect is composed on
+    foreign: Option<MagColorEffect>,
+    consumer: Option<String>,
+    conflict: bool,
+}
+
+static TRACKER: Mutex<Tracker> = Mutex::new(Tracker { written: None, foreign: None, consumer: None, conflict: false });
+
+static YIELDED: AtomicBool = AtomicBool::new(false);
+
+/// Whether fullscreen effect writes are held back for another consumer
+pub fn is_yielded() -> bool {
+    YIELDED.load(Ordering::SeqCst)
+}
+
+/// Matrix to write for `effect`: composed on the other consumer's matrix, if there is one
+pub fn compose(effect: &MagColorEffect) -> MagColorEffect {
+    match TRACKER.lock().unwrap().foreign {
+        Some(foreign) => foreign.then(effect),
+        None => *effect,
+    }
+}
+
+/// Remember the matrix just written so a change by someone else can be told apart
+pub fn written(effect: &MagColorEffect) {
+    TRACKER.lock().unwrap().written = Some(*effect);
+}
+
+fn same(a: &MagColorEffect, b: &MagColorEffect) -> bool {
+    a.transform.iter().flatten().zip(b.transform.iter().flatten()).all(|(x, y)| (x - y).abs() <= MATCH_TOLERANCE)
+}
+
+/// Current conflict state
+pub fn state() -> ConflictState {
+    let tracker = TRACKER.lock().unwrap();
+    ConflictState {
+        conflict: tracker.conflict,
+        consumer: tracker.consumer.clone(),
+        policy: crate::settings::get().magnification_conflict,
+        yielded: is_yielded(),
+    }
+}
+
+/// Name of a known consumer that is running
+fn running_consumer() -> Option<String> {
+    let processes = crate::allowlist::running_processes();
+    KNOWN_CONSUMERS
+        .iter()
+        .find(|(process, _)| processes.iter().any(|p| p.eq_ignore_ascii_case(process)))
+        .map(|(_, name)| name.to_string())
+}
+
+/// Compare the fullscreen effect with ours and apply the policy
+/// Returns the new state if the conflict started, ended or changed consumer
+pub fn check() -> Option<ConflictState> {
+    let consumer = running_consumer();
+    let current = magnification::read_fullscreen_effect();
+    let (overwritten, changed) = {
+        let mut tracker = TRACKER.lock().unwrap();
+        let overwritten = match (current, tracker.written) {
+            (Some(current), Some(written)) if !same(&current, &written) => Some(current),
+            _ => None,
+        };
+        // A process's effect ends with it, leaving identity behind once the consumer exits
+        if overwritten.is_some() {
+            tracker.foreign = overwritten;
+        }
+        let conflict = consumer.is_some() || tracker.foreign.is_some_and(|f| !same(&f, &MagColorEffect::identity()));
+        if !conflict {
+            tracker.foreign = None;
+        }
+        let changed = conflict != tracker.conflict || consumer != tracker.consumer;
+        tracker.conflict = conflict;
+        tracker.consumer = consumer;
+        (overwritten.is_some(), changed)
+    };
+
+    if !overwritten && !changed {
+        return None;
+    }
+    let state = apply_policy();
+    if changed {
+        crate::crash::log(format!("Magnification conflict: {:?}", state));
+    }
+    changed.then_some(state)
+}
+
+/// Yield or take over according to the policy and the last check
+pub fn apply_policy() -> ConflictState {
+    let state = state();
+    let yielded = state.conflict && state.policy == ConflictPolicy::Yield;
+    YIELDED.store(yielded, Ordering::SeqCst);
+    // Taking over re-applies ours on top of theirs; ending a yield brings ours back
+    if !yielded && !crate::suspend::is_suspended() {
+        magnification::reapply();
+    }
+    ConflictState { yielded, ..state }
+}
+
+/// Start a background thread that watches for other fullscreen color effect consumers
+/// `on_change` receives the state whenever a conflict starts, ends or changes consumer
+pub fn start_watcher<F>(on_change: F)
+where
+    F: Fn(ConflictState) + Send + 'static,
+{
+    thread::spawn(move || {
+        while !crate::shutdown::is_shutting_down() {
+            if let Some(state) = check() {
+                on_change(state);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,598 @@
+//! Magnification API Module - System-wide color transformation
+//! Uses Windows Magnification API (MagSetFullscreenColorEffect) for instant shadow lift
+//! No admin required, GPU-accelerated, works system-wide
+
+use std::ptr;
+use std::ffi::c_void;
+use std::sync::Mutex;
+
+/// Black lift of the shadow lift matrix at full intensity
+/// Max 50% lift for strong night vision effect
+pub const MAX_LIFT: f32 = 0.50;
+
+/// MAGCOLOREFFECT is a 5x5 matrix that transforms RGBA colors
+/// The matrix operates on [R, G, B, A, 1] vectors
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct MagColorEffect {
+    pub transform: [[f32; 5]; 5],
+}
+
+impl Default for MagColorEffect {
+    fn default() -> Self {
+        // Identity matrix (no change)
+        Self {
+            transform: [
+                [1.0, 0.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 0.0, 1.0],
+            ]
+        }
+    }
+}
+
+impl MagColorEffect {
+    /// Create an identity matrix (no color change)
+    pub fn identity() -> Self {
+        Self::default()
+    }
+    
+    /// Create a shadow lift effect matrix (for dark scenes)
+    /// intensity: 0.0 (no effect) to 1.0 (max shadow lift)
+    /// 
+    /// Formula: output = offset + input * scale
+    /// This lifts black level while preserving whites
+    /// 
+    /// MAGCOLOREFFECT matrix layout (row-major, applied as: color_out = color_in * matrix):
+    /// Row 0: [R_scale, 0, 0, 0, 0]  - Red output
+    /// Row 1: [0, G_scale, 0, 0, 0]  - Green output  
+    /// Row 2: [0, 0, B_scale, 0, 0]  - Blue output
+    /// Row 3: [0, 0, 0, 1, 0]        - Alpha (unchanged)
+    /// Row 4: [R_offset, G_offset, B_offset, 0, 1] - Translation/offset
+    pub fn shadow_lift(intensity: f32) -> Self {
+        let intensity = intensity.max(0.0).min(1.0);
+        let offset = intensity * MAX_LIFT;
+        let scale = 1.0 - offset;
+        
+        
+        Self {
+            transform: [
+                [scale, 0.0,   0.0,   0.0, 0.0],    // Red output
+                [0.0,   scale, 0.0,   0.0, 0.0],    // Green output
+                [0.0,   0.0,   scale, 0.0, 0.0],    // Blue output
+                [0.0,   0.0,   0.0,   1.0, 0.0],    // Alpha unchanged
+                [offset, offset, offset, 0.0, 1.0], // Offset/translation row
+            ]
+        }
+    }
+    
+    /// Create a dim effect matrix (for bright scenes)
+    /// intensity: 0.0 (no dim) to 1.0 (max dim)
+    /// 
+    /// Formula: output = input * scale
+    /// This reduces overall brightness proportionally
+    pub fn dim(intensity: f32) -> Self {
+        let intensity = intensity.max(0.0).min(1.0);
+        let scale = 1.0 - (intensity * 0.30); // Max 30% dim
+        
+        Self {
+            transform: [
+                [scale, 0.0,   0.0,   0.0, 0.0],
+                [0.0,   scale, 0.0,   0.0, 0.0],
+                [0.0,   0.0,   scale, 0.0, 0.0],
+                [0.0,   0.0,   0.0,   1.0, 0.0],
+                [0.0,   0.0,   0.0,   0.0, 1.0],
+            ]
+        }
+    }
+    
+    /// Create a color temperature effect matrix
+    /// kelvin: 6500 (neutral) down to warmer values, scaling green and blue below red
+    pub fn warm(kelvin: f32) -> Self {
+        let [r, g, b] = crate::temperature::channel_gains(kelvin);
+        Self {
+            transform: [
+                [r,   0.0, 0.0, 0.0, 0.0],
+                [0.0, g,   0.0, 0.0, 0.0],
+                [0.0, 0.0, b,   0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 0.0, 1.0],
+            ]
+        }
+    }
+    
+    /// Effect of applying this matrix and then `next`
+    /// Colors are row vectors, so the combined matrix is self * next
+    pub fn then(&self, next: &MagColorEffect) -> Self {
+        let mut transform = [[0.0; 5]; 5];
+        for (row, out) in transform.iter_mut().enumerate() {
+            for (col, value) in out.iter_mut().enumerate() {
+                *value = (0..5).map(|k| self.transform[row][k] * next.transform[k][col]).sum();
+            }
+        }
+        Self { transform }
+    }
+    
+    /// Transform an RGB color (0.0 - 1.0 per channel) without clamping
+    /// Colors are row vectors [R, G, B, A, 1] multiplied by the matrix, with opaque alpha
+    fn map(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let input = [rgb[0], rgb[1], rgb[2], 1.0, 1.0];
+        std::array::from_fn(|channel| (0..5).map(|row| input[row] * self.transform[row][channel]).sum())
+    }
+    
+    /// Transform an 8-bit RGB color the same way the compositor would, clamping the result
+    pub fn apply(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let output = self.map(rgb.map(|c| c as f32 / 255.0));
+        output.map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+    
+    /// Lowest and highest value each RGB output can take for inputs in 0.0 - 1.0
+    pub fn output_range(&self) -> [(f32, f32); 3] {
+        std::array::from_fn(|channel| {
+            // Alpha is opaque, so its row adds to the offset like the translation row
+            let offset = self.transform[3][channel] + self.transform[4][channel];
+            (0..3)
+                .map(|row| self.transform[row][channel])
+                .fold((offset, offset), |(low, high), weight| (low + weight.min(0.0), high + weight.max(0.0)))
+        })
+    }
+    
+    /// Brightness of white after the effect (Rec. 709 luma)
+    pub fn white_level(&self) -> f32 {
+        let [r, g, b] = self.map([1.0; 3]).map(|c| c.clamp(0.0, 1.0));
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+    
+    /// Rescale each RGB output that could leave 0.0 - 1.0 so its whole range fits, instead of
+    /// letting the compositor clip it; None if the matrix holds NaN or infinite values
+    pub fn clamped(&self) -> Option<Self> {
+        if self.transform.iter().flatten().any(|value| !value.is_finite()) {
+            return None;
+        }
+        let mut effect = *self;
+        for (channel, (low, high)) in self.output_range().into_iter().enumerate() {
+            let (low, high) = (low.min(0.0), high.max(1.0));
+            if low == 0.0 && high == 1.0 {
+                continue;
+            }
+            let scale = 1.0 / (high - low);
+            for row in effect.transform.iter_mut().take(4) {
+                row[channel] *= scale;
+            }
+            effect.transform[4][channel] = effect.transform[4][channel] * scale - low * scale;
+        }
+        Some(effect)
+    }
+    
+    /// Check that white stays white and black sits at or above `lift` on every channel,
+    /// within half an 8-bit step; for composed lift matrices before they are applied
+    pub fn verify_levels(&self, lift: f32) -> Result<(), String> {
+        let white = self.map([1.0; 3]);
+        let black = self.map([0.0; 3]);
+        if white.iter().any(|v| (v - 1.0).abs() > LEVEL_TOLERANCE) {
+            return Err(crate::i18n::format("error.effect_white", &[&format!("{:?}", white)]));
+        }
+        if black.iter().any(|v| *v < lift - LEVEL_TOLERANCE) {
+            return Err(crate::i18n::format("error.effect_black", &[&format!("{:?}", black), &lift]));
+        }
+        Ok(())
+    }
+}
+
+/// Tolerance of verify_levels: half an 8-bit step
+const LEVEL_TOLERANCE: f32 = 0.5 / 255.0;
+
+/// Effect most recently applied through MagSetFullscreenColorEffect
+static ACTIVE_EFFECT: Mutex<Option<MagColorEffect>> = Mutex::new(None);
+
+/// Get the color effect currently applied to the screen (identity if none)
+pub fn active_effect() -> MagColorEffect {
+    ACTIVE_EFFECT.lock().unwrap().unwrap_or_default()
+}
+
+/// Color temperature layered on the last Magnification lift, if any
+static APPLIED_KELVIN: Mutex<Option<f32>> = Mutex::new(None);
+
+/// Get the color temperature applied with the current lift, if any
+pub fn applied_kelvin() -> Option<f32> {
+    *APPLIED_KELVIN.lock().unwrap()
+}
+
+/// Get the color effect Noctis has applied, if any
+pub fn recorded_effect() -> Option<MagColorEffect> {
+    *ACTIVE_EFFECT.lock().unwrap()
+}
+
+#[cfg(all(windows, feature = "magnification"))]
+mod windows_api {
+    use super::*;
+    use crate::normalize::Backend;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    
+    #[link(name = "magnification")]
+    extern "system" {
+        fn MagInitialize() -> i32;
+        fn MagUninitialize() -> i32;
+        fn MagSetFullscreenColorEffect(pEffect: *const MagColorEffect) -> i32;
+        fn MagGetFullscreenColorEffect(pEffect: *mut MagColorEffect) -> i32;
+        fn MagSetFullscreenTransform(magLevel: f32, xOffset: i32, yOffset: i32) -> i32;
+    }
+    
+    static mut INITIALIZED: bool = false;
+
+    /// Minimum time between MagSetFullscreenColorEffect calls
+    const MIN_WRITE_INTERVAL: Duration = Duration::from_millis(16);
+
+    /// Serializes all Magnification calls (the API is not thread-safe) and holds the last write time
+    static WRITE_LOCK: Mutex<Option<Instant>> = Mutex::new(None);
+
+    /// Bumped for every requested write so a writer superseded while waiting can skip its write
+    static WRITE_GENERATION: AtomicU64 = AtomicU64::new(0);
+    
+    /// Initialize the Magnification API
+    pub fn init() -> Result<(), String> {
+        unsafe {
+            if !INITIALIZED {
+                if MagInitialize() == 0 {
+                    return Err(crate::i18n::text("error.mag_initialize"));
+                }
+                // Set magnification to 1.0 (no zoom, just color effect passthrough)
+                if MagSetFullscreenTransform(1.0, 0, 0) == 0 {
+                    return Err(crate::i18n::text("error.mag_transform"));
+                }
+                INITIALIZED = true;
+            }
+            Ok(())
+        }
+    }
+    
+    /// What a write puts on screen
+    enum Write<'a> {
+        /// A new effect, recorded as the active one
+        Record(&'a MagColorEffect),
+        /// An effect shown without recording it, such as identity while bypassed
+        Show(&'a MagColorEffect),
+        /// The recorded effect (identity if none), read when the write's turn comes so a
+        /// rewrite never puts back an effect that was replaced while it waited
+        Recorded,
+    }
+
+    /// Write a color effect to the screen
+    /// Writes are serialized and rate limited; if a newer write is requested while this one
+    /// waits, this one is dropped (last write wins), though a new effect is still recorded
+    fn write_color_effect(write: Write) -> Result<(), String> {
+        let generation = WRITE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut last_write = WRITE_LOCK.lock().unwrap();
+        if let Some(wait) = last_write.and_then(|t| MIN_WRITE_INTERVAL.checked_sub(t.elapsed())) {
+            thread::sleep(wait);
+        }
+        if WRITE_GENERATION.load(Ordering::SeqCst) != generation {
+            if let Write::Record(effect) = write {
+                *ACTIVE_EFFECT.lock().unwrap() = Some(*effect);
+                crate::session::changed();
+            }
+            return Ok(());
+        }
+        let effect = &match write {
+            Write::Record(effect) | Write::Show(effect) => *effect,
+            Write::Recorded => ACTIVE_EFFECT.lock().unwrap().unwrap_or_else(MagColorEffect::identity),
+        };
+
+        init()?;
+        
+        // The wind-down warms and dims whatever is active; an inverting Windows color filter
+        // on top would turn the effect around
+        let shown = crate::color_filter::compensate(&crate::wind_down::compose(effect));
+        // With the magnifier host up the effect is drawn by its window and the fullscreen
+        // effect stays neutral, so the excluded window keeps its true colors
+        let fullscreen = if crate::magnifier_host::is_running() {
+            crate::magnifier_host::set_effect(&shown);
+            MagColorEffect::identity()
+        } else {
+            shown
+        };
+        // Another consumer's matrix stays underneath ours; while yielding to it, only record
+        let fullscreen = crate::mag_conflict::compose(&fullscreen);
+        unsafe {
+            if !crate::mag_conflict::is_yielded() && MagSetFullscreenColorEffect(&fullscreen) == 0 {
+                // Get Windows error code for debugging
+                #[link(name = "kernel32")]
+                extern "system" {
+                    fn GetLastError() -> u32;
+                }
+                let error = GetLastError();
+                crate::crash::log(format!("MagSetFullscreenColorEffect failed with error {}", error));
+                return Err(crate::i18n::format("error.mag_color_effect", &[&error]));
+            }
+        }
+        if !crate::mag_conflict::is_yielded() {
+            crate::mag_conflict::written(&fullscreen);
+        }
+        *last_write = Some(Instant::now());
+        if let Write::Record(_) = write {
+            *ACTIVE_EFFECT.lock().unwrap() = Some(*effect);
+            crate::session::changed();
+            crate::ramp_log::record_matrix(effect);
+        }
+        Ok(())
+    }
+    
+    /// Apply a color effect to the entire screen
+    /// Matrices whose output could leave 0.0 - 1.0 are rescaled to fit, and invalid ones rejected
+    /// While effects are suspended the effect is only recorded and applied on resume
+    pub fn set_color_effect(effect: &MagColorEffect) -> Result<(), String> {
+        if crate::shutdown::is_shutting_down() {
+            return Err(crate::i18n::text("error.shutting_down"));
+        }
+        let clamped = effect.clamped().ok_or_else(|| crate::i18n::text("error.effect_invalid"))?;
+        let effect = &clamped;
+        if crate::suspend::is_suspended() {
+            *ACTIVE_EFFECT.lock().unwrap() = Some(*effect);
+            crate::session::changed();
+            return Ok(());
+        }
+        crate::perf::time(crate::perf::Stage::Apply, || write_color_effect(Write::Record(effect)))
+    }
+    
+    /// Show the screen unadjusted, keeping the recorded effect for later
+    pub fn bypass() {
+        if ACTIVE_EFFECT.lock().unwrap().is_some() {
+            let _ = write_color_effect(Write::Show(&MagColorEffect::identity()));
+        }
+    }
+    
+    /// Re-apply the recorded effect after a bypass
+    pub fn reapply() {
+        if ACTIVE_EFFECT.lock().unwrap().is_some() {
+            let _ = write_color_effect(Write::Recorded);
+        }
+    }
+    
+    /// Rewrite the recorded effect (or none) so the wind-down layer follows its progress
+    pub fn refresh() {
+        if crate::suspend::is_suspended() {
+            return;
+        }
+        let _ = write_color_effect(Write::Recorded);
+    }
+    
+    /// Read the fullscreen color effect now on screen, whoever set it
+    /// None until Noctis has used the Magnification API
+    pub fn read_fullscreen_effect() -> Option<MagColorEffect> {
+        let _last_write = WRITE_LOCK.lock().unwrap();
+        unsafe {
+            if !INITIALIZED {
+                return None;
+            }
+            let mut effect = MagColorEffect::identity();
+            (MagGetFullscreenColorEffect(&mut effect) != 0).then_some(effect)
+        }
+    }
+    
+    /// Remove any effect and release the Magnification API, for shutdown
+    pub fn shutdown() {
+        crate::magnifier_host::stop();
+        let _last_write = WRITE_LOCK.lock().unwrap();
+        unsafe {
+            if INITIALIZED {
+                MagSetFullscreenColorEffect(&MagColorEffect::identity());
+                MagUninitialize();
+                INITIALIZED = false;
+            }
+        }
+        *ACTIVE_EFFECT.lock().unwrap() = None;
+    }
+    
+    /// Apply shadow lift effect (for dark scenes), held to the friendly preset under a contrast theme
+    pub fn apply_shadow_lift(intensity: f32) -> Result<(), String> {
+        let intensity = if crate::accessibility::friendly() {
+            intensity.min(crate::accessibility::FRIENDLY_MAX_INTENSITY)
+        } else {
+            intensity
+        };
+        let lift = crate::normalize::equivalent(Backend::Magnification, intensity);
+        let effect = MagColorEffect::shadow_lift(lift);
+        effect.verify_levels(lift * MAX_LIFT)?;
+        set_color_effect(&effect)?;
+        *APPLIED_KELVIN.lock().unwrap() = None;
+        crate::night_mode::record(crate::night_mode::Source::Magnification, intensity);
+        Ok(())
+    }
+    
+    /// Apply dim effect (for bright scenes), skipped under a contrast theme
+    pub fn apply_dim(intensity: f32) -> Result<(), String> {
+        if crate::accessibility::friendly() {
+            return remove_effects();
+        }
+        let effect = MagColorEffect::dim(intensity);
+        set_color_effect(&effect)?;
+        *APPLIED_KELVIN.lock().unwrap() = None;
+        crate::night_mode::record(crate::night_mode::Source::Magnification, 0.0);
+        Ok(())
+    }
+    
+    /// Apply shadow lift followed by a color temperature, or just the lift if `kelvin` is None
+    /// Contrast themes get the lift alone
+    pub fn apply_lift_and_temperature(intensity: f32, kelvin: Option<f32>) -> Result<(), String> {
+        let Some(kelvin) = kelvin.filter(|_| !crate::accessibility::friendly()) else {
+            return if intensity > 0.0 { apply_shadow_lift(intensity) } else { remove_effects() };
+        };
+        let lift = crate::normalize::equivalent(Backend::Magnification, intensity);
+        let effect = MagColorEffect::shadow_lift(lift).then(&MagColorEffect::warm(kelvin));
+        set_color_effect(&effect)?;
+        *APPLIED_KELVIN.lock().unwrap() = Some(kelvin);
+        crate::night_mode::record(crate::night_mode::Source::Magnification, intensity);
+        Ok(())
+    }
+    
+    /// Remove all color effects (restore normal)
+    pub fn remove_effects() -> Result<(), String> {
+        let effect = MagColorEffect::identity();
+        set_color_effect(&effect)?;
+        *APPLIED_KELVIN.lock().unwrap() = None;
+        crate::night_mode::record(crate::night_mode::Source::Magnification, 0.0);
+        Ok(())
+    }
+    
+    /// Previous auto-adjust decision, kept while readings sit in the dead zone
+    static LAST_DECISION: Mutex<Option<crate::adjustment::AdjustmentDecision>> = Mutex::new(None);
+
+    /// When auto-adjust first engaged this session, for the engage ramp
+    static ENGAGED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+    /// Start a new auto-adjust session: the next engagement fades in again
+    pub fn new_adjustment_session() {
+        *LAST_DECISION.lock().unwrap() = None;
+        *ENGAGED_AT.lock().unwrap() = None;
+        crate::controller::clear_override();
+    }
+
+    /// Smart auto-adjustment based on screen brightness, see adjustment::decide
+    /// brightness: 0.0 (completely dark) to 1.0 (completely bright)
+    /// The first engagement of a session ramps in over the active profile's engage ramp
+    /// (or the global one) so the lift doesn't appear all at once, the controller mode
+    /// composes any manual offset, and the result is held under the time-of-day cap and
+    /// warmed if color temperature coupling is on
+    pub fn apply_smart_adjustment(brightness: f32) -> Result<(), String> {
+        // Manual mode, or a recent manual change in auto mode
+        if !crate::controller::allows_auto(brightness) {
+            return Ok(());
+        }
+        let config = crate::profiles::sensing().adjustment;
+        let mut last = LAST_DECISION.lock().unwrap();
+        let decision = crate::adjustment::decide(brightness, &config, *last);
+        *last = Some(decision);
+        drop(last);
+        let baseline = if decision.engaged {
+            let ramp_ms = crate::profiles::active().and_then(|p| p.engage_ramp_ms).unwrap_or(config.engage_ramp_ms);
+            let engaged_at = *ENGAGED_AT.lock().unwrap().get_or_insert_with(Instant::now);
+            decision.intensity * crate::adjustment::engage_fraction(engaged_at.elapsed(), Duration::from_millis(ramp_ms))
+        } else {
+            0.0
+        };
+        let intensity = crate::controller::compose(baseline).min(crate::schedule::current_cap());
+        apply_lift_and_temperature(intensity, crate::temperature::current_kelvin(brightness))
+    }
+}
+
+#[cfg(all(windows, feature = "magnification"))]
+pub use windows_api::*;
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn init() -> Result<(), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn new_adjustment_session() {}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn bypass() {}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn reapply() {}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn refresh() {}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn shutdown() {}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn read_fullscreen_effect() -> Option<MagColorEffect> {
+    None
+}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn set_color_effect(_effect: &MagColorEffect) -> Result<(), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn apply_shadow_lift(_intensity: f32) -> Result<(), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn apply_lift_and_temperature(_intensity: f32, _kelvin: Option<f32>) -> Result<(), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn apply_dim(_intensity: f32) -> Result<(), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn remove_effects() -> Result<(), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn apply_smart_adjustment(_brightness: f32) -> Result<(), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map_clamped(effect: &MagColorEffect, rgb: [f32; 3]) -> [f32; 3] {
+        effect.clamped().unwrap().map(rgb)
+    }
+
+    #[test]
+    fn shadow_lift_keeps_white_and_lifts_black() {
+        for intensity in [0.0, 0.3, 1.0] {
+            let effect = MagColorEffect::shadow_lift(intensity);
+            assert!(effect.verify_levels(intensity * MAX_LIFT).is_ok());
+        }
+    }
+
+    #[test]
+    fn lift_composed_with_warmth_moves_white() {
+        let effect = MagColorEffect::shadow_lift(0.5).then(&MagColorEffect::warm(3000.0));
+        assert!(effect.verify_levels(0.0).is_err());
+    }
+
+    #[test]
+    fn black_below_lift_is_rejected() {
+        let effect = MagColorEffect::shadow_lift(0.2);
+        assert!(effect.verify_levels(0.2 * MAX_LIFT).is_ok());
+        assert!(effect.verify_levels(0.5).is_err());
+    }
+
+    #[test]
+    fn in_range_matrix_is_unchanged_by_clamping() {
+        let effect = MagColorEffect::shadow_lift(0.6);
+        assert_eq!(effect.clamped().unwrap().transform, effect.transform);
+    }
+
+    #[test]
+    fn out_of_range_matrix_is_rescaled_into_range() {
+        let mut effect = MagColorEffect::shadow_lift(1.0);
+        effect.transform[0][0] = 1.5;
+        effect.transform[4][2] = -0.4;
+        for (low, high) in effect.clamped().unwrap().output_range() {
+            assert!(low >= -1e-6 && high <= 1.0 + 1e-6, "range {}..{}", low, high);
+        }
+        let black = map_clamped(&effect, [0.0; 3]);
+        let white = map_clamped(&effect, [1.0; 3]);
+        // Red overshot white, so its white now sits exactly at full scale
+        assert!((white[0] - 1.0).abs() < 1e-6);
+        assert!(black.iter().all(|v| *v >= -1e-6));
+    }
+
+    #[test]
+    fn non_finite_matrix_is_refused() {
+        let mut effect = MagColorEffect::identity();
+        effect.transform[1][1] = f32::NAN;
+        assert!(effect.clamped().is_none());
+        effect.transform[1][1] = f32::INFINITY;
+        assert!(effect.clamped().is_none());
+    }
+}
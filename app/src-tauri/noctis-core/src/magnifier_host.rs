@@ -0,0 +1,430 @@
+//! Magnifier host - shows the Magnification color effect through a screen-sized magnifier
+//! window instead of the fullscreen effect, so Noctis's own window can be left out of it
+//! MagSetWindowFilterList keeps the window out of the magnified image and a hole in the host's
+//! region lets the real window show through with true colors, for an accurate preview
+
+use crate::magnification::MagColorEffect;
+use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+use std::sync::Mutex;
+
+/// Host window while it runs, 0 otherwise
+static HOST: AtomicIsize = AtomicIsize::new(0);
+
+/// Window kept out of the effect
+static EXCLUDED: AtomicIsize = AtomicIsize::new(0);
+
+/// Set when the excluded window changes so the host thread updates the filter list
+static EXCLUSION_CHANGED: AtomicBool = AtomicBool::new(false);
+
+/// Effect waiting to be applied by the host thread
+static PENDING: Mutex<Option<MagColorEffect>> = Mutex::new(None);
+
+/// Whether effects go through the host rather than the fullscreen effect
+pub fn is_running() -> bool {
+    HOST.load(Ordering::SeqCst) != 0
+}
+
+/// Show `effect` through the host from its next refresh
+pub fn set_effect(effect: &MagColorEffect) {
+    *PENDING.lock().unwrap() = Some(*effect);
+}
+
+#[cfg(all(windows, feature = "magnification"))]
+mod windows_api {
+    use super::*;
+    use std::cell::Cell;
+    use std::ffi::c_void;
+    use std::ptr;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// How often the magnified image is refreshed, about once per 60Hz frame
+    const REFRESH_MS: u32 = 16;
+
+    const WS_POPUP: u32 = 0x8000_0000;
+    const WS_CHILD: u32 = 0x4000_0000;
+    const WS_VISIBLE: u32 = 0x1000_0000;
+    const WS_EX_TOPMOST: u32 = 0x0000_0008;
+    const WS_EX_TRANSPARENT: u32 = 0x0000_0020;
+    const WS_EX_TOOLWINDOW: u32 = 0x0000_0080;
+    const WS_EX_LAYERED: u32 = 0x0008_0000;
+    const WS_EX_NOACTIVATE: u32 = 0x0800_0000;
+    const LWA_ALPHA: u32 = 0x2;
+    const WM_DESTROY: u32 = 0x0002;
+    const WM_CLOSE: u32 = 0x0010;
+    const WM_TIMER: u32 = 0x0113;
+    const SM_XVIRTUALSCREEN: i32 = 76;
+    const SM_YVIRTUALSCREEN: i32 = 77;
+    const SM_CXVIRTUALSCREEN: i32 = 78;
+    const SM_CYVIRTUALSCREEN: i32 = 79;
+    const SWP_NOACTIVATE: u32 = 0x0010;
+    const HWND_TOPMOST: isize = -1;
+    const RGN_DIFF: i32 = 4;
+    const MW_FILTERMODE_EXCLUDE: u32 = 0;
+    const DWMWA_EXTENDED_FRAME_BOUNDS: u32 = 9;
+
+    /// RECT structure
+    #[repr(C)]
+    #[derive(Clone, Copy, PartialEq, Default)]
+    struct Rect {
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+    }
+
+    /// POINT structure
+    #[repr(C)]
+    #[derive(Default)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    /// MSG structure for the host thread's message loop
+    #[repr(C)]
+    struct Msg {
+        hwnd: *mut c_void,
+        message: u32,
+        w_param: usize,
+        l_param: isize,
+        time: u32,
+        pt: Point,
+    }
+
+    type WndProc = unsafe extern "system" fn(*mut c_void, u32, usize, isize) -> isize;
+
+    /// WNDCLASSW structure
+    #[repr(C)]
+    struct WndClass {
+        style: u32,
+        wnd_proc: Option<WndProc>,
+        cls_extra: i32,
+        wnd_extra: i32,
+        instance: *mut c_void,
+        icon: *mut c_void,
+        cursor: *mut c_void,
+        background: *mut c_void,
+        menu_name: *const u16,
+        class_name: *const u16,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn RegisterClassW(class: *const WndClass) -> u16;
+        fn CreateWindowExW(
+            ex_style: u32,
+            class_name: *const u16,
+            window_name: *const u16,
+            style: u32,
+            x: i32,
+            y: i32,
+            width: i32,
+            height: i32,
+            parent: *mut c_void,
+            menu: *mut c_void,
+            instance: *mut c_void,
+            param: *mut c_void,
+        ) -> *mut c_void;
+        fn DestroyWindow(hwnd: *mut c_void) -> i32;
+        fn DefWindowProcW(hwnd: *mut c_void, msg: u32, w_param: usize, l_param: isize) -> isize;
+        fn GetMessageW(msg: *mut Msg, hwnd: *mut c_void, filter_min: u32, filter_max: u32) -> i32;
+        fn TranslateMessage(msg: *const Msg) -> i32;
+        fn DispatchMessageW(msg: *const Msg) -> isize;
+        fn PostMessageW(hwnd: *mut c_void, msg: u32, w_param: usize, l_param: isize) -> i32;
+        fn PostQuitMessage(exit_code: i32);
+        fn SetLayeredWindowAttributes(hwnd: *mut c_void, key: u32, alpha: u8, flags: u32) -> i32;
+        fn SetTimer(hwnd: *mut c_void, id: usize, elapse: u32, timer_proc: *const c_void) -> usize;
+        fn SetWindowPos(hwnd: *mut c_void, after: isize, x: i32, y: i32, cx: i32, cy: i32, flags: u32) -> i32;
+        fn MoveWindow(hwnd: *mut c_void, x: i32, y: i32, width: i32, height: i32, repaint: i32) -> i32;
+        fn InvalidateRect(hwnd: *mut c_void, rect: *const Rect, erase: i32) -> i32;
+        fn GetSystemMetrics(index: i32) -> i32;
+        fn GetWindowRect(hwnd: *mut c_void, rect: *mut Rect) -> i32;
+        fn IsWindowVisible(hwnd: *mut c_void) -> i32;
+        fn IsIconic(hwnd: *mut c_void) -> i32;
+        fn SetWindowRgn(hwnd: *mut c_void, region: *mut c_void, redraw: i32) -> i32;
+    }
+
+    #[link(name = "gdi32")]
+    extern "system" {
+        fn CreateRectRgn(left: i32, top: i32, right: i32, bottom: i32) -> *mut c_void;
+        fn CombineRgn(dest: *mut c_void, src1: *mut c_void, src2: *mut c_void, mode: i32) -> i32;
+        fn DeleteObject(object: *mut c_void) -> i32;
+    }
+
+    #[link(name = "dwmapi")]
+    extern "system" {
+        fn DwmGetWindowAttribute(hwnd: *mut c_void, attribute: u32, value: *mut c_void, size: u32) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetModuleHandleW(module_name: *const u16) -> *mut c_void;
+    }
+
+    #[link(name = "magnification")]
+    extern "system" {
+        fn MagInitialize() -> i32;
+        fn MagUninitialize() -> i32;
+        fn MagSetWindowSource(hwnd: *mut c_void, source: Rect) -> i32;
+        fn MagSetColorEffect(hwnd: *mut c_void, effect: *const MagColorEffect) -> i32;
+        fn MagSetWindowFilterList(hwnd: *mut c_void, mode: u32, count: i32, list: *mut *mut c_void) -> i32;
+    }
+
+    thread_local! {
+        /// Magnifier control inside the host window
+        static MAGNIFIER: Cell<isize> = const { Cell::new(0) };
+        /// Screen area last covered by the host
+        static SCREEN: Cell<Rect> = const { Cell::new(Rect { left: 0, top: 0, right: 0, bottom: 0 }) };
+        /// Hole last cut for the excluded window, in screen coordinates
+        static HOLE: Cell<Option<Rect>> = const { Cell::new(None) };
+    }
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    /// Bounds of the whole virtual desktop
+    fn virtual_screen() -> Rect {
+        unsafe {
+            let left = GetSystemMetrics(SM_XVIRTUALSCREEN);
+            let top = GetSystemMetrics(SM_YVIRTUALSCREEN);
+            Rect {
+                left,
+                top,
+                right: left + GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                bottom: top + GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            }
+        }
+    }
+
+    /// Visible bounds of the excluded window, None while it is hidden or minimized
+    /// The DWM frame bounds leave out the invisible resize borders GetWindowRect includes
+    fn excluded_bounds() -> Option<Rect> {
+        let hwnd = EXCLUDED.load(Ordering::SeqCst) as *mut c_void;
+        if hwnd.is_null() {
+            return None;
+        }
+        unsafe {
+            if IsWindowVisible(hwnd) == 0 || IsIconic(hwnd) != 0 {
+                return None;
+            }
+            let mut rect = Rect::default();
+            let size = std::mem::size_of::<Rect>() as u32;
+            if DwmGetWindowAttribute(hwnd, DWMWA_EXTENDED_FRAME_BOUNDS, &mut rect as *mut _ as *mut c_void, size) != 0
+                && GetWindowRect(hwnd, &mut rect) == 0
+            {
+                return None;
+            }
+            Some(rect)
+        }
+    }
+
+    /// Cut the excluded window out of the host so the real window shows through
+    fn update_hole(host: *mut c_void, screen: Rect) {
+        let hole = excluded_bounds();
+        if HOLE.get() == hole {
+            return;
+        }
+        HOLE.set(hole);
+        unsafe {
+            let Some(hole) = hole else {
+                SetWindowRgn(host, ptr::null_mut(), 1);
+                return;
+            };
+            let region = CreateRectRgn(0, 0, screen.right - screen.left, screen.bottom - screen.top);
+            let cut = CreateRectRgn(hole.left - screen.left, hole.top - screen.top, hole.right - screen.left, hole.bottom - screen.top);
+            CombineRgn(region, region, cut, RGN_DIFF);
+            DeleteObject(cut);
+            // The window owns the region from here on
+            SetWindowRgn(host, region, 1);
+        }
+    }
+
+    /// Follow the desktop layout, apply any new effect or exclusion and redraw the magnified image
+    fn refresh(host: *mut c_void) {
+        let magnifier = MAGNIFIER.get() as *mut c_void;
+        let screen = virtual_screen();
+        unsafe {
+            if SCREEN.get() != screen {
+                SCREEN.set(screen);
+                HOLE.set(None);
+                let (width, height) = (screen.right - screen.left, screen.bottom - screen.top);
+                SetWindowPos(host, HWND_TOPMOST, screen.left, screen.top, width, height, SWP_NOACTIVATE);
+                MoveWindow(magnifier, 0, 0, width, height, 0);
+            }
+            if EXCLUSION_CHANGED.swap(false, Ordering::SeqCst) {
+                let mut excluded = EXCLUDED.load(Ordering::SeqCst) as *mut c_void;
+                let count = i32::from(!excluded.is_null());
+                MagSetWindowFilterList(magnifier, MW_FILTERMODE_EXCLUDE, count, &mut excluded);
+                HOLE.set(None);
+                SetWindowRgn(host, ptr::null_mut(), 1);
+            }
+            if let Some(effect) = PENDING.lock().unwrap().take() {
+                MagSetColorEffect(magnifier, &effect);
+            }
+            update_hole(host, screen);
+            MagSetWindowSource(magnifier, screen);
+            InvalidateRect(magnifier, ptr::null(), 1);
+        }
+    }
+
+    unsafe extern "system" fn host_window_proc(hwnd: *mut c_void, msg: u32, w_param: usize, l_param: isize) -> isize {
+        match msg {
+            WM_TIMER => {
+                refresh(hwnd);
+                0
+            }
+            WM_CLOSE => {
+                DestroyWindow(hwnd);
+                0
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                0
+            }
+            _ => DefWindowProcW(hwnd, msg, w_param, l_param),
+        }
+    }
+
+    /// Create the host and its magnifier control on the calling thread
+    unsafe fn create_host() -> Result<*mut c_void, String> {
+        if MagInitialize() == 0 {
+            return Err(crate::i18n::text("error.mag_initialize"));
+        }
+        let class_name = wide("NoctisMagnifierHost");
+        let instance = GetModuleHandleW(ptr::null());
+        let class = WndClass {
+            style: 0,
+            wnd_proc: Some(host_window_proc),
+            cls_extra: 0,
+            wnd_extra: 0,
+            instance,
+            icon: ptr::null_mut(),
+            cursor: ptr::null_mut(),
+            background: ptr::null_mut(),
+            menu_name: ptr::null(),
+            class_name: class_name.as_ptr(),
+        };
+        RegisterClassW(&class);
+
+        // Layered and transparent: drawn over everything but never hit-tested or activated
+        let screen = virtual_screen();
+        let host = CreateWindowExW(
+            WS_EX_TOPMOST | WS_EX_LAYERED | WS_EX_TRANSPARENT | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE,
+            class_name.as_ptr(),
+            class_name.as_ptr(),
+            WS_POPUP | WS_VISIBLE,
+            screen.left,
+            screen.top,
+            screen.right - screen.left,
+            screen.bottom - screen.top,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            instance,
+            ptr::null_mut(),
+        );
+        if host.is_null() {
+            MagUninitialize();
+            return Err(crate::i18n::text("error.magnifier_host_window"));
+        }
+        SetLayeredWindowAttributes(host, 0, 255, LWA_ALPHA);
+
+        let magnifier_class = wide("Magnifier");
+        let magnifier = CreateWindowExW(
+            0,
+            magnifier_class.as_ptr(),
+            ptr::null(),
+            WS_CHILD | WS_VISIBLE,
+            0,
+            0,
+            screen.right - screen.left,
+            screen.bottom - screen.top,
+            host,
+            ptr::null_mut(),
+            instance,
+            ptr::null_mut(),
+        );
+        if magnifier.is_null() {
+            DestroyWindow(host);
+            MagUninitialize();
+            return Err(crate::i18n::text("error.magnifier_window"));
+        }
+        MAGNIFIER.set(magnifier as isize);
+        SCREEN.set(screen);
+        // The host would otherwise land in brightness captures showing the lifted image
+        let _ = crate::sensor::exclude_from_capture(host as isize);
+        SetTimer(host, 1, REFRESH_MS, ptr::null());
+        Ok(host)
+    }
+
+    /// Start showing effects through the host, leaving `excluded` (a window handle) untouched
+    /// Already running hosts just switch the excluded window
+    pub fn start(excluded: isize) -> Result<(), String> {
+        EXCLUDED.store(excluded, Ordering::SeqCst);
+        EXCLUSION_CHANGED.store(true, Ordering::SeqCst);
+        if is_running() {
+            return Ok(());
+        }
+
+        let (ready_tx, ready_rx) = mpsc::channel();
+        thread::spawn(move || unsafe {
+            let host = match create_host() {
+                Ok(host) => host,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            HOST.store(host as isize, Ordering::SeqCst);
+            let _ = ready_tx.send(Ok(()));
+
+            let mut msg = Msg {
+                hwnd: ptr::null_mut(),
+                message: 0,
+                w_param: 0,
+                l_param: 0,
+                time: 0,
+                pt: Point::default(),
+            };
+            while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) > 0 {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+            HOST.store(0, Ordering::SeqCst);
+            MagUninitialize();
+            // Back to the fullscreen effect
+            if !crate::suspend::is_suspended() {
+                crate::magnification::reapply();
+            }
+        });
+        ready_rx.recv().map_err(|_| "Magnifier host thread exited".to_string())??;
+
+        // Move the current effect from the fullscreen effect to the host
+        if !crate::suspend::is_suspended() {
+            crate::magnification::reapply();
+        }
+        Ok(())
+    }
+
+    /// Close the host; the effect returns to the fullscreen effect once it is gone
+    pub fn stop() {
+        let host = HOST.load(Ordering::SeqCst);
+        if host != 0 {
+            unsafe {
+                PostMessageW(host as *mut c_void, WM_CLOSE, 0, 0);
+            }
+        }
+    }
+}
+
+#[cfg(all(windows, feature = "magnification"))]
+pub use windows_api::*;
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn start(_excluded: isize) -> Result<(), String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+#[cfg(not(all(windows, feature = "magnification")))]
+pub fn stop() {}
@@ -0,0 +1,129 @@
+//! Recording markers - timestamps every moment night vision becomes visible or goes away,
+//! so streamers can line recordings up with it when editing highlights. Markers go to the UI
+//! as events and, if enabled, to a CSV sidecar per Noctis run
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Recording marker settings
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct MarkerSettings {
+    /// Also append markers to a CSV file
+    pub sidecar: bool,
+    /// Folder the CSV files go to (empty = markers in the config folder)
+    pub folder: String,
+}
+
+/// Night vision became visible or went away
+#[derive(Serialize, Clone, Debug)]
+pub struct Marker {
+    /// Unix time in milliseconds
+    pub at_ms: u64,
+    pub engaged: bool,
+    /// Strongest intensity applied, 0.0 when disengaged
+    pub intensity: f32,
+    /// Game profile active at the time
+    pub profile: Option<String>,
+}
+
+/// Wakes the marker thread to compare the visible state with the last marker
+static NUDGE: OnceLock<Mutex<mpsc::Sender<()>>> = OnceLock::new();
+
+/// Sidecar for this run, named when the first marker is written
+static SIDECAR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// Whether night vision is on screen: applied and not suspended
+/// The blink of a clean screenshot doesn't count
+fn visible() -> (bool, f32) {
+    let mode = crate::night_mode::current();
+    let suspended = crate::suspend::reasons().iter().any(|r| r != crate::screenshot::REASON);
+    let engaged = mode.engaged && !suspended;
+    (engaged, if engaged { mode.intensity } else { 0.0 })
+}
+
+/// Check for a change in what's on screen, e.g. after a suspension starts or ends
+pub fn check() {
+    if let Some(nudge) = NUDGE.get() {
+        let _ = nudge.lock().unwrap().send(());
+    }
+}
+
+fn sidecar_folder() -> PathBuf {
+    let config = crate::settings::get().markers;
+    if config.folder.is_empty() {
+        crate::settings::config_dir().join("markers")
+    } else {
+        PathBuf::from(config.folder)
+    }
+}
+
+/// Append a marker to this run's sidecar, starting the file with a header
+fn write_sidecar(marker: &Marker) -> Result<(), String> {
+    let mut sidecar = SIDECAR.lock().unwrap();
+    let path = match sidecar.as_ref() {
+        Some(path) => path.clone(),
+        None => {
+            let folder = sidecar_folder();
+            std::fs::create_dir_all(&folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+            let path = folder.join(format!("noctis-markers-{}.csv", marker.at_ms / 1000));
+            std::fs::write(&path, "unix_ms,event,intensity,profile\n")
+                .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+            sidecar.insert(path).clone()
+        }
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let event = if marker.engaged { "engaged" } else { "disengaged" };
+    // Profile names may hold commas or quotes
+    let profile = marker.profile.as_deref().map(|p| format!("\"{}\"", p.replace('"', "\"\""))).unwrap_or_default();
+    writeln!(file, "{},{},{:.2},{}", marker.at_ms, event, marker.intensity, profile)
+        .map_err(|e| format!("Failed to write marker: {}", e))
+}
+
+/// Start recording markers, passing each to `on_marker` (which runs on the marker thread)
+pub fn start<F>(on_marker: F)
+where
+    F: Fn(&Marker) + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    if NUDGE.set(Mutex::new(tx)).is_err() {
+        return;
+    }
+    crate::night_mode::subscribe(|_| check());
+
+    thread::spawn(move || {
+        let mut shown = false;
+        while rx.recv().is_ok() {
+            // Several changes may have queued up; only where they left the screen matters
+            while rx.try_recv().is_ok() {}
+            let (engaged, intensity) = visible();
+            if engaged == shown {
+                continue;
+            }
+            shown = engaged;
+            let marker = Marker {
+                at_ms: now_ms(),
+                engaged,
+                intensity,
+                profile: crate::profiles::active().map(|p| p.name),
+            };
+            if crate::settings::get().markers.sidecar {
+                if let Err(e) = write_sidecar(&marker) {
+                    crate::crash::log(e);
+                }
+            }
+            on_marker(&marker);
+        }
+    });
+}
@@ -0,0 +1,144 @@
+//! Hidden message window - one window and thread for the system broadcasts several
+//! modules follow: display power (WM_POWERBROADCAST, WM_DISPLAYCHANGE), fast user
+//! switching (WM_WTSSESSION_CHANGE) and logoff/shutdown (WM_QUERYENDSESSION, WM_ENDSESSION)
+//! Each module registers for its notifications on the window and handles its own messages
+
+use std::ffi::c_void;
+use std::ptr;
+use std::thread;
+
+/// POINT structure
+#[repr(C)]
+#[derive(Default)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+/// MSG structure for the window thread's message loop
+#[repr(C)]
+struct Msg {
+    hwnd: *mut c_void,
+    message: u32,
+    w_param: usize,
+    l_param: isize,
+    time: u32,
+    pt: Point,
+}
+
+type WndProc = unsafe extern "system" fn(*mut c_void, u32, usize, isize) -> isize;
+
+/// WNDCLASSW structure
+#[repr(C)]
+struct WndClass {
+    style: u32,
+    wnd_proc: Option<WndProc>,
+    cls_extra: i32,
+    wnd_extra: i32,
+    instance: *mut c_void,
+    icon: *mut c_void,
+    cursor: *mut c_void,
+    background: *mut c_void,
+    menu_name: *const u16,
+    class_name: *const u16,
+}
+
+#[cfg(windows)]
+#[link(name = "user32")]
+extern "system" {
+    fn RegisterClassW(class: *const WndClass) -> u16;
+    fn CreateWindowExW(
+        ex_style: u32,
+        class_name: *const u16,
+        window_name: *const u16,
+        style: u32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        parent: *mut c_void,
+        menu: *mut c_void,
+        instance: *mut c_void,
+        param: *mut c_void,
+    ) -> *mut c_void;
+    fn DefWindowProcW(hwnd: *mut c_void, msg: u32, w_param: usize, l_param: isize) -> isize;
+    fn GetMessageW(msg: *mut Msg, hwnd: *mut c_void, filter_min: u32, filter_max: u32) -> i32;
+    fn TranslateMessage(msg: *const Msg) -> i32;
+    fn DispatchMessageW(msg: *const Msg) -> isize;
+}
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetModuleHandleW(module_name: *const u16) -> *mut c_void;
+}
+
+/// Window procedure: offer the message to each module, falling back to the default
+#[cfg(windows)]
+unsafe extern "system" fn window_proc(hwnd: *mut c_void, msg: u32, w_param: usize, l_param: isize) -> isize {
+    crate::display_power::window_message(msg, w_param, l_param)
+        .or_else(|| crate::user_switch::window_message(msg, w_param))
+        .or_else(|| crate::shutdown::window_message(msg, w_param))
+        .unwrap_or_else(|| DefWindowProcW(hwnd, msg, w_param, l_param))
+}
+
+/// Create the window on a background thread and pump its messages
+/// Call once, after the modules' callbacks are set
+#[cfg(windows)]
+pub fn start() {
+    thread::spawn(|| unsafe {
+        let class_name: Vec<u16> = "NoctisMessages".encode_utf16().chain(std::iter::once(0)).collect();
+        let instance = GetModuleHandleW(ptr::null());
+        let class = WndClass {
+            style: 0,
+            wnd_proc: Some(window_proc),
+            cls_extra: 0,
+            wnd_extra: 0,
+            instance,
+            icon: ptr::null_mut(),
+            cursor: ptr::null_mut(),
+            background: ptr::null_mut(),
+            menu_name: ptr::null(),
+            class_name: class_name.as_ptr(),
+        };
+        RegisterClassW(&class);
+
+        // Top-level but never shown (no WS_VISIBLE): message-only windows don't receive
+        // WM_DISPLAYCHANGE or WM_ENDSESSION
+        let hwnd = CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            class_name.as_ptr(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            instance,
+            ptr::null_mut(),
+        );
+        if hwnd.is_null() {
+            return;
+        }
+        crate::user_switch::register(hwnd);
+        crate::display_power::register(hwnd);
+
+        let mut msg = Msg {
+            hwnd: ptr::null_mut(),
+            message: 0,
+            w_param: 0,
+            l_param: 0,
+            time: 0,
+            pt: Point::default(),
+        };
+        while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn start() {}
@@ -0,0 +1,144 @@
+//! Config schema versioning - upgrades settings/profile files written by older versions
+//! The original file is backed up before a migrated copy replaces it, and a file that can't
+//! be used is set aside before the defaults that replace it are saved
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// A single schema upgrade from version N to N + 1
+pub type Migration = fn(Value) -> Value;
+
+/// Schema version stored in a config file (files from before versioning are version 0)
+pub fn version_of(value: &Value) -> u32 {
+    value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+/// Path of the pre-migration backup, e.g. "settings.v0.bak.json"
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("config");
+    path.with_file_name(format!("{}.v{}.bak.json", stem, version))
+}
+
+/// Path a config file that couldn't be used is copied to, e.g. "settings.unreadable.bak.json"
+fn unreadable_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("config");
+    path.with_file_name(format!("{}.unreadable.bak.json", stem))
+}
+
+/// Bring a parsed config file up to the latest version (`migrations.len()`)
+/// On upgrade the original is copied to a backup and the migrated file is written in its place
+/// Files from a newer version are refused, since saving them would drop what this one doesn't know
+pub fn migrate(path: &Path, value: Value, migrations: &[Migration]) -> Result<Value, String> {
+    let from = version_of(&value);
+    let latest = migrations.len() as u32;
+    if from > latest {
+        return Err(format!("{} is from a newer version of Noctis (schema {}, this one reads up to {})", path.display(), from, latest));
+    }
+    if from == latest {
+        return Ok(value);
+    }
+
+    std::fs::copy(path, backup_path(path, from))
+        .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+
+    let mut value = value;
+    for migration in &migrations[from as usize..] {
+        value = migration(value);
+    }
+    if let Value::Object(map) = &mut value {
+        map.insert("version".to_string(), Value::from(latest));
+    }
+
+    let json = serde_json::to_string_pretty(&value)
+        .map_err(|e| format!("Failed to serialize migrated config: {}", e))?;
+    crate::config_lock::write_file(path, &json)?;
+    Ok(value)
+}
+
+/// Read a config file and bring it up to the latest version; None if it doesn't exist
+pub fn read(path: &Path, migrations: &[Migration]) -> Result<Option<Value>, String> {
+    let json = match std::fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let value = serde_json::from_str(&json).map_err(|e| format!("Invalid JSON in {}: {}", path.display(), e))?;
+    migrate(path, value, migrations).map(Some)
+}
+
+/// Keep a copy of a config file that couldn't be used, before defaults are saved over it
+pub fn set_aside(path: &Path, error: &str) {
+    let backup = unreadable_path(path);
+    match std::fs::copy(path, &backup) {
+        Ok(_) => crate::crash::log(format!("{}; kept a copy at {}", error, backup.display())),
+        Err(e) => crate::crash::log(format!("{}; failed to keep a copy: {}", error, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MIGRATIONS: &[Migration] = &[
+        |value| value,
+        |mut value| {
+            value["renamed"] = value["old"].take();
+            value
+        },
+    ];
+
+    fn temp_config(name: &str, value: &Value) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("noctis-migration-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        std::fs::write(&path, value.to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn missing_version_is_zero() {
+        assert_eq!(version_of(&json!({})), 0);
+        assert_eq!(version_of(&json!({ "version": 3 })), 3);
+    }
+
+    #[test]
+    fn old_file_is_upgraded_and_backed_up() {
+        let original = json!({ "old": 5 });
+        let path = temp_config("upgrade", &original);
+        let migrated = migrate(&path, original.clone(), MIGRATIONS).unwrap();
+        assert_eq!(migrated["renamed"], 5);
+        assert_eq!(version_of(&migrated), 2);
+
+        let backup: Value = serde_json::from_str(&std::fs::read_to_string(backup_path(&path, 0)).unwrap()).unwrap();
+        assert_eq!(backup, original);
+        let written: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, migrated);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn current_file_is_unchanged() {
+        let value = json!({ "version": 2, "old": 1 });
+        assert_eq!(migrate(Path::new("unused.json"), value.clone(), MIGRATIONS).unwrap(), value);
+    }
+
+    #[test]
+    fn newer_file_is_refused() {
+        assert!(migrate(Path::new("unused.json"), json!({ "version": 7 }), MIGRATIONS).is_err());
+    }
+
+    #[test]
+    fn missing_file_reads_as_none_and_invalid_as_error() {
+        let path = temp_config("read", &json!({}));
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(read(&path, MIGRATIONS).is_err());
+
+        set_aside(&path, "invalid");
+        assert_eq!(std::fs::read_to_string(unreadable_path(&path)).unwrap(), "{ not json");
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(read(&path, MIGRATIONS).unwrap().is_none());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,79 @@
+//! Monitor groups - named sets of monitors (e.g. a "gaming wall" of three displays) that
+//! commands, profiles and rules can adjust as one. Members are stored by device id rather
+//! than index, so a group keeps its monitors when hotplugging reorders them
+
+use crate::gamma;
+use serde::{Deserialize, Serialize};
+
+/// A named set of monitors
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MonitorGroup {
+    /// Unique display name
+    pub name: String,
+    /// Device ids of the member monitors, see `gamma::MonitorInfo::device_id`
+    pub monitors: Vec<String>,
+}
+
+/// What a command adjusts: a monitor by index, or every connected member of a group by name
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum Target {
+    Monitor(u32),
+    Group(String),
+}
+
+impl Target {
+    /// Indices of the connected monitors this target adjusts
+    pub fn resolve(&self) -> Result<Vec<u32>, String> {
+        match self {
+            Target::Monitor(index) => {
+                if gamma::get_monitors().iter().any(|m| m.index == *index) {
+                    Ok(vec![*index])
+                } else {
+                    Err(crate::i18n::format("error.monitor_not_found", &[index]))
+                }
+            }
+            Target::Group(name) => members(name),
+        }
+    }
+}
+
+/// Get all monitor groups
+pub fn list() -> Vec<MonitorGroup> {
+    crate::settings::get().monitor_groups
+}
+
+/// Replace the monitor groups
+pub fn set(groups: Vec<MonitorGroup>) -> Result<(), String> {
+    for (i, group) in groups.iter().enumerate() {
+        if group.name.trim().is_empty() {
+            return Err(crate::i18n::text("error.group_name_empty"));
+        }
+        if groups[..i].iter().any(|g| g.name == group.name) {
+            return Err(crate::i18n::format("error.group_name_repeated", &[&group.name]));
+        }
+        if group.monitors.is_empty() {
+            return Err(crate::i18n::format("error.group_empty", &[&group.name]));
+        }
+    }
+    crate::settings::update(|s| s.monitor_groups = groups)?;
+    Ok(())
+}
+
+/// Indices of a group's connected members, in the current enumeration order
+/// Disconnected members are skipped; it's an error only if none are connected
+pub fn members(name: &str) -> Result<Vec<u32>, String> {
+    let group = list()
+        .into_iter()
+        .find(|g| g.name == name)
+        .ok_or_else(|| crate::i18n::format("error.group_not_found", &[&name]))?;
+    let indices: Vec<u32> = gamma::get_monitors()
+        .into_iter()
+        .filter(|m| group.monitors.contains(&m.device_id))
+        .map(|m| m.index)
+        .collect();
+    if indices.is_empty() {
+        return Err(crate::i18n::format("error.group_disconnected", &[&name]));
+    }
+    Ok(indices)
+}
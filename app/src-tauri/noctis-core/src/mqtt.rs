@@ -0,0 +1,175 @@
+//! MQTT bridge for home automation
+//! Publishes night mode state to `<prefix>/state` (retained JSON) and accepts commands on
+//! `<prefix>/set` in the local control protocol (see control.rs)
+
+use serde::{Deserialize, Serialize};
+
+/// MQTT integration settings
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub client_id: String,
+    /// Topics are published under this prefix, e.g. "noctis/state"
+    pub topic_prefix: String,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            username: String::new(),
+            password: String::new(),
+            client_id: "noctis".to_string(),
+            topic_prefix: "noctis".to_string(),
+        }
+    }
+}
+
+/// Broker connection, left out of builds without the network feature
+#[cfg(feature = "network")]
+mod bridge {
+    use super::MqttSettings;
+    use crate::control::{self, Command};
+    use crate::night_mode;
+    use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS};
+    use serde::Serialize;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Wait between connection attempts, and between checks while disabled
+    const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+    /// How often to check whether the broker settings changed
+    const SETTINGS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+    const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+    /// State changes within this window are published as one message
+    const PUBLISH_DELAY: Duration = Duration::from_millis(200);
+
+    /// State published for home automation
+    #[derive(Serialize)]
+    struct MqttState {
+        active: bool,
+        intensity: f32,
+        profile: Option<String>,
+    }
+
+    /// Connected client and the prefix it publishes under
+    static CLIENT: Mutex<Option<(Client, String)>> = Mutex::new(None);
+
+    /// A state publish is scheduled
+    static PUBLISH_PENDING: AtomicBool = AtomicBool::new(false);
+
+    /// Publish the current state, if connected
+    /// Publishes from a short-lived thread since callers may hold locks the state lookup needs;
+    /// changes within PUBLISH_DELAY (e.g. a fade) are coalesced into one message
+    pub fn publish_state() {
+        if CLIENT.lock().unwrap().is_none() || PUBLISH_PENDING.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        thread::spawn(|| {
+            thread::sleep(PUBLISH_DELAY);
+            PUBLISH_PENDING.store(false, Ordering::SeqCst);
+            publish_now();
+        });
+    }
+
+    fn publish_now() {
+        let guard = CLIENT.lock().unwrap();
+        let Some((client, prefix)) = guard.as_ref() else { return };
+
+        let mode = night_mode::current();
+        let state = MqttState {
+            active: mode.engaged,
+            intensity: mode.intensity,
+            profile: crate::profiles::active().map(|p| p.name),
+        };
+        if let Ok(json) = serde_json::to_string(&state) {
+            let _ = client.try_publish(format!("{}/state", prefix), QoS::AtLeastOnce, true, json);
+        }
+    }
+
+    /// Stay connected to the broker until the settings change or the connection drops
+    fn run(config: &MqttSettings, on_command: &dyn Fn(Command) -> Result<(), String>) {
+        let availability = format!("{}/availability", config.topic_prefix);
+        let control = format!("{}/set", config.topic_prefix);
+
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(KEEP_ALIVE);
+        options.set_last_will(LastWill::new(&availability, "offline", QoS::AtLeastOnce, true));
+        if !config.username.is_empty() {
+            options.set_credentials(&config.username, &config.password);
+        }
+
+        let (client, mut connection) = Client::new(options, 16);
+        *CLIENT.lock().unwrap() = Some((client.clone(), config.topic_prefix.clone()));
+
+        loop {
+            match connection.recv_timeout(SETTINGS_CHECK_INTERVAL) {
+                Ok(Ok(Event::Incoming(Packet::ConnAck(_)))) => {
+                    let _ = client.subscribe(&control, QoS::AtLeastOnce);
+                    let _ = client.try_publish(&availability, QoS::AtLeastOnce, true, "online");
+                    publish_now();
+                }
+                Ok(Ok(Event::Incoming(Packet::Publish(publish)))) if publish.topic == control => {
+                    let text = String::from_utf8_lossy(&publish.payload);
+                    if let Err(e) = control::parse(&text).and_then(on_command) {
+                        crate::crash::log(format!("MQTT command {} failed: {}", text, e));
+                    }
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(_)) | Err(rumqttc::RecvTimeoutError::Disconnected) => break,
+                Err(rumqttc::RecvTimeoutError::Timeout) => {
+                    if crate::settings::get().mqtt != *config || crate::shutdown::is_shutting_down() {
+                        let _ = client.try_publish(&availability, QoS::AtLeastOnce, true, "offline");
+                        let _ = client.disconnect();
+                        break;
+                    }
+                }
+            }
+        }
+        *CLIENT.lock().unwrap() = None;
+    }
+
+    /// Start the MQTT bridge on a background thread
+    /// Connects whenever the integration is enabled and follows settings changes
+    pub fn start<F>(on_command: F)
+    where
+        F: Fn(Command) -> Result<(), String> + Send + 'static,
+    {
+        night_mode::subscribe(|_| publish_state());
+
+        thread::spawn(move || {
+            while !crate::shutdown::is_shutting_down() {
+                let config = crate::settings::get().mqtt;
+                if config.enabled {
+                    run(&config, &on_command);
+                }
+                thread::sleep(RECONNECT_DELAY);
+            }
+        });
+    }
+}
+
+#[cfg(feature = "network")]
+pub use bridge::{publish_state, start};
+
+#[cfg(not(feature = "network"))]
+pub fn publish_state() {}
+
+#[cfg(not(feature = "network"))]
+pub fn start<F>(_on_command: F)
+where
+    F: Fn(crate::control::Command) -> Result<(), String> + Send + 'static,
+{
+}
@@ -0,0 +1,77 @@
+//! Night mode state - whether an adjustment is engaged, and how strongly
+//! Tracks intensities applied by the gamma and Magnification paths so integrations
+//! (peripheral lighting, smart lights, home automation) can follow along
+
+use std::sync::Mutex;
+
+/// What applied an intensity
+#[derive(Clone, Copy, PartialEq)]
+pub enum Source {
+    /// Gamma ramp on a monitor index
+    Gamma(u32),
+    /// Fullscreen Magnification color effect
+    Magnification,
+}
+
+/// Summary of the current adjustment
+#[derive(serde::Serialize, Clone, Copy, PartialEq, Default, Debug)]
+pub struct NightMode {
+    /// Any adjustment above zero is applied
+    pub engaged: bool,
+    /// Strongest intensity currently applied (0.0 - 1.0)
+    pub intensity: f32,
+}
+
+type Listener = Box<dyn Fn(NightMode) + Send>;
+
+static INTENSITIES: Mutex<Vec<(Source, f32)>> = Mutex::new(Vec::new());
+static LISTENERS: Mutex<Vec<Listener>> = Mutex::new(Vec::new());
+
+fn summarize(intensities: &[(Source, f32)]) -> NightMode {
+    let intensity = intensities.iter().map(|(_, i)| *i).fold(0.0f32, f32::max);
+    NightMode { engaged: intensity > 0.0, intensity }
+}
+
+/// Get the current night mode state
+pub fn current() -> NightMode {
+    summarize(&INTENSITIES.lock().unwrap())
+}
+
+/// Get the intensity a source currently applies (0.0 if none)
+pub fn intensity_of(source: Source) -> f32 {
+    let intensities = INTENSITIES.lock().unwrap();
+    intensities.iter().find(|(s, _)| *s == source).map(|(_, i)| *i).unwrap_or(0.0)
+}
+
+/// Get every source applying a non-zero intensity
+pub fn applied() -> Vec<(Source, f32)> {
+    INTENSITIES.lock().unwrap().clone()
+}
+
+/// Record the intensity a source now applies, notifying listeners if the state changed
+pub fn record(source: Source, intensity: f32) {
+    let (before, after) = {
+        let mut intensities = INTENSITIES.lock().unwrap();
+        let before = summarize(&intensities);
+        intensities.retain(|(s, _)| *s != source);
+        if intensity > 0.0 {
+            intensities.push((source, intensity.min(1.0)));
+        }
+        (before, summarize(&intensities))
+    };
+
+    if before != after {
+        for listener in LISTENERS.lock().unwrap().iter() {
+            listener(after);
+        }
+    }
+}
+
+/// Call `on_change` whenever the night mode state changes
+/// Listeners run on the thread that applied the change, so slow work should be handed off
+pub fn subscribe<F>(on_change: F)
+where
+    F: Fn(NightMode) + Send + 'static,
+{
+    LISTENERS.lock().unwrap().push(Box::new(on_change));
+}
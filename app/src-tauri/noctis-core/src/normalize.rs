@@ -0,0 +1,50 @@
+//! Cross-backend intensity normalization - the gamma curve, Magnification matrix and ICC
+//! profile lift shadows by different amounts at the same intensity. Linear-lift backends get
+//! the intensity at which they brighten shadows as much as the default gamma curve would
+
+use crate::gamma::{self, CurveConstants};
+
+/// Inputs up to this level count as shadows; backends are matched on their mean output over it
+const SHADOW_RANGE: f32 = 0.25;
+
+/// Samples taken of the reference curve across the shadows
+const SAMPLES: usize = 64;
+
+/// Backends that lift black linearly: output = offset + input * (1 - offset)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Backend {
+    Magnification,
+    Icc,
+}
+
+impl Backend {
+    /// Black lift at full intensity
+    fn max_lift(self) -> f32 {
+        match self {
+            Backend::Magnification => crate::magnification::MAX_LIFT,
+            Backend::Icc => crate::icc_profile::SHADOW_LIFT_STRONG,
+        }
+    }
+}
+
+/// Mean shadow output of the gamma backend's default curve at an intensity
+fn reference_shadow_mean(intensity: f32) -> f32 {
+    let constants = CurveConstants::default();
+    let total: f32 = (0..SAMPLES)
+        .map(|i| gamma::curve_level((i as f32 + 0.5) / SAMPLES as f32 * SHADOW_RANGE, intensity, constants))
+        .sum();
+    total / SAMPLES as f32
+}
+
+/// Intensity for a backend that looks like the gamma backend at `intensity`
+/// Backends whose maximum lift falls short of the reference stop at 1.0
+/// Returns `intensity` unchanged when normalization is turned off
+pub fn equivalent(backend: Backend, intensity: f32) -> f32 {
+    if !crate::settings::get().normalize_backends {
+        return intensity;
+    }
+    // A linear lift averages offset + (1 - offset) * SHADOW_RANGE / 2 over the shadows
+    let half = SHADOW_RANGE / 2.0;
+    let offset = (reference_shadow_mean(intensity) - half) / (1.0 - half);
+    (offset / backend.max_lift()).clamp(0.0, 1.0)
+}
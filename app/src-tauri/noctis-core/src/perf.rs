@@ -0,0 +1,191 @@
+//! Pipeline timings - how long capture, analysis, curve building and apply take, so a
+//! "Noctis causes stutter" report can come with numbers. Keeps the latest samples per stage
+//! and can also write each timing as an ETW event for WPR / PerfView traces
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Samples kept per stage for the percentiles
+const SAMPLES: usize = 512;
+
+/// A stage of the adjustment pipeline
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// Grabbing pixels for the sensor (GDI blit or Graphics Capture frame)
+    Capture,
+    /// Turning captured pixels into a brightness reading
+    Analysis,
+    /// Building a gamma ramp from the curve
+    Curve,
+    /// Writing a gamma ramp or Magnification matrix to the driver
+    Apply,
+}
+
+impl Stage {
+    const ALL: [Stage; 4] = [Stage::Capture, Stage::Analysis, Stage::Curve, Stage::Apply];
+
+    fn name(self) -> &'static str {
+        match self {
+            Stage::Capture => "capture",
+            Stage::Analysis => "analysis",
+            Stage::Curve => "curve",
+            Stage::Apply => "apply",
+        }
+    }
+}
+
+/// Timings of one stage, in microseconds
+#[derive(Serialize, Clone, Debug)]
+pub struct StageStats {
+    pub stage: Stage,
+    /// Timings recorded since start or reset, including ones no longer kept
+    pub count: u64,
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+/// Timings of every stage over the latest samples
+#[derive(Serialize, Clone, Debug)]
+pub struct PerfStats {
+    pub stages: Vec<StageStats>,
+    /// Timings are also written as ETW events
+    pub etw: bool,
+}
+
+/// Latest durations in microseconds and total count per stage, in Stage::ALL order
+static SAMPLED: Mutex<Vec<(VecDeque<u64>, u64)>> = Mutex::new(Vec::new());
+
+/// Microseconds spent in every stage since start; unlike the samples, never reset
+static TOTAL_US: AtomicU64 = AtomicU64::new(0);
+
+/// Record how long a stage took
+pub fn record(stage: Stage, elapsed: Duration) {
+    let micros = elapsed.as_micros() as u64;
+    TOTAL_US.fetch_add(micros, Ordering::Relaxed);
+    {
+        let mut sampled = SAMPLED.lock().unwrap();
+        if sampled.is_empty() {
+            sampled.resize(Stage::ALL.len(), (VecDeque::with_capacity(SAMPLES), 0));
+        }
+        let (samples, count) = &mut sampled[stage as usize];
+        if samples.len() == SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(micros);
+        *count += 1;
+    }
+    if crate::settings::get().perf_etw {
+        etw::write(stage.name(), micros);
+    }
+}
+
+/// Run a stage and record its duration
+pub fn time<T>(stage: Stage, run: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = run();
+    record(stage, started.elapsed());
+    result
+}
+
+/// Microseconds spent in the pipeline since start, for measuring its cost over a period
+pub fn total_us() -> u64 {
+    TOTAL_US.load(Ordering::Relaxed)
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Current timings of every stage
+pub fn stats() -> PerfStats {
+    let sampled = SAMPLED.lock().unwrap();
+    let stages = Stage::ALL
+        .iter()
+        .map(|&stage| {
+            let (samples, count) = sampled.get(stage as usize).cloned().unwrap_or_default();
+            let mut sorted: Vec<u64> = samples.into_iter().collect();
+            sorted.sort_unstable();
+            StageStats {
+                stage,
+                count,
+                mean_us: sorted.iter().sum::<u64>() / sorted.len().max(1) as u64,
+                p50_us: percentile(&sorted, 50.0),
+                p95_us: percentile(&sorted, 95.0),
+                p99_us: percentile(&sorted, 99.0),
+                max_us: sorted.last().copied().unwrap_or(0),
+            }
+        })
+        .collect();
+    PerfStats { stages, etw: crate::settings::get().perf_etw }
+}
+
+/// Forget every recorded timing, e.g. before reproducing a stutter
+pub fn reset() {
+    SAMPLED.lock().unwrap().clear();
+}
+
+#[cfg(windows)]
+mod etw {
+    use std::sync::OnceLock;
+
+    /// Provider "Noctis-Pipeline"; `wpr -start` / PerfView `/providers=*Noctis-Pipeline` or this GUID
+    const PROVIDER: Guid = Guid {
+        data1: 0x5f6a_4c1e,
+        data2: 0x9b3d,
+        data3: 0x4e7a,
+        data4: [0x8c, 0x21, 0x6d, 0x0f, 0x3b, 0x9a, 0x7e, 0x45],
+    };
+
+    /// TRACE_LEVEL_VERBOSE
+    const LEVEL_VERBOSE: u8 = 5;
+
+    #[repr(C)]
+    struct Guid {
+        data1: u32,
+        data2: u16,
+        data3: u16,
+        data4: [u8; 8],
+    }
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn EventRegister(provider: *const Guid, callback: *const std::ffi::c_void, context: *const std::ffi::c_void, handle: *mut u64) -> u32;
+        fn EventWriteString(handle: u64, level: u8, keyword: u64, text: *const u16) -> u32;
+    }
+
+    /// Provider handle, registered on first use for the life of the process
+    fn handle() -> Option<u64> {
+        static HANDLE: OnceLock<Option<u64>> = OnceLock::new();
+        *HANDLE.get_or_init(|| {
+            let mut handle = 0;
+            let status = unsafe { EventRegister(&PROVIDER, std::ptr::null(), std::ptr::null(), &mut handle) };
+            (status == 0).then_some(handle)
+        })
+    }
+
+    /// Write one timing as a string event, e.g. "stage=capture us=812"
+    pub fn write(stage: &str, micros: u64) {
+        let Some(handle) = handle() else { return };
+        let text: Vec<u16> = format!("stage={} us={}", stage, micros).encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe {
+            EventWriteString(handle, LEVEL_VERBOSE, 0, text.as_ptr());
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod etw {
+    pub fn write(_stage: &str, _micros: u64) {}
+}
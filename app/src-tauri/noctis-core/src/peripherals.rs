@@ -0,0 +1,122 @@
+//! Peripheral lighting sync - dims RGB keyboards/mice through OpenRGB's SDK server
+//! Loads a user-made OpenRGB profile when night mode engages and another when it disengages
+//! Requires OpenRGB running with its SDK server enabled (Settings > SDK Server)
+
+use crate::night_mode;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// OpenRGB SDK packet ids
+const PACKET_REQUEST_PROTOCOL_VERSION: u32 = 40;
+const PACKET_SET_CLIENT_NAME: u32 = 50;
+const PACKET_REQUEST_LOAD_PROFILE: u32 = 152;
+
+/// SDK protocol version spoken by this client (profile control needs 2+)
+const PROTOCOL_VERSION: u32 = 3;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// OpenRGB integration settings
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct PeripheralSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// OpenRGB profile loaded when night mode engages, e.g. a dim red layout
+    pub night_profile: String,
+    /// OpenRGB profile loaded when night mode disengages
+    pub day_profile: String,
+}
+
+impl Default for PeripheralSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "127.0.0.1".to_string(),
+            port: 6742,
+            night_profile: "Noctis Night".to_string(),
+            day_profile: "Noctis Day".to_string(),
+        }
+    }
+}
+
+/// Write one SDK packet: "ORGB" magic, device index, packet id, payload size, payload
+fn send_packet(stream: &mut TcpStream, packet_id: u32, payload: &[u8]) -> std::io::Result<()> {
+    let mut packet = Vec::with_capacity(16 + payload.len());
+    packet.extend_from_slice(b"ORGB");
+    packet.extend_from_slice(&0u32.to_le_bytes());
+    packet.extend_from_slice(&packet_id.to_le_bytes());
+    packet.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    packet.extend_from_slice(payload);
+    stream.write_all(&packet)
+}
+
+fn c_string(s: &str) -> Vec<u8> {
+    s.bytes().chain(std::iter::once(0)).collect()
+}
+
+/// Connect to the OpenRGB SDK server and load a profile by name
+pub fn load_profile(config: &PeripheralSettings, profile: &str) -> Result<(), String> {
+    let address = (config.host.as_str(), config.port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Invalid OpenRGB address: {}", e))?
+        .next()
+        .ok_or_else(|| crate::i18n::text("error.openrgb_address"))?;
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+        .map_err(|e| format!("Failed to connect to OpenRGB: {}", e))?;
+    let _ = stream.set_read_timeout(Some(CONNECT_TIMEOUT));
+
+    let send = |stream: &mut TcpStream, id: u32, payload: &[u8]| {
+        send_packet(stream, id, payload).map_err(|e| format!("Failed to talk to OpenRGB: {}", e))
+    };
+
+    // Negotiate the protocol; servers older than the profile API never reply
+    send(&mut stream, PACKET_REQUEST_PROTOCOL_VERSION, &PROTOCOL_VERSION.to_le_bytes())?;
+    let mut reply = [0u8; 20];
+    stream
+        .read_exact(&mut reply)
+        .map_err(|_| "OpenRGB is too old to load profiles".to_string())?;
+
+    send(&mut stream, PACKET_SET_CLIENT_NAME, &c_string("Noctis"))?;
+    send(&mut stream, PACKET_REQUEST_LOAD_PROFILE, &c_string(profile))?;
+    Ok(())
+}
+
+/// Load the profile matching a night mode state, if the integration is enabled
+fn sync(engaged: bool) {
+    let config = crate::settings::get().peripherals;
+    if !config.enabled {
+        return;
+    }
+    let profile = if engaged { &config.night_profile } else { &config.day_profile };
+    if !profile.is_empty() {
+        let _ = load_profile(&config, profile);
+    }
+}
+
+/// Follow night mode engage/disengage on a background thread
+pub fn start() {
+    let (tx, rx) = mpsc::channel::<bool>();
+    let engaged = AtomicBool::new(night_mode::current().engaged);
+    night_mode::subscribe(move |state| {
+        if engaged.swap(state.engaged, Ordering::SeqCst) != state.engaged {
+            let _ = tx.send(state.engaged);
+        }
+    });
+
+    thread::spawn(move || {
+        while let Ok(mut engaged) = rx.recv() {
+            // Only the latest state matters if several changes queued up while connecting
+            while let Ok(next) = rx.try_recv() {
+                engaged = next;
+            }
+            sync(engaged);
+        }
+    });
+}
@@ -0,0 +1,35 @@
+//! Portable mode - everything stays next to the executable (e.g. on a USB stick)
+//! Enabled by a `portable` marker file beside the exe or the `--portable` flag
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Marker file that enables portable mode when placed next to the exe
+const MARKER_FILE: &str = "portable";
+
+/// Command line flag that enables portable mode
+const PORTABLE_FLAG: &str = "--portable";
+
+static PORTABLE: OnceLock<bool> = OnceLock::new();
+
+/// Directory containing the running executable
+pub fn exe_dir() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Check whether Noctis is running in portable mode
+/// Portable mode keeps config next to the exe, never writes the registry or autostart
+/// entries, and only uses per-user color profile associations
+pub fn is_portable() -> bool {
+    *PORTABLE.get_or_init(|| {
+        std::env::args().any(|arg| arg == PORTABLE_FLAG) || exe_dir().join(MARKER_FILE).exists()
+    })
+}
+
+/// Config folder used in portable mode
+pub fn data_dir() -> PathBuf {
+    exe_dir().join("NoctisData")
+}
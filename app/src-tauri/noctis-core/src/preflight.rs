@@ -0,0 +1,78 @@
+//! Preflight report - checks the environment Noctis needs before onboarding
+//! Each check is run for real (a gamma ramp written back unchanged, a pixel captured) so
+//! the onboarding UI can point users at the actual problem instead of a generic failure
+
+use crate::{gamma, magnification, sensor};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Result of one check
+#[derive(Serialize, Clone)]
+pub struct Check {
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+impl From<Result<(), String>> for Check {
+    fn from(result: Result<(), String>) -> Self {
+        Check { ok: result.is_ok(), error: result.err() }
+    }
+}
+
+/// Gamma check for one monitor
+#[derive(Serialize, Clone)]
+pub struct MonitorCheck {
+    pub index: u32,
+    pub name: String,
+    #[serde(flatten)]
+    pub check: Check,
+}
+
+/// Everything the onboarding UI needs to know about the environment
+#[derive(Serialize, Clone)]
+pub struct PreflightReport {
+    /// Running elevated (needed to install color profiles outside portable mode)
+    pub admin: bool,
+    /// The Windows color profile directory can be written
+    pub color_directory: Check,
+    /// SetDeviceGammaRamp accepts writes, per monitor
+    pub gamma: Vec<MonitorCheck>,
+    pub magnification: Check,
+    /// The screen can be captured for the brightness sensor
+    pub screen_capture: Check,
+}
+
+/// Windows color profile directory
+fn color_directory() -> PathBuf {
+    let windir = std::env::var("WINDIR").unwrap_or("C:\\Windows".to_string());
+    [windir.as_str(), "System32", "spool", "drivers", "color"].iter().collect()
+}
+
+/// Create and remove a file in the color directory
+fn check_color_directory() -> Result<(), String> {
+    let probe = color_directory().join("noctis-preflight.tmp");
+    std::fs::write(&probe, b"").map_err(|e| format!("Cannot write {}: {}", color_directory().display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Capture one pixel of the primary monitor
+fn check_screen_capture() -> Result<(), String> {
+    let monitors = gamma::get_monitors();
+    let primary = monitors.iter().find(|m| m.is_primary).or(monitors.first()).ok_or_else(|| crate::i18n::text("error.no_monitors"))?;
+    sensor::get_pixel(primary.x + primary.width as i32 / 2, primary.y + primary.height as i32 / 2).map(|_| ())
+}
+
+/// Run every check
+pub fn run() -> PreflightReport {
+    PreflightReport {
+        admin: crate::elevation::is_elevated(),
+        color_directory: check_color_directory().into(),
+        gamma: gamma::get_monitors()
+            .into_iter()
+            .map(|m| MonitorCheck { check: gamma::probe(m.index).into(), index: m.index, name: m.name })
+            .collect(),
+        magnification: magnification::init().into(),
+        screen_capture: check_screen_capture().into(),
+    }
+}
@@ -0,0 +1,156 @@
+//! Process launch watcher - WMI process-creation events
+//! Lets game profiles be applied the moment a game starts, before its window appears
+
+use std::thread;
+
+/// A newly started process
+#[derive(serde::Serialize, Clone)]
+pub struct LaunchedProcess {
+    pub pid: u32,
+    /// Executable file name, e.g. "EscapeFromTarkov.exe"
+    pub process: String,
+    /// `process` may be cut short: kernel trace events keep about 15 characters of the name,
+    /// and the full one could not be read from the process
+    pub truncated: bool,
+}
+
+impl LaunchedProcess {
+    /// Whether the process runs an executable (case-insensitive)
+    /// A truncated name matches any executable it is the start of
+    pub fn is(&self, executable: &str) -> bool {
+        if self.truncated {
+            executable.get(..self.process.len()).is_some_and(|start| start.eq_ignore_ascii_case(&self.process))
+        } else {
+            self.process.eq_ignore_ascii_case(executable)
+        }
+    }
+}
+
+/// Kernel trace events: delivered immediately, but only available to administrators
+const START_TRACE_QUERY: &str = "SELECT * FROM Win32_ProcessStartTrace";
+
+/// Instance creation events: available to all users, polled by WMI every second
+const CREATION_QUERY: &str =
+    "SELECT * FROM __InstanceCreationEvent WITHIN 1 WHERE TargetInstance ISA 'Win32_Process'";
+
+#[cfg(windows)]
+mod wmi {
+    use super::*;
+    use windows::core::{w, Interface, BSTR, VARIANT};
+    use windows::Win32::Security::PSECURITY_DESCRIPTOR;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoInitializeSecurity, CLSCTX_INPROC_SERVER,
+        COINIT_MULTITHREADED, EOAC_NONE, RPC_C_AUTHN_LEVEL_DEFAULT, RPC_C_IMP_LEVEL_IMPERSONATE,
+    };
+    use windows::Win32::System::Wmi::{
+        IWbemClassObject, IWbemLocator, WbemLocator, WBEM_FLAG_FORWARD_ONLY,
+        WBEM_FLAG_RETURN_IMMEDIATELY, WBEM_INFINITE,
+    };
+
+    /// Read a string property from a WMI object
+    unsafe fn get_string(object: &IWbemClassObject, name: windows::core::PCWSTR) -> Option<String> {
+        let mut value = VARIANT::default();
+        object.Get(name, 0, &mut value, None, None).ok()?;
+        BSTR::try_from(&value).ok().map(|s| s.to_string())
+    }
+
+    /// Read an integer property from a WMI object
+    unsafe fn get_u32(object: &IWbemClassObject, name: windows::core::PCWSTR) -> Option<u32> {
+        let mut value = VARIANT::default();
+        object.Get(name, 0, &mut value, None, None).ok()?;
+        u32::try_from(&value).ok()
+    }
+
+    /// Extract the process from a Win32_ProcessStartTrace event
+    /// The event's ProcessName is truncated, so the full name is read from the process itself
+    unsafe fn read_start_trace(event: &IWbemClassObject) -> Option<LaunchedProcess> {
+        let pid = get_u32(event, w!("ProcessID"))?;
+        match crate::foreground::process_path(pid) {
+            Some(path) => Some(LaunchedProcess { pid, process: crate::foreground::file_name(&path), truncated: false }),
+            None => Some(LaunchedProcess { pid, process: get_string(event, w!("ProcessName"))?, truncated: true }),
+        }
+    }
+
+    /// Extract the process from an __InstanceCreationEvent's embedded Win32_Process
+    unsafe fn read_creation_event(event: &IWbemClassObject) -> Option<LaunchedProcess> {
+        let mut value = VARIANT::default();
+        event.Get(w!("TargetInstance"), 0, &mut value, None, None).ok()?;
+        let instance: IWbemClassObject = windows::core::IUnknown::try_from(&value).ok()?.cast().ok()?;
+        Some(LaunchedProcess {
+            pid: get_u32(&instance, w!("ProcessId"))?,
+            process: get_string(&instance, w!("Name"))?,
+            truncated: false,
+        })
+    }
+
+    /// Subscribe to process creation and block forever delivering launches
+    pub unsafe fn watch(on_launch: &dyn Fn(&LaunchedProcess)) -> Result<(), String> {
+        CoInitializeEx(None, COINIT_MULTITHREADED)
+            .ok()
+            .map_err(|e| format!("Failed to initialize COM: {}", e))?;
+
+        // May already be set up by another component in the process, which is fine
+        let _ = CoInitializeSecurity(
+            PSECURITY_DESCRIPTOR::default(),
+            -1,
+            None,
+            None,
+            RPC_C_AUTHN_LEVEL_DEFAULT,
+            RPC_C_IMP_LEVEL_IMPERSONATE,
+            None,
+            EOAC_NONE,
+            None,
+        );
+
+        let locator: IWbemLocator = CoCreateInstance(&WbemLocator, None, CLSCTX_INPROC_SERVER)
+            .map_err(|e| format!("Failed to create WMI locator: {}", e))?;
+        let services = locator
+            .ConnectServer(&BSTR::from("ROOT\\CIMV2"), None, None, None, 0, None, None)
+            .map_err(|e| format!("Failed to connect to WMI: {}", e))?;
+
+        let flags = WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY;
+        let (events, is_trace) =
+            match services.ExecNotificationQuery(&BSTR::from("WQL"), &BSTR::from(START_TRACE_QUERY), flags, None) {
+                Ok(events) => (events, true),
+                Err(_) => {
+                    let events = services
+                        .ExecNotificationQuery(&BSTR::from("WQL"), &BSTR::from(CREATION_QUERY), flags, None)
+                        .map_err(|e| format!("Failed to subscribe to process events: {}", e))?;
+                    (events, false)
+                }
+            };
+
+        loop {
+            let mut objects = [None];
+            let mut returned = 0u32;
+            events
+                .Next(WBEM_INFINITE, &mut objects, &mut returned)
+                .ok()
+                .map_err(|e| format!("Process event subscription failed: {}", e))?;
+
+            let Some(event) = objects[0].take() else { continue };
+            let launched = if is_trace { read_start_trace(&event) } else { read_creation_event(&event) };
+            if let Some(launched) = launched {
+                on_launch(&launched);
+            }
+        }
+    }
+}
+
+/// Start a background thread that calls `on_launch` for every new process
+#[cfg(windows)]
+pub fn start_watcher<F>(on_launch: F)
+where
+    F: Fn(&LaunchedProcess) + Send + 'static,
+{
+    thread::spawn(move || {
+        let _ = unsafe { wmi::watch(&on_launch) };
+    });
+}
+
+#[cfg(not(windows))]
+pub fn start_watcher<F>(_on_launch: F)
+where
+    F: Fn(&LaunchedProcess) + Send + 'static,
+{
+}
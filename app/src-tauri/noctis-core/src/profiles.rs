@@ -0,0 +1,479 @@
+//! Per-game profiles - applied automatically when the game's window is focused
+//! Stored as JSON next to the settings file
+
+use crate::adjustment::AdjustmentConfig;
+use crate::foreground::ForegroundApp;
+use crate::gamma;
+use crate::sensor::SensorSettings;
+use crate::process_watcher::LaunchedProcess;
+use crate::migration::{self, Migration};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Profile store file name
+pub const PROFILES_FILE: &str = "profiles.json";
+
+/// Upgrades from each older profile store schema, index N migrates version N to N + 1
+const MIGRATIONS: &[Migration] = &[
+    // v0 -> v1: bare array wrapped in a versioned object
+    |value| serde_json::json!({ "profiles": value }),
+];
+
+/// Time taken to ramp from warmup to full intensity once the game is focused
+const WARMUP_RAMP: Duration = Duration::from_millis(1500);
+const WARMUP_STEP: Duration = Duration::from_millis(50);
+
+/// How far a profile's intensity moves toward the user's manual choice after each session
+const LEARN_RATE: f32 = 0.25;
+
+/// Manual adjustments needed before learning starts moving a profile's intensity
+const LEARN_MIN_ADJUSTMENTS: u32 = 3;
+
+/// What happens when a profile's game loses focus
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FocusLossPolicy {
+    /// Leave the profile's effects applied
+    Keep,
+    /// Restore the display immediately
+    #[default]
+    Revert,
+    /// Restore the display if the game stays unfocused for the delay
+    RevertAfter { delay_ms: u64 },
+}
+
+/// How the brightness sensor samples and what the auto-adjust controller does with the readings
+/// A profile's copy replaces the global settings as a whole while it is active
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct Sensing {
+    pub sensor: SensorSettings,
+    pub adjustment: AdjustmentConfig,
+}
+
+/// A game profile
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct GameProfile {
+    /// Unique display name
+    pub name: String,
+    /// Executable name that activates the profile, e.g. "EscapeFromTarkov.exe"
+    pub process: String,
+    /// Shadow Hunter intensity: 0.0 (normal) to 1.0 (max)
+    pub intensity: f32,
+    /// Monitor index the profile adjusts
+    pub monitor: u32,
+    #[serde(default)]
+    pub focus_loss: FocusLossPolicy,
+    /// Reduced intensity applied while the game loads (process started, window not focused yet)
+    /// Ramps to `intensity` when the game window gains focus
+    #[serde(default)]
+    pub warmup_intensity: Option<f32>,
+    /// Opt-in: drift `intensity` toward the levels the user manually picks while playing
+    #[serde(default)]
+    pub learn_preference: bool,
+    /// Sessions in which the user manually changed intensity (used by learn_preference)
+    #[serde(default)]
+    pub manual_adjustments: u32,
+    /// Suspend effects while HDR video plays during this profile (None follows the global setting)
+    #[serde(default)]
+    pub hdr_video_bypass: Option<bool>,
+    /// Auto-adjust engage ramp for this game in ms (None follows the global setting)
+    #[serde(default)]
+    pub engage_ramp_ms: Option<u64>,
+    /// Sensor region, thresholds and sampling rate for this game (None follows the global settings)
+    #[serde(default)]
+    pub sensing: Option<Sensing>,
+    /// Monitor group the profile adjusts as a whole instead of `monitor`
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+/// On-disk layout of the profile store
+#[derive(Serialize, Deserialize)]
+struct ProfileStore {
+    version: u32,
+    profiles: Vec<GameProfile>,
+}
+
+/// Profile activation changes reported to the caller
+pub enum ProfileEvent {
+    Activated(GameProfile),
+    Deactivated(GameProfile),
+}
+
+/// Currently applied profile and whether its game has focus
+struct ActiveProfile {
+    profile: GameProfile,
+    focused: bool,
+    /// Intensity currently applied (below the profile's during warmup)
+    intensity: f32,
+    /// Last intensity the user picked by hand this session
+    manual: Option<f32>,
+}
+
+static PROFILES: Mutex<Option<Vec<GameProfile>>> = Mutex::new(None);
+static ACTIVE: Mutex<Option<ActiveProfile>> = Mutex::new(None);
+
+/// Bumped whenever a profile's game gains focus so pending delayed reverts can tell they are stale
+static FOCUS_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn profiles_path() -> PathBuf {
+    crate::settings::config_dir().join(PROFILES_FILE)
+}
+
+fn read() -> Result<Option<Vec<GameProfile>>, String> {
+    let path = profiles_path();
+    let Some(value) = migration::read(&path, MIGRATIONS)? else { return Ok(None) };
+    serde_json::from_value::<ProfileStore>(value)
+        .map(|store| Some(store.profiles))
+        .map_err(|e| format!("Invalid profiles in {}: {}", path.display(), e))
+}
+
+/// Stored profiles, none if the file is missing
+/// An unreadable file is set aside first so saving doesn't overwrite the only copy
+fn load() -> Vec<GameProfile> {
+    read()
+        .unwrap_or_else(|e| {
+            migration::set_aside(&profiles_path(), &e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+fn save(profiles: &[GameProfile]) -> Result<(), String> {
+    let store = ProfileStore {
+        version: MIGRATIONS.len() as u32,
+        profiles: profiles.to_vec(),
+    };
+    let json = serde_json::to_string_pretty(&store)
+        .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+    crate::config_lock::write_file(&profiles_path(), &json)
+}
+
+/// Get all stored profiles
+pub fn list() -> Vec<GameProfile> {
+    PROFILES.lock().unwrap().get_or_insert_with(load).clone()
+}
+
+/// Add a profile, or replace the one with the same name
+pub fn upsert(profile: GameProfile) -> Result<Vec<GameProfile>, String> {
+    if profile.name.trim().is_empty() {
+        return Err(crate::i18n::text("error.profile_name_empty"));
+    }
+    if profile.process.trim().is_empty() {
+        return Err(crate::i18n::text("error.profile_process_empty"));
+    }
+    if let Some(group) = profile.group.as_ref().filter(|g| !crate::monitor_groups::list().iter().any(|m| &m.name == *g)) {
+        return Err(crate::i18n::format("error.group_not_found", &[group]));
+    }
+    if let Some(sensing) = &profile.sensing {
+        crate::adjustment::validate(&sensing.adjustment)?;
+        crate::sensor::validate(&sensing.sensor)?;
+    }
+
+    let mut guard = PROFILES.lock().unwrap();
+    let profiles = guard.get_or_insert_with(load);
+    match profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+    save(profiles)?;
+    Ok(profiles.clone())
+}
+
+/// Delete a profile by name
+pub fn delete(name: &str) -> Result<Vec<GameProfile>, String> {
+    let mut guard = PROFILES.lock().unwrap();
+    let profiles = guard.get_or_insert_with(load);
+    profiles.retain(|p| p.name != name);
+    save(profiles)?;
+    Ok(profiles.clone())
+}
+
+/// Re-read profiles from disk, e.g. after the file was edited externally
+/// An active profile that was edited is re-applied; one that was deleted is deactivated
+/// Keeps the current profiles if the file is missing or invalid (such as mid-save)
+pub fn reload<F>(notify: F) -> Option<Vec<GameProfile>>
+where
+    F: Fn(ProfileEvent),
+{
+    let profiles = read().ok().flatten()?;
+    *PROFILES.lock().unwrap() = Some(profiles.clone());
+
+    let Some(current) = active() else { return Some(profiles) };
+    match profiles.iter().find(|p| p.name == current.name) {
+        Some(updated) if *updated != current => {
+            let mut active = ACTIVE.lock().unwrap();
+            if let Some(a) = active.as_mut().filter(|a| a.profile == current) {
+                a.profile = updated.clone();
+                a.intensity = if a.focused { updated.intensity } else { updated.warmup_intensity.unwrap_or(updated.intensity) };
+                apply(updated, a.intensity);
+                drop(active);
+                notify(ProfileEvent::Activated(updated.clone()));
+            }
+        }
+        Some(_) => {}
+        None => {
+            if deactivate(&current) {
+                notify(ProfileEvent::Deactivated(current));
+            }
+        }
+    }
+    Some(profiles)
+}
+
+/// Find the profile for an executable name (case-insensitive)
+pub fn find_for_process(process: &str) -> Option<GameProfile> {
+    list().into_iter().find(|p| p.process.eq_ignore_ascii_case(process))
+}
+
+/// Find a profile by name, or by executable with or without ".exe" (case-insensitive)
+pub fn find_by_name(name: &str) -> Option<GameProfile> {
+    let profiles = list();
+    let executable = format!("{}.exe", name);
+    let by_process = |p: &&GameProfile| p.process.eq_ignore_ascii_case(name) || p.process.eq_ignore_ascii_case(&executable);
+    profiles.iter().find(|p| p.name.eq_ignore_ascii_case(name)).or_else(|| profiles.iter().find(by_process)).cloned()
+}
+
+/// Get the currently applied profile
+pub fn active() -> Option<GameProfile> {
+    ACTIVE.lock().unwrap().as_ref().map(|a| a.profile.clone())
+}
+
+/// Sensing configuration in effect: the active profile's if it has one, else the global settings
+/// Sensor and controller settings always come from the same source, so a profile switch never
+/// pairs one profile's region with another's thresholds
+pub fn sensing() -> Sensing {
+    if let Some(sensing) = ACTIVE.lock().unwrap().as_ref().and_then(|a| a.profile.sensing.clone()) {
+        return sensing;
+    }
+    let settings = crate::settings::get();
+    Sensing { sensor: settings.sensor, adjustment: settings.adjustment }
+}
+
+impl GameProfile {
+    /// Monitors the profile adjusts: its group's connected members, or its monitor
+    pub fn monitors(&self) -> Vec<u32> {
+        match &self.group {
+            Some(group) => crate::monitor_groups::members(group).unwrap_or_default(),
+            None => vec![self.monitor],
+        }
+    }
+}
+
+/// Apply a profile's adjustment at the given intensity
+fn apply(profile: &GameProfile, intensity: f32) {
+    for monitor in profile.monitors() {
+        let _ = gamma::set_gamma(intensity, monitor);
+    }
+}
+
+/// Restore the display adjusted by a profile
+fn revert(profile: &GameProfile) {
+    for monitor in profile.monitors() {
+        let _ = gamma::set_gamma(0.0, monitor);
+    }
+}
+
+/// Drop the active profile and restore the display if it is still `expected`
+fn deactivate(expected: &GameProfile) -> bool {
+    let mut active = ACTIVE.lock().unwrap();
+    match active.as_ref() {
+        Some(a) if &a.profile == expected => {
+            revert(expected);
+            let manual = active.take().and_then(|a| a.manual);
+            drop(active);
+            if let Some(manual) = manual {
+                learn(expected, manual);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Revert the active profile ahead of applying a different one in its place
+fn displace(active: &mut Option<ActiveProfile>) -> Option<ActiveProfile> {
+    let previous = active.take()?;
+    revert(&previous.profile);
+    Some(previous)
+}
+
+/// Learn from and report a displaced profile, once the active profile lock is released
+fn report_displaced<F>(previous: Option<ActiveProfile>, notify: &F)
+where
+    F: Fn(ProfileEvent),
+{
+    let Some(previous) = previous else { return };
+    if let Some(manual) = previous.manual {
+        learn(&previous.profile, manual);
+    }
+    notify(ProfileEvent::Deactivated(previous.profile));
+}
+
+/// Record an intensity the user set by hand on a monitor
+/// Counts toward the active profile's learned preference if it opted in
+pub fn record_manual_intensity(monitor: u32, intensity: f32) {
+    let mut active = ACTIVE.lock().unwrap();
+    if let Some(a) = active.as_mut().filter(|a| a.profile.monitors().contains(&monitor) && a.focused) {
+        // A manual choice overrides any warmup ramp still in progress
+        FOCUS_GENERATION.fetch_add(1, Ordering::SeqCst);
+        a.intensity = intensity;
+        if a.profile.learn_preference {
+            a.manual = Some(intensity);
+        }
+    }
+}
+
+/// Move a profile's stored intensity toward the level the user settled on in a session
+fn learn(profile: &GameProfile, manual: f32) {
+    let mut guard = PROFILES.lock().unwrap();
+    let profiles = guard.get_or_insert_with(load);
+    let Some(stored) = profiles.iter_mut().find(|p| p.name == profile.name) else { return };
+    if !stored.learn_preference {
+        return;
+    }
+
+    stored.manual_adjustments += 1;
+    if stored.manual_adjustments >= LEARN_MIN_ADJUSTMENTS {
+        let learned = stored.intensity + (manual - stored.intensity) * LEARN_RATE;
+        stored.intensity = learned.clamp(0.0, 1.0);
+    }
+    let _ = save(profiles);
+}
+
+/// Ramp a warmed-up profile to its full intensity on a background thread
+/// Stops early if the profile is deactivated or replaced, or its game loses and regains focus
+fn ramp_up(profile: GameProfile, from: f32) {
+    let generation = FOCUS_GENERATION.load(Ordering::SeqCst);
+    let steps = (WARMUP_RAMP.as_millis() / WARMUP_STEP.as_millis()).max(1) as u32;
+
+    thread::spawn(move || {
+        for step in 1..=steps {
+            thread::sleep(WARMUP_STEP);
+            let mut active = ACTIVE.lock().unwrap();
+            let Some(a) = active.as_mut().filter(|a| a.profile == profile) else { return };
+            if FOCUS_GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            let t = step as f32 / steps as f32;
+            a.intensity = from + (profile.intensity - from) * t;
+            apply(&profile, a.intensity);
+        }
+    });
+}
+
+/// Apply a game's profile as soon as its process starts, before the window is shown
+/// The profile counts as unfocused until the game's window gains focus
+pub fn prepare<F>(launched: &LaunchedProcess, notify: F)
+where
+    F: Fn(ProfileEvent),
+{
+    let Some(profile) = list().into_iter().find(|p| launched.is(&p.process)) else { return };
+    warm_up(profile, notify);
+}
+
+/// Apply a profile's warmup intensity until its game gains focus
+fn warm_up<F>(profile: GameProfile, notify: F)
+where
+    F: Fn(ProfileEvent),
+{
+    let mut active = ACTIVE.lock().unwrap();
+    if active.as_ref().is_some_and(|a| a.profile == profile) {
+        return;
+    }
+    let previous = displace(&mut active);
+    let intensity = profile.warmup_intensity.unwrap_or(profile.intensity);
+    apply(&profile, intensity);
+    *active = Some(ActiveProfile { profile: profile.clone(), focused: false, intensity, manual: None });
+    drop(active);
+    report_displaced(previous, &notify);
+    notify(ProfileEvent::Activated(profile));
+}
+
+/// Activate a profile by name ahead of its game, as a launcher or deep link asks
+/// It behaves like a profile prepared at process launch until the game gains focus
+pub fn activate<F>(name: &str, notify: F) -> Result<GameProfile, String>
+where
+    F: Fn(ProfileEvent),
+{
+    let profile = find_by_name(name).ok_or_else(|| crate::i18n::format("error.profile_not_found", &[&name]))?;
+    warm_up(profile.clone(), notify);
+    Ok(profile)
+}
+
+/// Deactivate a profile by name once its game has exited, if it is still the active one
+pub fn release<F>(name: &str, notify: F) -> Result<(), String>
+where
+    F: Fn(ProfileEvent),
+{
+    let profile = find_by_name(name).ok_or_else(|| crate::i18n::format("error.profile_not_found", &[&name]))?;
+    FOCUS_GENERATION.fetch_add(1, Ordering::SeqCst);
+    if deactivate(&profile) {
+        notify(ProfileEvent::Deactivated(profile));
+    }
+    Ok(())
+}
+
+/// React to a foreground change: activate a matching profile, or apply the
+/// active profile's focus-loss policy when its game is no longer focused
+pub fn on_foreground_change<F>(app: &ForegroundApp, notify: F)
+where
+    F: Fn(ProfileEvent) + Send + 'static,
+{
+    if let Some(profile) = find_for_process(&app.process) {
+        FOCUS_GENERATION.fetch_add(1, Ordering::SeqCst);
+        let mut active = ACTIVE.lock().unwrap();
+        if let Some(a) = active.as_mut() {
+            if a.profile == profile {
+                a.focused = true;
+                if a.intensity < profile.intensity {
+                    ramp_up(profile, a.intensity);
+                }
+                return;
+            }
+        }
+        // Another profiled game was active: restore its monitors before taking over
+        let previous = displace(&mut active);
+        apply(&profile, profile.intensity);
+        *active = Some(ActiveProfile { profile: profile.clone(), focused: true, intensity: profile.intensity, manual: None });
+        drop(active);
+        report_displaced(previous, &notify);
+        notify(ProfileEvent::Activated(profile));
+        return;
+    }
+
+    let profile = {
+        let mut active = ACTIVE.lock().unwrap();
+        match active.as_mut() {
+            Some(a) if a.focused => {
+                a.focused = false;
+                a.profile.clone()
+            }
+            _ => return,
+        }
+    };
+
+    match profile.focus_loss {
+        FocusLossPolicy::Keep => {}
+        FocusLossPolicy::Revert => {
+            if deactivate(&profile) {
+                notify(ProfileEvent::Deactivated(profile));
+            }
+        }
+        FocusLossPolicy::RevertAfter { delay_ms } => {
+            let generation = FOCUS_GENERATION.load(Ordering::SeqCst);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(delay_ms));
+                // Refocusing a profiled game supersedes this revert
+                if FOCUS_GENERATION.load(Ordering::SeqCst) == generation && deactivate(&profile) {
+                    notify(ProfileEvent::Deactivated(profile));
+                }
+            });
+        }
+    }
+}
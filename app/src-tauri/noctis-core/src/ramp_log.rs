@@ -0,0 +1,258 @@
+//! Ramp logging - optional record of every applied curve and matrix for A/B comparisons
+//! Each app run writes one JSON-lines log under `<config>/ramp_logs`, named by its start time;
+//! compare_sessions summarizes two logs so users tuning a game can see what actually changed
+
+use crate::gamma::GammaRamp;
+use crate::magnification::MagColorEffect;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const LOG_DIR: &str = "ramp_logs";
+
+/// Ramp inputs compared between sessions (every 16th level plus white)
+const COMPARE_POINTS: [usize; 17] = [0, 16, 32, 48, 64, 80, 96, 112, 128, 144, 160, 176, 192, 208, 224, 240, 255];
+
+/// One logged write
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LogEntry {
+    Gamma {
+        /// Milliseconds since the session started
+        t_ms: u64,
+        monitor: u32,
+        /// Red, green and blue tables, 256 entries each
+        ramp: Vec<u16>,
+    },
+    Magnification {
+        t_ms: u64,
+        matrix: [[f32; 5]; 5],
+    },
+}
+
+/// Summary of one monitor's ramps over a session
+#[derive(Serialize, Clone)]
+pub struct MonitorSummary {
+    pub monitor: u32,
+    pub writes: usize,
+    /// Mean green-channel output at each of COMPARE_POINTS over all writes
+    pub average_curve: Vec<f32>,
+}
+
+/// Summary of a logged session
+#[derive(Serialize, Clone)]
+pub struct RampSessionSummary {
+    pub id: String,
+    pub duration_ms: u64,
+    pub monitors: Vec<MonitorSummary>,
+    pub magnification_writes: usize,
+    /// Mean Magnification matrix over all writes
+    pub average_matrix: Option<[[f32; 5]; 5]>,
+}
+
+/// Difference of one monitor between two sessions (b minus a)
+#[derive(Serialize, Clone)]
+pub struct MonitorDiff {
+    pub monitor: u32,
+    pub writes_delta: i64,
+    /// (input level, mean output difference in 16-bit ramp units)
+    pub curve_delta: Vec<(u8, f32)>,
+}
+
+/// Result of comparing two logged sessions
+#[derive(Serialize, Clone)]
+pub struct SessionComparison {
+    pub a: RampSessionSummary,
+    pub b: RampSessionSummary,
+    pub monitors: Vec<MonitorDiff>,
+    /// Mean matrix difference, if both sessions used Magnification
+    pub matrix_delta: Option<[[f32; 5]; 5]>,
+}
+
+struct LogWriter {
+    file: File,
+    started: Instant,
+}
+
+static WRITER: Mutex<Option<LogWriter>> = Mutex::new(None);
+
+/// Session id of this run: its start time in Unix seconds
+static SESSION_ID: OnceLock<String> = OnceLock::new();
+
+fn log_dir() -> PathBuf {
+    crate::settings::config_dir().join(LOG_DIR)
+}
+
+fn session_id() -> &'static str {
+    SESSION_ID.get_or_init(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0).to_string()
+    })
+}
+
+/// Append an entry to this run's log if logging is enabled
+fn append(make_entry: impl FnOnce(u64) -> LogEntry) {
+    if !crate::settings::get().ramp_logging {
+        return;
+    }
+
+    let mut writer = WRITER.lock().unwrap();
+    if writer.is_none() {
+        let _ = std::fs::create_dir_all(log_dir());
+        let path = log_dir().join(format!("{}.jsonl", session_id()));
+        let Ok(file) = OpenOptions::new().create(true).append(true).open(path) else { return };
+        *writer = Some(LogWriter { file, started: Instant::now() });
+    }
+    let Some(writer) = writer.as_mut() else { return };
+
+    let entry = make_entry(writer.started.elapsed().as_millis() as u64);
+    if let Ok(line) = serde_json::to_string(&entry) {
+        let _ = writeln!(writer.file, "{}", line);
+    }
+}
+
+/// Log a gamma ramp applied to a monitor
+pub fn record_ramp(monitor: u32, ramp: &GammaRamp) {
+    append(|t_ms| LogEntry::Gamma {
+        t_ms,
+        monitor,
+        ramp: ramp.red.iter().chain(&ramp.green).chain(&ramp.blue).copied().collect(),
+    });
+}
+
+/// Log a Magnification color matrix
+pub fn record_matrix(effect: &MagColorEffect) {
+    append(|t_ms| LogEntry::Magnification { t_ms, matrix: effect.transform });
+}
+
+/// List logged session ids, newest first
+pub fn list_sessions() -> Vec<String> {
+    let mut ids: Vec<String> = std::fs::read_dir(log_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| e.file_name().to_str()?.strip_suffix(".jsonl").map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+    ids.sort_by(|a, b| b.cmp(a));
+    ids
+}
+
+fn read_session(id: &str) -> Result<Vec<LogEntry>, String> {
+    let file = File::open(log_dir().join(format!("{}.jsonl", id)))
+        .map_err(|e| format!("Failed to open session {}: {}", id, e))?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+/// Summarize a logged session
+pub fn summarize(id: &str) -> Result<RampSessionSummary, String> {
+    let entries = read_session(id)?;
+
+    let mut monitors: Vec<(u32, usize, Vec<f64>)> = Vec::new();
+    let mut matrix_sum = [[0.0f64; 5]; 5];
+    let mut matrix_writes = 0;
+    let mut duration_ms = 0;
+
+    for entry in &entries {
+        match entry {
+            LogEntry::Gamma { t_ms, monitor, ramp } if ramp.len() == 768 => {
+                duration_ms = duration_ms.max(*t_ms);
+                let index = match monitors.iter().position(|(m, _, _)| m == monitor) {
+                    Some(index) => index,
+                    None => {
+                        monitors.push((*monitor, 0, vec![0.0; COMPARE_POINTS.len()]));
+                        monitors.len() - 1
+                    }
+                };
+                let (_, writes, sums) = &mut monitors[index];
+                *writes += 1;
+                for (sum, point) in sums.iter_mut().zip(COMPARE_POINTS) {
+                    *sum += ramp[256 + point] as f64;
+                }
+            }
+            LogEntry::Magnification { t_ms, matrix } => {
+                duration_ms = duration_ms.max(*t_ms);
+                matrix_writes += 1;
+                for (row, values) in matrix.iter().enumerate() {
+                    for (column, value) in values.iter().enumerate() {
+                        matrix_sum[row][column] += *value as f64;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let average_matrix = (matrix_writes > 0).then(|| {
+        matrix_sum.map(|row| row.map(|sum| (sum / matrix_writes as f64) as f32))
+    });
+
+    Ok(RampSessionSummary {
+        id: id.to_string(),
+        duration_ms,
+        monitors: monitors
+            .into_iter()
+            .map(|(monitor, writes, sums)| MonitorSummary {
+                monitor,
+                writes,
+                average_curve: sums.iter().map(|sum| (sum / writes as f64) as f32).collect(),
+            })
+            .collect(),
+        magnification_writes: matrix_writes,
+        average_matrix,
+    })
+}
+
+/// Compare two logged sessions (b relative to a)
+pub fn compare_sessions(a: &str, b: &str) -> Result<SessionComparison, String> {
+    let a = summarize(a)?;
+    let b = summarize(b)?;
+
+    let mut indices: Vec<u32> = a.monitors.iter().chain(&b.monitors).map(|m| m.monitor).collect();
+    indices.sort_unstable();
+    indices.dedup();
+
+    let monitors = indices
+        .into_iter()
+        .map(|monitor| {
+            let find = |s: &RampSessionSummary| s.monitors.iter().find(|m| m.monitor == monitor).cloned();
+            let (before, after) = (find(&a), find(&b));
+            let curve = |m: &Option<MonitorSummary>, i: usize| {
+                // A monitor missing from a session counts as unadjusted (identity)
+                m.as_ref().map(|m| m.average_curve[i]).unwrap_or(COMPARE_POINTS[i] as f32 * 257.0)
+            };
+            MonitorDiff {
+                monitor,
+                writes_delta: after.as_ref().map_or(0, |m| m.writes) as i64
+                    - before.as_ref().map_or(0, |m| m.writes) as i64,
+                curve_delta: COMPARE_POINTS
+                    .iter()
+                    .enumerate()
+                    .map(|(i, point)| (*point as u8, curve(&after, i) - curve(&before, i)))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let matrix_delta = match (a.average_matrix, b.average_matrix) {
+        (Some(ma), Some(mb)) => {
+            let mut delta = [[0.0f32; 5]; 5];
+            for row in 0..5 {
+                for column in 0..5 {
+                    delta[row][column] = mb[row][column] - ma[row][column];
+                }
+            }
+            Some(delta)
+        }
+        _ => None,
+    };
+
+    Ok(SessionComparison { a, b, monitors, matrix_delta })
+}
@@ -0,0 +1,341 @@
+//! Rules engine - "when this happens, if that holds, do this" automation stored as JSON next
+//! to the settings file. Triggers and conditions share one set of predicates over the time,
+//! scene brightness, running processes and power state; a rule fires when its trigger turns true
+//! and all its conditions hold, and covers what the schedule, profiles and power hooks do alone
+
+use crate::i18n;
+use crate::migration::{self, Migration};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Rule store file name
+pub const RULES_FILE: &str = "rules.json";
+
+/// Upgrades from each older rule store schema, index N migrates version N to N + 1
+const MIGRATIONS: &[Migration] = &[];
+
+/// How often rules are evaluated
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Scene brightness readings older than this are ignored
+const READING_MAX_AGE_MS: u64 = 10_000;
+
+/// Something that is either true or false at a given moment
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Predicate {
+    /// Local time is within [from, to) hours, wrapping past midnight when from > to
+    Time { from: u8, to: u8 },
+    /// Latest sensor reading is within the given bounds (0.0 - 1.0)
+    Brightness {
+        #[serde(default)]
+        below: Option<f32>,
+        #[serde(default)]
+        above: Option<f32>,
+    },
+    /// A process with this executable name is running
+    Process { name: String },
+    /// The focused window belongs to this executable
+    Foreground { process: String },
+    /// Running on battery power
+    OnBattery,
+    /// Battery charge is below this percentage
+    Battery { below: u8 },
+    /// Night vision is applied
+    Engaged,
+    /// The inner predicate is false, e.g. a process exited
+    Not { predicate: Box<Predicate> },
+}
+
+/// What a rule does when it fires
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    /// Apply a game profile by name
+    ApplyProfile { name: String },
+    /// Deactivate a game profile by name
+    ReleaseProfile { name: String },
+    /// Set the intensity on a monitor or group (None = active profile's monitor or the primary)
+    SetIntensity {
+        intensity: f32,
+        #[serde(default)]
+        monitor: Option<crate::monitor_groups::Target>,
+    },
+    /// Restore every display
+    Disable,
+    /// Show a message to the user
+    Notify { message: String },
+}
+
+/// A rule: trigger, conditions, actions
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Rule {
+    /// Unique display name
+    pub name: String,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Fires the rule when it turns true
+    pub trigger: Predicate,
+    /// Must all hold when the trigger fires
+    #[serde(default)]
+    pub conditions: Vec<Predicate>,
+    /// Carried out in order
+    pub actions: Vec<Action>,
+}
+
+/// Message from a Notify action, shown by the UI
+#[derive(Serialize, Clone, Debug)]
+pub struct Notification {
+    pub rule: String,
+    pub message: String,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// On-disk layout of the rule store
+#[derive(Serialize, Deserialize)]
+struct RuleStore {
+    version: u32,
+    rules: Vec<Rule>,
+}
+
+/// State predicates are evaluated against, read once per pass
+struct Snapshot {
+    hour: u8,
+    brightness: Option<f32>,
+    processes: Vec<String>,
+    foreground: Option<String>,
+    power: Option<PowerStatus>,
+    engaged: bool,
+}
+
+/// Power source and battery charge
+#[derive(Clone, Copy)]
+struct PowerStatus {
+    on_battery: bool,
+    /// Battery charge in percent, if there is a battery
+    percent: Option<u8>,
+}
+
+static RULES: Mutex<Option<Vec<Rule>>> = Mutex::new(None);
+
+fn rules_path() -> PathBuf {
+    crate::settings::config_dir().join(RULES_FILE)
+}
+
+fn read() -> Result<Option<Vec<Rule>>, String> {
+    let path = rules_path();
+    let Some(value) = migration::read(&path, MIGRATIONS)? else { return Ok(None) };
+    serde_json::from_value::<RuleStore>(value)
+        .map(|store| Some(store.rules))
+        .map_err(|e| format!("Invalid rules in {}: {}", path.display(), e))
+}
+
+/// Stored rules, none if the file is missing
+/// An unreadable file is set aside first so saving doesn't overwrite the only copy
+fn load() -> Vec<Rule> {
+    read()
+        .unwrap_or_else(|e| {
+            migration::set_aside(&rules_path(), &e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+fn save(rules: &[Rule]) -> Result<(), String> {
+    let store = RuleStore {
+        version: MIGRATIONS.len() as u32,
+        rules: rules.to_vec(),
+    };
+    let json = serde_json::to_string_pretty(&store)
+        .map_err(|e| format!("Failed to serialize rules: {}", e))?;
+    crate::config_lock::write_file(&rules_path(), &json)
+}
+
+fn validate_predicate(predicate: &Predicate) -> Result<(), String> {
+    match predicate {
+        Predicate::Time { from, to } if *from > 23 || *to > 23 => Err(i18n::text("error.rule_hours")),
+        Predicate::Brightness { below, above } => {
+            if below.is_none() && above.is_none() {
+                return Err(i18n::text("error.rule_brightness_bound"));
+            }
+            if let Some(bound) = [below, above].into_iter().flatten().find(|b| !(0.0..=1.0).contains(*b)) {
+                return Err(i18n::format("error.unit_interval", &[&i18n::text("field.brightness"), bound]));
+            }
+            Ok(())
+        }
+        Predicate::Process { name } | Predicate::Foreground { process: name } if name.trim().is_empty() => {
+            Err(i18n::text("error.rule_process_empty"))
+        }
+        Predicate::Battery { below } if *below > 100 => Err(i18n::text("error.rule_battery")),
+        Predicate::Not { predicate } => validate_predicate(predicate),
+        _ => Ok(()),
+    }
+}
+
+fn validate_action(action: &Action) -> Result<(), String> {
+    match action {
+        Action::ApplyProfile { name } | Action::ReleaseProfile { name } if crate::profiles::find_by_name(name).is_none() => {
+            Err(i18n::format("error.profile_not_found", &[name]))
+        }
+        Action::SetIntensity { intensity, .. } if !(0.0..=1.0).contains(intensity) => {
+            Err(i18n::format("error.unit_interval", &[&i18n::text("field.intensity"), intensity]))
+        }
+        Action::Notify { message } if message.trim().is_empty() => Err(i18n::text("error.rule_message_empty")),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate(rules: &[Rule]) -> Result<(), String> {
+    for (i, rule) in rules.iter().enumerate() {
+        if rule.name.trim().is_empty() {
+            return Err(i18n::text("error.rule_name_empty"));
+        }
+        if rules[..i].iter().any(|r| r.name == rule.name) {
+            return Err(i18n::format("error.rule_name_repeated", &[&rule.name]));
+        }
+        if rule.actions.is_empty() {
+            return Err(i18n::format("error.rule_no_actions", &[&rule.name]));
+        }
+        std::iter::once(&rule.trigger)
+            .chain(&rule.conditions)
+            .try_for_each(validate_predicate)
+            .map_err(|e| i18n::format("error.rule", &[&rule.name, &e]))?;
+        rule.actions.iter().try_for_each(validate_action).map_err(|e| i18n::format("error.rule", &[&rule.name, &e]))?;
+    }
+    Ok(())
+}
+
+/// Get all stored rules
+pub fn list() -> Vec<Rule> {
+    RULES.lock().unwrap().get_or_insert_with(load).clone()
+}
+
+/// Replace the stored rules
+pub fn set(rules: Vec<Rule>) -> Result<(), String> {
+    validate(&rules)?;
+    save(&rules)?;
+    *RULES.lock().unwrap() = Some(rules);
+    Ok(())
+}
+
+/// Re-read rules from disk, e.g. after the file was edited externally
+/// Keeps the current rules if the file is missing or invalid (such as mid-save)
+pub fn reload() -> Option<Vec<Rule>> {
+    let rules = read().ok().flatten()?;
+    *RULES.lock().unwrap() = Some(rules.clone());
+    Some(rules)
+}
+
+#[cfg(windows)]
+fn power_status() -> Option<PowerStatus> {
+    /// SYSTEM_POWER_STATUS structure
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    const NO_SYSTEM_BATTERY: u8 = 128;
+    const UNKNOWN: u8 = 255;
+
+    let mut status = SystemPowerStatus::default();
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    let has_battery = status.battery_flag & NO_SYSTEM_BATTERY == 0 && status.battery_flag != UNKNOWN;
+    Some(PowerStatus {
+        on_battery: status.ac_line_status == 0,
+        percent: (has_battery && status.battery_life_percent != UNKNOWN).then_some(status.battery_life_percent),
+    })
+}
+
+#[cfg(not(windows))]
+fn power_status() -> Option<PowerStatus> {
+    None
+}
+
+fn snapshot() -> Snapshot {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+    let brightness = crate::history::get()
+        .readings
+        .last()
+        .filter(|r| now.saturating_sub(r.at_ms) <= READING_MAX_AGE_MS)
+        .map(|r| r.brightness);
+    Snapshot {
+        hour: crate::schedule::local_hour(),
+        brightness,
+        processes: crate::allowlist::running_processes(),
+        foreground: crate::foreground::current().map(|app| app.process),
+        power: power_status(),
+        engaged: crate::night_mode::current().engaged,
+    }
+}
+
+fn holds(predicate: &Predicate, now: &Snapshot) -> bool {
+    match predicate {
+        Predicate::Time { from, to } if from <= to => (*from..*to).contains(&now.hour),
+        Predicate::Time { from, to } => now.hour >= *from || now.hour < *to,
+        Predicate::Brightness { below, above } => now
+            .brightness
+            .is_some_and(|b| below.is_none_or(|below| b < below) && above.is_none_or(|above| b > above)),
+        Predicate::Process { name } => now.processes.iter().any(|p| p.eq_ignore_ascii_case(name)),
+        Predicate::Foreground { process } => now.foreground.as_ref().is_some_and(|p| p.eq_ignore_ascii_case(process)),
+        Predicate::OnBattery => now.power.is_some_and(|p| p.on_battery),
+        Predicate::Battery { below } => now.power.and_then(|p| p.percent).is_some_and(|percent| percent < *below),
+        Predicate::Engaged => now.engaged,
+        Predicate::Not { predicate } => !holds(predicate, now),
+    }
+}
+
+/// Start a background thread that evaluates rules, passing each action of a firing rule to
+/// `run` along with the rule's name
+/// Triggers already true at startup or when a rule is added don't fire until they turn true again
+pub fn start<F>(run: F)
+where
+    F: Fn(&str, &Action) -> Result<(), String> + Send + 'static,
+{
+    thread::spawn(move || {
+        // Trigger state per rule, keyed by the whole rule so an edited rule starts over
+        let mut last: Vec<(Rule, bool)> = Vec::new();
+        while !crate::shutdown::is_shutting_down() {
+            let rules: Vec<Rule> = list().into_iter().filter(|r| r.enabled).collect();
+            if !rules.is_empty() && !crate::safe_mode::is_active() {
+                let now = snapshot();
+                let mut next = Vec::with_capacity(rules.len());
+                for rule in rules {
+                    let triggered = holds(&rule.trigger, &now);
+                    let was = last.iter().find(|(r, _)| *r == rule).map(|(_, t)| *t).unwrap_or(triggered);
+                    if triggered && !was && rule.conditions.iter().all(|c| holds(c, &now)) {
+                        crate::crash::log(format!("Rule {} fired", rule.name));
+                        for action in &rule.actions {
+                            if let Err(e) = run(&rule.name, action) {
+                                crate::crash::log(format!("Rule {}: {}", rule.name, e));
+                            }
+                        }
+                    }
+                    next.push((rule, triggered));
+                }
+                last = next;
+            } else {
+                last.clear();
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
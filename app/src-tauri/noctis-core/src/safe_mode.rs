@@ -0,0 +1,48 @@
+//! Safe mode - the `--safe-mode` flag starts with the display reset and every effect off
+//! For recovering from a saved configuration that makes the screen unusable: nothing saved
+//! is applied until the user leaves safe mode from the UI. The emergency reset enters it too
+
+use crate::{gamma, icc_profile, magnification, suspend};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Command line flag that starts Noctis in safe mode
+const SAFE_MODE_FLAG: &str = "--safe-mode";
+
+/// Suspension reason held while in safe mode
+const REASON: &str = "safe_mode";
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Check whether Noctis is running in safe mode
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Reset every monitor (gamma, Magnification and color profiles) and hold effects off
+/// until the user leaves safe mode. Returns Some(true) if effects were suspended by this call
+pub fn enter() -> Option<bool> {
+    ACTIVE.store(true, Ordering::SeqCst);
+    gamma::restore_all();
+    let _ = magnification::remove_effects();
+    icc_profile::remove_all();
+    suspend::suspend(REASON).then_some(true)
+}
+
+/// Enter safe mode if the flag was given, returning whether it was
+pub fn start() -> bool {
+    if !std::env::args().any(|arg| arg == SAFE_MODE_FLAG) {
+        return false;
+    }
+    crate::crash::log("Started in safe mode");
+    enter();
+    true
+}
+
+/// Leave safe mode and allow effects again
+/// Returns Some(false) if effects resumed, None if nothing changed
+pub fn exit() -> Option<bool> {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return None;
+    }
+    suspend::resume(REASON).then_some(false)
+}
@@ -0,0 +1,191 @@
+//! Schedule - caps auto-adjust intensity by time of day, with separate weekday and weekend days
+//! Each cap holds from its hour until the next one, so lower ceilings in the early evening
+//! keep the lift gentle before bedtime and full strength returns at night
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum intensity from an hour of the day on
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct IntensityCap {
+    /// Local hour (0 - 23) the cap starts at
+    pub from_hour: u8,
+    /// Highest intensity auto-adjust may apply (0.0 - 1.0)
+    pub max_intensity: f32,
+}
+
+/// Day of the week
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// Day from its index counted from Sunday = 0, as Windows reports it
+    fn from_sunday_index(index: u16) -> Weekday {
+        Self::ALL[(index as usize + 6) % 7]
+    }
+
+    fn previous(self) -> Weekday {
+        Self::ALL[(self as usize + 6) % 7]
+    }
+
+    fn is_weekend(self) -> bool {
+        matches!(self, Weekday::Saturday | Weekday::Sunday)
+    }
+}
+
+/// Caps for one day of the week, replacing the weekday or weekend caps on that day
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct DayOverride {
+    pub day: Weekday,
+    pub caps: Vec<IntensityCap>,
+}
+
+/// Time-of-day schedule configuration
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScheduleSettings {
+    pub enabled: bool,
+    /// Caps from Monday to Friday, in any order; the last one of the day carries over past midnight
+    #[serde(alias = "caps")]
+    pub weekday: Vec<IntensityCap>,
+    /// Caps on Saturday and Sunday
+    pub weekend: Vec<IntensityCap>,
+    pub overrides: Vec<DayOverride>,
+}
+
+impl Default for ScheduleSettings {
+    fn default() -> Self {
+        let cap = |from_hour, max_intensity| IntensityCap { from_hour, max_intensity };
+        Self {
+            enabled: false,
+            weekday: vec![cap(6, 1.0), cap(17, 0.5), cap(20, 0.75), cap(22, 1.0)],
+            // Later gaming sessions: the gentle evening caps start later
+            weekend: vec![cap(0, 1.0), cap(19, 0.5), cap(22, 0.75)],
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl ScheduleSettings {
+    /// Caps that apply on a day: its override, else the weekday or weekend caps
+    pub fn caps_for(&self, day: Weekday) -> &[IntensityCap] {
+        match self.overrides.iter().find(|o| o.day == day) {
+            Some(o) => &o.caps,
+            None if day.is_weekend() => &self.weekend,
+            None => &self.weekday,
+        }
+    }
+
+    /// Cap in force at an hour of a day: the latest one started by then, else the last one
+    /// of the previous day. No caps means no limit
+    pub fn cap_at(&self, day: Weekday, hour: u8) -> f32 {
+        self.caps_for(day)
+            .iter()
+            .filter(|c| c.from_hour <= hour)
+            .max_by_key(|c| c.from_hour)
+            .or_else(|| self.caps_for(day.previous()).iter().max_by_key(|c| c.from_hour))
+            .map(|c| c.max_intensity)
+            .unwrap_or(1.0)
+    }
+}
+
+/// Check that every cap has a valid hour, no hour is listed twice in a day,
+/// and each day has at most one override
+pub fn validate(config: &ScheduleSettings) -> Result<(), String> {
+    let lists = [&config.weekday, &config.weekend].into_iter().chain(config.overrides.iter().map(|o| &o.caps));
+    for caps in lists {
+        for (i, cap) in caps.iter().enumerate() {
+            if cap.from_hour > 23 {
+                return Err(crate::i18n::format("error.schedule_hour", &[&cap.from_hour]));
+            }
+            if caps[..i].iter().any(|c| c.from_hour == cap.from_hour) {
+                return Err(crate::i18n::format("error.schedule_hour_repeated", &[&cap.from_hour]));
+            }
+        }
+    }
+    for (i, o) in config.overrides.iter().enumerate() {
+        if config.overrides[..i].iter().any(|other| other.day == o.day) {
+            return Err(crate::i18n::format("error.schedule_override_repeated", &[&format!("{:?}", o.day)]));
+        }
+    }
+    Ok(())
+}
+
+/// Every cap in the schedule, for range checks
+pub fn all_caps(config: &ScheduleSettings) -> impl Iterator<Item = &IntensityCap> {
+    config.weekday.iter().chain(&config.weekend).chain(config.overrides.iter().flat_map(|o| &o.caps))
+}
+
+/// Current local day and minute of the day
+#[cfg(windows)]
+fn local_time() -> (Weekday, u16) {
+    /// SYSTEMTIME structure
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemTime {
+        year: u16,
+        month: u16,
+        day_of_week: u16,
+        day: u16,
+        hour: u16,
+        minute: u16,
+        second: u16,
+        milliseconds: u16,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetLocalTime(time: *mut SystemTime);
+    }
+
+    let mut time = SystemTime::default();
+    unsafe { GetLocalTime(&mut time) };
+    (Weekday::from_sunday_index(time.day_of_week), time.hour * 60 + time.minute)
+}
+
+/// Current day and minute of the day (UTC, no time zone database off Windows)
+#[cfg(not(windows))]
+fn local_time() -> (Weekday, u16) {
+    let secs = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    // 1970-01-01 was a Thursday
+    let day = (secs / 86400 + 4) % 7;
+    (Weekday::from_sunday_index(day as u16), (secs / 60 % 1440) as u16)
+}
+
+/// Highest intensity auto-adjust may apply right now (1.0 while the schedule is off)
+pub fn current_cap() -> f32 {
+    let config = crate::settings::get().schedule;
+    if !config.enabled {
+        return 1.0;
+    }
+    let (day, minute) = local_time();
+    config.cap_at(day, (minute / 60) as u8)
+}
+
+/// Current local hour (0 - 23)
+pub fn local_hour() -> u8 {
+    (local_time().1 / 60) as u8
+}
+
+/// Current local minute of the day (0 - 1439)
+pub fn local_minute() -> u16 {
+    local_time().1
+}
@@ -0,0 +1,94 @@
+//! Clean screenshots - captures the desktop as it looks without Noctis's adjustments
+//! Effects are suspended for just the capture (a couple of frames plus the blit) and
+//! come back before the PNG is encoded, so sharing a screenshot needs no manual toggling
+
+use serde::{Deserialize, Serialize};
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Suspension reason held during the capture
+pub const REASON: &str = "screenshot";
+
+/// Wait after suspending for the compositor to present an unadjusted frame
+const SETTLE: Duration = Duration::from_millis(35);
+
+/// Clean screenshot settings
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ScreenshotSettings {
+    /// Hotkey that takes a clean screenshot, e.g. "Ctrl+Alt+P" (empty = none)
+    pub hotkey: String,
+    /// Folder screenshots are saved to (empty = Pictures\Noctis)
+    pub folder: String,
+}
+
+pub fn validate(config: &ScreenshotSettings) -> Result<(), String> {
+    if !config.hotkey.is_empty() {
+        config.hotkey.parse::<crate::hotkey::Hotkey>().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// A captured desktop, top-down BGRA
+pub struct Frame {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// Bounds of all monitors together
+fn desktop_bounds() -> Result<(i32, i32, i32, i32), String> {
+    let monitors = crate::gamma::get_monitors();
+    if monitors.is_empty() {
+        return Err(crate::i18n::text("error.no_monitors"));
+    }
+    let left = monitors.iter().map(|m| m.x).min().unwrap_or(0);
+    let top = monitors.iter().map(|m| m.y).min().unwrap_or(0);
+    let right = monitors.iter().map(|m| m.x + m.width as i32).max().unwrap_or(0);
+    let bottom = monitors.iter().map(|m| m.y + m.height as i32).max().unwrap_or(0);
+    Ok((left, top, right - left, bottom - top))
+}
+
+/// Capture the whole desktop with effects suspended for the moment it takes
+pub fn capture() -> Result<Frame, String> {
+    let (left, top, width, height) = desktop_bounds()?;
+    if crate::suspend::suspend(REASON) {
+        thread::sleep(SETTLE);
+    }
+    let pixels = crate::sensor::capture_region(left, top, width, height);
+    crate::suspend::resume(REASON);
+    Ok(Frame { width: width as u32, height: height as u32, pixels: pixels? })
+}
+
+/// Folder screenshots go to
+fn folder() -> PathBuf {
+    let config = crate::settings::get().screenshot;
+    if !config.folder.is_empty() {
+        return PathBuf::from(config.folder);
+    }
+    match std::env::var_os("USERPROFILE") {
+        Some(profile) => PathBuf::from(profile).join("Pictures").join("Noctis"),
+        None => crate::settings::config_dir().join("screenshots"),
+    }
+}
+
+/// Write a frame as a PNG in the screenshot folder, returning its path
+pub fn save(frame: &Frame) -> Result<PathBuf, String> {
+    let folder = folder();
+    std::fs::create_dir_all(&folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    let path = folder.join(format!("noctis-{}.png", now));
+
+    let file = std::fs::File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), frame.width, frame.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let rgb: Vec<u8> = frame.pixels.chunks_exact(4).flat_map(|bgra| [bgra[2], bgra[1], bgra[0]]).collect();
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&rgb))
+        .map_err(|e| format!("Failed to write screenshot: {}", e))?;
+    Ok(path)
+}
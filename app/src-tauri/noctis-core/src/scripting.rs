@@ -0,0 +1,208 @@
+//! Scripting hooks (opt-in) - runs a Rhai script from the config folder for custom adjustment
+//! logic. The script only sees a small sandboxed API over the controller: no file, process or
+//! network access, no imports or eval, and a cap on the work done per call. It is reloaded
+//! when the file changes
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Script file name, in the config folder
+pub const SCRIPT_FILE: &str = "script.rhai";
+
+/// Scripting settings
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct ScriptSettings {
+    pub enabled: bool,
+    /// How often `on_tick` runs in ms
+    pub interval_ms: u64,
+}
+
+impl Default for ScriptSettings {
+    fn default() -> Self {
+        Self { enabled: false, interval_ms: 1000 }
+    }
+}
+
+pub fn validate(config: &ScriptSettings) -> Result<(), String> {
+    if !(100..=60_000).contains(&config.interval_ms) {
+        return Err(crate::i18n::text("error.script_interval"));
+    }
+    Ok(())
+}
+
+/// Script state reported to the UI
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct ScriptStatus {
+    /// The script compiled and is running
+    pub running: bool,
+    /// Last compile or runtime error
+    pub error: Option<String>,
+}
+
+static STATUS: Mutex<ScriptStatus> = Mutex::new(ScriptStatus { running: false, error: None });
+
+/// Path of the user's script
+pub fn script_path() -> PathBuf {
+    crate::settings::config_dir().join(SCRIPT_FILE)
+}
+
+/// Current script state
+pub fn status() -> ScriptStatus {
+    STATUS.lock().unwrap().clone()
+}
+
+/// Rhai engine and the script thread, left out of builds without the scripting feature
+#[cfg(feature = "scripting")]
+mod runtime {
+    use super::{script_path, ScriptStatus, SCRIPT_FILE, STATUS};
+    use rhai::{Dynamic, Engine, Scope, AST};
+    use std::thread;
+    use std::time::{Duration, SystemTime};
+
+    /// Operations a single hook call may run before it is stopped
+    const MAX_OPERATIONS: u64 = 200_000;
+
+    /// How often a disabled script checks whether it was turned on
+    const IDLE_INTERVAL: Duration = Duration::from_secs(2);
+
+    fn set_status(running: bool, error: Option<String>) {
+        if let Some(e) = &error {
+            crate::crash::log(format!("Script: {}", e));
+        }
+        *STATUS.lock().unwrap() = ScriptStatus { running, error };
+    }
+
+    /// Engine with the controller API and the sandbox limits
+    fn engine() -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(4096);
+        engine.set_max_array_size(1024);
+        engine.set_max_map_size(256);
+        engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.on_print(|text| crate::crash::log(format!("Script: {}", text)));
+        engine.on_debug(|text, _, _| crate::crash::log(format!("Script: {}", text)));
+
+        // Latest scene brightness (0.0 - 1.0), or () without a recent reading
+        engine.register_fn("brightness", || -> Dynamic {
+            crate::history::get().readings.last().map_or(Dynamic::UNIT, |r| Dynamic::from_float(r.brightness as f64))
+        });
+        // Strongest intensity applied by any source
+        engine.register_fn("intensity", || crate::night_mode::current().intensity as f64);
+        engine.register_fn("set_intensity", |intensity: f64| set_intensity(intensity, None));
+        engine.register_fn("set_intensity", |intensity: f64, monitor: i64| set_intensity(intensity, Some(monitor)));
+        // Executable of the focused window, "" if unknown
+        engine.register_fn("foreground", || crate::foreground::current().map(|app| app.process).unwrap_or_default());
+        engine.register_fn("hour", || crate::schedule::local_hour() as i64);
+        engine.register_fn("profile", || -> Dynamic {
+            crate::profiles::active().map_or(Dynamic::UNIT, |p| p.name.into())
+        });
+        engine
+    }
+
+    /// Set the intensity on a monitor (default: active profile's monitor or the primary)
+    fn set_intensity(intensity: f64, monitor: Option<i64>) -> Result<(), Box<rhai::EvalAltResult>> {
+        let monitor = match monitor {
+            Some(m) => u32::try_from(m).map_err(|_| format!("Invalid monitor {}", m))?,
+            None => crate::profiles::active().map(|p| p.monitor).unwrap_or(0),
+        };
+        crate::gamma::set_gamma((intensity as f32).clamp(0.0, 1.0), monitor).map_err(|e| e.into())
+    }
+
+    /// A compiled script and the variables its top level left behind
+    struct Loaded {
+        ast: AST,
+        scope: Scope<'static>,
+        modified: Option<SystemTime>,
+    }
+
+    fn modified() -> Option<SystemTime> {
+        std::fs::metadata(script_path()).and_then(|m| m.modified()).ok()
+    }
+
+    /// Compile the script and run its top level once
+    fn load(engine: &Engine) -> Result<Loaded, String> {
+        let modified = modified();
+        let source = std::fs::read_to_string(script_path()).map_err(|e| format!("Failed to read {}: {}", SCRIPT_FILE, e))?;
+        let ast = engine.compile(&source).map_err(|e| e.to_string())?;
+        let mut scope = Scope::new();
+        engine.run_ast_with_scope(&mut scope, &ast).map_err(|e| e.to_string())?;
+        Ok(Loaded { ast, scope, modified })
+    }
+
+    /// Call a hook if the script defines it
+    fn call(engine: &Engine, loaded: &mut Loaded, hook: &str, args: impl rhai::FuncArgs) -> Result<(), String> {
+        if !loaded.ast.iter_functions().any(|f| f.name == hook) {
+            return Ok(());
+        }
+        engine
+            .call_fn::<Dynamic>(&mut loaded.scope, &loaded.ast, hook, args)
+            .map(|_| ())
+            .map_err(|e| format!("{}: {}", hook, e))
+    }
+
+    /// Start a background thread that runs the script's hooks while scripting is enabled:
+    /// `on_tick()` every interval and `on_foreground(process)` when the focused app changes
+    pub fn start() {
+        thread::spawn(|| {
+            let engine = engine();
+            let mut loaded: Option<Loaded> = None;
+            let mut failed_at: Option<Option<SystemTime>> = None;
+            let mut foreground = String::new();
+
+            while !crate::shutdown::is_shutting_down() {
+                let config = crate::settings::get().scripting;
+                if !config.enabled || crate::safe_mode::is_active() {
+                    if loaded.take().is_some() || failed_at.take().is_some() {
+                        set_status(false, None);
+                    }
+                    thread::sleep(IDLE_INTERVAL);
+                    continue;
+                }
+
+                // Reload after an edit; a broken script waits for the next one
+                let current = modified();
+                if loaded.as_ref().is_some_and(|l| l.modified != current) || (loaded.is_none() && failed_at != Some(current)) {
+                    match load(&engine) {
+                        Ok(script) => {
+                            loaded = Some(script);
+                            failed_at = None;
+                            set_status(true, None);
+                        }
+                        Err(e) => {
+                            loaded = None;
+                            failed_at = Some(current);
+                            set_status(false, Some(e));
+                        }
+                    }
+                }
+
+                if let Some(script) = loaded.as_mut() {
+                    let app = crate::foreground::current().map(|app| app.process).unwrap_or_default();
+                    let mut result = Ok(());
+                    if app != foreground {
+                        foreground = app.clone();
+                        result = call(&engine, script, "on_foreground", (app,));
+                    }
+                    let result = result.and_then(|_| call(&engine, script, "on_tick", ()));
+                    // Runtime errors are reported but the script keeps running
+                    if let Err(e) = result {
+                        set_status(true, Some(e));
+                    }
+                }
+                thread::sleep(Duration::from_millis(config.interval_ms));
+            }
+        });
+    }
+}
+
+#[cfg(feature = "scripting")]
+pub use runtime::start;
+
+#[cfg(not(feature = "scripting"))]
+pub fn start() {}
@@ -0,0 +1,709 @@
+//! Screen brightness sensor - Raw Windows FFI implementation
+//! Uses direct linkage to gdi32.dll and user32.dll
+
+use serde::{Deserialize, Serialize};
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::{Mutex, OnceLock};
+
+// GDI constants
+const SRCCOPY: u32 = 0x00CC0020;
+const BI_RGB: u32 = 0;
+const DIB_RGB_COLORS: u32 = 0;
+const HALFTONE: i32 = 4;
+
+// Bitmap info structures
+#[repr(C)]
+struct BitmapInfoHeader {
+    bi_size: u32,
+    bi_width: i32,
+    bi_height: i32,
+    bi_planes: u16,
+    bi_bit_count: u16,
+    bi_compression: u32,
+    bi_size_image: u32,
+    bi_x_pels_per_meter: i32,
+    bi_y_pels_per_meter: i32,
+    bi_clr_used: u32,
+    bi_clr_important: u32,
+}
+
+#[repr(C)]
+struct RgbQuad {
+    blue: u8,
+    green: u8,
+    red: u8,
+    reserved: u8,
+}
+
+#[repr(C)]
+struct BitmapInfo {
+    bmi_header: BitmapInfoHeader,
+    bmi_colors: [RgbQuad; 1],
+}
+
+/// RECT structure
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct Rect {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+type EnumWindowsProc = unsafe extern "system" fn(*mut c_void, isize) -> i32;
+
+type HDC = *mut c_void;
+type HBITMAP = *mut c_void;
+type HGDIOBJ = *mut c_void;
+
+#[cfg(windows)]
+#[link(name = "gdi32")]
+extern "system" {
+    fn CreateCompatibleDC(hdc: HDC) -> HDC;
+    fn CreateCompatibleBitmap(hdc: HDC, cx: i32, cy: i32) -> HBITMAP;
+    fn SelectObject(hdc: HDC, h: HGDIOBJ) -> HGDIOBJ;
+    fn BitBlt(hdc: HDC, x: i32, y: i32, cx: i32, cy: i32, hdc_src: HDC, x1: i32, y1: i32, rop: u32) -> i32;
+    fn StretchBlt(hdc: HDC, x: i32, y: i32, cx: i32, cy: i32, hdc_src: HDC, x1: i32, y1: i32, cx1: i32, cy1: i32, rop: u32) -> i32;
+    fn SetStretchBltMode(hdc: HDC, mode: i32) -> i32;
+    fn GetDIBits(hdc: HDC, hbm: HBITMAP, start: u32, clines: u32, lpv_bits: *mut c_void, lpbmi: *mut BitmapInfo, usage: u32) -> i32;
+    fn DeleteObject(ho: HGDIOBJ) -> i32;
+    fn DeleteDC(hdc: HDC) -> i32;
+    fn GetPixel(hdc: HDC, x: i32, y: i32) -> u32;
+}
+
+#[cfg(windows)]
+#[link(name = "user32")]
+extern "system" {
+    fn GetDC(hwnd: *mut c_void) -> HDC;
+    fn ReleaseDC(hwnd: *mut c_void, hdc: HDC) -> i32;
+    fn GetSystemMetrics(n_index: i32) -> i32;
+    fn SetWindowDisplayAffinity(hwnd: *mut c_void, affinity: u32) -> i32;
+    fn GetWindowDisplayAffinity(hwnd: *mut c_void, affinity: *mut u32) -> i32;
+    fn EnumWindows(callback: EnumWindowsProc, l_param: isize) -> i32;
+    fn GetWindowThreadProcessId(hwnd: *mut c_void, process_id: *mut u32) -> u32;
+    fn IsWindowVisible(hwnd: *mut c_void) -> i32;
+    fn IsIconic(hwnd: *mut c_void) -> i32;
+    fn GetWindowRect(hwnd: *mut c_void, rect: *mut Rect) -> i32;
+}
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetLastError() -> u32;
+    fn GetCurrentProcessId() -> u32;
+}
+
+// Window content is left out of screen captures (Windows 10 2004+)
+const WDA_EXCLUDEFROMCAPTURE: u32 = 0x11;
+
+const SM_CXSCREEN: i32 = 0;
+const SM_CYSCREEN: i32 = 1;
+
+// GetPixel failure value
+const CLR_INVALID: u32 = 0xFFFFFFFF;
+
+/// Patch layout as fractions of the monitor: corners, center and the rule-of-thirds points
+const PATCH_CENTERS: [(f32, f32); 9] = [
+    (0.1, 0.1),
+    (0.9, 0.1),
+    (0.1, 0.9),
+    (0.9, 0.9),
+    (0.5, 0.5),
+    (1.0 / 3.0, 1.0 / 3.0),
+    (2.0 / 3.0, 1.0 / 3.0),
+    (1.0 / 3.0, 2.0 / 3.0),
+    (2.0 / 3.0, 2.0 / 3.0),
+];
+
+/// Downscaled frame the patches are cut from, about 1/12 of a 1080p monitor per axis
+const PATCH_FRAME_WIDTH: i32 = 160;
+const PATCH_FRAME_HEIGHT: i32 = 90;
+
+/// Width of the whole-monitor frame: 64x36 on a 16:9 panel, as cheap to analyze as
+/// the center crop
+const FULL_FRAME_WIDTH: i32 = 64;
+
+/// Which part of a monitor the sensor measures
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorRegion {
+    /// A 100x100 crop at the center
+    #[default]
+    Center,
+    /// Patches spread across the monitor, see PATCH_CENTERS
+    Patches,
+    /// The whole monitor, downscaled to FULL_FRAME_WIDTH
+    Full,
+}
+
+/// How a pixel's brightness is measured
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BrightnessScale {
+    /// Perceived lightness (CIE L*) of linear luminance, so 0.5 is mid-gray
+    #[default]
+    Perceptual,
+    /// Mean of the gamma-encoded channels, as measured before
+    Encoded,
+}
+
+/// How the sensor reads the screen
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureBackend {
+    /// GDI BitBlt of the desktop
+    #[default]
+    Gdi,
+    /// Windows.Graphics.Capture, see graphics_capture
+    GraphicsCapture,
+}
+
+/// Sensor configuration
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct SensorSettings {
+    pub region: SensorRegion,
+    pub backend: CaptureBackend,
+    /// Measure the foreground window instead of the monitor (Graphics Capture only)
+    pub capture_window: bool,
+    pub scale: BrightnessScale,
+    /// Time between readings of the auto-adjust loop
+    pub interval_ms: u64,
+}
+
+impl Default for SensorSettings {
+    fn default() -> Self {
+        Self {
+            region: SensorRegion::default(),
+            backend: CaptureBackend::default(),
+            capture_window: false,
+            scale: BrightnessScale::default(),
+            interval_ms: 100,
+        }
+    }
+}
+
+/// Reject a zero sampling interval, which would run captures back to back
+pub fn validate(config: &SensorSettings) -> Result<(), String> {
+    if config.interval_ms == 0 {
+        return Err(crate::i18n::text("error.sampling_interval"));
+    }
+    Ok(())
+}
+
+/// Color picker result: what was rendered vs. what the display shows after our adjustments
+#[derive(serde::Serialize, Clone)]
+pub struct PixelSample {
+    pub x: i32,
+    pub y: i32,
+    pub monitor: Option<u32>,
+    /// Captured color before any Noctis transform [R, G, B]
+    pub raw: [u8; 3],
+    /// Color after the active Magnification matrix and gamma ramp [R, G, B]
+    pub transformed: [u8; 3],
+}
+
+/// Whether the sensor is currently able to capture
+#[derive(serde::Serialize, Clone, PartialEq, Default)]
+pub struct SensorStatus {
+    /// The last capture succeeded
+    pub ok: bool,
+    /// Why the last capture failed
+    pub error: Option<String>,
+}
+
+static STATUS: Mutex<Option<SensorStatus>> = Mutex::new(None);
+
+/// Get the sensor status from the most recent capture (not ok if nothing was captured yet)
+pub fn status() -> SensorStatus {
+    STATUS.lock().unwrap().clone().unwrap_or_default()
+}
+
+/// Last successful reading per monitor origin, repeated while sensing is paused
+static LAST_READINGS: Mutex<Vec<((i32, i32), f32)>> = Mutex::new(Vec::new());
+
+/// The reading to report while the sample area is covered by a Noctis window: the last one,
+/// so the current adjustment holds while the user tunes it
+fn held_reading(monitor_x: i32, monitor_y: i32) -> Result<f32, String> {
+    LAST_READINGS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(origin, _)| *origin == (monitor_x, monitor_y))
+        .map(|(_, brightness)| *brightness)
+        .ok_or_else(|| crate::i18n::text("error.sample_area_covered"))
+}
+
+/// Measure average brightness of a monitor region, tracking the sensor status
+/// Returns 0.0 (black) to 1.0 (white)
+pub fn get_screen_brightness(monitor_x: i32, monitor_y: i32, monitor_w: i32, monitor_h: i32) -> Result<f32, String> {
+    // Graphics Capture can't reach an elevated game's window from here, but the broker can
+    let result = if crate::frame_guard::is_paused() {
+        Err(crate::i18n::text("error.sensing_paused_frame_guard"))
+    } else if crate::elevation::use_broker(crate::foreground::current().as_ref()) {
+        crate::elevation::brightness(monitor_x, monitor_y, monitor_w, monitor_h)
+    } else {
+        capture_brightness(monitor_x, monitor_y, monitor_w, monitor_h)
+    };
+    if let Ok(brightness) = result {
+        let mut last = LAST_READINGS.lock().unwrap();
+        last.retain(|(origin, _)| *origin != (monitor_x, monitor_y));
+        last.push(((monitor_x, monitor_y), brightness));
+    }
+    let status = SensorStatus { ok: result.is_ok(), error: result.as_ref().err().cloned() };
+    let changed = STATUS.lock().unwrap().replace(status.clone()) != Some(status);
+    if changed {
+        crate::state::changed();
+    }
+    if let Ok(brightness) = result {
+        let monitor = crate::gamma::monitor_at(monitor_x + monitor_w / 2, monitor_y + monitor_h / 2);
+        crate::history::record_reading(monitor.map(|m| m.index), brightness);
+    }
+    result
+}
+
+/// Captures the configured sample region of the specified monitor region
+#[cfg(all(windows, feature = "sensor"))]
+fn capture_brightness(monitor_x: i32, monitor_y: i32, monitor_w: i32, monitor_h: i32) -> Result<f32, String> {
+    if !crate::display_power::displays_on() {
+        return Err(crate::i18n::text("error.displays_asleep"));
+    }
+    if crate::shutdown::is_shutting_down() {
+        return Err(crate::i18n::text("error.shutting_down"));
+    }
+
+    use crate::graphics_capture::{self, Target};
+    use crate::perf::Stage;
+
+    let config = crate::profiles::sensing().sensor;
+    let window = match config.backend {
+        CaptureBackend::GraphicsCapture if config.capture_window => crate::foreground::window(),
+        _ => None,
+    };
+
+    // When measuring the foreground window, its bounds replace the monitor's
+    let (monitor_x, monitor_y, monitor_w, monitor_h) = match window {
+        Some(hwnd) => graphics_capture::bounds(Target::Window(hwnd))?,
+        None => (monitor_x, monitor_y, monitor_w, monitor_h),
+    };
+    let target = match window {
+        Some(hwnd) => Target::Window(hwnd),
+        None => Target::Monitor { x: monitor_x + monitor_w / 2, y: monitor_y + monitor_h / 2 },
+    };
+
+    // GDI only sees an HDR desktop after it was clipped to SDR
+    let backend = match config.backend {
+        CaptureBackend::Gdi if graphics_capture::is_hdr(target) => CaptureBackend::GraphicsCapture,
+        backend => backend,
+    };
+    if backend == CaptureBackend::Gdi {
+        graphics_capture::stop(target);
+    }
+    let capture = |left, top, region_w, region_h, width, height| {
+        crate::perf::time(Stage::Capture, || match backend {
+            CaptureBackend::Gdi => capture_gdi(left, top, region_w, region_h, width, height),
+            CaptureBackend::GraphicsCapture => graphics_capture::capture(target, left, top, region_w, region_h, width, height),
+        })
+    };
+
+    // Our own settings window would read as a bright scene; window captures don't see it
+    let own_windows = match window {
+        Some(_) => Vec::new(),
+        None => own_window_rects(),
+    };
+    let covered = |left: i32, top: i32, width: i32, height: i32| {
+        own_windows.iter().any(|r| r.left < left + width && r.right > left && r.top < top + height && r.bottom > top)
+    };
+
+    match config.region {
+        SensorRegion::Center => {
+            let sample_size: i32 = 100;
+
+            // Calculate center of the specified monitor
+            let left = monitor_x + (monitor_w / 2) - (sample_size / 2);
+            let top = monitor_y + (monitor_h / 2) - (sample_size / 2);
+            if covered(left, top, sample_size, sample_size) {
+                return held_reading(monitor_x, monitor_y);
+            }
+
+            let (width, height) = crate::frame_guard::frame_size(sample_size, sample_size);
+            let pixels = capture(left, top, sample_size, sample_size, width, height)?;
+            Ok(crate::perf::time(Stage::Analysis, || percentile_brightness(&pixels, config.scale)))
+        }
+        SensorRegion::Patches => {
+            // Measure around the window by leaving out the patches it covers
+            let (patch_w, patch_h) = (monitor_w / 10, monitor_h / 10);
+            let skip = |cx: f32, cy: f32| {
+                let left = monitor_x + (cx * monitor_w as f32) as i32 - patch_w / 2;
+                let top = monitor_y + (cy * monitor_h as f32) as i32 - patch_h / 2;
+                covered(left, top, patch_w, patch_h)
+            };
+            if PATCH_CENTERS.iter().all(|(cx, cy)| skip(*cx, *cy)) {
+                return held_reading(monitor_x, monitor_y);
+            }
+
+            let (width, height) = crate::frame_guard::frame_size(PATCH_FRAME_WIDTH, PATCH_FRAME_HEIGHT);
+            let pixels = capture(monitor_x, monitor_y, monitor_w, monitor_h, width, height)?;
+            let brightness = crate::perf::time(Stage::Analysis, || patch_brightness(&pixels, width, height, config.scale, skip));
+            match brightness {
+                Some(brightness) => Ok(brightness),
+                None => held_reading(monitor_x, monitor_y),
+            }
+        }
+        SensorRegion::Full => {
+            if covered(monitor_x, monitor_y, monitor_w, monitor_h) {
+                return held_reading(monitor_x, monitor_y);
+            }
+
+            // Keep the monitor's aspect ratio so ultrawide panels aren't squashed
+            let height = (FULL_FRAME_WIDTH * monitor_h / monitor_w.max(1)).clamp(1, FULL_FRAME_WIDTH);
+            let (width, height) = crate::frame_guard::frame_size(FULL_FRAME_WIDTH, height);
+            let pixels = capture(monitor_x, monitor_y, monitor_w, monitor_h, width, height)?;
+            Ok(crate::perf::time(Stage::Analysis, || percentile_brightness(&pixels, config.scale)))
+        }
+    }
+}
+
+/// Copy a screen region into a `width` x `height` top-down BGRA buffer in one blit,
+/// averaging pixels down when the region is larger than the buffer
+#[cfg(windows)]
+fn capture_gdi(left: i32, top: i32, region_w: i32, region_h: i32, width: i32, height: i32) -> Result<Vec<u8>, String> {
+    unsafe {
+        let hdc_screen = GetDC(ptr::null_mut());
+        if hdc_screen.is_null() {
+            return Err(crate::i18n::text("error.screen_dc"));
+        }
+
+        let hdc_mem = CreateCompatibleDC(hdc_screen);
+        if hdc_mem.is_null() {
+            ReleaseDC(ptr::null_mut(), hdc_screen);
+            return Err(crate::i18n::text("error.compatible_dc"));
+        }
+
+        let hbm = CreateCompatibleBitmap(hdc_screen, width, height);
+        if hbm.is_null() {
+            DeleteDC(hdc_mem);
+            ReleaseDC(ptr::null_mut(), hdc_screen);
+            return Err(crate::i18n::text("error.create_bitmap"));
+        }
+
+        let old_bm = SelectObject(hdc_mem, hbm);
+
+        let copied = if region_w == width && region_h == height {
+            BitBlt(hdc_mem, 0, 0, width, height, hdc_screen, left, top, SRCCOPY)
+        } else {
+            // HALFTONE averages each block instead of dropping pixels
+            SetStretchBltMode(hdc_mem, HALFTONE);
+            StretchBlt(hdc_mem, 0, 0, width, height, hdc_screen, left, top, region_w, region_h, SRCCOPY)
+        };
+        if copied == 0 {
+            SelectObject(hdc_mem, old_bm);
+            DeleteObject(hbm);
+            DeleteDC(hdc_mem);
+            ReleaseDC(ptr::null_mut(), hdc_screen);
+            return Err(crate::i18n::text("error.bitblt"));
+        }
+
+        let mut bmi = BitmapInfo {
+            bmi_header: BitmapInfoHeader {
+                bi_size: std::mem::size_of::<BitmapInfoHeader>() as u32,
+                bi_width: width,
+                bi_height: -height, // Top-down
+                bi_planes: 1,
+                bi_bit_count: 32,
+                bi_compression: BI_RGB,
+                bi_size_image: 0,
+                bi_x_pels_per_meter: 0,
+                bi_y_pels_per_meter: 0,
+                bi_clr_used: 0,
+                bi_clr_important: 0,
+            },
+            bmi_colors: [RgbQuad { blue: 0, green: 0, red: 0, reserved: 0 }; 1],
+        };
+
+        let mut pixels: Vec<u8> = vec![0; (width * height) as usize * 4];
+
+        let result = GetDIBits(
+            hdc_mem,
+            hbm,
+            0,
+            height as u32,
+            pixels.as_mut_ptr() as *mut c_void,
+            &mut bmi,
+            DIB_RGB_COLORS,
+        );
+
+        SelectObject(hdc_mem, old_bm);
+        DeleteObject(hbm);
+        DeleteDC(hdc_mem);
+        ReleaseDC(ptr::null_mut(), hdc_screen);
+
+        if result == 0 {
+            return Err(crate::i18n::text("error.get_dibits"));
+        }
+
+        Ok(pixels)
+    }
+}
+
+/// Copy a screen region at full resolution into a top-down BGRA buffer
+#[cfg(windows)]
+pub fn capture_region(left: i32, top: i32, width: i32, height: i32) -> Result<Vec<u8>, String> {
+    if width <= 0 || height <= 0 {
+        return Err(crate::i18n::format("error.capture_size", &[&width, &height]));
+    }
+    capture_gdi(left, top, width, height, width, height)
+}
+
+#[cfg(not(windows))]
+pub fn capture_region(_left: i32, _top: i32, _width: i32, _height: i32) -> Result<Vec<u8>, String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+/// 10th percentile brightness of BGRA pixels, 0.0 - 1.0
+/// Responds to the darkest areas: better than average at detecting "any darkness in view"
+fn percentile_brightness(pixels: &[u8], scale: BrightnessScale) -> f32 {
+    let histogram = luminance_histogram(pixels, scale);
+    let total: u32 = histogram.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+
+    // 10th percentile = first level whose cumulative count passes 10% of the pixels
+    let percentile_index = total / 10;
+    let mut cumulative = 0;
+    for (level, count) in histogram.iter().enumerate() {
+        cumulative += count;
+        if cumulative > percentile_index {
+            // Normalize to 0.0-1.0 range for smart adjustment logic
+            return level as f32 / 255.0;
+        }
+    }
+    1.0
+}
+
+/// Pixels per histogram chunk, sized so a chunk's counts stay in L1 cache
+const HISTOGRAM_CHUNK_PIXELS: usize = 4096;
+
+/// Steps of linear luminance in the lightness table
+const LIGHTNESS_STEPS: usize = 4096;
+
+/// Lookup tables for perceptual brightness
+struct Tables {
+    /// sRGB-encoded byte to linear light
+    linear: [f32; 256],
+    /// Linear luminance (quantized to LIGHTNESS_STEPS) to CIE L* scaled to a byte
+    lightness: Vec<u8>,
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+fn tables() -> &'static Tables {
+    TABLES.get_or_init(|| {
+        let linear = std::array::from_fn(|i| {
+            let c = i as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        });
+        let lightness = (0..LIGHTNESS_STEPS)
+            .map(|i| {
+                let y = i as f32 / (LIGHTNESS_STEPS - 1) as f32;
+                let l = if y <= 216.0 / 24389.0 { y * 24389.0 / 27.0 } else { 116.0 * y.cbrt() - 16.0 };
+                (l / 100.0 * 255.0).round().clamp(0.0, 255.0) as u8
+            })
+            .collect();
+        Tables { linear, lightness }
+    })
+}
+
+/// Count pixels by brightness on the given scale
+fn chunk_histogram(pixels: &[u8], scale: BrightnessScale) -> [u32; 256] {
+    let mut histogram = [0u32; 256];
+    match scale {
+        BrightnessScale::Perceptual => {
+            let tables = tables();
+            for pixel in pixels.chunks_exact(4) {
+                // Rec. 709 luminance of the linearized channels (pixels are BGRA)
+                let y = 0.0722 * tables.linear[pixel[0] as usize]
+                    + 0.7152 * tables.linear[pixel[1] as usize]
+                    + 0.2126 * tables.linear[pixel[2] as usize];
+                let step = ((y * (LIGHTNESS_STEPS - 1) as f32) as usize).min(LIGHTNESS_STEPS - 1);
+                histogram[tables.lightness[step] as usize] += 1;
+            }
+        }
+        BrightnessScale::Encoded => {
+            for pixel in pixels.chunks_exact(4) {
+                let luminance = (pixel[0] as u32 + pixel[1] as u32 + pixel[2] as u32) / 3;
+                histogram[luminance as usize] += 1;
+            }
+        }
+    }
+    histogram
+}
+
+fn merge_histograms(mut a: [u32; 256], b: [u32; 256]) -> [u32; 256] {
+    for (a, b) in a.iter_mut().zip(b) {
+        *a += b;
+    }
+    a
+}
+
+/// Luminance histogram of BGRA pixels, split over all cores for large captures
+#[cfg(feature = "parallel")]
+fn luminance_histogram(pixels: &[u8], scale: BrightnessScale) -> [u32; 256] {
+    use rayon::prelude::*;
+
+    // Below a few chunks the thread hand-off costs more than it saves
+    if pixels.len() < HISTOGRAM_CHUNK_PIXELS * 4 * 8 {
+        return chunk_histogram(pixels, scale);
+    }
+    pixels
+        .par_chunks(HISTOGRAM_CHUNK_PIXELS * 4)
+        .map(|chunk| chunk_histogram(chunk, scale))
+        .reduce(|| [0u32; 256], merge_histograms)
+}
+
+/// Luminance histogram of BGRA pixels
+#[cfg(not(feature = "parallel"))]
+fn luminance_histogram(pixels: &[u8], scale: BrightnessScale) -> [u32; 256] {
+    pixels
+        .chunks(HISTOGRAM_CHUNK_PIXELS * 4)
+        .map(|chunk| chunk_histogram(chunk, scale))
+        .fold([0u32; 256], merge_histograms)
+}
+
+/// Median of the per-patch brightness over PATCH_CENTERS in a downscaled frame
+/// A bright HUD element or menu can only sway the patches it covers
+/// Patches for which `skip` returns true are left out; None if every patch was skipped
+fn patch_brightness(
+    pixels: &[u8],
+    width: i32,
+    height: i32,
+    scale: BrightnessScale,
+    skip: impl Fn(f32, f32) -> bool,
+) -> Option<f32> {
+    let patch_w = (width / 10).max(1);
+    let patch_h = (height / 10).max(1);
+
+    let mut values: Vec<f32> = PATCH_CENTERS
+        .iter()
+        .filter(|(cx, cy)| !skip(*cx, *cy))
+        .map(|(cx, cy)| {
+            let left = ((cx * width as f32) as i32 - patch_w / 2).clamp(0, width - patch_w);
+            let top = ((cy * height as f32) as i32 - patch_h / 2).clamp(0, height - patch_h);
+            let patch: Vec<u8> = (top..top + patch_h)
+                .flat_map(|y| {
+                    let start = ((y * width + left) * 4) as usize;
+                    pixels[start..start + patch_w as usize * 4].iter().copied()
+                })
+                .collect();
+            percentile_brightness(&patch, scale)
+        })
+        .collect();
+
+    values.sort_by(f32::total_cmp);
+    values.get(values.len() / 2).copied()
+}
+
+/// Screen rectangles of this process's visible windows that appear in screen captures
+#[cfg(all(windows, feature = "sensor"))]
+fn own_window_rects() -> Vec<Rect> {
+    unsafe extern "system" fn collect(hwnd: *mut c_void, l_param: isize) -> i32 {
+        let rects = &mut *(l_param as *mut Vec<Rect>);
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        if pid != GetCurrentProcessId() || IsWindowVisible(hwnd) == 0 || IsIconic(hwnd) != 0 {
+            return 1;
+        }
+        let mut affinity = 0u32;
+        if GetWindowDisplayAffinity(hwnd, &mut affinity) != 0 && affinity == WDA_EXCLUDEFROMCAPTURE {
+            return 1;
+        }
+        let mut rect = Rect::default();
+        if GetWindowRect(hwnd, &mut rect) != 0 && rect.right > rect.left && rect.bottom > rect.top {
+            rects.push(rect);
+        }
+        1
+    }
+
+    let mut rects: Vec<Rect> = Vec::new();
+    unsafe {
+        EnumWindows(collect, &mut rects as *mut Vec<Rect> as isize);
+    }
+    rects
+}
+
+/// Keep one of our own windows (overlay indicator, previews) out of screen captures,
+/// so the sensor measures the content behind it instead of our UI
+#[cfg(windows)]
+pub fn exclude_from_capture(hwnd: isize) -> Result<(), String> {
+    unsafe {
+        if SetWindowDisplayAffinity(hwnd as *mut c_void, WDA_EXCLUDEFROMCAPTURE) == 0 {
+            return Err(crate::i18n::format("error.exclude_capture", &[&GetLastError()]));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn exclude_from_capture(_hwnd: isize) -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+/// Read a single screen pixel in virtual-desktop coordinates
+/// GDI captures composed content before scanout, so this is the untransformed color
+#[cfg(windows)]
+pub fn get_pixel(x: i32, y: i32) -> Result<[u8; 3], String> {
+    unsafe {
+        let hdc_screen = GetDC(ptr::null_mut());
+        if hdc_screen.is_null() {
+            return Err(crate::i18n::text("error.screen_dc"));
+        }
+
+        let color = GetPixel(hdc_screen, x, y);
+        ReleaseDC(ptr::null_mut(), hdc_screen);
+
+        if color == CLR_INVALID {
+            return Err(crate::i18n::format("error.read_pixel", &[&x, &y]));
+        }
+
+        // COLORREF is 0x00BBGGRR
+        Ok([
+            (color & 0xFF) as u8,
+            ((color >> 8) & 0xFF) as u8,
+            ((color >> 16) & 0xFF) as u8,
+        ])
+    }
+}
+
+#[cfg(not(windows))]
+pub fn get_pixel(_x: i32, _y: i32) -> Result<[u8; 3], String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
+
+/// Sample a pixel and compute how it looks with the currently active transform
+/// Magnification effects are applied by the compositor, gamma ramps after that at scanout
+pub fn sample_pixel(x: i32, y: i32) -> Result<PixelSample, String> {
+    let raw = get_pixel(x, y)?;
+    let monitor = crate::gamma::monitor_at(x, y).map(|m| m.index);
+
+    let mut transformed = raw;
+    if !crate::suspend::is_suspended() {
+        transformed = crate::magnification::active_effect().apply(transformed);
+        if let Some(ramp) = monitor.and_then(crate::gamma::active_ramp) {
+            transformed = ramp.apply(transformed);
+        }
+    }
+
+    Ok(PixelSample { x, y, monitor, raw, transformed })
+}
+
+#[cfg(not(all(windows, feature = "sensor")))]
+fn capture_brightness(_x: i32, _y: i32, _w: i32, _h: i32) -> Result<f32, String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
@@ -0,0 +1,146 @@
+//! Session snapshots - the applied adjustment is saved continuously so it can be restored
+//! after a restart. An unexpected reboot mid-night can return to the same adjustment
+//! automatically; after a clean exit the UI can offer to restore it
+
+use crate::gamma::{self, GammaRamp};
+use crate::magnification::{self, MagColorEffect};
+use crate::night_mode::{self, Source};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Session snapshot file name
+pub const SESSION_FILE: &str = "session.json";
+
+/// Changes are batched so a fade writes the snapshot once it settles, not on every step
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Ramp applied to one monitor
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MonitorSnapshot {
+    /// Monitor device name, e.g. `\\.\DISPLAY1` (indices can change between boots)
+    pub monitor: String,
+    pub intensity: f32,
+    /// Red, green and blue tables, 256 entries each
+    pub ramp: Vec<u16>,
+}
+
+/// Everything Noctis had applied at the time of the snapshot
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionSnapshot {
+    /// Unix time in seconds
+    pub saved_at: u64,
+    /// False if Noctis exited without running its shutdown sequence (crash, power loss)
+    pub clean_exit: bool,
+    pub monitors: Vec<MonitorSnapshot>,
+    /// Magnification color matrix, if one was applied
+    pub magnification: Option<[[f32; 5]; 5]>,
+    pub magnification_intensity: f32,
+}
+
+/// Snapshot left by the previous run, read once at startup
+static LAST_SESSION: OnceLock<Option<SessionSnapshot>> = OnceLock::new();
+
+static DIRTY: Mutex<bool> = Mutex::new(false);
+static WAKE: Condvar = Condvar::new();
+static SAVER: OnceLock<()> = OnceLock::new();
+
+fn session_path() -> PathBuf {
+    crate::settings::config_dir().join(SESSION_FILE)
+}
+
+fn read() -> Option<SessionSnapshot> {
+    let json = std::fs::read_to_string(session_path()).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Capture the currently applied state
+fn capture(clean_exit: bool) -> SessionSnapshot {
+    let monitors = gamma::get_monitors()
+        .into_iter()
+        .filter_map(|m| {
+            let ramp = gamma::active_ramp(m.index)?;
+            Some(MonitorSnapshot {
+                intensity: night_mode::intensity_of(Source::Gamma(m.index)),
+                monitor: m.name,
+                ramp: ramp.red.iter().chain(&ramp.green).chain(&ramp.blue).copied().collect(),
+            })
+        })
+        .collect();
+
+    SessionSnapshot {
+        saved_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        clean_exit,
+        monitors,
+        magnification: magnification::recorded_effect().map(|e| e.transform),
+        magnification_intensity: night_mode::intensity_of(Source::Magnification),
+    }
+}
+
+fn save(snapshot: &SessionSnapshot) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(snapshot)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+    crate::config_lock::write_file(&session_path(), &json)
+}
+
+/// Note that the applied state changed; the snapshot is written shortly after
+pub fn changed() {
+    if crate::shutdown::is_shutting_down() {
+        return;
+    }
+    SAVER.get_or_init(|| {
+        thread::spawn(|| loop {
+            {
+                let mut dirty = DIRTY.lock().unwrap();
+                while !*dirty {
+                    dirty = WAKE.wait(dirty).unwrap();
+                }
+            }
+            thread::sleep(SAVE_DELAY);
+            *DIRTY.lock().unwrap() = false;
+            if !crate::shutdown::is_shutting_down() {
+                let _ = save(&capture(false));
+            }
+        });
+    });
+    *DIRTY.lock().unwrap() = true;
+    WAKE.notify_one();
+}
+
+/// Mark the session as cleanly ended, keeping what was applied so it can still be offered
+/// Must run before effects are removed during shutdown
+pub fn finish() {
+    let _ = save(&capture(true));
+}
+
+/// Get the snapshot left by the previous run
+pub fn last() -> Option<SessionSnapshot> {
+    LAST_SESSION.get_or_init(read).clone()
+}
+
+/// Re-apply the previous run's adjustment
+pub fn restore_last_session() -> Result<SessionSnapshot, String> {
+    let snapshot = last().ok_or_else(|| crate::i18n::text("error.no_last_session"))?;
+    let monitors = gamma::get_monitors();
+
+    for saved in &snapshot.monitors {
+        let Some(monitor) = monitors.iter().find(|m| m.name == saved.monitor) else { continue };
+        if saved.ramp.len() != 768 {
+            continue;
+        }
+        let mut ramp = GammaRamp { red: [0; 256], green: [0; 256], blue: [0; 256] };
+        ramp.red.copy_from_slice(&saved.ramp[..256]);
+        ramp.green.copy_from_slice(&saved.ramp[256..512]);
+        ramp.blue.copy_from_slice(&saved.ramp[512..]);
+        gamma::set_ramp(monitor.index, &ramp)?;
+        night_mode::record(Source::Gamma(monitor.index), saved.intensity);
+    }
+
+    if let Some(transform) = snapshot.magnification {
+        magnification::set_color_effect(&MagColorEffect { transform })?;
+        night_mode::record(Source::Magnification, snapshot.magnification_intensity);
+    }
+    Ok(snapshot)
+}
@@ -0,0 +1,103 @@
+//! Session timer - a Pomodoro-style dark session: night vision is switched on for a set
+//! number of minutes, then faded out with a completion event. Switching the toggle off
+//! during the session cancels it
+
+use crate::toggle;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest session, in minutes
+const MAX_MINUTES: u32 = 12 * 60;
+
+/// How long the effects take to fade out when the session ends
+const END_FADE: Duration = Duration::from_secs(10);
+
+/// How often a running session checks the toggle and the clock
+const TICK: Duration = Duration::from_secs(1);
+
+/// How a session ended
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEnd {
+    /// The time ran out and the effects were faded out
+    Completed,
+    /// The toggle was switched off during the session
+    Cancelled,
+}
+
+/// A running session
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct SessionStatus {
+    pub minutes: u32,
+    pub remaining_secs: u64,
+}
+
+struct Running {
+    minutes: u32,
+    ends_at: Instant,
+}
+
+static RUNNING: Mutex<Option<Running>> = Mutex::new(None);
+
+/// Bumped by every start and stop, so a replaced session's thread exits quietly
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// The running session, if any
+pub fn status() -> Option<SessionStatus> {
+    RUNNING.lock().unwrap().as_ref().map(|r| SessionStatus {
+        minutes: r.minutes,
+        remaining_secs: r.ends_at.saturating_duration_since(Instant::now()).as_secs(),
+    })
+}
+
+/// Start a session of `minutes`, replacing any running one and switching the toggle on
+/// Returns whether the toggle was switched on; `on_end` is called once if the session runs
+/// out or is cancelled, but not when it is replaced or stopped
+pub fn start<F>(minutes: u32, on_end: F) -> Result<bool, String>
+where
+    F: Fn(SessionEnd) + Send + 'static,
+{
+    if !(1..=MAX_MINUTES).contains(&minutes) {
+        return Err(crate::i18n::format("error.session_length", &[&MAX_MINUTES, &minutes]));
+    }
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let ends_at = Instant::now() + Duration::from_secs(minutes as u64 * 60);
+    *RUNNING.lock().unwrap() = Some(Running { minutes, ends_at });
+    let switched_on = !toggle::is_active() && toggle::toggle();
+    crate::state::changed();
+
+    thread::spawn(move || {
+        let current = || GENERATION.load(Ordering::SeqCst) == generation;
+        while current() && Instant::now() < ends_at {
+            if crate::shutdown::is_shutting_down() {
+                return;
+            }
+            if !toggle::is_active() {
+                if GENERATION.compare_exchange(generation, generation + 1, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+                    *RUNNING.lock().unwrap() = None;
+                    on_end(SessionEnd::Cancelled);
+                }
+                return;
+            }
+            thread::sleep(TICK);
+        }
+        if GENERATION.compare_exchange(generation, generation + 1, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            return;
+        }
+        *RUNNING.lock().unwrap() = None;
+        toggle::fade_off(END_FADE);
+        crate::state::changed();
+        on_end(SessionEnd::Completed);
+    });
+    Ok(switched_on)
+}
+
+/// Stop the running session early, leaving night vision as it is
+/// Returns whether a session was running
+pub fn stop() -> bool {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    RUNNING.lock().unwrap().take().is_some()
+}
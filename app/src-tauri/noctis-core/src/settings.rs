@@ -0,0 +1,332 @@
+//! Settings store - persisted backend configuration
+//! Stored as JSON under %APPDATA%\Noctis and cached in memory after first load
+
+use crate::config_lock::{self, ConfigLock};
+use crate::migration::{self, Migration};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Settings file name
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Pointer to a custom config folder, always kept in the default folder
+const LOCATION_FILE: &str = "location.json";
+
+/// Upgrades from each older settings schema, index N migrates version N to N + 1
+const MIGRATIONS: &[Migration] = &[
+    // v0 -> v1: version field introduced
+    |value| value,
+];
+
+/// Color-critical tools that should never see adjusted output
+const DEFAULT_BLOCKLIST: &[&str] = &[
+    "Photoshop.exe",
+    "Lightroom.exe",
+    "Adobe Premiere Pro.exe",
+    "AfterFX.exe",
+    "Resolve.exe",
+    "gimp-2.10.exe",
+    "krita.exe",
+];
+
+/// Shadow Hunter curve constants measured for a panel
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PanelTuning {
+    /// Monitor device name, e.g. `\\.\DISPLAY1`
+    pub monitor: String,
+    #[serde(flatten)]
+    pub curve: crate::gamma::CurveConstants,
+}
+
+/// What the main window does when closed, minimized and at startup
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WindowPolicy {
+    /// Closing hides the window to the tray instead of exiting
+    pub close_to_tray: bool,
+    /// Minimizing hides the window to the tray instead of the taskbar
+    pub minimize_to_tray: bool,
+    /// Start with the window hidden in the tray
+    pub start_minimized: bool,
+    /// Device id of the monitor the window is placed on, see `gamma::MonitorInfo::device_id`
+    pub pin_monitor: Option<String>,
+    /// Show the window on the pinned monitor when a game profile activates on another one
+    pub show_on_profile: bool,
+}
+
+impl Default for WindowPolicy {
+    fn default() -> Self {
+        Self {
+            close_to_tray: true,
+            minimize_to_tray: false,
+            start_minimized: false,
+            pin_monitor: None,
+            show_on_profile: false,
+        }
+    }
+}
+
+/// Backend settings persisted across restarts
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    /// Schema version, see MIGRATIONS
+    pub version: u32,
+    /// Process names during which all effects are suspended
+    pub blocklist: Vec<String>,
+    /// Per-panel curve constants from the gamma measurement report
+    pub panel_tuning: Vec<PanelTuning>,
+    /// Add a mild S-curve above the shadow lift so text stays crisp (gamma path)
+    pub preserve_contrast: bool,
+    /// Scale Magnification and ICC intensities to match the gamma curve's shadow lift
+    pub normalize_backends: bool,
+    /// Draw Magnification effects through a magnifier window that leaves the Noctis window unadjusted
+    pub exclude_own_window: bool,
+    /// RGB keyboard/mouse dimming through OpenRGB
+    pub peripherals: crate::peripherals::PeripheralSettings,
+    /// Hue / Home Assistant bias lighting
+    pub lights: crate::lights::LightSettings,
+    /// Home automation bridge
+    pub mqtt: crate::mqtt::MqttSettings,
+    /// Suspend effects while HDR video plays (profiles can override)
+    pub hdr_video_bypass: bool,
+    /// Re-apply the previous adjustment at startup if Noctis did not exit cleanly
+    pub restore_unclean_session: bool,
+    /// What launching Noctis does to the display, unless a crashed session was restored
+    pub startup: crate::startup::StartupPolicy,
+    /// Log every applied ramp and matrix for comparing sessions
+    pub ramp_logging: bool,
+    /// Where and how the brightness sensor samples
+    pub sensor: crate::sensor::SensorSettings,
+    /// Auto-adjust controller thresholds
+    pub adjustment: crate::adjustment::AdjustmentConfig,
+    /// Global toggle hotkey, e.g. "Insert" or "Ctrl+Shift+F"
+    pub hotkey: String,
+    /// Processes or profile names the hotkey is limited to (empty = everywhere)
+    pub hotkey_scope: Vec<String>,
+    /// Hold-a-chord-and-scroll intensity control
+    pub wheel: crate::wheel::WheelSettings,
+    /// Only allow effects while one of these processes runs
+    pub allowlist: crate::allowlist::AllowlistSettings,
+    /// Start without the window (tray, hotkeys and auto-adjust only)
+    pub background_mode: bool,
+    pub window: WindowPolicy,
+    /// Language for tray and error text, e.g. "de" (None follows the system language)
+    pub locale: Option<String>,
+    pub updates: crate::updates::UpdateSettings,
+    /// Write a minidump and recent log lines locally when Noctis crashes
+    pub crash_reports: bool,
+    /// Time-of-day ceilings on auto-adjust intensity
+    pub schedule: crate::schedule::ScheduleSettings,
+    /// Whether intensity is set by hand, by auto-adjust, or both
+    pub mode: crate::controller::Mode,
+    /// Warm the display with scene darkness or the time of night
+    pub temperature: crate::temperature::TemperatureSettings,
+    pub display_sleep: crate::display_sleep::DisplaySleepSettings,
+    /// Keep the display from dimming or sleeping while a game profile is engaged
+    pub keep_awake: bool,
+    /// Local control socket for scripts
+    pub control: crate::control::ControlSettings,
+    /// Discord Rich Presence (opt-in)
+    pub discord: crate::discord::DiscordSettings,
+    /// How effects behave while a Windows contrast theme is on
+    pub high_contrast: crate::accessibility::HighContrastPolicy,
+    /// Dimmer, custom or hidden cursor while the screen is dimmed
+    pub cursor: crate::cursor::CursorSettings,
+    /// Hotkey and folder for screenshots taken without effects
+    pub screenshot: crate::screenshot::ScreenshotSettings,
+    /// Hotkey that opens or closes the compact widget, e.g. "Ctrl+Alt+N" (empty = none)
+    pub mini_window_hotkey: String,
+    /// CSV sidecar of recording markers
+    pub markers: crate::markers::MarkerSettings,
+    /// Take over or yield when another process uses the fullscreen color effect
+    pub magnification_conflict: crate::mag_conflict::ConflictPolicy,
+    /// Undo an inverting Windows color filter around our Magnification matrix
+    pub compensate_color_filter: bool,
+    /// Rhai script hooks
+    pub scripting: crate::scripting::ScriptSettings,
+    /// Named sets of monitors adjusted as one
+    pub monitor_groups: Vec<crate::monitor_groups::MonitorGroup>,
+    /// Which outputs of a duplicated display ICC profiles are associated with
+    pub clone_outputs: crate::topology::CloneOutputs,
+    /// Also write pipeline timings as ETW events, for WPR / PerfView traces
+    pub perf_etw: bool,
+    /// Back off sensing when the pipeline costs too much while a game profile is active
+    pub frame_guard: crate::frame_guard::GuardSettings,
+    /// Warm and dim every display late at night, then remind to stop
+    pub wind_down: crate::wind_down::WindDownSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: MIGRATIONS.len() as u32,
+            blocklist: DEFAULT_BLOCKLIST.iter().map(|s| s.to_string()).collect(),
+            panel_tuning: Vec::new(),
+            preserve_contrast: false,
+            normalize_backends: true,
+            exclude_own_window: false,
+            peripherals: Default::default(),
+            lights: Default::default(),
+            mqtt: Default::default(),
+            hdr_video_bypass: true,
+            restore_unclean_session: true,
+            startup: Default::default(),
+            ramp_logging: false,
+            sensor: Default::default(),
+            adjustment: Default::default(),
+            hotkey: crate::hotkey::DEFAULT.to_string(),
+            hotkey_scope: Vec::new(),
+            wheel: Default::default(),
+            allowlist: Default::default(),
+            background_mode: false,
+            window: Default::default(),
+            locale: None,
+            updates: Default::default(),
+            crash_reports: false,
+            schedule: Default::default(),
+            mode: Default::default(),
+            temperature: Default::default(),
+            display_sleep: Default::default(),
+            keep_awake: false,
+            control: Default::default(),
+            discord: Default::default(),
+            high_contrast: Default::default(),
+            cursor: Default::default(),
+            screenshot: Default::default(),
+            mini_window_hotkey: String::new(),
+            markers: Default::default(),
+            magnification_conflict: Default::default(),
+            compensate_color_filter: true,
+            scripting: Default::default(),
+            monitor_groups: Vec::new(),
+            clone_outputs: Default::default(),
+            perf_etw: false,
+            frame_guard: Default::default(),
+            wind_down: Default::default(),
+        }
+    }
+}
+
+/// Where the config folder lives when it has been moved (e.g. into a OneDrive/Syncthing folder)
+#[derive(Serialize, Deserialize)]
+struct Location {
+    config_dir: PathBuf,
+}
+
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+static CONFIG_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Default config directory under %APPDATA% (next to the exe in portable mode)
+fn default_config_dir() -> PathBuf {
+    if crate::portable::is_portable() {
+        return crate::portable::data_dir();
+    }
+    let mut path = PathBuf::from(std::env::var("APPDATA").unwrap_or(".".to_string()));
+    path.push("Noctis");
+    path
+}
+
+/// Resolve the config directory from the location pointer, if any
+fn resolve_config_dir() -> PathBuf {
+    std::fs::read_to_string(default_config_dir().join(LOCATION_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str::<Location>(&json).ok())
+        .map(|location| location.config_dir)
+        .unwrap_or_else(default_config_dir)
+}
+
+/// Get the Noctis config directory
+pub fn config_dir() -> PathBuf {
+    CONFIG_DIR.lock().unwrap().get_or_insert_with(resolve_config_dir).clone()
+}
+
+/// Move the config store to a custom directory (None restores the default)
+/// Existing settings and profiles are copied over unless the target already has its own,
+/// so pointing a second machine at a synced folder picks up the shared configuration
+pub fn set_config_dir(dir: Option<PathBuf>) -> Result<PathBuf, String> {
+    let old_dir = config_dir();
+    let new_dir = dir.unwrap_or_else(default_config_dir);
+    std::fs::create_dir_all(&new_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    // Fails early if the folder isn't writable or another machine is mid-write
+    drop(ConfigLock::acquire(&new_dir)?);
+
+    if new_dir != old_dir {
+        for file in [SETTINGS_FILE, crate::profiles::PROFILES_FILE] {
+            let target = new_dir.join(file);
+            if !target.exists() && old_dir.join(file).exists() {
+                std::fs::copy(old_dir.join(file), &target)
+                    .map_err(|e| format!("Failed to copy {}: {}", file, e))?;
+            }
+        }
+    }
+
+    let pointer = default_config_dir().join(LOCATION_FILE);
+    if new_dir == default_config_dir() {
+        let _ = std::fs::remove_file(&pointer);
+    } else {
+        let json = serde_json::to_string_pretty(&Location { config_dir: new_dir.clone() })
+            .map_err(|e| format!("Failed to serialize config location: {}", e))?;
+        config_lock::write_file(&pointer, &json)?;
+    }
+
+    *CONFIG_DIR.lock().unwrap() = Some(new_dir.clone());
+    *SETTINGS.lock().unwrap() = Some(load());
+    Ok(new_dir)
+}
+
+fn settings_path() -> PathBuf {
+    config_dir().join(SETTINGS_FILE)
+}
+
+/// Read settings from disk, migrating older schemas; None if missing
+fn read() -> Result<Option<Settings>, String> {
+    let path = settings_path();
+    let Some(value) = migration::read(&path, MIGRATIONS)? else { return Ok(None) };
+    serde_json::from_value(value).map(Some).map_err(|e| format!("Invalid settings in {}: {}", path.display(), e))
+}
+
+/// Read settings from disk, falling back to defaults if missing
+/// An unreadable file is set aside first so the defaults don't overwrite the only copy
+fn load() -> Settings {
+    read()
+        .unwrap_or_else(|e| {
+            migration::set_aside(&settings_path(), &e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+fn save(settings: &Settings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    config_lock::write_file(&settings_path(), &json)
+}
+
+/// Get the current settings
+pub fn get() -> Settings {
+    SETTINGS.lock().unwrap().get_or_insert_with(load).clone()
+}
+
+/// Re-read settings from disk, e.g. after the file was edited externally
+/// Keeps the current settings if the file is missing or invalid (such as mid-save)
+pub fn reload() -> Option<Settings> {
+    let settings = read().ok().flatten()?;
+    *SETTINGS.lock().unwrap() = Some(settings.clone());
+    Some(settings)
+}
+
+/// Modify settings and persist them, returning the updated settings
+pub fn update<F>(modify: F) -> Result<Settings, String>
+where
+    F: FnOnce(&mut Settings),
+{
+    let mut guard = SETTINGS.lock().unwrap();
+    let settings = guard.get_or_insert_with(load);
+    modify(settings);
+    save(settings)?;
+    Ok(settings.clone())
+}
@@ -0,0 +1,73 @@
+//! Shutdown coordinator - restores the display on every exit path
+//! Tray quit, window close, console Ctrl+C/close and logoff/shutdown all funnel into `run`,
+//! which stops background work and removes every effect exactly once
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Check whether shutdown has started; background loops should stop and no new effects be applied
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Stop background work and restore the display
+/// Safe to call from any thread and any number of times; only the first call does the work
+/// Returns true if this call performed the shutdown
+pub fn run() -> bool {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    crate::session::finish();
+    crate::cursor::restore();
+    crate::magnification::shutdown();
+    crate::gamma::restore_all();
+    crate::display_sleep::wake();
+    true
+}
+
+type CtrlHandler = unsafe extern "system" fn(u32) -> i32;
+
+const WM_QUERYENDSESSION: u32 = 0x0011;
+const WM_ENDSESSION: u32 = 0x0016;
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn SetConsoleCtrlHandler(handler: Option<CtrlHandler>, add: i32) -> i32;
+}
+
+/// Console control handler (Ctrl+C, Ctrl+Break, console close, logoff, shutdown)
+#[cfg(windows)]
+unsafe extern "system" fn console_ctrl_handler(_ctrl_type: u32) -> i32 {
+    run();
+    // Let the default handler terminate the process
+    0
+}
+
+/// Handle a message for the shared message window, if it's logoff or shutdown
+#[cfg(windows)]
+pub fn window_message(msg: u32, w_param: usize) -> Option<isize> {
+    match msg {
+        WM_QUERYENDSESSION => Some(1),
+        WM_ENDSESSION => {
+            if w_param != 0 {
+                run();
+            }
+            Some(0)
+        }
+        _ => None,
+    }
+}
+
+/// Hook console signals, which bypass the Tauri event loop
+/// Logoff/shutdown arrive on the message_window
+#[cfg(windows)]
+pub fn install_handlers() {
+    unsafe {
+        SetConsoleCtrlHandler(Some(console_ctrl_handler), 1);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn install_handlers() {}
@@ -0,0 +1,75 @@
+//! Automation snippets - ready-to-use AutoHotkey and PowerShell scripts for common actions
+//! They talk to the local control socket (or the control pipe when only that is enabled),
+//! so users can build their own automation around Noctis
+
+use crate::control::{ControlSettings, PIPE_NAME};
+use serde::Deserialize;
+
+/// Script language to generate
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptKind {
+    /// AutoHotkey v2
+    AutoHotkey,
+    PowerShell,
+}
+
+/// `{connect}` is replaced with statements that open `$client` and set `$stream`
+const POWERSHELL: &str = r#"# Noctis automation - requires Settings > Integrations > Local control
+function Send-Noctis([string]$Command) {
+    {connect}
+    try {
+        $writer = [System.IO.StreamWriter]::new($stream)
+        $writer.WriteLine($Command)
+        $writer.Flush()
+        [System.IO.StreamReader]::new($stream).ReadLine()
+    } finally {
+        $client.Close()
+    }
+}
+
+Send-Noctis "toggle"                               # toggle night mode, like the hotkey
+# Send-Noctis "off"                                # remove all adjustments
+# Send-Noctis "0.4"                                # set intensity on the adjusted monitor
+# Send-Noctis '{"intensity": 0.4, "monitor": 2}'   # set intensity on a specific monitor
+"#;
+
+/// `{connect}` is replaced with a one-line version of the PowerShell connect statements
+const AUTOHOTKEY: &str = r#"; Noctis automation (AutoHotkey v2) - requires Settings > Integrations > Local control
+#Requires AutoHotkey v2.0
+
+NoctisSend(command) {
+    script := "{connect}"
+        . "$w=[IO.StreamWriter]::new($stream);$w.WriteLine('" command "');$w.Flush();$client.Close()"
+    Run 'powershell -NoProfile -WindowStyle Hidden -Command "' script '"', , "Hide"
+}
+
+^!n::NoctisSend("toggle")   ; Ctrl+Alt+N: toggle night mode
+^!o::NoctisSend("off")      ; Ctrl+Alt+O: remove all adjustments
+^!1::NoctisSend("0.3")      ; Ctrl+Alt+1: light lift
+^!2::NoctisSend("0.6")      ; Ctrl+Alt+2: strong lift
+"#;
+
+/// PowerShell statements that connect to Noctis, preferring the socket
+fn connect(config: &ControlSettings) -> String {
+    if config.pipe && !config.enabled {
+        format!(
+            "$client = [System.IO.Pipes.NamedPipeClientStream]::new('.', '{}', 'InOut'); $client.Connect(2000); $stream = $client;",
+            PIPE_NAME
+        )
+    } else {
+        format!(
+            "$client = [System.Net.Sockets.TcpClient]::new('127.0.0.1', {}); $stream = $client.GetStream();",
+            config.port
+        )
+    }
+}
+
+/// Snippet for a script language, pointed at the enabled control transport
+pub fn generate(kind: ScriptKind, config: &ControlSettings) -> String {
+    let template = match kind {
+        ScriptKind::AutoHotkey => AUTOHOTKEY,
+        ScriptKind::PowerShell => POWERSHELL,
+    };
+    template.replace("{connect}", &connect(config))
+}
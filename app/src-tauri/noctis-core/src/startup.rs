@@ -0,0 +1,39 @@
+//! Startup policy - what launching Noctis does to the display, evaluated once in setup
+//! Crash recovery (`restore_unclean_session`) runs before this and takes precedence
+
+use crate::{schedule, session, toggle};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupPolicy {
+    /// Leave the display untouched until the toggle is switched on
+    #[default]
+    AlwaysOff,
+    /// Put back what was applied when Noctis last exited
+    RestoreLast,
+    /// Switch on if the time-of-day schedule allows any adjustment right now
+    FollowSchedule,
+    /// Switch on and let auto-adjust decide from the sensor
+    FollowSensor,
+}
+
+/// Apply a startup policy, returning whether the toggle is now on
+pub fn apply(policy: StartupPolicy) -> bool {
+    match policy {
+        StartupPolicy::AlwaysOff => false,
+        StartupPolicy::RestoreLast => {
+            let restored = session::restore_last_session()
+                .is_ok_and(|s| s.magnification.is_some() || s.monitors.iter().any(|m| m.intensity > 0.0));
+            if restored {
+                toggle::mark_active();
+            }
+            restored
+        }
+        StartupPolicy::FollowSchedule => {
+            let config = crate::settings::get().schedule;
+            config.enabled && schedule::current_cap() > 0.0 && toggle::toggle()
+        }
+        StartupPolicy::FollowSensor => toggle::toggle(),
+    }
+}
@@ -0,0 +1,141 @@
+//! Application state snapshot - one serializable view of everything the backend is doing
+//! The UI, tray and external integrations read it with get_state and follow `state-changed`
+//! events instead of tracking individual events and drifting out of sync
+
+use crate::gamma;
+use crate::night_mode::{self, Source};
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread;
+
+/// Which mechanism is adjusting the display
+#[derive(serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    Gamma,
+    Magnification,
+}
+
+/// Adjustment applied to one monitor
+#[derive(serde::Serialize, Clone, PartialEq)]
+pub struct MonitorState {
+    pub index: u32,
+    pub name: String,
+    pub intensity: f32,
+}
+
+/// Time-of-day schedule in effect
+#[derive(serde::Serialize, Clone, PartialEq)]
+pub struct ScheduleState {
+    pub enabled: bool,
+    /// Highest intensity auto-adjust may apply right now (1.0 while the schedule is off)
+    pub cap: f32,
+}
+
+/// Complete backend state
+#[derive(serde::Serialize, Clone, PartialEq)]
+pub struct AppState {
+    /// Any adjustment is applied
+    pub enabled: bool,
+    /// Strongest intensity applied
+    pub intensity: f32,
+    pub backend: Option<Backend>,
+    pub monitors: Vec<MonitorState>,
+    pub schedule: ScheduleState,
+    /// Intensity of the fullscreen Magnification effect
+    pub magnification_intensity: f32,
+    /// Active game profile name
+    pub profile: Option<String>,
+    /// Reasons effects are suspended (empty if not suspended)
+    pub suspended: Vec<String>,
+    pub displays_on: bool,
+    pub sensor: crate::sensor::SensorStatus,
+}
+
+type Listener = Box<dyn Fn(&AppState) + Send>;
+
+/// Last state reported to the listener, to suppress duplicate events
+static LAST: Mutex<Option<AppState>> = Mutex::new(None);
+static LISTENER: Mutex<Option<Listener>> = Mutex::new(None);
+
+/// Changes are reported from a dedicated thread: callers are often deep inside other
+/// modules' locks (e.g. a profile applying gamma), and building the snapshot takes them too
+static PENDING: Mutex<bool> = Mutex::new(false);
+static WAKE: Condvar = Condvar::new();
+static REPORTER: OnceLock<()> = OnceLock::new();
+
+/// Build a snapshot of the current state
+pub fn get() -> AppState {
+    let mode = night_mode::current();
+    let magnification_intensity = night_mode::intensity_of(Source::Magnification);
+    let monitors: Vec<MonitorState> = gamma::get_monitors()
+        .into_iter()
+        .map(|m| MonitorState {
+            intensity: night_mode::intensity_of(Source::Gamma(m.index)),
+            index: m.index,
+            name: m.name,
+        })
+        .collect();
+
+    let backend = if magnification_intensity > 0.0 {
+        Some(Backend::Magnification)
+    } else if monitors.iter().any(|m| m.intensity > 0.0) {
+        Some(Backend::Gamma)
+    } else {
+        None
+    };
+
+    AppState {
+        enabled: mode.engaged,
+        intensity: mode.intensity,
+        backend,
+        monitors,
+        schedule: ScheduleState { enabled: crate::settings::get().schedule.enabled, cap: crate::schedule::current_cap() },
+        magnification_intensity,
+        profile: crate::profiles::active().map(|p| p.name),
+        suspended: crate::suspend::reasons(),
+        displays_on: crate::display_power::displays_on(),
+        sensor: crate::sensor::status(),
+    }
+}
+
+/// Note a backend change; the state is re-evaluated and reported shortly after if it differs
+/// from the last report
+pub fn changed() {
+    REPORTER.get_or_init(|| {
+        thread::spawn(|| loop {
+            {
+                let mut pending = PENDING.lock().unwrap();
+                while !*pending {
+                    pending = WAKE.wait(pending).unwrap();
+                }
+                *pending = false;
+            }
+            report();
+        });
+    });
+    *PENDING.lock().unwrap() = true;
+    WAKE.notify_one();
+}
+
+fn report() {
+    let state = get();
+    {
+        let mut last = LAST.lock().unwrap();
+        if last.as_ref() == Some(&state) {
+            return;
+        }
+        *last = Some(state.clone());
+    }
+    if let Some(listener) = LISTENER.lock().unwrap().as_ref() {
+        listener(&state);
+    }
+}
+
+/// Set the function that reports state changes (e.g. emits `state-changed` to the UI)
+pub fn on_change<F>(listener: F)
+where
+    F: Fn(&AppState) + Send + 'static,
+{
+    *LISTENER.lock().unwrap() = Some(Box::new(listener));
+    night_mode::subscribe(|_| changed());
+}
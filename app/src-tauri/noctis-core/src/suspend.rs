@@ -0,0 +1,59 @@
+//! Effect suspension - temporarily shows the screen unadjusted
+//! Adjustments requested while suspended are recorded and applied on resume
+
+use std::sync::Mutex;
+
+/// Active suspension reasons; effects are bypassed while any are present
+static REASONS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Check whether effects are currently suspended
+pub fn is_suspended() -> bool {
+    !REASONS.lock().unwrap().is_empty()
+}
+
+/// Get the active suspension reasons
+pub fn reasons() -> Vec<String> {
+    REASONS.lock().unwrap().clone()
+}
+
+/// Suspend effects for a reason
+/// Returns true if this call moved effects from active to suspended
+pub fn suspend(reason: &str) -> bool {
+    let was_suspended = {
+        let mut reasons = REASONS.lock().unwrap();
+        if reasons.iter().any(|r| r == reason) {
+            return false;
+        }
+        let was_suspended = !reasons.is_empty();
+        reasons.push(reason.to_string());
+        was_suspended
+    };
+
+    if was_suspended {
+        return false;
+    }
+    crate::crash::log(format!("Effects suspended: {}", reason));
+    crate::magnification::bypass();
+    crate::gamma::bypass_all();
+    crate::markers::check();
+    true
+}
+
+/// Lift a suspension reason
+/// Returns true if this call moved effects from suspended back to active
+pub fn resume(reason: &str) -> bool {
+    {
+        let mut reasons = REASONS.lock().unwrap();
+        let before = reasons.len();
+        reasons.retain(|r| r != reason);
+        if reasons.len() == before || !reasons.is_empty() {
+            return false;
+        }
+    }
+
+    crate::crash::log(format!("Effects resumed: {}", reason));
+    crate::gamma::reapply_all();
+    crate::magnification::reapply();
+    crate::markers::check();
+    true
+}
@@ -0,0 +1,115 @@
+//! Color temperature coupling - warms the display as scenes get darker or the night deeper
+//! The controller picks a temperature on every auto-adjust reading and the Magnification
+//! backend applies it together with the shadow lift
+
+use serde::{Deserialize, Serialize};
+
+/// Temperature of an unadjusted display
+pub const NEUTRAL_KELVIN: f32 = 6500.0;
+
+/// Warmest temperature allowed, below which the screen turns unreadably orange
+const MIN_KELVIN: f32 = 1900.0;
+
+/// Local hours at which warming starts, reaches full strength, and has faded out by morning
+const NIGHT_START_HOUR: f32 = 18.0;
+const NIGHT_DEEP_HOUR: f32 = 23.0;
+const DAWN_HOUR: f32 = 6.0;
+
+/// What drives the warming
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Coupling {
+    /// Darker scenes are warmer
+    #[default]
+    Scene,
+    /// Warmer the later it gets in the evening
+    Night,
+}
+
+/// Color temperature coupling configuration
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TemperatureSettings {
+    pub enabled: bool,
+    pub coupling: Coupling,
+    /// Temperature at full darkness or the deepest part of the night
+    pub warmest_kelvin: f32,
+}
+
+impl Default for TemperatureSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            coupling: Coupling::Scene,
+            warmest_kelvin: 3400.0,
+        }
+    }
+}
+
+pub fn validate(config: &TemperatureSettings) -> Result<(), String> {
+    if !(MIN_KELVIN..=NEUTRAL_KELVIN).contains(&config.warmest_kelvin) {
+        return Err(crate::i18n::format(
+            "error.color_temperature",
+            &[&MIN_KELVIN, &NEUTRAL_KELVIN, &config.warmest_kelvin],
+        ));
+    }
+    Ok(())
+}
+
+/// How far into the night an hour is: 0 in the daytime, rising through the evening to 1,
+/// and falling back to 0 by dawn
+fn night_depth(hour: f32) -> f32 {
+    if hour >= NIGHT_START_HOUR {
+        ((hour - NIGHT_START_HOUR) / (NIGHT_DEEP_HOUR - NIGHT_START_HOUR)).min(1.0)
+    } else if hour < DAWN_HOUR {
+        // Full strength until an hour before dawn, then fading out over that hour
+        (DAWN_HOUR - hour).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Temperature for a scene brightness (0.0 - 1.0) at a local hour, or None while coupling is off
+pub fn target_kelvin(config: &TemperatureSettings, brightness: f32, hour: f32) -> Option<f32> {
+    if !config.enabled {
+        return None;
+    }
+    let warmth = match config.coupling {
+        Coupling::Scene => 1.0 - brightness.clamp(0.0, 1.0),
+        Coupling::Night => night_depth(hour),
+    };
+    Some(NEUTRAL_KELVIN + (config.warmest_kelvin - NEUTRAL_KELVIN) * warmth)
+}
+
+/// Relative red, green and blue output (0.0 - 1.0) of a blackbody at a temperature
+/// Fitted approximation of the Planckian locus, good from 1000K to 40000K
+fn blackbody(kelvin: f32) -> [f32; 3] {
+    let t = kelvin / 100.0;
+    let red = if t <= 66.0 { 255.0 } else { 329.698_73 * (t - 60.0).powf(-0.133_204_76) };
+    let green = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_16 * (t - 60.0).powf(-0.075_514_85)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+    [red, green, blue].map(|c| (c / 255.0).clamp(0.0, 1.0))
+}
+
+/// Channel gains that shift white from the neutral temperature to `kelvin`
+pub fn channel_gains(kelvin: f32) -> [f32; 3] {
+    let target = blackbody(kelvin);
+    let neutral = blackbody(NEUTRAL_KELVIN);
+    [0, 1, 2].map(|i| (target[i] / neutral[i]).min(1.0))
+}
+
+/// Current temperature target for a reading, using the local hour for night coupling
+pub fn current_kelvin(brightness: f32) -> Option<f32> {
+    let config = crate::settings::get().temperature;
+    target_kelvin(&config, brightness, crate::schedule::local_hour() as f32)
+}
@@ -0,0 +1,118 @@
+//! Toggle memory - what the toggle hotkey turns back on
+//! Switching off remembers each monitor's last non-zero adjustment, which backend applied it and
+//! any color temperature layered on it, so switching on returns to exactly that instead of
+//! starting over from nothing
+
+use crate::night_mode::{self, Source};
+use crate::{gamma, magnification};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Intensity steps of a fade-out
+const FADE_STEPS: u32 = 30;
+
+/// Whether the toggle is on (the UI starts inactive)
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Adjustments in place when the toggle last switched off, with the kelvin of a warmed
+/// Magnification lift
+static REMEMBERED: Mutex<Vec<(Source, f32, Option<f32>)>> = Mutex::new(Vec::new());
+
+/// Adjustments applied now, with the color temperature of the Magnification lift
+fn applied() -> Vec<(Source, f32, Option<f32>)> {
+    night_mode::applied()
+        .into_iter()
+        .map(|(source, intensity)| match source {
+            Source::Magnification => (source, intensity, magnification::applied_kelvin()),
+            Source::Gamma(_) => (source, intensity, None),
+        })
+        .collect()
+}
+
+/// Apply an adjustment on the backend it was remembered from
+fn apply(source: Source, intensity: f32, kelvin: Option<f32>) -> Result<(), String> {
+    match source {
+        Source::Gamma(monitor) => gamma::set_gamma(intensity, monitor),
+        Source::Magnification => magnification::apply_lift_and_temperature(intensity, kelvin),
+    }
+}
+
+/// Check whether the toggle is on
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Flip the toggle, returning whether it is now on
+pub fn toggle() -> bool {
+    let active = !ACTIVE.fetch_xor(true, Ordering::SeqCst);
+    if active {
+        magnification::new_adjustment_session();
+        restore();
+    } else {
+        remember_and_clear();
+    }
+    active
+}
+
+/// Mark the toggle on for adjustments put back by other means (a restored session), so the
+/// next toggle switches them off
+pub fn mark_active() {
+    ACTIVE.store(true, Ordering::SeqCst);
+}
+
+/// Switch the toggle off if it is on, returning whether it was
+pub fn switch_off() -> bool {
+    let was_active = ACTIVE.swap(false, Ordering::SeqCst);
+    if was_active {
+        remember_and_clear();
+    }
+    was_active
+}
+
+/// Switch the toggle off like `switch_off`, but fade the adjustments out over `duration`
+/// The toggle is off from the start so auto-adjust doesn't fight the fade; switching it
+/// back on stops the fade
+pub fn fade_off(duration: Duration) -> bool {
+    let was_active = ACTIVE.swap(false, Ordering::SeqCst);
+    if !was_active {
+        return false;
+    }
+    let applied = applied();
+    if !applied.is_empty() {
+        *REMEMBERED.lock().unwrap() = applied.clone();
+    }
+    for step in (0..FADE_STEPS).rev() {
+        thread::sleep(duration / FADE_STEPS);
+        if is_active() {
+            return true;
+        }
+        let fraction = step as f32 / FADE_STEPS as f32;
+        for &(source, intensity, kelvin) in &applied {
+            let _ = apply(source, intensity * fraction, kelvin);
+        }
+    }
+    remember_and_clear();
+    true
+}
+
+/// Save what is applied (unless nothing is, keeping the last non-zero state) and restore the display
+fn remember_and_clear() {
+    let applied = applied();
+    if !applied.is_empty() {
+        *REMEMBERED.lock().unwrap() = applied;
+    }
+    for m in gamma::get_monitors() {
+        let _ = gamma::set_gamma(0.0, m.index);
+    }
+    let _ = magnification::remove_effects();
+}
+
+/// Re-apply the remembered adjustments on the backend that applied them
+fn restore() {
+    let remembered = REMEMBERED.lock().unwrap().clone();
+    for (source, intensity, kelvin) in remembered {
+        let _ = apply(source, intensity, kelvin);
+    }
+}
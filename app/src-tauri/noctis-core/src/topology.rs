@@ -0,0 +1,244 @@
+//! Display topology - which outputs each GDI display is shown on, from QueryDisplayConfig
+//! A duplicated ("cloned") display is one GDI device such as \\.\DISPLAY1 driving several
+//! monitors, so per-monitor state (ICC associations, stable ids) has to follow the topology
+
+use serde::{Deserialize, Serialize};
+
+/// A monitor a display is shown on
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Output {
+    /// Monitor device interface path, as WCS and `gamma::MonitorInfo::device_id` use
+    pub device_path: String,
+    /// EDID name, e.g. "DELL U2720Q" (empty for some virtual outputs)
+    pub friendly_name: String,
+    /// Wireless or virtual output (Miracast, indirect display drivers used for streaming)
+    pub virtual_output: bool,
+}
+
+/// Which outputs of a duplicated display per-output adjustments (ICC profiles) go to
+/// Gamma ramps belong to the display, so every clone shares them whatever the policy
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CloneOutputs {
+    /// Every monitor showing the display, so clones look alike
+    #[default]
+    All,
+    /// Only physical monitors, leaving wireless and virtual outputs (streams, captures) untouched
+    Physical,
+}
+
+impl CloneOutputs {
+    /// Whether an output receives per-output adjustments under this policy
+    pub fn includes(self, output: &Output) -> bool {
+        self == CloneOutputs::All || !output.virtual_output
+    }
+}
+
+#[cfg(windows)]
+mod windows_api {
+    use super::Output;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct Luid {
+        low: u32,
+        high: i32,
+    }
+
+    /// DISPLAYCONFIG_PATH_SOURCE_INFO
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct PathSourceInfo {
+        adapter_id: Luid,
+        id: u32,
+        mode_info_idx: u32,
+        status_flags: u32,
+    }
+
+    /// DISPLAYCONFIG_PATH_TARGET_INFO
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct PathTargetInfo {
+        adapter_id: Luid,
+        id: u32,
+        mode_info_idx: u32,
+        output_technology: u32,
+        rotation: u32,
+        scaling: u32,
+        refresh_rate: [u32; 2],
+        scan_line_ordering: u32,
+        target_available: i32,
+        status_flags: u32,
+    }
+
+    /// DISPLAYCONFIG_PATH_INFO
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct PathInfo {
+        source: PathSourceInfo,
+        target: PathTargetInfo,
+        flags: u32,
+    }
+
+    /// DISPLAYCONFIG_MODE_INFO; only needed as buffer space
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct ModeInfo {
+        info_type: u32,
+        id: u32,
+        adapter_id: Luid,
+        mode: [u64; 6],
+    }
+
+    /// DISPLAYCONFIG_DEVICE_INFO_HEADER
+    #[repr(C)]
+    struct DeviceInfoHeader {
+        info_type: u32,
+        size: u32,
+        adapter_id: Luid,
+        id: u32,
+    }
+
+    /// DISPLAYCONFIG_SOURCE_DEVICE_NAME
+    #[repr(C)]
+    struct SourceDeviceName {
+        header: DeviceInfoHeader,
+        gdi_device_name: [u16; 32],
+    }
+
+    /// DISPLAYCONFIG_TARGET_DEVICE_NAME
+    #[repr(C)]
+    struct TargetDeviceName {
+        header: DeviceInfoHeader,
+        flags: u32,
+        output_technology: u32,
+        edid_manufacture_id: u16,
+        edid_product_code_id: u16,
+        connector_instance: u32,
+        friendly_name: [u16; 64],
+        device_path: [u16; 128],
+    }
+
+    const QDC_ONLY_ACTIVE_PATHS: u32 = 0x2;
+    const DEVICE_INFO_GET_SOURCE_NAME: u32 = 1;
+    const DEVICE_INFO_GET_TARGET_NAME: u32 = 2;
+
+    /// DISPLAYCONFIG_OUTPUT_TECHNOLOGY values that aren't a locally attached monitor
+    const OUTPUT_OTHER: u32 = 0xFFFF_FFFF;
+    const OUTPUT_MIRACAST: u32 = 15;
+    const OUTPUT_INDIRECT_VIRTUAL: u32 = 17;
+
+    /// Buffers grew between sizing and querying because the topology changed; retry this often
+    const QUERY_ATTEMPTS: u32 = 3;
+    const ERROR_INSUFFICIENT_BUFFER: i32 = 122;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetDisplayConfigBufferSizes(flags: u32, num_paths: *mut u32, num_modes: *mut u32) -> i32;
+        fn QueryDisplayConfig(
+            flags: u32,
+            num_paths: *mut u32,
+            paths: *mut PathInfo,
+            num_modes: *mut u32,
+            modes: *mut ModeInfo,
+            topology: *mut u32,
+        ) -> i32;
+        fn DisplayConfigGetDeviceInfo(packet: *mut DeviceInfoHeader) -> i32;
+    }
+
+    fn wide_to_string(wide: &[u16]) -> String {
+        let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+        String::from_utf16_lossy(&wide[..len])
+    }
+
+    fn active_paths() -> Vec<PathInfo> {
+        for _ in 0..QUERY_ATTEMPTS {
+            let (mut num_paths, mut num_modes) = (0u32, 0u32);
+            if unsafe { GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut num_paths, &mut num_modes) } != 0 {
+                return Vec::new();
+            }
+            let mut paths = vec![PathInfo::default(); num_paths as usize];
+            let mut modes = vec![ModeInfo::default(); num_modes as usize];
+            let result = unsafe {
+                QueryDisplayConfig(
+                    QDC_ONLY_ACTIVE_PATHS,
+                    &mut num_paths,
+                    paths.as_mut_ptr(),
+                    &mut num_modes,
+                    modes.as_mut_ptr(),
+                    std::ptr::null_mut(),
+                )
+            };
+            match result {
+                0 => {
+                    paths.truncate(num_paths as usize);
+                    return paths;
+                }
+                ERROR_INSUFFICIENT_BUFFER => continue,
+                _ => return Vec::new(),
+            }
+        }
+        Vec::new()
+    }
+
+    fn source_name(source: &PathSourceInfo) -> Option<String> {
+        let mut packet = SourceDeviceName {
+            header: DeviceInfoHeader {
+                info_type: DEVICE_INFO_GET_SOURCE_NAME,
+                size: std::mem::size_of::<SourceDeviceName>() as u32,
+                adapter_id: source.adapter_id,
+                id: source.id,
+            },
+            gdi_device_name: [0; 32],
+        };
+        let ok = unsafe { DisplayConfigGetDeviceInfo(&mut packet.header) } == 0;
+        ok.then(|| wide_to_string(&packet.gdi_device_name))
+    }
+
+    fn output(target: &PathTargetInfo) -> Option<Output> {
+        let mut packet = TargetDeviceName {
+            header: DeviceInfoHeader {
+                info_type: DEVICE_INFO_GET_TARGET_NAME,
+                size: std::mem::size_of::<TargetDeviceName>() as u32,
+                adapter_id: target.adapter_id,
+                id: target.id,
+            },
+            flags: 0,
+            output_technology: 0,
+            edid_manufacture_id: 0,
+            edid_product_code_id: 0,
+            connector_instance: 0,
+            friendly_name: [0; 64],
+            device_path: [0; 128],
+        };
+        if unsafe { DisplayConfigGetDeviceInfo(&mut packet.header) } != 0 {
+            return None;
+        }
+        Some(Output {
+            device_path: wide_to_string(&packet.device_path),
+            friendly_name: wide_to_string(&packet.friendly_name),
+            virtual_output: matches!(packet.output_technology, OUTPUT_OTHER | OUTPUT_MIRACAST | OUTPUT_INDIRECT_VIRTUAL),
+        })
+    }
+
+    /// Outputs a GDI display (e.g. "\\.\DISPLAY1") is shown on, sorted by device path
+    pub fn outputs(display_name: &str) -> Vec<Output> {
+        let mut outputs: Vec<Output> = active_paths()
+            .iter()
+            .filter(|p| source_name(&p.source).is_some_and(|name| name.eq_ignore_ascii_case(display_name)))
+            .filter_map(|p| output(&p.target))
+            .filter(|o| !o.device_path.is_empty())
+            .collect();
+        outputs.sort_by(|a, b| a.device_path.cmp(&b.device_path));
+        outputs.dedup_by(|a, b| a.device_path == b.device_path);
+        outputs
+    }
+}
+
+#[cfg(windows)]
+pub use windows_api::outputs;
+
+#[cfg(not(windows))]
+pub fn outputs(_display_name: &str) -> Vec<Output> {
+    Vec::new()
+}
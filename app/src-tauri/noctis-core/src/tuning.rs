@@ -0,0 +1,200 @@
+//! Guided curve tuning - simulated annealing over the Shadow Hunter curve constants driven by
+//! the user's "better / worse" answers. Each step fades a small variation of the current curve
+//! onto the monitor; better ones are kept, worse ones are occasionally kept while the search is
+//! still hot so it can leave a local preference. Steps shrink as it cools, converging on a curve
+//! that is stored as the panel's tuning
+
+use crate::gamma::{self, CurveConstants, GammaRamp};
+use crate::settings::{self, PanelTuning};
+use serde::Serialize;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Starting temperature: step size is scaled by it, and a worse variation is kept with
+/// probability e^(-1 / temperature)
+const START_TEMPERATURE: f32 = 1.0;
+
+/// Temperature kept after each answer
+const COOLING: f32 = 0.85;
+
+/// The search has converged below this temperature (about 15 answers)
+const MIN_TEMPERATURE: f32 = 0.1;
+
+/// Largest change per step at the starting temperature
+const MAX_LIFT_STEP: f32 = 0.06;
+const MIN_GAMMA_STEP: f32 = 0.1;
+
+/// Range the constants are searched in
+const MAX_LIFT_RANGE: (f32, f32) = (0.05, 0.45);
+const MIN_GAMMA_RANGE: (f32, f32) = (0.3, 0.9);
+
+/// Time taken to fade between two curves
+const FADE: Duration = Duration::from_millis(300);
+const FADE_STEPS: u32 = 15;
+
+/// A variation shown to the user
+#[derive(Serialize, Clone, Debug)]
+pub struct Proposal {
+    pub monitor: u32,
+    /// Intensity the curves are shown at
+    pub intensity: f32,
+    /// Answers given so far
+    pub step: u32,
+    pub temperature: f32,
+    /// Curve kept so far
+    pub current: CurveConstants,
+    /// Variation on screen, waiting for an answer
+    pub candidate: CurveConstants,
+    /// Cool enough to stop; `finish` stores `current`
+    pub converged: bool,
+}
+
+/// A tuning session in progress
+struct Session {
+    proposal: Proposal,
+    /// Ramp on the monitor before tuning started
+    previous: Option<GammaRamp>,
+    rng: u64,
+}
+
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+/// Next value of a xorshift generator, as a float in [0, 1)
+fn random(state: &mut u64) -> f32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    (*state >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// A random neighbour of `curve`, further away the hotter the search
+fn neighbour(curve: CurveConstants, temperature: f32, rng: &mut u64) -> CurveConstants {
+    let mut step = |size: f32| (random(rng) * 2.0 - 1.0) * size * temperature;
+    CurveConstants {
+        max_lift: (curve.max_lift + step(MAX_LIFT_STEP)).clamp(MAX_LIFT_RANGE.0, MAX_LIFT_RANGE.1),
+        min_gamma: (curve.min_gamma + step(MIN_GAMMA_STEP)).clamp(MIN_GAMMA_RANGE.0, MIN_GAMMA_RANGE.1),
+    }
+}
+
+/// Blend one ramp into another over FADE, so variations are compared without a hard cut
+fn fade(monitor: u32, from: &GammaRamp, to: &GammaRamp) -> Result<(), String> {
+    let blend = |a: &[u16; 256], b: &[u16; 256], t: f32| {
+        let mut out = [0u16; 256];
+        for (i, entry) in out.iter_mut().enumerate() {
+            *entry = (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t).round() as u16;
+        }
+        out
+    };
+    for step in 1..=FADE_STEPS {
+        let t = step as f32 / FADE_STEPS as f32;
+        let ramp = GammaRamp { red: blend(&from.red, &to.red, t), green: blend(&from.green, &to.green, t), blue: blend(&from.blue, &to.blue, t) };
+        gamma::set_ramp(monitor, &ramp)?;
+        thread::sleep(FADE / FADE_STEPS);
+    }
+    Ok(())
+}
+
+fn monitor_name(monitor: u32) -> Result<String, String> {
+    gamma::get_monitors()
+        .into_iter()
+        .find(|m| m.index == monitor)
+        .map(|m| m.name)
+        .ok_or_else(|| crate::i18n::format("error.monitor_not_found", &[&monitor]))
+}
+
+/// Start tuning a monitor's curve at an intensity, from its stored tuning or the defaults
+pub fn start(monitor: u32, intensity: f32) -> Result<Proposal, String> {
+    let name = monitor_name(monitor)?;
+    let current = settings::get().panel_tuning.iter().find(|t| t.monitor == name).map(|t| t.curve).unwrap_or_default();
+    let mut rng = std::collections::hash_map::RandomState::new().build_hasher().finish() | 1;
+    let candidate = neighbour(current, START_TEMPERATURE, &mut rng);
+
+    let mut session = SESSION.lock().unwrap();
+    if let Some(old) = session.take() {
+        restore(&old);
+    }
+    let previous = gamma::active_ramp(monitor);
+    let from = previous.clone().unwrap_or_else(gamma::identity_ramp);
+    fade(monitor, &from, &gamma::curve_ramp(intensity, candidate))?;
+
+    let proposal = Proposal {
+        monitor,
+        intensity,
+        step: 0,
+        temperature: START_TEMPERATURE,
+        current,
+        candidate,
+        converged: false,
+    };
+    *session = Some(Session { proposal: proposal.clone(), previous, rng });
+    Ok(proposal)
+}
+
+/// Record whether the variation on screen looks better than the curve kept so far, and show
+/// the next one unless the search has converged
+pub fn respond(better: bool) -> Result<Proposal, String> {
+    let mut guard = SESSION.lock().unwrap();
+    let session = guard.as_mut().ok_or_else(|| crate::i18n::text("error.no_tuning"))?;
+    let proposal = &mut session.proposal;
+    if proposal.converged {
+        return Ok(proposal.clone());
+    }
+
+    let shown = proposal.candidate;
+    let accept = better || random(&mut session.rng) < (-1.0 / proposal.temperature).exp();
+    if accept {
+        proposal.current = shown;
+    }
+    proposal.step += 1;
+    proposal.temperature *= COOLING;
+    proposal.converged = proposal.temperature < MIN_TEMPERATURE;
+
+    // Once converged the kept curve stays on screen for a last look
+    proposal.candidate = if proposal.converged {
+        proposal.current
+    } else {
+        neighbour(proposal.current, proposal.temperature, &mut session.rng)
+    };
+    let (monitor, intensity) = (proposal.monitor, proposal.intensity);
+    fade(monitor, &gamma::curve_ramp(intensity, shown), &gamma::curve_ramp(intensity, proposal.candidate))?;
+    Ok(proposal.clone())
+}
+
+/// The tuning in progress, if any
+pub fn current() -> Option<Proposal> {
+    SESSION.lock().unwrap().as_ref().map(|s| s.proposal.clone())
+}
+
+/// Put back what the monitor showed before tuning, redrawn with the stored tuning
+fn restore(session: &Session) {
+    let monitor = session.proposal.monitor;
+    let applied = crate::night_mode::intensity_of(crate::night_mode::Source::Gamma(monitor));
+    let _ = if applied > 0.0 {
+        gamma::set_gamma(applied, monitor)
+    } else {
+        match &session.previous {
+            Some(ramp) => gamma::set_ramp(monitor, ramp),
+            None => gamma::reset_monitor(monitor),
+        }
+    };
+}
+
+/// End tuning, storing the kept curve as the panel's tuning if `save`
+pub fn finish(save: bool) -> Result<Option<CurveConstants>, String> {
+    let Some(session) = SESSION.lock().unwrap().take() else { return Ok(None) };
+    let result = if save {
+        let name = monitor_name(session.proposal.monitor)?;
+        let curve = session.proposal.current;
+        settings::update(|s| {
+            s.panel_tuning.retain(|t| t.monitor != name);
+            s.panel_tuning.push(PanelTuning { monitor: name.clone(), curve });
+        })?;
+        Some(curve)
+    } else {
+        None
+    };
+    restore(&session);
+    Ok(result)
+}
@@ -0,0 +1,107 @@
+//! Update checker - polls a static JSON manifest for new releases (opt-in)
+//! Releases can be rolled out in stages: each install has a fixed random bucket and only
+//! sees a release once the manifest's rollout fraction reaches it
+
+use serde::{Deserialize, Serialize};
+use std::hash::{BuildHasher, Hasher};
+
+/// Manifest published alongside each release
+const DEFAULT_ENDPOINT: &str = "https://github.com/edwarddjss/noctis/releases/latest/download/latest.json";
+
+#[cfg(feature = "network")]
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Update check configuration
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct UpdateSettings {
+    /// Check for updates at startup
+    pub enabled: bool,
+    /// URL of the release manifest
+    pub endpoint: String,
+    /// This install's position in staged rollouts (0.0 - 1.0), assigned on the first check
+    pub rollout_bucket: Option<f32>,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            rollout_bucket: None,
+        }
+    }
+}
+
+/// A published release, as described by the manifest
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Release {
+    pub version: String,
+    #[serde(default)]
+    pub notes: String,
+    /// Download page or installer
+    #[serde(default)]
+    pub url: String,
+    /// Fraction of installs the release is offered to (1.0 = everyone)
+    #[serde(default = "full_rollout")]
+    pub rollout: f32,
+}
+
+fn full_rollout() -> f32 {
+    1.0
+}
+
+/// Numeric parts of a version like "1.2.0" (pre-release suffixes are ignored)
+fn version_parts(version: &str) -> Vec<u32> {
+    version
+        .trim_start_matches('v')
+        .split(['.', '-', '+'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+fn is_newer(candidate: &str, current: &str) -> bool {
+    version_parts(candidate) > version_parts(current)
+}
+
+/// This install's rollout bucket, assigning a random one the first time
+fn rollout_bucket() -> Result<f32, String> {
+    if let Some(bucket) = crate::settings::get().updates.rollout_bucket {
+        return Ok(bucket);
+    }
+    // RandomState is seeded from the OS, which is all the randomness this needs
+    let random = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    let bucket = (random % 10_000) as f32 / 10_000.0;
+    crate::settings::update(|s| s.updates.rollout_bucket = Some(bucket))?;
+    Ok(bucket)
+}
+
+/// Download and parse the release manifest
+#[cfg(feature = "network")]
+fn fetch(endpoint: &str) -> Result<Release, String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .into();
+    agent
+        .get(endpoint)
+        .call()
+        .map_err(|e| format!("Failed to check for updates: {}", e))?
+        .body_mut()
+        .read_json()
+        .map_err(|e| format!("Invalid update manifest: {}", e))
+}
+
+#[cfg(not(feature = "network"))]
+fn fetch(_endpoint: &str) -> Result<Release, String> {
+    Err(crate::i18n::text("error.not_in_build"))
+}
+
+/// Fetch the manifest and return the release if it is newer than `current` and rolled out to this install
+pub fn check(current: &str) -> Result<Option<Release>, String> {
+    let release = fetch(&crate::settings::get().updates.endpoint)?;
+    if !is_newer(&release.version, current) || rollout_bucket()? >= release.rollout {
+        return Ok(None);
+    }
+    Ok(Some(release))
+}
@@ -0,0 +1,69 @@
+//! Fast user switching - suspends effects while this session is disconnected from the console
+//! Gamma ramps belong to the display, not the session, so they would otherwise stay on screen
+//! for whoever logs in next. Listens for WM_WTSSESSION_CHANGE on the message_window and resumes
+//! effects when the session is connected again, locally or over Remote Desktop
+
+use std::ffi::c_void;
+use std::sync::Mutex;
+
+/// Suspension reason used while the session is disconnected
+const REASON: &str = "session_disconnected";
+
+const WM_WTSSESSION_CHANGE: u32 = 0x02B1;
+const WTS_CONSOLE_CONNECT: usize = 0x1;
+const WTS_CONSOLE_DISCONNECT: usize = 0x2;
+const WTS_REMOTE_CONNECT: usize = 0x3;
+const WTS_REMOTE_DISCONNECT: usize = 0x4;
+const NOTIFY_FOR_THIS_SESSION: u32 = 0;
+
+#[cfg(windows)]
+#[link(name = "wtsapi32")]
+extern "system" {
+    fn WTSRegisterSessionNotification(hwnd: *mut c_void, flags: u32) -> i32;
+}
+
+type ChangeCallback = Box<dyn Fn(Option<bool>) + Send>;
+
+/// Called with each suspend change
+static WATCHER: Mutex<Option<ChangeCallback>> = Mutex::new(None);
+
+/// Suspend or resume effects for a session change
+/// Returns Some(true) if effects were suspended, Some(false) if resumed, None if unchanged
+fn session_changed(event: usize) -> Option<bool> {
+    match event {
+        WTS_CONSOLE_DISCONNECT | WTS_REMOTE_DISCONNECT if crate::suspend::suspend(REASON) => Some(true),
+        WTS_CONSOLE_CONNECT | WTS_REMOTE_CONNECT if crate::suspend::resume(REASON) => Some(false),
+        _ => None,
+    }
+}
+
+/// Handle a message for the shared message window, if it's a session change
+#[cfg(windows)]
+pub fn window_message(msg: u32, event: usize) -> Option<isize> {
+    if msg != WM_WTSSESSION_CHANGE {
+        return None;
+    }
+    let changed = session_changed(event);
+    if let Some(on_change) = WATCHER.lock().unwrap().as_ref() {
+        on_change(changed);
+    }
+    Some(0)
+}
+
+/// Ask for session changes on the shared message window
+///
+/// # Safety
+/// `hwnd` must be a window owned by the calling thread
+#[cfg(windows)]
+pub unsafe fn register(hwnd: *mut c_void) {
+    WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION);
+}
+
+/// Follow this session connecting and disconnecting, once message_window is started
+/// `on_change` receives suspend changes in the same form as blocklist::enforce
+pub fn on_change<F>(on_change: F)
+where
+    F: Fn(Option<bool>) + Send + 'static,
+{
+    *WATCHER.lock().unwrap() = Some(Box::new(on_change));
+}
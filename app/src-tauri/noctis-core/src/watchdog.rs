@@ -0,0 +1,91 @@
+//! Watchdog companion - resets the display if Noctis dies without cleaning up
+//! Gamma ramps outlive the process that set them, so a crash would leave the screen lifted.
+//! A second copy of the executable started with `--watchdog <pid>` waits for the main
+//! process to exit and, if the session snapshot says it never shut down cleanly, restores
+//! default ramps. (Magnification effects end with the process that applied them.)
+
+use std::ffi::c_void;
+
+/// Command line flag that runs the executable as a watchdog for a process id
+const WATCHDOG_FLAG: &str = "--watchdog";
+
+const SYNCHRONIZE: u32 = 0x00100000;
+const INFINITE: u32 = 0xFFFFFFFF;
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> *mut c_void;
+    fn WaitForSingleObject(handle: *mut c_void, milliseconds: u32) -> u32;
+    fn CloseHandle(handle: *mut c_void) -> i32;
+}
+
+/// Process id to watch if this instance was started as a watchdog
+pub fn watched_pid() -> Option<u32> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == WATCHDOG_FLAG)?;
+    args.get(index + 1)?.parse().ok()
+}
+
+/// Whether the last session left effects behind without a clean shutdown
+fn abandoned_effects() -> bool {
+    crate::session::last().is_some_and(|last| !last.clean_exit && !last.monitors.is_empty())
+}
+
+/// Restore default ramps if the previous session was abandoned
+/// Used at startup in case the watchdog itself was killed along with the app
+pub fn cleanup_abandoned() {
+    if abandoned_effects() {
+        crate::gamma::restore_all();
+    }
+}
+
+/// Block until a process exits (returns at once if it can't be opened)
+#[cfg(windows)]
+pub fn wait_for(pid: u32) {
+    unsafe {
+        let handle = OpenProcess(SYNCHRONIZE, 0, pid);
+        if handle.is_null() {
+            return;
+        }
+        WaitForSingleObject(handle, INFINITE);
+        CloseHandle(handle);
+    }
+}
+
+/// Block until a process exits, then clean up after it if needed
+#[cfg(windows)]
+pub fn run(pid: u32) {
+    wait_for(pid);
+    // Swapped cursors also outlive the process; reloading the user's scheme is harmless otherwise
+    crate::cursor::restore_scheme();
+    cleanup_abandoned();
+}
+
+/// Start the watchdog for this process
+#[cfg(windows)]
+pub fn spawn() -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))?;
+    let mut command = std::process::Command::new(exe);
+    command.arg(WATCHDOG_FLAG).arg(std::process::id().to_string());
+    // The watchdog must find the same config folder to read the session snapshot
+    if crate::portable::is_portable() {
+        command.arg("--portable");
+    }
+    command
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .map_err(|e| format!("Failed to start watchdog: {}", e))?;
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn run(_pid: u32) {}
+
+#[cfg(not(windows))]
+pub fn spawn() -> Result<(), String> {
+    Err(crate::i18n::text("error.windows_only"))
+}
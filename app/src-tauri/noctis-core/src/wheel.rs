@@ -0,0 +1,221 @@
+//! Intensity wheel - hold a modifier chord and scroll to adjust intensity
+//! A low-level mouse hook is installed only while the chord is held, so scrolling
+//! everywhere else (and mouse input latency in games) is untouched
+
+use crate::hotkey::{self, Modifier};
+use crate::night_mode::{self, Source};
+use crate::{command_queue, controller, gamma, profiles};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Mutex;
+
+/// How often the hook thread checks whether the chord is held and applies scrolling
+const POLL_MS: u32 = 30;
+
+/// Wheel movement of one notch
+const WHEEL_DELTA: f32 = 120.0;
+
+/// Intensity wheel configuration
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WheelSettings {
+    pub enabled: bool,
+    /// Modifiers to hold while scrolling, e.g. "Ctrl+Alt"
+    pub chord: String,
+    /// Intensity change per wheel notch
+    pub step: f32,
+}
+
+impl Default for WheelSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            chord: "Ctrl+Alt".to_string(),
+            step: 0.05,
+        }
+    }
+}
+
+/// Active chord and step, read by the polling thread (None while disabled)
+static CHORD: Mutex<Option<(Vec<Modifier>, f32)>> = Mutex::new(None);
+
+/// Monitor and intensity being adjusted while the chord is held
+/// Accumulated locally so fast scrolling isn't lost to the queue coalescing writes
+static TARGET: Mutex<Option<(u32, f32)>> = Mutex::new(None);
+
+/// Wheel delta swallowed by the hook but not yet applied, and where the cursor was
+/// The hook only records these; the thread's timer tick does the slow work, because
+/// Windows silently removes low-level hooks that take too long to return
+static PENDING: AtomicI32 = AtomicI32::new(0);
+static CURSOR: (AtomicI32, AtomicI32) = (AtomicI32::new(0), AtomicI32::new(0));
+
+/// Validate and activate wheel settings
+pub fn configure(config: &WheelSettings) -> Result<(), String> {
+    let chord = hotkey::parse_modifiers(&config.chord).map_err(|e| e.to_string())?;
+    if !(config.step > 0.0 && config.step <= 1.0) {
+        return Err(crate::i18n::format("error.unit_interval", &[&crate::i18n::text("field.wheel_step"), &config.step]));
+    }
+    *CHORD.lock().unwrap() = config.enabled.then_some((chord, config.step));
+    Ok(())
+}
+
+/// Monitor the wheel adjusts: the active profile's, else the one under the cursor
+fn target_monitor(x: i32, y: i32) -> u32 {
+    if let Some(profile) = profiles::active() {
+        return profile.monitor;
+    }
+    let monitors = gamma::get_monitors();
+    monitors
+        .iter()
+        .find(|m| x >= m.x && y >= m.y && x < m.x + m.width as i32 && y < m.y + m.height as i32)
+        .or_else(|| monitors.iter().find(|m| m.is_primary))
+        .map(|m| m.index)
+        .unwrap_or(1)
+}
+
+/// Record wheel movement from the hook (positive = scrolled up = stronger)
+fn scroll(delta: i32, x: i32, y: i32) {
+    PENDING.fetch_add(delta, Ordering::Relaxed);
+    CURSOR.0.store(x, Ordering::Relaxed);
+    CURSOR.1.store(y, Ordering::Relaxed);
+}
+
+/// Apply the wheel movement recorded since the last tick
+fn flush() {
+    let delta = PENDING.swap(0, Ordering::Relaxed);
+    if delta == 0 {
+        return;
+    }
+    let Some(step) = CHORD.lock().unwrap().as_ref().map(|(_, step)| *step) else { return };
+    let notches = delta as f32 / WHEEL_DELTA;
+    let mut target = TARGET.lock().unwrap();
+    let (monitor, intensity) = *target.get_or_insert_with(|| {
+        let monitor = target_monitor(CURSOR.0.load(Ordering::Relaxed), CURSOR.1.load(Ordering::Relaxed));
+        (monitor, night_mode::intensity_of(Source::Gamma(monitor)))
+    });
+    let intensity = (intensity + notches * step).clamp(0.0, 1.0);
+    *target = Some((monitor, intensity));
+    let _ = command_queue::submit(monitor, intensity);
+    profiles::record_manual_intensity(monitor, intensity);
+    controller::manual_override();
+}
+
+#[cfg(windows)]
+mod hook {
+    use std::ffi::c_void;
+    use std::ptr;
+
+    const WH_MOUSE_LL: i32 = 14;
+    const WM_MOUSEWHEEL: usize = 0x020A;
+    const WM_TIMER: u32 = 0x0113;
+
+    /// POINT structure
+    #[repr(C)]
+    #[derive(Default)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    /// MSLLHOOKSTRUCT structure
+    #[repr(C)]
+    struct MouseHookData {
+        pt: Point,
+        mouse_data: u32,
+        flags: u32,
+        time: u32,
+        extra_info: usize,
+    }
+
+    /// MSG structure for the hook thread's message loop
+    #[repr(C)]
+    struct Msg {
+        hwnd: *mut c_void,
+        message: u32,
+        w_param: usize,
+        l_param: isize,
+        time: u32,
+        pt: Point,
+    }
+
+    type HookProc = unsafe extern "system" fn(i32, usize, isize) -> isize;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn SetWindowsHookExW(id: i32, hook: HookProc, module: *mut c_void, thread_id: u32) -> *mut c_void;
+        fn UnhookWindowsHookEx(hook: *mut c_void) -> i32;
+        fn CallNextHookEx(hook: *mut c_void, code: i32, w_param: usize, l_param: isize) -> isize;
+        fn GetAsyncKeyState(key: i32) -> i16;
+        fn SetTimer(hwnd: *mut c_void, id: usize, elapse_ms: u32, func: *const c_void) -> usize;
+        fn GetMessageW(msg: *mut Msg, hwnd: *mut c_void, filter_min: u32, filter_max: u32) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetModuleHandleW(module_name: *const u16) -> *mut c_void;
+    }
+
+    /// Whether every modifier in the active chord is held
+    fn chord_held() -> bool {
+        let chord = super::CHORD.lock().unwrap();
+        let Some((modifiers, _)) = chord.as_ref() else { return false };
+        modifiers
+            .iter()
+            .all(|m| m.virtual_keys().iter().any(|&vk| unsafe { GetAsyncKeyState(vk) } < 0))
+    }
+
+    /// Swallow wheel events while the chord is held and record them for the next tick
+    unsafe extern "system" fn mouse_proc(code: i32, w_param: usize, l_param: isize) -> isize {
+        if code >= 0 && w_param == WM_MOUSEWHEEL && chord_held() {
+            let data = &*(l_param as *const MouseHookData);
+            let delta = (data.mouse_data >> 16) as u16 as i16;
+            super::scroll(delta as i32, data.pt.x, data.pt.y);
+            return 1;
+        }
+        CallNextHookEx(ptr::null_mut(), code, w_param, l_param)
+    }
+
+    /// Poll the chord and keep the hook installed only while it's held
+    pub fn run() {
+        unsafe {
+            SetTimer(ptr::null_mut(), 0, super::POLL_MS, ptr::null());
+            let mut hook: *mut c_void = ptr::null_mut();
+            let mut msg = Msg {
+                hwnd: ptr::null_mut(),
+                message: 0,
+                w_param: 0,
+                l_param: 0,
+                time: 0,
+                pt: Point::default(),
+            };
+            // The hook is called on this thread, so it must keep pumping messages
+            while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) > 0 {
+                if msg.message != WM_TIMER {
+                    continue;
+                }
+                super::flush();
+                let held = chord_held();
+                if held && hook.is_null() {
+                    hook = SetWindowsHookExW(WH_MOUSE_LL, mouse_proc, GetModuleHandleW(ptr::null()), 0);
+                } else if !held && !hook.is_null() {
+                    UnhookWindowsHookEx(hook);
+                    hook = ptr::null_mut();
+                    // The next chord starts from whatever intensity is applied by then
+                    *super::TARGET.lock().unwrap() = None;
+                }
+            }
+        }
+    }
+}
+
+/// Start the wheel thread with the saved settings
+#[cfg(windows)]
+pub fn start() {
+    let _ = configure(&crate::settings::get().wheel);
+    std::thread::spawn(hook::run);
+}
+
+#[cfg(not(windows))]
+pub fn start() {
+    let _ = configure(&crate::settings::get().wheel);
+}
@@ -0,0 +1,186 @@
+//! Wind-down - from a set time of night, warms and dims every display over 30 - 60 minutes
+//! and then reminds the player to stop. Layered on top of whatever Magnification effect is
+//! active, held until morning, and skippable for the rest of the night
+
+use crate::magnification::MagColorEffect;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How often the wind-down progress is updated
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Allowed length of the fade, in minutes
+const MIN_DURATION: u16 = 30;
+const MAX_DURATION: u16 = 60;
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Smallest progress change worth rewriting the effect for
+const PROGRESS_STEP: f32 = 0.01;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct WindDownSettings {
+    pub enabled: bool,
+    /// Local time the fade starts
+    pub start_hour: u8,
+    pub start_minute: u8,
+    /// Minutes from the start to full warmth and dimming
+    pub duration_minutes: u16,
+    /// Local hour the wind-down lets go in the morning
+    pub end_hour: u8,
+    /// Temperature at the end of the fade
+    pub warmest_kelvin: f32,
+    /// Dimming at the end of the fade (0.0 - 1.0 of the Magnification dim range)
+    pub dim: f32,
+    /// Remind to stop once the fade completes
+    pub reminder: bool,
+}
+
+impl Default for WindDownSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start_hour: 23,
+            start_minute: 0,
+            duration_minutes: 45,
+            end_hour: 6,
+            warmest_kelvin: 2700.0,
+            dim: 0.6,
+            reminder: true,
+        }
+    }
+}
+
+impl WindDownSettings {
+    fn start(&self) -> u16 {
+        self.start_hour as u16 * 60 + self.start_minute as u16
+    }
+
+    /// Minutes from the start until the wind-down lets go in the morning
+    fn window(&self) -> u16 {
+        match (self.end_hour as u16 * 60 + MINUTES_PER_DAY - self.start()) % MINUTES_PER_DAY {
+            0 => MINUTES_PER_DAY,
+            window => window,
+        }
+    }
+
+    /// How far the fade is at a local minute of the day: None outside the wind-down,
+    /// rising from 0 at the start to 1 after `duration_minutes`, then held until the end hour
+    pub fn progress_at(&self, minute: u16) -> Option<f32> {
+        let since = (minute + MINUTES_PER_DAY - self.start()) % MINUTES_PER_DAY;
+        (since < self.window()).then(|| (since as f32 / self.duration_minutes.max(1) as f32).min(1.0))
+    }
+}
+
+pub fn validate(config: &WindDownSettings) -> Result<(), String> {
+    if config.start_hour > 23 || config.end_hour > 23 || config.start_minute > 59 {
+        return Err(crate::i18n::format(
+            "error.wind_down_times",
+            &[&format!("{}:{:02}", config.start_hour, config.start_minute), &format!("{}:00", config.end_hour)],
+        ));
+    }
+    if !(MIN_DURATION..=MAX_DURATION).contains(&config.duration_minutes) {
+        return Err(crate::i18n::format(
+            "error.wind_down_duration",
+            &[&MIN_DURATION, &MAX_DURATION, &config.duration_minutes],
+        ));
+    }
+    if config.window() <= config.duration_minutes {
+        return Err(crate::i18n::text("error.wind_down_window"));
+    }
+    crate::temperature::validate(&crate::temperature::TemperatureSettings {
+        enabled: true,
+        coupling: Default::default(),
+        warmest_kelvin: config.warmest_kelvin,
+    })?;
+    if !(0.0..=1.0).contains(&config.dim) {
+        return Err(crate::i18n::format("error.unit_interval", &[&crate::i18n::text("field.wind_down_dim"), &config.dim]));
+    }
+    Ok(())
+}
+
+/// Wind-down state, sent to the UI when it changes
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct WindDownStatus {
+    /// Inside tonight's wind-down window
+    pub active: bool,
+    /// Skipped for the rest of tonight
+    pub skipped: bool,
+    /// Fade progress (0.0 - 1.0)
+    pub progress: f32,
+}
+
+/// Tonight's wind-down was skipped; cleared when the window ends
+static SKIPPED: AtomicBool = AtomicBool::new(false);
+
+/// Current wind-down state
+pub fn status() -> WindDownStatus {
+    let config = crate::settings::get().wind_down;
+    let progress = config.enabled.then(|| config.progress_at(crate::schedule::local_minute())).flatten();
+    WindDownStatus {
+        active: progress.is_some(),
+        skipped: progress.is_some() && SKIPPED.load(Ordering::Relaxed),
+        progress: progress.unwrap_or(0.0),
+    }
+}
+
+/// Warmth and dimming in force right now, as (kelvin, dim), or None if nothing applies
+fn current() -> Option<(f32, f32)> {
+    let status = status();
+    if !status.active || status.skipped || crate::suspend::is_suspended() || crate::accessibility::friendly() {
+        return None;
+    }
+    let config = crate::settings::get().wind_down;
+    let kelvin = crate::temperature::NEUTRAL_KELVIN + (config.warmest_kelvin - crate::temperature::NEUTRAL_KELVIN) * status.progress;
+    Some((kelvin, config.dim * status.progress))
+}
+
+/// An effect with the wind-down warmth and dimming applied after it
+pub fn compose(effect: &MagColorEffect) -> MagColorEffect {
+    match current() {
+        Some((kelvin, dim)) => effect.then(&MagColorEffect::warm(kelvin)).then(&MagColorEffect::dim(dim)),
+        None => *effect,
+    }
+}
+
+/// Skip tonight's wind-down, or bring it back
+pub fn skip(skipped: bool) {
+    SKIPPED.store(skipped, Ordering::Relaxed);
+    crate::magnification::refresh();
+}
+
+/// Follow the wind-down progress; `on_change` gets the status whenever it changes and
+/// `on_reminder` the reminder text once per night, when the fade completes
+pub fn start<C, R>(on_change: C, on_reminder: R)
+where
+    C: Fn(WindDownStatus) + Send + 'static,
+    R: Fn(String) + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut last = WindDownStatus::default();
+        let mut reminded = false;
+        while !crate::shutdown::is_shutting_down() {
+            let status = status();
+            if !status.active {
+                SKIPPED.store(false, Ordering::Relaxed);
+                reminded = false;
+            }
+            let moved = (status.progress - last.progress).abs() >= PROGRESS_STEP;
+            if status.active != last.active || status.skipped != last.skipped || moved {
+                crate::magnification::refresh();
+                on_change(status.clone());
+                last = status.clone();
+            }
+            if status.active && !status.skipped && status.progress >= 1.0 && !reminded {
+                reminded = true;
+                if crate::settings::get().wind_down.reminder {
+                    on_reminder(crate::i18n::text("wind_down.reminder"));
+                }
+            }
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,55 @@
+//! FFI workers - run blocking display and capture calls off the IPC thread
+//! A slow driver call (CreateDCW, SetDeviceGammaRamp, BitBlt) only stalls its own lane,
+//! never the UI or tray. Each lane runs jobs one at a time, in submission order
+
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A dedicated thread that runs submitted jobs sequentially
+pub struct Worker {
+    name: &'static str,
+    sender: OnceLock<Mutex<mpsc::Sender<Job>>>,
+}
+
+/// Gamma ramps, Magnification effects and other display writes
+pub static DISPLAY: Worker = Worker::new("noctis-display");
+
+/// Screen capture for the sensor and calibration
+pub static CAPTURE: Worker = Worker::new("noctis-capture");
+
+impl Worker {
+    const fn new(name: &'static str) -> Self {
+        Self { name, sender: OnceLock::new() }
+    }
+
+    fn sender(&self) -> &Mutex<mpsc::Sender<Job>> {
+        self.sender.get_or_init(|| {
+            let (tx, rx) = mpsc::channel::<Job>();
+            let _ = thread::Builder::new().name(self.name.to_string()).spawn(move || {
+                for job in rx {
+                    job();
+                }
+            });
+            Mutex::new(tx)
+        })
+    }
+
+    /// Run a job on this worker and wait for its result without blocking the caller's thread
+    pub async fn run<T, F>(&self, job: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.sender()
+            .lock()
+            .unwrap()
+            .send(Box::new(move || {
+                let _ = tx.send(job());
+            }))
+            .map_err(|_| format!("{} worker stopped", self.name))?;
+        rx.await.map_err(|_| format!("{} worker stopped", self.name))?
+    }
+}
@@ -0,0 +1,291 @@
+//! Config commands - integration settings and where the config store lives
+
+use crate::{blocklist, control, crash, discord, elevation, foreground, gamma, i18n, lights, markers, mqtt, night_mode, perf, peripherals, portable, preflight, profiles, safe_mode, scripting, settings, snippets, startup, updates, worker};
+use tauri::{AppHandle, Emitter};
+
+#[tauri::command]
+pub fn get_peripheral_settings() -> peripherals::PeripheralSettings {
+    settings::get().peripherals
+}
+
+/// Update the OpenRGB integration, loading the profile for the current state if enabled
+#[tauri::command]
+pub async fn set_peripheral_settings(config: peripherals::PeripheralSettings) -> Result<(), String> {
+    settings::update(|s| s.peripherals = config.clone())?;
+    let profile = if night_mode::current().engaged { config.night_profile.clone() } else { config.day_profile.clone() };
+    if config.enabled && !profile.is_empty() {
+        tauri::async_runtime::spawn_blocking(move || peripherals::load_profile(&config, &profile))
+            .await
+            .map_err(|e| e.to_string())??;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_light_settings() -> lights::LightSettings {
+    settings::get().lights
+}
+
+/// Update the smart-light integration and bring the lights in line with the current state
+#[tauri::command]
+pub async fn set_light_settings(config: lights::LightSettings) -> Result<(), String> {
+    settings::update(|s| s.lights = config)?;
+    tauri::async_runtime::spawn_blocking(lights::sync_now)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn get_mqtt_settings() -> mqtt::MqttSettings {
+    settings::get().mqtt
+}
+
+/// Update the MQTT bridge; it reconnects with the new settings within a second
+#[tauri::command]
+pub fn set_mqtt_settings(config: mqtt::MqttSettings) -> Result<(), String> {
+    settings::update(|s| s.mqtt = config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_control_settings() -> control::ControlSettings {
+    settings::get().control
+}
+
+/// Update the local control socket and pipe; they follow the new settings within a second
+#[tauri::command]
+pub fn set_control_settings(config: control::ControlSettings) -> Result<(), String> {
+    if config.port == 0 {
+        return Err(i18n::text("error.control_port"));
+    }
+    settings::update(|s| s.control = config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_discord_settings() -> discord::DiscordSettings {
+    settings::get().discord
+}
+
+/// Update Discord Rich Presence; it connects or clears itself within seconds
+#[tauri::command]
+pub fn set_discord_settings(config: discord::DiscordSettings) -> Result<(), String> {
+    let config = discord::DiscordSettings { application_id: config.application_id.trim().to_string(), ..config };
+    discord::validate(&config)?;
+    settings::update(|s| s.discord = config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_marker_settings() -> markers::MarkerSettings {
+    settings::get().markers
+}
+
+/// Update the recording marker sidecar; markers are always sent to the UI
+#[tauri::command]
+pub fn set_marker_settings(config: markers::MarkerSettings) -> Result<(), String> {
+    settings::update(|s| s.markers = config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_script_settings() -> scripting::ScriptSettings {
+    settings::get().scripting
+}
+
+/// Turn the script hooks on or off; the script itself is the script.rhai file in the config folder
+#[tauri::command]
+pub fn set_script_settings(config: scripting::ScriptSettings) -> Result<(), String> {
+    scripting::validate(&config)?;
+    settings::update(|s| s.scripting = config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_script_status() -> scripting::ScriptStatus {
+    scripting::status()
+}
+
+/// AutoHotkey or PowerShell snippet for common actions, using the enabled control transport
+#[tauri::command]
+pub fn export_automation_snippet(kind: snippets::ScriptKind) -> String {
+    snippets::generate(kind, &settings::get().control)
+}
+
+/// Move the config store to a custom folder (e.g. OneDrive/Syncthing); null restores the default
+#[tauri::command]
+pub fn set_config_dir(app: AppHandle, path: Option<String>) -> Result<String, String> {
+    let dir = settings::set_config_dir(path.map(std::path::PathBuf::from))?;
+    crate::emit_suspend_change(&app, blocklist::enforce(foreground::current().as_ref()));
+    let _ = app.emit("settings-changed", settings::get());
+    let list = profiles::reload(|event| crate::emit_profile_event(&app, event)).unwrap_or_default();
+    let _ = app.emit("profiles-changed", list);
+    Ok(dir.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn get_config_dir() -> String {
+    settings::config_dir().to_string_lossy().to_string()
+}
+
+/// Whether running in portable mode, so the UI can hide autostart/install options
+#[tauri::command]
+pub fn is_portable() -> bool {
+    portable::is_portable()
+}
+
+#[tauri::command]
+pub fn get_background_mode() -> bool {
+    settings::get().background_mode
+}
+
+/// Start without the window from the next launch on
+#[tauri::command]
+pub fn set_background_mode(enabled: bool) -> Result<(), String> {
+    settings::update(|s| s.background_mode = enabled)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_startup_policy() -> startup::StartupPolicy {
+    settings::get().startup
+}
+
+/// Choose what launching Noctis does to the display, from the next launch on
+#[tauri::command]
+pub fn set_startup_policy(policy: startup::StartupPolicy) -> Result<(), String> {
+    settings::update(|s| s.startup = policy)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_window_policy() -> settings::WindowPolicy {
+    settings::get().window
+}
+
+/// Update the window policy, moving the window to a newly pinned monitor
+#[tauri::command]
+pub fn set_window_policy(app: AppHandle, policy: settings::WindowPolicy) -> Result<(), String> {
+    if let Some(pinned) = &policy.pin_monitor {
+        if !gamma::get_monitors().iter().any(|m| &m.device_id == pinned) {
+            return Err(i18n::format("error.monitor_disconnected", &[pinned]));
+        }
+    }
+    settings::update(|s| s.window = policy)?;
+    crate::pin_main_window(&app);
+    Ok(())
+}
+
+/// Configured locale (None follows the system language) and the locales available
+#[tauri::command]
+pub fn get_locale() -> (Option<String>, Vec<String>) {
+    (settings::get().locale, i18n::LOCALES.iter().map(|l| l.to_string()).collect())
+}
+
+#[tauri::command]
+pub fn set_locale(app: AppHandle, locale: Option<String>) -> Result<(), String> {
+    if let Some(locale) = locale.as_deref().filter(|l| !i18n::is_supported(l)) {
+        return Err(i18n::format("error.unsupported_locale", &[&locale]));
+    }
+    settings::update(|s| s.locale = locale)?;
+    crate::refresh_tray(&app);
+    Ok(())
+}
+
+/// Check the release manifest now; None if this is the latest version (or not yet rolled out here)
+/// Also emits `update-available` so every open view hears about it
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<updates::Release>, String> {
+    let current = app.package_info().version.to_string();
+    let release = tauri::async_runtime::spawn_blocking(move || updates::check(&current))
+        .await
+        .map_err(|e| e.to_string())??;
+    if let Some(release) = &release {
+        let _ = app.emit("update-available", release);
+    }
+    Ok(release)
+}
+
+#[tauri::command]
+pub fn get_update_settings() -> updates::UpdateSettings {
+    settings::get().updates
+}
+
+/// Update the check settings, keeping the assigned rollout bucket
+#[tauri::command]
+pub fn set_update_settings(config: updates::UpdateSettings) -> Result<(), String> {
+    settings::update(|s| s.updates = updates::UpdateSettings { rollout_bucket: s.updates.rollout_bucket, ..config })?;
+    Ok(())
+}
+
+/// Turn crash reporting on or off (takes effect at the next start)
+#[tauri::command]
+pub fn set_crash_reporting(enabled: bool) -> Result<(), String> {
+    settings::update(|s| s.crash_reports = enabled)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_crash_reports() -> Vec<crash::CrashReport> {
+    crash::list()
+}
+
+/// Copy a crash report and its minidump into a folder, e.g. to attach to a bug report
+#[tauri::command]
+pub fn export_crash_report(id: String, destination: String) -> Result<Vec<String>, String> {
+    let files = crash::export(&id, std::path::Path::new(&destination))?;
+    Ok(files.iter().map(|f| f.display().to_string()).collect())
+}
+
+/// Check permissions and display APIs, for the onboarding UI to act on
+/// Runs on the display lane since it touches the gamma and Magnification drivers
+#[tauri::command]
+pub async fn run_preflight() -> Result<preflight::PreflightReport, String> {
+    worker::DISPLAY.run(|| Ok(preflight::run())).await
+}
+
+/// Whether an elevated game in the foreground is out of the sensor's and hotkeys' reach
+#[tauri::command]
+pub fn get_elevation_status() -> elevation::ElevationStatus {
+    elevation::status(foreground::current().as_ref())
+}
+
+/// Start the elevated broker that captures and reads hotkeys for elevated games (UAC prompt)
+#[tauri::command]
+pub async fn start_elevated_broker() -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(elevation::start_broker).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn stop_elevated_broker() {
+    elevation::stop_broker();
+}
+
+/// Timings of the capture, analysis, curve and apply stages over their latest samples
+#[tauri::command]
+pub fn get_perf_stats() -> perf::PerfStats {
+    perf::stats()
+}
+
+#[tauri::command]
+pub fn reset_perf_stats() {
+    perf::reset();
+}
+
+/// Also write every timing as an ETW event, for recording alongside a game in WPR or PerfView
+#[tauri::command]
+pub fn set_perf_etw(enabled: bool) -> Result<(), String> {
+    settings::update(|s| s.perf_etw = enabled)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn is_safe_mode() -> bool {
+    safe_mode::is_active()
+}
+
+/// Leave safe mode, letting saved profiles and adjustments apply again
+#[tauri::command]
+pub fn exit_safe_mode(app: AppHandle) {
+    crate::emit_suspend_change(&app, safe_mode::exit());
+}
@@ -0,0 +1,488 @@
+//! Display commands - gamma, Magnification and auto-adjust control
+//! Writes run on the display worker lane so the UI thread never waits on a driver
+
+use crate::{accessibility, adjustment, backend, blue_light, backend_compare, calibration, color_filter, cursor, mag_conflict, command_queue, controller, gamma, magnification, night_mode, profiles, ramp_log, schedule, session, session_timer, settings, state, temperature, toggle, topology, tuning, wind_down, worker};
+use super::{monitor_index, unit_interval};
+use crate::monitor_groups::{self, Target};
+use tauri::{AppHandle, Emitter};
+
+/// Queued: rapid calls for the same monitor are coalesced and only the latest is applied
+/// `monitor` is a monitor index or a monitor group name
+#[tauri::command]
+pub fn set_gamma(value: f32, monitor: Target) -> Result<(), String> {
+    let value = unit_interval("field.intensity", value)?;
+    monitor.resolve()?.into_iter().try_for_each(|m| command_queue::submit(m, value))
+}
+
+/// Set intensity from a user action (slider, hotkey), as opposed to the auto-adjust loop
+/// Feeds the active profile's learned preference and holds auto-adjust off for a while;
+/// in hybrid mode it becomes an offset from the auto-adjust baseline instead
+#[tauri::command]
+pub async fn set_manual_intensity(value: f32, monitor: Target) -> Result<(), String> {
+    let (value, monitors) = (unit_interval("field.intensity", value)?, monitor.resolve()?);
+    for &monitor in &monitors {
+        profiles::record_manual_intensity(monitor, value);
+    }
+    if controller::mode() == controller::Mode::Hybrid {
+        // Auto-adjust owns the Magnification effect here, so the offset is applied through it
+        controller::set_manual_offset(value);
+        return worker::DISPLAY.run(move || magnification::apply_shadow_lift(value)).await;
+    }
+    for monitor in monitors {
+        command_queue::submit(monitor, value)?;
+    }
+    controller::manual_override();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn dim_monitor(brightness: f32, monitor: Target) -> Result<(), String> {
+    let (brightness, monitors) = (unit_interval("field.brightness", brightness)?, monitor.resolve()?);
+    worker::DISPLAY.run(move || monitors.into_iter().try_for_each(|m| gamma::dim_monitor(brightness, m))).await
+}
+
+/// Apply smart auto-adjustment based on screen brightness
+/// Uses Magnification API for instant system-wide effect
+/// brightness: 0.0-1.0 (screen brightness from sensor)
+#[tauri::command]
+pub async fn apply_smart_adjustment(brightness: f32) -> Result<(), String> {
+    let brightness = unit_interval("field.brightness", brightness)?;
+    worker::DISPLAY.run(move || magnification::apply_smart_adjustment(brightness)).await
+}
+
+/// What auto-adjust would do for a brightness with the given thresholds, without applying it
+/// `previous` is the decision before this reading, which the dead zone may keep
+#[tauri::command]
+pub fn simulate_adjustment(
+    brightness: f32,
+    config: adjustment::AdjustmentConfig,
+    previous: Option<adjustment::AdjustmentDecision>,
+) -> Result<adjustment::AdjustmentDecision, String> {
+    let brightness = unit_interval("field.brightness", brightness)?;
+    adjustment::validate(&config)?;
+    Ok(adjustment::decide(brightness, &config, previous))
+}
+
+#[tauri::command]
+pub fn get_adjustment_config() -> adjustment::AdjustmentConfig {
+    settings::get().adjustment
+}
+
+#[tauri::command]
+pub fn set_adjustment_config(config: adjustment::AdjustmentConfig) -> Result<(), String> {
+    adjustment::validate(&config)?;
+    settings::update(|s| s.adjustment = config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_mode() -> controller::Mode {
+    controller::mode()
+}
+
+/// Switch between "manual", "auto" and "hybrid"; leaving auto-adjust clears its effect
+#[tauri::command]
+pub async fn set_mode(mode: controller::Mode) -> Result<(), String> {
+    controller::set_mode(mode)?;
+    if mode == controller::Mode::Manual {
+        worker::DISPLAY
+            .run(|| {
+                magnification::new_adjustment_session();
+                magnification::remove_effects()
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_accessibility_state() -> accessibility::AccessibilityState {
+    accessibility::state()
+}
+
+/// Choose how effects behave under a contrast theme, applying a suspend change right away
+#[tauri::command]
+pub fn set_high_contrast_policy(app: AppHandle, policy: accessibility::HighContrastPolicy) -> Result<(), String> {
+    settings::update(|s| s.high_contrast = policy)?;
+    crate::emit_suspend_change(&app, accessibility::enforce());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_magnification_conflict() -> mag_conflict::ConflictState {
+    mag_conflict::state()
+}
+
+/// Choose whether to take over from or yield to another fullscreen color effect consumer
+#[tauri::command]
+pub async fn set_magnification_conflict_policy(policy: mag_conflict::ConflictPolicy) -> Result<mag_conflict::ConflictState, String> {
+    settings::update(|s| s.magnification_conflict = policy)?;
+    worker::DISPLAY.run(|| Ok(mag_conflict::apply_policy())).await
+}
+
+/// Windows color filter state, and whether it distorts the current effect
+#[tauri::command]
+pub fn get_system_color_filter() -> color_filter::SystemColorFilter {
+    color_filter::state()
+}
+
+/// Turn compensation for inverting color filters on or off, re-applying the effect with it
+#[tauri::command]
+pub async fn set_compensate_color_filter(enabled: bool) -> Result<color_filter::SystemColorFilter, String> {
+    settings::update(|s| s.compensate_color_filter = enabled)?;
+    worker::DISPLAY
+        .run(|| {
+            magnification::reapply();
+            Ok(color_filter::state())
+        })
+        .await
+}
+
+#[tauri::command]
+pub fn get_temperature_settings() -> temperature::TemperatureSettings {
+    settings::get().temperature
+}
+
+/// Couple color temperature to scene darkness or the time of night (applied by auto-adjust)
+#[tauri::command]
+pub fn set_temperature_settings(config: temperature::TemperatureSettings) -> Result<(), String> {
+    temperature::validate(&config)?;
+    settings::update(|s| s.temperature = config)?;
+    Ok(())
+}
+
+/// Switch to the night temperature curve that meets a low-blue-light target
+#[tauri::command]
+pub fn apply_blue_light_preset(preset: blue_light::BlueLightPreset) -> Result<temperature::TemperatureSettings, String> {
+    let config = blue_light::preset_settings(preset);
+    set_temperature_settings(config.clone())?;
+    Ok(config)
+}
+
+/// Estimated blue-light and melanopic reduction of the temperature curve, now and at its warmest
+#[tauri::command]
+pub fn get_blue_light_report() -> blue_light::BlueLightReport {
+    blue_light::report()
+}
+
+#[tauri::command]
+pub fn get_cursor_settings() -> cursor::CursorSettings {
+    settings::get().cursor
+}
+
+/// Swap the cursor for a dimmer, custom or hidden one while the screen is dimmed
+#[tauri::command]
+pub fn set_cursor_settings(config: cursor::CursorSettings) -> Result<(), String> {
+    cursor::validate(&config)?;
+    settings::update(|s| s.cursor = config)?;
+    cursor::refresh();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_schedule() -> schedule::ScheduleSettings {
+    settings::get().schedule
+}
+
+/// Set the time-of-day intensity caps auto-adjust is held under
+#[tauri::command]
+pub fn set_schedule(config: schedule::ScheduleSettings) -> Result<(), String> {
+    schedule::validate(&config)?;
+    for cap in schedule::all_caps(&config) {
+        unit_interval("field.max_intensity", cap.max_intensity)?;
+    }
+    settings::update(|s| s.schedule = config)?;
+    state::changed();
+    Ok(())
+}
+
+/// Give one day of the week its own caps, or return it to the weekday/weekend ones (None)
+#[tauri::command]
+pub fn set_schedule_override(day: schedule::Weekday, caps: Option<Vec<schedule::IntensityCap>>) -> Result<schedule::ScheduleSettings, String> {
+    let mut config = settings::get().schedule;
+    config.overrides.retain(|o| o.day != day);
+    if let Some(caps) = caps {
+        config.overrides.push(schedule::DayOverride { day, caps });
+    }
+    set_schedule(config.clone())?;
+    Ok(config)
+}
+
+#[tauri::command]
+pub fn get_wind_down_settings() -> wind_down::WindDownSettings {
+    settings::get().wind_down
+}
+
+/// Configure the late-night wind-down; a running one follows the new settings right away
+#[tauri::command]
+pub async fn set_wind_down_settings(config: wind_down::WindDownSettings) -> Result<(), String> {
+    wind_down::validate(&config)?;
+    settings::update(|s| s.wind_down = config)?;
+    worker::DISPLAY
+        .run(|| {
+            magnification::refresh();
+            Ok(())
+        })
+        .await
+}
+
+#[tauri::command]
+pub fn get_wind_down_status() -> wind_down::WindDownStatus {
+    wind_down::status()
+}
+
+/// Skip tonight's wind-down (or bring it back); it returns on its own the next night
+#[tauri::command]
+pub async fn skip_wind_down(skipped: bool) -> Result<wind_down::WindDownStatus, String> {
+    worker::DISPLAY
+        .run(move || {
+            wind_down::skip(skipped);
+            Ok(())
+        })
+        .await?;
+    Ok(wind_down::status())
+}
+
+/// Keep night vision on for a timed session, then fade it out and emit "session-timer-ended"
+#[tauri::command]
+pub fn start_session(app: AppHandle, minutes: u32) -> Result<(), String> {
+    let handle = app.clone();
+    let switched_on = session_timer::start(minutes, move |end| {
+        if end == session_timer::SessionEnd::Completed {
+            let _ = handle.emit("toggle-system", false);
+        }
+        let _ = handle.emit("session-timer-ended", end);
+    })?;
+    if switched_on {
+        let _ = app.emit("toggle-system", true);
+    }
+    Ok(())
+}
+
+/// End the running session early, leaving night vision as it is
+#[tauri::command]
+pub fn stop_session() -> bool {
+    session_timer::stop()
+}
+
+#[tauri::command]
+pub fn get_session_status() -> Option<session_timer::SessionStatus> {
+    session_timer::status()
+}
+
+/// Disable all screen adjustments (restore normal)
+#[tauri::command]
+pub async fn disable_adjustment() -> Result<(), String> {
+    worker::DISPLAY
+        .run(|| {
+            magnification::new_adjustment_session();
+            magnification::remove_effects()
+        })
+        .await
+}
+
+#[tauri::command]
+pub fn get_monitors() -> Vec<gamma::MonitorInfo> {
+    gamma::get_monitors()
+}
+
+#[tauri::command]
+pub fn get_monitor_groups() -> Vec<monitor_groups::MonitorGroup> {
+    monitor_groups::list()
+}
+
+/// Replace the monitor groups; members are device ids from `get_monitors`
+#[tauri::command]
+pub fn set_monitor_groups(groups: Vec<monitor_groups::MonitorGroup>) -> Result<(), String> {
+    monitor_groups::set(groups)
+}
+
+#[tauri::command]
+pub fn get_clone_outputs() -> topology::CloneOutputs {
+    settings::get().clone_outputs
+}
+
+/// Choose whether ICC profiles reach every clone of a duplicated display or only physical
+/// monitors; takes effect the next time a profile is applied
+#[tauri::command]
+pub fn set_clone_outputs(policy: topology::CloneOutputs) -> Result<(), String> {
+    settings::update(|s| s.clone_outputs = policy)?;
+    Ok(())
+}
+
+/// Sweep lift levels over rendered near-black patches and suggest an intensity
+#[tauri::command]
+pub async fn auto_calibrate(monitor: u32) -> Result<calibration::CalibrationResult, String> {
+    worker::CAPTURE.run(move || calibration::auto_calibrate(monitor)).await
+}
+
+/// Measure a monitor's response and tailor the Shadow Hunter curve to it
+#[tauri::command]
+pub async fn measure_panel(app: AppHandle, monitor: u32) -> Result<calibration::PanelReport, String> {
+    let report = worker::CAPTURE.run(move || calibration::measure_panel(monitor)).await?;
+    let _ = app.emit("settings-changed", settings::get());
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn reset_panel_tuning(app: AppHandle, monitor: u32) -> Result<(), String> {
+    calibration::reset_panel(monitor)?;
+    let _ = app.emit("settings-changed", settings::get());
+    Ok(())
+}
+
+/// Start guided curve tuning on a monitor, showing the first variation at `intensity`
+#[tauri::command]
+pub async fn start_curve_tuning(monitor: u32, intensity: f32) -> Result<tuning::Proposal, String> {
+    let (monitor, intensity) = (monitor_index(monitor)?, unit_interval("field.intensity", intensity)?);
+    worker::DISPLAY.run(move || tuning::start(monitor, intensity)).await
+}
+
+/// Answer whether the variation on screen looks better, showing the next one
+#[tauri::command]
+pub async fn respond_curve_tuning(better: bool) -> Result<tuning::Proposal, String> {
+    worker::DISPLAY.run(move || tuning::respond(better)).await
+}
+
+#[tauri::command]
+pub fn get_curve_tuning() -> Option<tuning::Proposal> {
+    tuning::current()
+}
+
+/// End curve tuning, storing the preferred curve as the panel's tuning if `save`
+#[tauri::command]
+pub async fn finish_curve_tuning(app: AppHandle, save: bool) -> Result<Option<gamma::CurveConstants>, String> {
+    let curve = worker::DISPLAY.run(move || tuning::finish(save)).await?;
+    if curve.is_some() {
+        let _ = app.emit("settings-changed", settings::get());
+    }
+    Ok(curve)
+}
+
+#[tauri::command]
+pub fn get_preserve_contrast() -> bool {
+    settings::get().preserve_contrast
+}
+
+/// Turn the contrast guard of the gamma curve on or off, redrawing applied ramps with it
+#[tauri::command]
+pub async fn set_preserve_contrast(enabled: bool) -> Result<(), String> {
+    settings::update(|s| s.preserve_contrast = enabled)?;
+    worker::DISPLAY
+        .run(|| {
+            for (source, intensity) in night_mode::applied() {
+                if let night_mode::Source::Gamma(monitor) = source {
+                    gamma::set_gamma(intensity, monitor)?;
+                }
+            }
+            Ok(())
+        })
+        .await
+}
+
+#[tauri::command]
+pub fn get_normalize_backends() -> bool {
+    settings::get().normalize_backends
+}
+
+/// Make the same intensity look alike on every backend; takes effect on the next adjustment
+#[tauri::command]
+pub fn set_normalize_backends(enabled: bool) -> Result<(), String> {
+    settings::update(|s| s.normalize_backends = enabled)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_exclude_own_window() -> bool {
+    settings::get().exclude_own_window
+}
+
+/// Keep the Noctis window out of Magnification effects so the settings UI shows true colors
+#[tauri::command]
+pub fn set_exclude_own_window(app: AppHandle, enabled: bool) -> Result<(), String> {
+    settings::update(|s| s.exclude_own_window = enabled)?;
+    crate::sync_window_exclusion(&app)
+}
+
+/// Apply the same lift through gamma, Magnification and ICC in turn, starting with gamma;
+/// the comparison hotkey moves to the next backend
+#[tauri::command]
+pub async fn start_backend_comparison(app: AppHandle, intensity: f32, monitor: u32) -> Result<backend_compare::Comparison, String> {
+    let intensity = unit_interval("field.intensity", intensity)?;
+    let monitor = monitor_index(monitor)?;
+    let comparison = worker::DISPLAY.run(move || backend_compare::start(intensity, monitor)).await?;
+    super::hotkeys::bind_backend_compare(&app)?;
+    Ok(comparison)
+}
+
+/// Show the next backend in the comparison
+#[tauri::command]
+pub async fn next_backend() -> Result<backend_compare::Comparison, String> {
+    worker::DISPLAY.run(backend_compare::next).await
+}
+
+/// Registered adjustment backends with their capabilities and whether they work here
+#[tauri::command]
+pub fn list_backends() -> Vec<backend::BackendInfo> {
+    backend::list()
+}
+
+#[tauri::command]
+pub fn get_backend_comparison() -> Option<backend_compare::Comparison> {
+    backend_compare::current()
+}
+
+/// End the comparison and log the backend the user picked, if any
+#[tauri::command]
+pub async fn finish_backend_comparison(app: AppHandle, chosen: Option<backend_compare::Candidate>) -> Result<(), String> {
+    super::hotkeys::unbind_backend_compare(&app);
+    worker::DISPLAY.run(move || backend_compare::finish(chosen)).await
+}
+
+/// Complete snapshot of the backend state
+#[tauri::command]
+pub fn get_state() -> state::AppState {
+    state::get()
+}
+
+/// Whether the toggle is on
+#[tauri::command]
+pub fn get_night_vision() -> bool {
+    toggle::is_active()
+}
+
+/// Flip the toggle like its hotkey does, returning whether it is now on
+#[tauri::command]
+pub fn toggle_night_vision(app: AppHandle) -> bool {
+    crate::toggle_system(&app);
+    toggle::is_active()
+}
+
+/// Snapshot left by the previous run, so the UI can offer to restore it
+#[tauri::command]
+pub fn get_last_session() -> Option<session::SessionSnapshot> {
+    session::last()
+}
+
+#[tauri::command]
+pub async fn restore_last_session() -> Result<session::SessionSnapshot, String> {
+    worker::DISPLAY.run(session::restore_last_session).await
+}
+
+/// Turn logging of applied ramps and matrices on or off
+#[tauri::command]
+pub fn set_ramp_logging(enabled: bool) -> Result<(), String> {
+    settings::update(|s| s.ramp_logging = enabled)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_ramp_sessions() -> Vec<String> {
+    ramp_log::list_sessions()
+}
+
+/// Diff two logged sessions (b relative to a)
+#[tauri::command]
+pub fn compare_sessions(a: String, b: String) -> Result<ramp_log::SessionComparison, String> {
+    ramp_log::compare_sessions(&a, &b)
+}
@@ -0,0 +1,302 @@
+//! Hotkey commands - the global toggle shortcut
+
+use crate::foreground::{self, ForegroundApp};
+use crate::hotkey::{self, Hotkey, ParseError};
+use crate::{backend_compare, display_sleep, elevation, emergency, i18n, screenshot, settings, wheel, worker};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+fn shortcut(hotkey: &Hotkey) -> Result<Shortcut, String> {
+    hotkey.accelerator().parse().map_err(|e| i18n::format("error.unsupported_hotkey", &[hotkey, &e]))
+}
+
+/// Unregister a saved binding if it parses and is registered
+fn unregister_saved(app: &AppHandle, saved: &str) {
+    let Some(shortcut) = saved.parse::<Hotkey>().ok().and_then(|h| shortcut(&h).ok()) else { return };
+    if app.global_shortcut().is_registered(shortcut) {
+        let _ = app.global_shortcut().unregister(shortcut);
+    }
+}
+
+/// Swap a saved binding for a new one (either may be empty), registering the new one first so
+/// the old one keeps working if the new one can't be registered
+fn rebind(app: &AppHandle, saved: &str, key: &str, register: fn(&AppHandle, &str) -> Result<(), String>) -> Result<(), String> {
+    let parsed = |key: &str| key.parse::<Hotkey>().ok().and_then(|h| shortcut(&h).ok());
+    if let Some(current) = parsed(saved).filter(|s| Some(*s) == parsed(key)) {
+        if app.global_shortcut().is_registered(current) {
+            return Ok(());
+        }
+    }
+    register(app, key)?;
+    unregister_saved(app, saved);
+    Ok(())
+}
+
+/// Replace the registered toggle shortcut with the given binding
+fn register(app: &AppHandle, hotkey: &Hotkey) -> Result<(), String> {
+    let shortcut = shortcut(hotkey)?;
+
+    // Unregister the previous toggle binding (still the saved one), leaving other shortcuts alone
+    unregister_saved(app, &settings::get().hotkey);
+    if app.global_shortcut().is_registered(shortcut) {
+        let _ = app.global_shortcut().unregister(shortcut);
+    }
+
+    // Register new shortcut, ignoring presses that land after focus left the scoped apps
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _, event| {
+            if event.state == ShortcutState::Released
+                && hotkey::in_scope(&settings::get().hotkey_scope, foreground::current().as_ref())
+            {
+                crate::toggle_system(app);
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Register the hotkey saved in settings at startup
+/// Falls back to the default if the saved one no longer parses or another app holds it
+pub fn restore(app: &AppHandle) {
+    let registered = settings::get().hotkey.parse::<Hotkey>().ok().is_some_and(|hotkey| register(app, &hotkey).is_ok());
+    if !registered {
+        if let Ok(hotkey) = hotkey::DEFAULT.parse::<Hotkey>() {
+            let _ = register(app, &hotkey);
+        }
+    }
+}
+
+/// Keep a scoped hotkey registered only while one of its apps is in the foreground,
+/// so the key reaches every other app untouched; while the elevated broker relays the
+/// hotkey for an elevated game it is unregistered so a press can't toggle twice
+pub fn sync_scope(app: &AppHandle, fg: Option<&ForegroundApp>) {
+    let settings = settings::get();
+    let Ok(hotkey) = settings.hotkey.parse::<Hotkey>() else { return };
+    let Ok(shortcut) = shortcut(&hotkey) else { return };
+    let active = hotkey::in_scope(&settings.hotkey_scope, fg) && !elevation::use_broker(fg);
+    let registered = app.global_shortcut().is_registered(shortcut);
+    if active && !registered {
+        let _ = register(app, &hotkey);
+    } else if !active && registered {
+        let _ = app.global_shortcut().unregister(shortcut);
+    }
+}
+
+/// Bind the toggle to a key like "Insert" or "Ctrl+Shift+F" and save it for the next start
+/// Returns the binding's display label so the UI shows it in one canonical form
+#[tauri::command]
+pub fn set_hotkey(app: AppHandle, key: String) -> Result<String, String> {
+    let hotkey: Hotkey = key.parse().map_err(|e: ParseError| e.to_string())?;
+    if hotkey::in_scope(&settings::get().hotkey_scope, foreground::current().as_ref()) {
+        register(&app, &hotkey)?;
+    } else {
+        shortcut(&hotkey)?;
+        unregister_saved(&app, &settings::get().hotkey);
+    }
+    let label = hotkey.to_string();
+    settings::update(|s| s.hotkey = label.clone())?;
+    Ok(label)
+}
+
+#[tauri::command]
+pub fn get_hotkey() -> String {
+    settings::get().hotkey
+}
+
+#[tauri::command]
+pub fn get_hotkey_scope() -> Vec<String> {
+    settings::get().hotkey_scope
+}
+
+/// Limit the hotkey to these process or profile names (empty = everywhere)
+#[tauri::command]
+pub fn set_hotkey_scope(app: AppHandle, scope: Vec<String>) -> Result<Vec<String>, String> {
+    let scope: Vec<String> = scope.iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+    let settings = settings::update(|s| s.hotkey_scope = scope)?;
+    sync_scope(&app, foreground::current().as_ref());
+    Ok(settings.hotkey_scope)
+}
+
+#[tauri::command]
+pub fn get_wheel_settings() -> wheel::WheelSettings {
+    settings::get().wheel
+}
+
+#[tauri::command]
+pub fn set_wheel_settings(config: wheel::WheelSettings) -> Result<(), String> {
+    wheel::configure(&config)?;
+    settings::update(|s| s.wheel = config)?;
+    Ok(())
+}
+
+/// The fixed emergency reset chord, which can't be rebound
+#[tauri::command]
+pub fn get_emergency_hotkey() -> &'static str {
+    emergency::LABEL
+}
+
+/// Register the display sleep hotkey, if one is set
+fn register_display_sleep(app: &AppHandle, key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return Ok(());
+    }
+    let hotkey: Hotkey = key.parse().map_err(|e: ParseError| e.to_string())?;
+    app.global_shortcut()
+        .on_shortcut(shortcut(&hotkey)?, |app, _, event| {
+            if event.state == ShortcutState::Released {
+                if let Ok(sleeping) = display_sleep::toggle() {
+                    crate::emit_display_sleep(app, sleeping);
+                }
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Register the saved display sleep hotkey at startup
+pub fn restore_display_sleep(app: &AppHandle) {
+    let _ = register_display_sleep(app, &settings::get().display_sleep.hotkey);
+}
+
+#[tauri::command]
+pub fn get_display_sleep() -> display_sleep::DisplaySleepSettings {
+    settings::get().display_sleep
+}
+
+/// Update the secondary display sleep hotkey and schedule
+#[tauri::command]
+pub fn set_display_sleep(app: AppHandle, config: display_sleep::DisplaySleepSettings) -> Result<(), String> {
+    display_sleep::validate(&config)?;
+    rebind(&app, &settings::get().display_sleep.hotkey, &config.hotkey, register_display_sleep)?;
+    settings::update(|s| s.display_sleep = config)?;
+    Ok(())
+}
+
+/// Sleep the secondary displays now, or wake them if they're asleep
+#[tauri::command]
+pub fn toggle_display_sleep(app: AppHandle) -> Result<bool, String> {
+    let sleeping = display_sleep::toggle()?;
+    crate::emit_display_sleep(&app, sleeping);
+    Ok(sleeping)
+}
+
+/// Capture the desktop without effects and save it, telling the UI where it went
+async fn clean_screenshot(app: &AppHandle) -> Result<String, String> {
+    let result = async {
+        let frame = worker::DISPLAY.run(screenshot::capture).await?;
+        let path = tauri::async_runtime::spawn_blocking(move || screenshot::save(&frame))
+            .await
+            .map_err(|e| e.to_string())??;
+        Ok(path.display().to_string())
+    }
+    .await;
+    match &result {
+        Ok(path) => {
+            let _ = app.emit("screenshot-saved", path);
+        }
+        Err(e) => {
+            let _ = app.emit("screenshot-failed", e);
+        }
+    }
+    result
+}
+
+/// Register the clean screenshot hotkey, if one is set
+fn register_screenshot(app: &AppHandle, key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return Ok(());
+    }
+    let hotkey: Hotkey = key.parse().map_err(|e: ParseError| e.to_string())?;
+    app.global_shortcut()
+        .on_shortcut(shortcut(&hotkey)?, |app, _, event| {
+            if event.state == ShortcutState::Released {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = clean_screenshot(&app).await;
+                });
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Register the saved screenshot hotkey at startup
+pub fn restore_screenshot(app: &AppHandle) {
+    let _ = register_screenshot(app, &settings::get().screenshot.hotkey);
+}
+
+#[tauri::command]
+pub fn get_screenshot_settings() -> screenshot::ScreenshotSettings {
+    settings::get().screenshot
+}
+
+/// Update the clean screenshot hotkey and folder
+#[tauri::command]
+pub fn set_screenshot_settings(app: AppHandle, config: screenshot::ScreenshotSettings) -> Result<(), String> {
+    screenshot::validate(&config)?;
+    rebind(&app, &settings::get().screenshot.hotkey, &config.hotkey, register_screenshot)?;
+    settings::update(|s| s.screenshot = config)?;
+    Ok(())
+}
+
+/// Take a clean screenshot now, returning the saved file's path
+#[tauri::command]
+pub async fn take_screenshot(app: AppHandle) -> Result<String, String> {
+    clean_screenshot(&app).await
+}
+
+/// Register the hotkey that opens or closes the compact widget, if one is set
+fn register_mini_window(app: &AppHandle, key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return Ok(());
+    }
+    let hotkey: Hotkey = key.parse().map_err(|e: ParseError| e.to_string())?;
+    app.global_shortcut()
+        .on_shortcut(shortcut(&hotkey)?, |app, _, event| {
+            if event.state == ShortcutState::Released {
+                crate::toggle_mini_window(app);
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Register the saved widget hotkey at startup
+pub fn restore_mini_window(app: &AppHandle) {
+    let _ = register_mini_window(app, &settings::get().mini_window_hotkey);
+}
+
+#[tauri::command]
+pub fn get_mini_window_hotkey() -> String {
+    settings::get().mini_window_hotkey
+}
+
+/// Change the widget hotkey (empty = none)
+#[tauri::command]
+pub fn set_mini_window_hotkey(app: AppHandle, key: String) -> Result<(), String> {
+    rebind(&app, &settings::get().mini_window_hotkey, &key, register_mini_window)?;
+    settings::update(|s| s.mini_window_hotkey = key)?;
+    Ok(())
+}
+
+/// Open the compact always-on-top widget, or close it if it's open
+#[tauri::command]
+pub fn toggle_mini_window(app: AppHandle) {
+    crate::toggle_mini_window(&app);
+}
+
+/// Bind the backend comparison hotkey for the length of a comparison
+pub fn bind_backend_compare(app: &AppHandle) -> Result<(), String> {
+    let hotkey: Hotkey = backend_compare::HOTKEY.parse().map_err(|e: ParseError| e.to_string())?;
+    unregister_saved(app, backend_compare::HOTKEY);
+    app.global_shortcut()
+        .on_shortcut(shortcut(&hotkey)?, |app, _, event| {
+            if event.state == ShortcutState::Released {
+                if let Ok(comparison) = backend_compare::next() {
+                    let _ = app.emit("backend-comparison", comparison);
+                }
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Release the backend comparison hotkey
+pub fn unbind_backend_compare(app: &AppHandle) {
+    unregister_saved(app, backend_compare::HOTKEY);
+}
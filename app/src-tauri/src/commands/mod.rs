@@ -0,0 +1,67 @@
+//! Tauri command handlers, grouped by area
+//! lib.rs registers them and wires up the background watchers; these stay thin wrappers
+//! that validate arguments and call into the backend modules
+
+pub mod config;
+pub mod display;
+pub mod hotkeys;
+pub mod profiles;
+pub mod sensor;
+
+use crate::i18n;
+
+/// Reject values outside 0.0 - 1.0 (including NaN) before they reach a backend
+/// `field` is the i18n id of the value's name, e.g. "field.intensity"
+fn unit_interval(field: &str, value: f32) -> Result<f32, String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(i18n::format("error.unit_interval", &[&i18n::text(field), &value]))
+    }
+}
+
+/// Reject monitor indices that don't match a connected monitor
+fn monitor_index(index: u32) -> Result<u32, String> {
+    if crate::gamma::get_monitors().iter().any(|m| m.index == index) {
+        Ok(index)
+    } else {
+        Err(i18n::format("error.monitor_not_found", &[&index]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_interval_accepts_bounds() {
+        assert_eq!(unit_interval("field.intensity", 0.0), Ok(0.0));
+        assert_eq!(unit_interval("field.intensity", 0.5), Ok(0.5));
+        assert_eq!(unit_interval("field.intensity", 1.0), Ok(1.0));
+    }
+
+    #[test]
+    fn unit_interval_rejects_nan() {
+        assert!(unit_interval("field.intensity", f32::NAN).is_err());
+    }
+
+    #[test]
+    fn unit_interval_rejects_out_of_range() {
+        assert!(unit_interval("field.intensity", -0.01).is_err());
+        assert!(unit_interval("field.intensity", 1.01).is_err());
+        assert!(unit_interval("field.intensity", f32::INFINITY).is_err());
+    }
+
+    #[test]
+    fn monitor_index_rejects_unknown_monitor() {
+        let unknown = crate::gamma::get_monitors().iter().map(|m| m.index).max().unwrap_or(0) + 1;
+        assert!(monitor_index(unknown).is_err());
+    }
+
+    #[test]
+    fn monitor_index_accepts_connected_monitors() {
+        for monitor in crate::gamma::get_monitors() {
+            assert_eq!(monitor_index(monitor.index), Ok(monitor.index));
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Profile commands - game profiles, automation rules and the process blocklist
+
+use crate::{allowlist, blocklist, foreground, launcher, profiles, rules, settings};
+use super::{monitor_index, unit_interval};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub fn get_profiles() -> Vec<profiles::GameProfile> {
+    profiles::list()
+}
+
+#[tauri::command]
+pub fn save_profile(profile: profiles::GameProfile) -> Result<Vec<profiles::GameProfile>, String> {
+    unit_interval("field.intensity", profile.intensity)?;
+    if let Some(warmup) = profile.warmup_intensity {
+        unit_interval("field.intensity", warmup)?;
+    }
+    // A group profile adjusts its group's members, not `monitor`
+    if profile.group.is_none() {
+        monitor_index(profile.monitor)?;
+    }
+    profiles::upsert(profile)
+}
+
+#[tauri::command]
+pub fn delete_profile(name: String) -> Result<Vec<profiles::GameProfile>, String> {
+    profiles::delete(&name)
+}
+
+#[tauri::command]
+pub fn get_rules() -> Vec<rules::Rule> {
+    rules::list()
+}
+
+/// Replace the trigger -> condition -> action rules
+#[tauri::command]
+pub fn set_rules(rules: Vec<rules::Rule>) -> Result<(), String> {
+    rules::set(rules)
+}
+
+#[tauri::command]
+pub fn get_blocklist() -> Vec<String> {
+    blocklist::list()
+}
+
+#[tauri::command]
+pub fn add_to_blocklist(app: AppHandle, process: String) -> Result<Vec<String>, String> {
+    let list = blocklist::add(&process)?;
+    crate::emit_suspend_change(&app, blocklist::enforce(foreground::current().as_ref()));
+    Ok(list)
+}
+
+#[tauri::command]
+pub fn remove_from_blocklist(app: AppHandle, process: String) -> Result<Vec<String>, String> {
+    let list = blocklist::remove(&process)?;
+    crate::emit_suspend_change(&app, blocklist::enforce(foreground::current().as_ref()));
+    Ok(list)
+}
+
+#[tauri::command]
+pub fn get_allowlist() -> allowlist::AllowlistSettings {
+    settings::get().allowlist
+}
+
+/// Update allowlist mode and apply it right away
+#[tauri::command]
+pub fn set_allowlist(app: AppHandle, config: allowlist::AllowlistSettings) -> Result<(), String> {
+    let processes = config.processes.iter().map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect();
+    settings::update(|s| s.allowlist = allowlist::AllowlistSettings { processes, ..config })?;
+    crate::emit_suspend_change(&app, allowlist::enforce());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_keep_awake() -> bool {
+    settings::get().keep_awake
+}
+
+/// Keep the display awake while a game profile is engaged
+#[tauri::command]
+pub fn set_keep_awake(enabled: bool) -> Result<(), String> {
+    settings::update(|s| s.keep_awake = enabled)?;
+    Ok(())
+}
+
+/// Steam launch options and Playnite scripts that apply a profile around its game
+#[tauri::command]
+pub fn get_launch_wrapper(profile: String) -> Result<launcher::LaunchWrapper, String> {
+    launcher::wrapper(&profile)
+}
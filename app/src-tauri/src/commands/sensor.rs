@@ -0,0 +1,77 @@
+//! Sensor commands - brightness capture, color picker and reading history
+//! Captures run on the capture worker lane, away from display writes
+
+use crate::{frame_guard, history, i18n, profiles, sensor, settings, worker};
+
+#[tauri::command]
+pub async fn get_sensor_data(x: i32, y: i32, width: i32, height: i32) -> Result<f32, String> {
+    if width <= 0 || height <= 0 {
+        return Err(i18n::format("error.capture_size", &[&width, &height]));
+    }
+    worker::CAPTURE.run(move || sensor::get_screen_brightness(x, y, width, height)).await
+}
+
+/// Color picker: raw captured color plus the color after the active transform
+#[tauri::command]
+pub async fn sample_pixel(x: i32, y: i32) -> Result<sensor::PixelSample, String> {
+    worker::CAPTURE.run(move || sensor::sample_pixel(x, y)).await
+}
+
+/// Keep the calling window (overlay indicator, preview) out of sensor captures
+#[tauri::command]
+pub fn exclude_from_capture(window: tauri::WebviewWindow) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        let hwnd = window.hwnd().map_err(|e| i18n::format("error.window_handle", &[&e]))?;
+        sensor::exclude_from_capture(hwnd.0 as isize)
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = window;
+        sensor::exclude_from_capture(0)
+    }
+}
+
+#[tauri::command]
+pub fn get_sensor_settings() -> sensor::SensorSettings {
+    settings::get().sensor
+}
+
+/// Update the sensor configuration, used from the next capture on
+#[tauri::command]
+pub fn set_sensor_settings(config: sensor::SensorSettings) -> Result<(), String> {
+    sensor::validate(&config)?;
+    settings::update(|s| s.sensor = config)?;
+    Ok(())
+}
+
+/// Sensor and auto-adjust settings in effect, from the active profile or the global settings
+/// The interval is stretched while the frame-rate guard has backed off
+#[tauri::command]
+pub fn get_sensing() -> profiles::Sensing {
+    let mut sensing = profiles::sensing();
+    sensing.sensor.interval_ms = frame_guard::interval_ms(sensing.sensor.interval_ms);
+    sensing
+}
+
+#[tauri::command]
+pub fn get_frame_guard_settings() -> frame_guard::GuardSettings {
+    settings::get().frame_guard
+}
+
+#[tauri::command]
+pub fn set_frame_guard_settings(config: frame_guard::GuardSettings) -> Result<(), String> {
+    frame_guard::set_settings(config)
+}
+
+/// Whether sensing is backed off to protect the frame rate, and what the pipeline costs
+#[tauri::command]
+pub fn get_frame_guard_status() -> frame_guard::GuardStatus {
+    frame_guard::status()
+}
+
+/// Sensor readings and applied intensities over the last ten minutes
+#[tauri::command]
+pub fn get_brightness_history() -> history::BrightnessHistory {
+    history::get()
+}
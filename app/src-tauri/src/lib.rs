@@ -1,215 +1,604 @@
-mod gamma;
-mod sensor;
-mod magnification;
+use noctis_core::{
+    gamma,
+    sensor,
+    magnification,
+    settings,
+    portable,
+    calibration,
+    suspend,
+    foreground,
+    blocklist,
+    profiles,
+    process_watcher,
+    config_watcher,
+    night_mode,
+    peripherals,
+    lights,
+    mqtt,
+    hdr_video,
+    display_power,
+    command_queue,
+    worker,
+    shutdown,
+    message_window,
+    session,
+    watchdog,
+    state,
+    ramp_log,
+    history,
+    adjustment,
+    hotkey,
+    wheel,
+    toggle,
+    allowlist,
+    background,
+    i18n,
+    updates,
+    crash,
+    preflight,
+    safe_mode,
+    emergency,
+    schedule,
+    controller,
+    temperature,
+    display_sleep,
+    keep_awake,
+    control,
+    snippets,
+    deep_link,
+    launcher,
+    discord,
+    accessibility,
+    magnifier_host,
+    cursor,
+    screenshot,
+    markers,
+    user_switch,
+    mag_conflict,
+    color_filter,
+    backend,
+    backend_compare,
+    rules,
+    scripting,
+    tuning,
+    monitor_groups,
+    topology,
+    elevation,
+    perf,
+    frame_guard,
+    wind_down,
+    session_timer,
+    blue_light,
+    startup,
+    control_panel,
+};
+
+mod commands;
 
-use gamma::MonitorInfo;
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Manager,
 };
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Shortcut, ShortcutState};
-
-#[tauri::command]
-fn set_gamma(value: f32, monitor: u32) -> Result<(), String> {
-    gamma::set_gamma(value, monitor)
-}
-
-#[tauri::command]
-fn dim_monitor(brightness: f32, monitor: u32) -> Result<(), String> {
-    gamma::dim_monitor(brightness, monitor)
-}
-
-/// Apply smart auto-adjustment based on screen brightness
-/// Uses Magnification API for instant system-wide effect
-/// brightness: 0.0-1.0 (screen brightness from sensor)
-#[tauri::command]
-fn apply_smart_adjustment(brightness: f32) -> Result<(), String> {
-    magnification::apply_smart_adjustment(brightness)
-}
-
-/// Disable all screen adjustments (restore normal)
-#[tauri::command]
-fn disable_adjustment() -> Result<(), String> {
-    magnification::remove_effects()
-}
-
-#[tauri::command]
-fn get_sensor_data(x: i32, y: i32, width: i32, height: i32) -> Result<f32, String> {
-    sensor::get_screen_brightness(x, y, width, height)
-}
-
-#[tauri::command]
-fn get_monitors() -> Vec<MonitorInfo> {
-    gamma::get_monitors()
-}
-
-#[tauri::command]
-fn set_hotkey(app: AppHandle, key: String) -> Result<(), String> {
-    let key_upper = key.to_uppercase();
-    let code = match key_upper.as_str() {
-        // Letters A-Z
-        "A" | "KEYA" => Code::KeyA,
-        "B" | "KEYB" => Code::KeyB,
-        "C" | "KEYC" => Code::KeyC,
-        "D" | "KEYD" => Code::KeyD,
-        "E" | "KEYE" => Code::KeyE,
-        "F" | "KEYF" => Code::KeyF,
-        "G" | "KEYG" => Code::KeyG,
-        "H" | "KEYH" => Code::KeyH,
-        "I" | "KEYI" => Code::KeyI,
-        "J" | "KEYJ" => Code::KeyJ,
-        "K" | "KEYK" => Code::KeyK,
-        "L" | "KEYL" => Code::KeyL,
-        "M" | "KEYM" => Code::KeyM,
-        "N" | "KEYN" => Code::KeyN,
-        "O" | "KEYO" => Code::KeyO,
-        "P" | "KEYP" => Code::KeyP,
-        "Q" | "KEYQ" => Code::KeyQ,
-        "R" | "KEYR" => Code::KeyR,
-        "S" | "KEYS" => Code::KeyS,
-        "T" | "KEYT" => Code::KeyT,
-        "U" | "KEYU" => Code::KeyU,
-        "V" | "KEYV" => Code::KeyV,
-        "W" | "KEYW" => Code::KeyW,
-        "X" | "KEYX" => Code::KeyX,
-        "Y" | "KEYY" => Code::KeyY,
-        "Z" | "KEYZ" => Code::KeyZ,
-        // Numbers
-        "0" | "DIGIT0" => Code::Digit0,
-        "1" | "DIGIT1" => Code::Digit1,
-        "2" | "DIGIT2" => Code::Digit2,
-        "3" | "DIGIT3" => Code::Digit3,
-        "4" | "DIGIT4" => Code::Digit4,
-        "5" | "DIGIT5" => Code::Digit5,
-        "6" | "DIGIT6" => Code::Digit6,
-        "7" | "DIGIT7" => Code::Digit7,
-        "8" | "DIGIT8" => Code::Digit8,
-        "9" | "DIGIT9" => Code::Digit9,
-        // Function keys
-        "F1" => Code::F1, "F2" => Code::F2, "F3" => Code::F3, "F4" => Code::F4,
-        "F5" => Code::F5, "F6" => Code::F6, "F7" => Code::F7, "F8" => Code::F8,
-        "F9" => Code::F9, "F10" => Code::F10, "F11" => Code::F11, "F12" => Code::F12,
-        // Navigation
-        "INSERT" => Code::Insert,
-        "DELETE" => Code::Delete,
-        "HOME" => Code::Home,
-        "END" => Code::End,
-        "PAGEUP" => Code::PageUp,
-        "PAGEDOWN" => Code::PageDown,
-        "ARROWUP" => Code::ArrowUp,
-        "ARROWDOWN" => Code::ArrowDown,
-        "ARROWLEFT" => Code::ArrowLeft,
-        "ARROWRIGHT" => Code::ArrowRight,
-        // Special
-        "ESCAPE" => Code::Escape,
-        "PAUSE" => Code::Pause,
-        "SCROLLLOCK" => Code::ScrollLock,
-        "BACKQUOTE" | "`" => Code::Backquote,
-        "MINUS" | "-" => Code::Minus,
-        "EQUAL" | "=" => Code::Equal,
-        "BRACKETLEFT" | "[" => Code::BracketLeft,
-        "BRACKETRIGHT" | "]" => Code::BracketRight,
-        "BACKSLASH" | "\\" => Code::Backslash,
-        "SEMICOLON" | ";" => Code::Semicolon,
-        "QUOTE" | "'" => Code::Quote,
-        "COMMA" | "," => Code::Comma,
-        "PERIOD" | "." => Code::Period,
-        "SLASH" | "/" => Code::Slash,
-        "SPACE" | " " => Code::Space,
-        "TAB" => Code::Tab,
-        "CAPSLOCK" => Code::CapsLock,
-        "NUMLOCK" => Code::NumLock,
-        // Numpad
-        "NUMPAD0" => Code::Numpad0, "NUMPAD1" => Code::Numpad1, "NUMPAD2" => Code::Numpad2,
-        "NUMPAD3" => Code::Numpad3, "NUMPAD4" => Code::Numpad4, "NUMPAD5" => Code::Numpad5,
-        "NUMPAD6" => Code::Numpad6, "NUMPAD7" => Code::Numpad7, "NUMPAD8" => Code::Numpad8,
-        "NUMPAD9" => Code::Numpad9,
-        "NUMPADADD" => Code::NumpadAdd,
-        "NUMPADSUBTRACT" => Code::NumpadSubtract,
-        "NUMPADMULTIPLY" => Code::NumpadMultiply,
-        "NUMPADDIVIDE" => Code::NumpadDivide,
-        "NUMPADDECIMAL" => Code::NumpadDecimal,
-        "NUMPADENTER" => Code::NumpadEnter,
-        _ => return Err(format!("Unsupported key: {}", key)),
+
+/// Forward profile activation changes to the frontend
+fn emit_profile_event(app: &AppHandle, event: profiles::ProfileEvent) {
+    let _ = match event {
+        profiles::ProfileEvent::Activated(p) => {
+            if let Some(monitor) = control_panel::show_for(&p) {
+                show_control_panel(app, &monitor);
+            }
+            app.emit("profile-activated", p)
+        }
+        profiles::ProfileEvent::Deactivated(p) => app.emit("profile-deactivated", p),
     };
-    
-    // Unregister all existing shortcuts
-    let _ = app.global_shortcut().unregister_all();
-    
-    // Register new shortcut
-    app.global_shortcut()
-        .on_shortcut(Shortcut::new(None, code), move |app, _, event| {
-            if event.state == ShortcutState::Released {
-                let _ = app.emit("toggle-system", ());
+    mqtt::publish_state();
+    state::changed();
+}
+
+/// Flip the toggle, restoring or clearing the remembered adjustments, and tell the UI
+fn toggle_system(app: &AppHandle) {
+    let _ = app.emit("toggle-system", toggle::toggle());
+    state::changed();
+}
+
+/// Carry out a command received from the MQTT control topic or the local control socket
+fn handle_command(app: &AppHandle, command: control::Command) -> Result<(), String> {
+    match command {
+        control::Command::Toggle => toggle_system(app),
+        control::Command::Disable => {
+            for m in gamma::get_monitors() {
+                let _ = gamma::set_gamma(0.0, m.index);
             }
-        })
-        .map_err(|e| e.to_string())?;
-    
+            let _ = magnification::remove_effects();
+        }
+        control::Command::SetIntensity { intensity, monitor } => {
+            let monitors = match monitor {
+                Some(target) => target.resolve()?,
+                None => profiles::active()
+                    .map(|p| p.monitors())
+                    .unwrap_or_else(|| gamma::get_monitors().into_iter().map(|m| m.index).collect()),
+            };
+            for monitor in monitors {
+                gamma::set_gamma(intensity, monitor)?;
+            }
+        }
+    }
     Ok(())
 }
 
+/// Carry out a noctis:// link from a shortcut, browser or game launcher
+fn handle_link(app: &AppHandle, link: deep_link::Link) -> Result<(), String> {
+    match link {
+        deep_link::Link::Command(command) => handle_command(app, command)?,
+        deep_link::Link::Profile(name) => {
+            if safe_mode::is_active() {
+                return Err(i18n::text("error.safe_mode_profiles"));
+            }
+            profiles::activate(&name, |event| emit_profile_event(app, event))?;
+        }
+        deep_link::Link::Release(name) => profiles::release(&name, |event| emit_profile_event(app, event))?,
+        deep_link::Link::Show => show_main_window(app),
+    }
+    Ok(())
+}
+
+/// Carry out an action of a rule that fired
+fn run_rule_action(app: &AppHandle, rule: &str, action: &rules::Action) -> Result<(), String> {
+    match action {
+        rules::Action::ApplyProfile { name } => {
+            profiles::activate(name, |event| emit_profile_event(app, event))?;
+        }
+        rules::Action::ReleaseProfile { name } => profiles::release(name, |event| emit_profile_event(app, event))?,
+        rules::Action::SetIntensity { intensity, monitor } => {
+            handle_command(app, control::Command::SetIntensity { intensity: *intensity, monitor: monitor.clone() })?
+        }
+        rules::Action::Disable => handle_command(app, control::Command::Disable)?,
+        rules::Action::Notify { message } => {
+            let notification = rules::Notification { rule: rule.to_string(), message: message.clone() };
+            let _ = app.emit("rule-notification", notification);
+        }
+    }
+    Ok(())
+}
+
+/// Notify the frontend when effects are suspended or resumed
+fn emit_suspend_change(app: &AppHandle, changed: Option<bool>) {
+    match changed {
+        Some(true) => { let _ = app.emit("effects-suspended", suspend::reasons()); }
+        Some(false) => { let _ = app.emit("effects-resumed", ()); }
+        None => return,
+    }
+    state::changed();
+}
+
+/// Tell the UI whether the secondary displays are asleep
+fn emit_display_sleep(app: &AppHandle, sleeping: bool) {
+    let _ = app.emit("secondary-displays-sleeping", sleeping);
+}
+
+/// Id of the tray icon, to update it after the locale changes
+const TRAY_ID: &str = "main";
+
+/// Tray menu in the current locale
+fn tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let quit = MenuItem::with_id(app, "quit", i18n::text("tray.quit"), true, None::<&str>)?;
+    let show = MenuItem::with_id(app, "show", i18n::text("tray.show"), true, None::<&str>)?;
+    Menu::with_items(app, &[&show, &quit])
+}
+
+/// Re-translate the tray menu and tooltip
+fn refresh_tray(app: &AppHandle) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        if let Ok(menu) = tray_menu(app) {
+            let _ = tray.set_menu(Some(menu));
+        }
+        let _ = tray.set_tooltip(Some(i18n::text("tray.tooltip")));
+    }
+}
+
+/// Create the main window from its config entry, applying the close and minimize policy
+fn create_main_window(app: &AppHandle, visible: bool) -> Option<tauri::WebviewWindow> {
+    let config = app.config().app.windows.iter().find(|w| w.label == "main")?;
+    let window = tauri::WebviewWindowBuilder::from_config(app, config).ok()?.visible(visible).build().ok()?;
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        let policy = settings::get().window;
+        match event {
+            tauri::WindowEvent::CloseRequested { api, .. } if policy.close_to_tray => {
+                api.prevent_close();
+                let _ = handle.hide();
+            }
+            tauri::WindowEvent::Resized(_) if policy.minimize_to_tray && handle.is_minimized().unwrap_or(false) => {
+                let _ = handle.hide();
+                let _ = handle.unminimize();
+            }
+            tauri::WindowEvent::Destroyed => magnifier_host::stop(),
+            _ => {}
+        }
+    });
+    let _ = sync_window_exclusion(app);
+    if let Some(monitor) = control_panel::pinned_monitor() {
+        place_window(&window, &monitor);
+    }
+    Some(window)
+}
+
+/// Move a window to its spot on a monitor
+fn place_window(window: &tauri::WebviewWindow, monitor: &gamma::MonitorInfo) {
+    let Ok(size) = window.outer_size() else { return };
+    let (x, y) = control_panel::position(monitor, size.width, size.height);
+    let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+}
+
+/// Bring the main window up on the pinned monitor without focusing it, so the game keeps
+/// the keyboard
+fn show_control_panel(app: &AppHandle, monitor: &gamma::MonitorInfo) {
+    let Some(window) = app.get_webview_window("main").or_else(|| create_main_window(app, false)) else { return };
+    place_window(&window, monitor);
+    let _ = window.show();
+}
+
+/// Move the main window to the pinned monitor after the pin changed
+fn pin_main_window(app: &AppHandle) {
+    let (Some(window), Some(monitor)) = (app.get_webview_window("main"), control_panel::pinned_monitor()) else { return };
+    place_window(&window, &monitor);
+}
+
+/// Label of the compact always-on-top widget
+const MINI_LABEL: &str = "mini";
+
+/// Open the compact widget (intensity slider and toggle), or close it if it's open
+fn toggle_mini_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MINI_LABEL) {
+        let _ = window.close();
+        return;
+    }
+    // The frontend renders the widget instead of the full app for this label
+    let _ = tauri::WebviewWindowBuilder::new(app, MINI_LABEL, tauri::WebviewUrl::App("index.html".into()))
+        .title("noctis")
+        .inner_size(240.0, 96.0)
+        .resizable(false)
+        .decorations(false)
+        .transparent(true)
+        .shadow(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .build();
+}
+
+/// Start or stop the magnifier host that keeps the main window out of Magnification effects
+fn sync_window_exclusion(app: &AppHandle) -> Result<(), String> {
+    let window = app.get_webview_window("main").filter(|_| settings::get().exclude_own_window);
+    let Some(window) = window else {
+        magnifier_host::stop();
+        return Ok(());
+    };
+    #[cfg(windows)]
+    {
+        let hwnd = window.hwnd().map_err(|e| i18n::format("error.window_handle", &[&e]))?;
+        magnifier_host::start(hwnd.0 as isize)
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = window;
+        magnifier_host::start(0)
+    }
+}
+
+/// Show the main window, creating it first if background mode started without it
+fn show_main_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main").or_else(|| create_main_window(app, true)) else { return };
+    let _ = window.show();
+    let _ = window.set_focus();
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Watchdog instances only wait for the main process and never start the UI
+    if let Some(pid) = watchdog::watched_pid() {
+        watchdog::run(pid);
+        return;
+    }
+    
+    // The elevated broker serves the main process for games run as administrator, without a UI
+    if let Some(pid) = elevation::broker_for() {
+        elevation::run_broker(pid);
+        return;
+    }
+    
+    // Launch wrappers run a game between applying and releasing its profile, without a UI
+    if let Some((profile, command)) = launcher::launch_args() {
+        std::process::exit(launcher::run(&profile, &command));
+    }
+    
+    // A noctis:// link goes to the running instance, if there is one, instead of starting another
+    let link = deep_link::from_args();
+    if link.as_deref().and_then(deep_link::forward).is_some() {
+        return;
+    }
+    
+    // Capture panics and SEH crashes for diagnosis if the user opted in
+    crash::install();
+    
+    // Keep WebView2's cache and storage next to the exe as well in portable mode
+    if portable::is_portable() {
+        std::env::set_var("WEBVIEW2_USER_DATA_FOLDER", portable::data_dir().join("WebView2"));
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .plugin(
-            tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(move |app, shortcut, event| {
-                    if event.state == ShortcutState::Released && shortcut == &Shortcut::new(None, Code::Insert) {
-                        let _ = app.emit("toggle-system", ());
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .setup(move |app| {
+            // Push every backend-originated change to the UI as one state snapshot
+            let handle = app.handle().clone();
+            state::on_change(move |state| {
+                let _ = handle.emit("state-changed", state);
+            });
+            history::start();
+            
+            // Return to the previous adjustment after a crash or power loss, or clear anything
+            // it left behind and apply the startup policy; safe mode resets the display instead
+            if safe_mode::start() {
+                // Nothing saved is applied until the user leaves safe mode
+            } else if settings::get().restore_unclean_session && session::last().is_some_and(|last| !last.clean_exit) {
+                let _ = session::restore_last_session();
+            } else {
+                watchdog::cleanup_abandoned();
+                if startup::apply(settings::get().startup) {
+                    let _ = app.emit("toggle-system", true);
+                    state::changed();
+                }
+            }
+            
+            // Reset the display if this process dies without shutting down
+            let _ = watchdog::spawn();
+            
+            // Register the saved toggle hotkey
+            commands::hotkeys::restore(app.handle());
+            
+            // Sleep secondary displays on their hotkey or nightly schedule, waking them on input
+            commands::hotkeys::restore_display_sleep(app.handle());
+            let handle = app.handle().clone();
+            display_sleep::start_watcher(move |sleeping| emit_display_sleep(&handle, sleeping));
+            
+            // Take screenshots without effects on their hotkey
+            commands::hotkeys::restore_screenshot(app.handle());
+            
+            // Summon the compact widget from its hotkey
+            commands::hotkeys::restore_mini_window(app.handle());
+            
+            // Failsafe chord that resets the display and pauses automation, whatever the settings
+            let handle = app.handle().clone();
+            emergency::start(move |was_active, changed| {
+                if was_active {
+                    let _ = handle.emit("toggle-system", false);
+                }
+                emit_suspend_change(&handle, changed);
+                state::changed();
+            });
+            
+            // Adjust intensity by scrolling while the wheel chord is held
+            wheel::start();
+            
+            // Suspend effects while blocklisted apps are in the foreground,
+            // and switch game profiles as their windows gain or lose focus
+            let handle = app.handle().clone();
+            foreground::start_watcher(move |fg| {
+                let _ = handle.emit("foreground-changed", fg);
+                emit_suspend_change(&handle, blocklist::enforce(Some(fg)));
+                if !safe_mode::is_active() {
+                    let profile_handle = handle.clone();
+                    profiles::on_foreground_change(fg, move |event| emit_profile_event(&profile_handle, event));
+                }
+                commands::hotkeys::sync_scope(&handle, Some(fg));
+            });
+            
+            // Explain when an elevated game is out of reach, and relay the hotkey through the broker
+            let handle = app.handle().clone();
+            let relay = app.handle().clone();
+            elevation::start(
+                move |status| {
+                    let _ = handle.emit("elevation-changed", status);
+                    commands::hotkeys::sync_scope(&handle, foreground::current().as_ref());
+                },
+                move || toggle_system(&relay),
+            );
+            
+            // Back off sensing while it costs a game too much frame time
+            let handle = app.handle().clone();
+            frame_guard::start(move |status| {
+                let _ = handle.emit("frame-guard-changed", status);
+            });
+
+            // Late-night wind-down: follow its fade and remind to stop once it completes
+            let handle = app.handle().clone();
+            let reminder = app.handle().clone();
+            wind_down::start(
+                move |status| {
+                    let _ = handle.emit("wind-down-changed", status);
+                },
+                move |message| {
+                    let _ = reminder.emit("wind-down-reminder", message);
+                },
+            );
+
+            // Hot-apply settings and profiles edited on disk
+            let handle = app.handle().clone();
+            config_watcher::start_watcher(move |change| match change {
+                config_watcher::ConfigChange::Settings => {
+                    if let Some(settings) = settings::reload() {
+                        emit_suspend_change(&handle, blocklist::enforce(foreground::current().as_ref()));
+                        let _ = handle.emit("settings-changed", settings);
                     }
-                })
-                .build(),
-        )
-        .setup(|app| {
-            // Register INSERT key as global hotkey
-            app.global_shortcut().register(Shortcut::new(None, Code::Insert))?;
+                }
+                config_watcher::ConfigChange::Profiles => {
+                    if let Some(list) = profiles::reload(|event| emit_profile_event(&handle, event)) {
+                        let _ = handle.emit("profiles-changed", list);
+                    }
+                }
+                config_watcher::ConfigChange::Rules => {
+                    if let Some(rules) = rules::reload() {
+                        let _ = handle.emit("rules-changed", rules);
+                    }
+                }
+            });
+            
+            // Prepare game profiles at process launch to avoid a bright flash at startup
+            let handle = app.handle().clone();
+            process_watcher::start_watcher(move |launched| {
+                if !safe_mode::is_active() {
+                    profiles::prepare(launched, |event| emit_profile_event(&handle, event));
+                }
+                emit_suspend_change(&handle, allowlist::enforce());
+            });
+            
+            // Hold the display awake through long stretches without input during game sessions
+            keep_awake::start();
+            
+            // Keep contrast themes readable: gentle untinted lift, or no effects at all
+            let handle = app.handle().clone();
+            accessibility::start_watcher(move |changed| emit_suspend_change(&handle, changed));
+            
+            // Keep effects off unless an allowlisted app is running
+            let handle = app.handle().clone();
+            allowlist::start_watcher(move |changed| emit_suspend_change(&handle, changed));
+            
+            // Sync RGB peripheral lighting and room lights with night mode
+            peripherals::start();
+            lights::start();
             
-            // Create tray menu
-            let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let show = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show, &quit])?;
+            // Publish state to and take commands from a home automation broker
+            let handle = app.handle().clone();
+            mqtt::start(move |command| handle_command(&handle, command));
+            
+            // Take the same commands from scripts on this machine
+            let handle = app.handle().clone();
+            control::start(move |command| handle_command(&handle, command));
+            
+            // Keep the white cursor from glaring over dimmed content, if the user opted in
+            cursor::start_watcher();
+            
+            // Timestamp night vision turning on and off for syncing recordings
+            let handle = app.handle().clone();
+            markers::start(move |marker| {
+                let _ = handle.emit("night-vision-marker", marker);
+            });
+            
+            // Let squadmates see night vision is on through Discord, if the user opted in
+            discord::start();
+            
+            // Bypass effects while HDR video plays to avoid banding and clipping
+            let handle = app.handle().clone();
+            hdr_video::start_watcher(move |changed| emit_suspend_change(&handle, changed));
+            
+            // Share the fullscreen color effect with Windows Magnifier and similar tools
+            let handle = app.handle().clone();
+            mag_conflict::start_watcher(move |state| {
+                let _ = handle.emit("magnification-conflict", state);
+            });
+            
+            // Follow the Windows color filters stacked on our Magnification effect
+            let handle = app.handle().clone();
+            color_filter::start_watcher(move |state| {
+                let _ = handle.emit("system-color-filter", state);
+            });
+            
+            // Carry out the user's trigger -> condition -> action rules
+            let handle = app.handle().clone();
+            rules::start(move |rule, action| run_rule_action(&handle, rule, action));
+            
+            // Run the user's Rhai script hooks, if enabled
+            scripting::start();
+            
+            // Take effects off the display while another user has the console
+            let handle = app.handle().clone();
+            user_switch::on_change(move |changed| emit_suspend_change(&handle, changed));
+            
+            // Hold gamma writes while displays sleep and re-apply effects when they wake
+            let handle = app.handle().clone();
+            display_power::on_change(move |on| {
+                let _ = handle.emit(if on { "displays-woke" } else { "displays-sleeping" }, ());
+                state::changed();
+            });
+            
+            // Restore the display on exit paths that skip the event loop (Ctrl+C, logoff)
+            shutdown::install_handlers();
+            
+            // One hidden window delivers the session, display power and logoff messages above
+            message_window::start();
             
             // Create tray icon using app's default icon
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(app.default_window_icon().cloned().expect("no icon"))
-                .menu(&menu)
-                .tooltip("Noctis - Night Vision")
+                .menu(&tray_menu(app.handle())?)
+                .tooltip(i18n::text("tray.tooltip"))
                 .on_menu_event(|app, event| {
                     match event.id.as_ref() {
                         "quit" => {
-                            // Reset all monitor gamma before quitting
-                            let monitors = gamma::get_monitors();
-                            for m in &monitors {
-                                let _ = gamma::set_gamma(1.0, m.index);
-                            }
+                            // Restore the display before the event loop winds down
+                            shutdown::run();
                             app.exit(0);
                         }
-                        "show" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
+                        "show" => show_main_window(app),
                         _ => {}
                     }
                 })
                 .on_tray_icon_event(|tray, event| {
                     if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        } else {
-                        }
+                        show_main_window(tray.app_handle());
                     }
                 })
                 .build(app)?;
             
+            // Look for a new release in the background if the user opted in
+            if settings::get().updates.enabled {
+                let handle = app.handle().clone();
+                std::thread::spawn(move || {
+                    if let Ok(Some(release)) = updates::check(&handle.package_info().version.to_string()) {
+                        let _ = handle.emit("update-available", release);
+                    }
+                });
+            }
+            
+            // The window is created here rather than from the config so background mode can skip it
+            // and start minimized can create it hidden in the tray
+            if !background::is_enabled() {
+                if settings::get().window.start_minimized {
+                    create_main_window(app.handle(), false);
+                } else {
+                    show_main_window(app.handle());
+                }
+            }
+            
+            // Take noctis:// links from later instances, then carry out the one this process started with
+            // Portable installs leave the registry alone
+            if !portable::is_portable() {
+                if let Err(e) = deep_link::register() {
+                    crash::log(e);
+                }
+            }
+            let handle = app.handle().clone();
+            deep_link::start_listener(move |link| handle_link(&handle, link));
+            if let Some(url) = &link {
+                if let Err(e) = deep_link::parse(url).and_then(|link| handle_link(app.handle(), link)) {
+                    crash::log(e);
+                }
+            }
+            
+            // Auto-adjust runs in the UI; without a window it runs in the backend
+            let handle = app.handle().clone();
+            background::start_auto_adjust(move || handle.get_webview_window("main").is_some());
+            
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![set_gamma, dim_monitor, get_sensor_data, get_monitors, set_hotkey, apply_smart_adjustment, disable_adjustment])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(tauri::generate_handler![commands::display::set_gamma, commands::display::set_manual_intensity, commands::display::dim_monitor, commands::display::apply_smart_adjustment, commands::display::simulate_adjustment, commands::display::get_adjustment_config, commands::display::set_adjustment_config, commands::display::get_mode, commands::display::set_mode, commands::display::get_accessibility_state, commands::display::set_high_contrast_policy, commands::display::get_magnification_conflict, commands::display::set_magnification_conflict_policy, commands::display::get_system_color_filter, commands::display::set_compensate_color_filter, commands::display::get_temperature_settings, commands::display::set_temperature_settings, commands::display::apply_blue_light_preset, commands::display::get_blue_light_report, commands::display::get_cursor_settings, commands::display::set_cursor_settings, commands::display::get_schedule, commands::display::set_schedule, commands::display::set_schedule_override, commands::display::get_wind_down_settings, commands::display::set_wind_down_settings, commands::display::get_wind_down_status, commands::display::skip_wind_down, commands::display::start_session, commands::display::stop_session, commands::display::get_session_status, commands::display::disable_adjustment, commands::display::get_monitors, commands::display::get_monitor_groups, commands::display::set_monitor_groups, commands::display::get_clone_outputs, commands::display::set_clone_outputs, commands::display::auto_calibrate, commands::display::measure_panel, commands::display::reset_panel_tuning, commands::display::start_curve_tuning, commands::display::respond_curve_tuning, commands::display::get_curve_tuning, commands::display::finish_curve_tuning, commands::display::get_preserve_contrast, commands::display::set_preserve_contrast, commands::display::get_normalize_backends, commands::display::set_normalize_backends, commands::display::get_exclude_own_window, commands::display::set_exclude_own_window, commands::display::start_backend_comparison, commands::display::next_backend, commands::display::get_backend_comparison, commands::display::list_backends, commands::display::finish_backend_comparison, commands::display::get_state, commands::display::get_night_vision, commands::display::toggle_night_vision, commands::display::get_last_session, commands::display::restore_last_session, commands::display::set_ramp_logging, commands::display::list_ramp_sessions, commands::display::compare_sessions, commands::sensor::get_sensor_data, commands::sensor::sample_pixel, commands::sensor::exclude_from_capture, commands::sensor::get_sensor_settings, commands::sensor::set_sensor_settings, commands::sensor::get_sensing, commands::sensor::get_frame_guard_settings, commands::sensor::set_frame_guard_settings, commands::sensor::get_frame_guard_status, commands::sensor::get_brightness_history, commands::hotkeys::set_hotkey, commands::hotkeys::get_hotkey, commands::hotkeys::get_hotkey_scope, commands::hotkeys::set_hotkey_scope, commands::hotkeys::get_wheel_settings, commands::hotkeys::set_wheel_settings, commands::hotkeys::get_emergency_hotkey, commands::hotkeys::get_display_sleep, commands::hotkeys::set_display_sleep, commands::hotkeys::toggle_display_sleep, commands::hotkeys::get_screenshot_settings, commands::hotkeys::set_screenshot_settings, commands::hotkeys::take_screenshot, commands::hotkeys::get_mini_window_hotkey, commands::hotkeys::set_mini_window_hotkey, commands::hotkeys::toggle_mini_window, commands::profiles::get_profiles, commands::profiles::save_profile, commands::profiles::delete_profile, commands::profiles::get_rules, commands::profiles::set_rules, commands::profiles::get_blocklist, commands::profiles::add_to_blocklist, commands::profiles::remove_from_blocklist, commands::profiles::get_allowlist, commands::profiles::set_allowlist, commands::profiles::get_keep_awake, commands::profiles::set_keep_awake, commands::profiles::get_launch_wrapper, commands::config::get_peripheral_settings, commands::config::set_peripheral_settings, commands::config::get_light_settings, commands::config::set_light_settings, commands::config::get_mqtt_settings, commands::config::set_mqtt_settings, commands::config::get_control_settings, commands::config::set_control_settings, commands::config::export_automation_snippet, commands::config::get_discord_settings, commands::config::set_discord_settings, commands::config::get_marker_settings, commands::config::set_marker_settings, commands::config::get_script_settings, commands::config::set_script_settings, commands::config::get_script_status, commands::config::set_config_dir, commands::config::get_config_dir, commands::config::is_portable, commands::config::get_background_mode, commands::config::set_background_mode, commands::config::get_startup_policy, commands::config::set_startup_policy, commands::config::get_window_policy, commands::config::set_window_policy, commands::config::get_locale, commands::config::set_locale, commands::config::check_for_updates, commands::config::get_update_settings, commands::config::set_update_settings, commands::config::set_crash_reporting, commands::config::list_crash_reports, commands::config::export_crash_report, commands::config::run_preflight, commands::config::get_elevation_status, commands::config::start_elevated_broker, commands::config::stop_elevated_broker, commands::config::get_perf_stats, commands::config::reset_perf_stats, commands::config::set_perf_etw, commands::config::is_safe_mode, commands::config::exit_safe_mode])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // Window close and app.exit both end here
+            if let tauri::RunEvent::Exit = event {
+                shutdown::run();
+            }
+        });
 }
@@ -25,7 +25,7 @@ interface Settings {
 
 const DEFAULT_SETTINGS: Settings = {
   monitorIndex: 1,
-  hotkey: "INSERT",
+  hotkey: "Insert",
 };
 
 // --- Title Bar ---
@@ -125,8 +125,10 @@ function NoctisView({
         setListening(false);
         return;
       }
-      const displayName = e.key.length === 1 ? e.key.toUpperCase() : e.key.toUpperCase();
-      onHotkeyChange(displayName);
+      // Wait for the actual key when only a modifier is down
+      if (["Control", "Alt", "Shift", "Meta"].includes(e.key)) return;
+      const modifiers = [e.ctrlKey && "Ctrl", e.altKey && "Alt", e.shiftKey && "Shift", e.metaKey && "Win"].filter(Boolean);
+      onHotkeyChange([...modifiers, e.code].join("+"));
       setListening(false);
     };
 
@@ -182,12 +184,14 @@ function App() {
         const parsed = JSON.parse(saved);
         const loadedSettings = { ...DEFAULT_SETTINGS, ...parsed };
         setSettings(loadedSettings);
-        // Register saved hotkey with backend
-        invoke("set_hotkey", { key: loadedSettings.hotkey }).catch(console.error);
       } catch (e) {
         console.error("Failed to load settings:", e);
       }
     }
+    // The backend persists and registers the hotkey itself
+    invoke<string>("get_hotkey")
+      .then((hotkey) => setSettings((current) => ({ ...current, hotkey })))
+      .catch(console.error);
   }, []);
 
   // Save settings when they change
@@ -202,16 +206,19 @@ function App() {
 
   // Handle hotkey change
   const handleHotkeyChange = (key: string) => {
-    const newSettings = { ...settings, hotkey: key };
-    setSettings(newSettings);
-    localStorage.setItem("noctis-settings", JSON.stringify(newSettings));
-    // Register the new hotkey with the backend
-    invoke("set_hotkey", { key }).catch(console.error);
+    // Register the new hotkey with the backend, which returns its display label
+    invoke<string>("set_hotkey", { key })
+      .then((label) => {
+        const newSettings = { ...settings, hotkey: label };
+        setSettings(newSettings);
+        localStorage.setItem("noctis-settings", JSON.stringify(newSettings));
+      })
+      .catch(console.error);
   };
 
   // Hotkey listener
   useEffect(() => {
-    const unlisten = listen("toggle-system", () => setActive(p => !p));
+    const unlisten = listen<boolean>("toggle-system", (event) => setActive(event.payload));
     return () => { unlisten.then(fn => fn()); };
   }, []);
 
@@ -235,6 +242,7 @@ function App() {
   // ============================================================================
   useEffect(() => {
     let interval: number | null = null;
+    let cancelled = false;
 
     // State
     let currentLevel = 0;              // Current discrete intensity level (0, 1, or 2)
@@ -332,11 +340,20 @@ function App() {
       }
     };
 
-    // Start loop
-    tick();
-    interval = window.setInterval(tick, POLL_MS);
+    // Start loop from the intensity the toggle restored, rather than fading in from zero
+    invoke<{ monitors: { index: number; intensity: number }[] }>("get_state")
+      .then(state => {
+        appliedIntensity = state.monitors.find(m => m.index === settings.monitorIndex)?.intensity ?? 0.0;
+      })
+      .catch(() => { })
+      .finally(() => {
+        if (cancelled) return;
+        tick();
+        interval = window.setInterval(tick, POLL_MS);
+      });
 
     return () => {
+      cancelled = true;
       if (interval) window.clearInterval(interval);
     };
   }, [active, settings.monitorIndex, monitors]);
@@ -0,0 +1,74 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import "./App.css";
+
+// ============================================================================
+// MINI WIDGET - INTENSITY SLIDER AND TOGGLE, ALWAYS ON TOP
+// ============================================================================
+
+// Same monitor the full app targets (shared localStorage)
+function targetMonitor(): number {
+  try {
+    return JSON.parse(localStorage.getItem("noctis-settings") ?? "{}").monitorIndex ?? 1;
+  } catch {
+    return 1;
+  }
+}
+
+function Mini() {
+  const appWindow = getCurrentWindow();
+  const [active, setActive] = useState(false);
+  const [intensity, setIntensity] = useState(0);
+
+  // Load the toggle and the target monitor's intensity
+  useEffect(() => {
+    invoke<boolean>("get_night_vision").then(setActive).catch(console.error);
+    invoke<{ monitors: { index: number; intensity: number }[] }>("get_state")
+      .then(state => setIntensity(state.monitors.find(m => m.index === targetMonitor())?.intensity ?? 0))
+      .catch(console.error);
+  }, []);
+
+  // Follow the toggle from the hotkey, tray or full app
+  useEffect(() => {
+    const unlisten = listen<boolean>("toggle-system", (event) => setActive(event.payload));
+    return () => { unlisten.then(fn => fn()); };
+  }, []);
+
+  const handleIntensity = (value: number) => {
+    setIntensity(value);
+    invoke("set_manual_intensity", { value, monitor: targetMonitor() }).catch(console.error);
+  };
+
+  const handleMouseDown = (e: React.MouseEvent) => {
+    if ((e.target as HTMLElement).closest("button, input")) return;
+    appWindow.startDragging();
+  };
+
+  return (
+    <div className="app-shell mini-shell" onMouseDown={handleMouseDown}>
+      <div className="mini-row">
+        <button
+          className={`mini-toggle ${active ? "active" : ""}`}
+          onClick={() => invoke<boolean>("toggle_night_vision").then(setActive).catch(console.error)}
+        >
+          <span className="status-dot" />
+        </button>
+        <input
+          className="mini-slider"
+          type="range"
+          min={0}
+          max={1}
+          step={0.01}
+          value={intensity}
+          onChange={(e) => handleIntensity(Number(e.target.value))}
+        />
+        <span className="mini-value">{Math.round(intensity * 100)}</span>
+        <button className="title-bar-btn close" onClick={() => appWindow.close()}>✕</button>
+      </div>
+    </div>
+  );
+}
+
+export default Mini;
@@ -1,9 +1,14 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import App from "./App";
+import Mini from "./Mini";
+
+// The backend opens the compact widget as its own window labelled "mini"
+const isMini = getCurrentWindow().label === "mini";
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {isMini ? <Mini /> : <App />}
   </React.StrictMode>,
 );